@@ -0,0 +1,161 @@
+//! `debugger adapters`: the configured debug adapters, and with
+//! `--features` which DAP features each one reports
+//!
+//! Features are read by starting each adapter and sending `initialize`
+//! alone, so nothing is launched. The probes run in-process, so no daemon
+//! is needed either.
+
+use crate::common::{config::Config, Error, Result};
+use crate::daemon::LocalSession;
+use crate::dap::Capabilities;
+use crate::ipc::protocol::Command;
+
+use super::OutputMode;
+
+/// A feature of the matrix: its `--agent` name, its label and whether
+/// capabilities report it
+type Feature = (&'static str, &'static str, fn(&Capabilities) -> bool);
+
+const FEATURES: &[Feature] = &[
+    ("conditional-breakpoints", "Conditional breakpoints", |c| {
+        c.supports_conditional_breakpoints
+    }),
+    ("hit-count-breakpoints", "Hit count breakpoints", |c| {
+        c.supports_hit_conditional_breakpoints
+    }),
+    ("function-breakpoints", "Function breakpoints", |c| c.supports_function_breakpoints),
+    ("instruction-breakpoints", "Instruction breakpoints", |c| {
+        c.supports_instruction_breakpoints
+    }),
+    ("data-breakpoints", "Data breakpoints", |c| c.supports_data_breakpoints),
+    ("restart", "Restart", |c| c.supports_restart_request),
+    ("step-back", "Step back", |c| c.supports_step_back),
+    ("single-thread", "Single-thread stepping", |c| {
+        c.supports_single_thread_execution_requests
+    }),
+    ("set-variable", "Set variable", |c| c.supports_set_variable),
+    ("set-expression", "Set expression", |c| c.supports_set_expression),
+    ("read-memory", "Read memory", |c| c.supports_read_memory_request),
+    ("disassemble", "Disassemble", |c| c.supports_disassemble_request),
+    ("exception-info", "Exception info", |c| c.supports_exception_info_request),
+    ("terminate-threads", "Terminate threads", |c| c.supports_terminate_threads_request),
+    ("cancel", "Cancel requests", |c| c.supports_cancel_request),
+    ("modules", "Modules", |c| c.supports_modules_request),
+    ("completions", "Completions", |c| c.supports_completions_request),
+    ("goto-targets", "Goto targets", |c| c.supports_goto_targets_request),
+];
+
+/// List the adapters configured in `config.toml`, probing each for its
+/// features if `features` is set
+pub async fn print(features: bool, mode: OutputMode) -> Result<()> {
+    let config = Config::load()?;
+    let mut names: Vec<&String> = config.adapters.keys().collect();
+    names.sort();
+
+    if names.is_empty() {
+        println!("No adapters configured; install one with 'debugger setup'");
+        return Ok(());
+    }
+
+    if !features {
+        for name in names {
+            let adapter = &config.adapters[name];
+            match mode {
+                OutputMode::Agent { .. } => {
+                    println!("ADAPTER {} {}", name, adapter.path.display())
+                }
+                OutputMode::Human => println!("{:<16} {}", name, adapter.path.display()),
+            }
+        }
+        return Ok(());
+    }
+
+    let mut session = LocalSession::start()?;
+    let mut probed = Vec::new();
+    for name in names {
+        match probe(&mut session, name).await {
+            Ok(capabilities) => probed.push((name.as_str(), capabilities)),
+            Err(e) => match mode {
+                OutputMode::Agent { .. } => println!("ADAPTER {} ERROR {}", name, e),
+                OutputMode::Human => println!("{}: could not be initialized: {}", name, e),
+            },
+        }
+    }
+    session.shutdown().await;
+
+    match mode {
+        OutputMode::Agent { .. } => {
+            for (name, capabilities) in &probed {
+                println!("ADAPTER {} {}", name, supported(capabilities).join(","));
+            }
+        }
+        OutputMode::Human if !probed.is_empty() => print!("{}", matrix(&probed)),
+        OutputMode::Human => {}
+    }
+    Ok(())
+}
+
+/// Capabilities the adapter `name` reports, without launching anything
+async fn probe(session: &mut LocalSession, name: &str) -> Result<Capabilities> {
+    let result = session
+        .send_command(Command::ProbeAdapter {
+            adapter: name.to_string(),
+        })
+        .await?;
+    serde_json::from_value(result["capabilities"].clone())
+        .map_err(|e| Error::Internal(format!("Bad capabilities from {}: {}", name, e)))
+}
+
+/// `--agent` names of the features `capabilities` report
+fn supported(capabilities: &Capabilities) -> Vec<&'static str> {
+    FEATURES
+        .iter()
+        .filter(|(_, _, has)| has(capabilities))
+        .map(|(id, _, _)| *id)
+        .collect()
+}
+
+/// One row per feature and one column per adapter, marking what each supports
+fn matrix(probed: &[(&str, Capabilities)]) -> String {
+    let label_width = FEATURES.iter().map(|(_, label, _)| label.len()).max().unwrap_or(0);
+    let mut out = format!("{:<width$}", "Feature", width = label_width);
+    for (name, _) in probed {
+        out.push_str(&format!("  {}", name));
+    }
+    out.push('\n');
+
+    for (_, label, has) in FEATURES {
+        out.push_str(&format!("{:<width$}", label, width = label_width));
+        for (name, capabilities) in probed {
+            let mark = if has(capabilities) { "✓" } else { "-" };
+            out.push_str(&format!("  {:<width$}", mark, width = name.chars().count()));
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_marks_each_adapters_features() {
+        let lldb = Capabilities {
+            supports_conditional_breakpoints: true,
+            ..Capabilities::default()
+        };
+        let gdb = Capabilities {
+            supports_step_back: true,
+            ..Capabilities::default()
+        };
+        let text = matrix(&[("lldb-dap", lldb.clone()), ("gdb", gdb)]);
+
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Feature                  lldb-dap  gdb");
+        assert_eq!(lines[1], "Conditional breakpoints  ✓         -");
+        assert!(text.contains("Step back                -         ✓\n"));
+        assert_eq!(supported(&lldb), vec!["conditional-breakpoints"]);
+    }
+}
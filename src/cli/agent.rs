@@ -0,0 +1,430 @@
+//! Terse output for `--agent` mode
+//!
+//! Every record is a single line with a stable upper-case tag or a plain
+//! `name=value` pair, so agents can grep it and spend few tokens reading it.
+
+use crate::ipc::protocol::{
+    BreakpointDetails, BreakpointInfo, ContextResult, DeadlockReport, ModuleInfo, OutputLimits,
+    RegistersResult, StackFrameInfo, StopResult, VariableInfo,
+};
+
+/// Collapse a value onto one line so each record stays on its own line
+fn one_line(value: &str) -> String {
+    value.replace('\r', "").replace('\n', "\\n")
+}
+
+fn location(source: Option<&str>, line: Option<u32>) -> String {
+    match (source, line) {
+        (Some(source), Some(line)) => format!("{}:{}", source, line),
+        (Some(source), None) => source.to_string(),
+        (None, Some(line)) => format!("?:{}", line),
+        (None, None) => "?".to_string(),
+    }
+}
+
+/// `STOP breakpoint simple.c:12 fn=add`, plus one `ONHIT` line per on-hit command
+pub fn format_stop(stop: &StopResult) -> String {
+    let mut line = format!(
+        "STOP {} {}",
+        stop.reason,
+        location(stop.source.as_deref(), stop.line)
+    );
+    if let Some(function) = &stop.function {
+        line.push_str(&format!(" fn={}", function));
+    }
+    if !stop.hit_breakpoint_ids.is_empty() {
+        let ids: Vec<String> = stop.hit_breakpoint_ids.iter().map(u32::to_string).collect();
+        line.push_str(&format!(" bp={}", ids.join(",")));
+    }
+    if let Some(signal) = &stop.signal_name {
+        line.push_str(&format!(" sig={}", signal));
+    }
+    if let Some(type_name) = stop.exception.as_ref().and_then(|e| e.type_name.as_ref()) {
+        line.push_str(&format!(" exc={}", type_name));
+    }
+    if let Some(text) = stop.exception_text.as_ref().or(stop.description.as_ref()) {
+        if stop.reason == "exception" {
+            line.push_str(&format!(" desc={}", one_line(text)));
+        }
+    }
+
+    for output in &stop.on_hit {
+        let body = match (&output.result, &output.error) {
+            (_, Some(error)) => format!("ERROR {}", one_line(error)),
+            (Some(result), None) => result.to_string(),
+            (None, None) => String::new(),
+        };
+        line.push_str(&format!("\nONHIT {} => {}", output.command, body));
+    }
+
+    for watch in &stop.watches {
+        let body = match (&watch.value, &watch.error) {
+            (Some(value), _) => one_line(value),
+            (None, error) => format!("ERROR {}", one_line(error.as_deref().unwrap_or("unknown"))),
+        };
+        line.push_str(&format!("\nWATCH {} {} => {}", watch.id, watch.expression, body));
+    }
+
+    line
+}
+
+/// `name=value`, or `name:type=value` when types are requested
+pub fn format_variable(var: &VariableInfo, types: bool) -> String {
+    let mut line = match (&var.type_name, types) {
+        (Some(type_name), true) => {
+            format!("{}:{}={}", var.name, type_name, one_line(&var.value))
+        }
+        _ => format!("{}={}", var.name, one_line(&var.value)),
+    };
+    let annotations = var.annotations();
+    if !annotations.is_empty() {
+        line.push_str(&format!(" [{}]", annotations.join(", ")));
+    }
+    if let Some(elements) = &var.elements {
+        line.push_str(&format!(" {{{}}}", one_line(elements)));
+    }
+    line
+}
+
+/// `#0 add simple.c:12`, `#3 LABEL [async boundary]` for a separator, and
+/// a trailing ` subtle` on frames the adapter deemphasizes
+pub fn format_frame(index: usize, frame: &StackFrameInfo) -> String {
+    match frame.presentation_hint.as_deref() {
+        Some("label") => format!("#{} LABEL {}", index, frame.name),
+        hint => format!(
+            "#{} {} {}{}",
+            index,
+            frame.name,
+            location(frame.source.as_deref(), frame.line),
+            if hint == Some("subtle") { " subtle" } else { "" }
+        ),
+    }
+}
+
+/// `BP 1 simple.c:12`, or `BP 1 pending <message>` when unverified, with
+/// why it is pending on a `REASON` line
+pub fn format_breakpoint(info: &BreakpointInfo) -> String {
+    let mut line = if info.verified {
+        format!(
+            "BP {} {}",
+            info.id,
+            location(info.source.as_deref(), info.line)
+        )
+    } else {
+        match &info.message {
+            Some(message) => format!("BP {} pending {}", info.id, one_line(message)),
+            None => format!("BP {} pending", info.id),
+        }
+    };
+    if let Some(reason) = &info.reason {
+        line = format!("{}\nREASON {}", line, one_line(reason));
+    }
+    match &info.warning {
+        Some(warning) => format!("{}\nWARN {}", line, one_line(warning)),
+        None => line,
+    }
+}
+
+/// The `BP` line, then one tagged line per detail that is set
+pub fn format_breakpoint_details(details: &BreakpointDetails) -> String {
+    let info = &details.info;
+    let mut lines = vec![
+        format_breakpoint(info),
+        format!("REQUESTED {}", details.location),
+        format!("HITS {}", details.hits),
+    ];
+    if !info.enabled {
+        lines.push("DISABLED".to_string());
+    }
+    if let Some(condition) = &info.condition {
+        lines.push(format!("CONDITION {}", one_line(condition)));
+    }
+    if let Some(hit_count) = info.hit_count {
+        lines.push(format!("HIT_COUNT {}", hit_count));
+    }
+    for command in &info.on_hit {
+        lines.push(format!("ON_HIT {}", command));
+    }
+    if let Some(adapter_id) = details.adapter_id {
+        lines.push(format!("ADAPTER_ID {}", adapter_id));
+    }
+    // Pending breakpoints already carry the message on the BP line
+    if let (true, Some(message)) = (info.verified, &info.message) {
+        lines.push(format!("MESSAGE {}", one_line(message)));
+    }
+    lines.join("\n")
+}
+
+/// `OUTPUT_LIMITS max_bytes=67108864 max_events=10000 bytes=120 events=3`
+pub fn format_output_limits(limits: &OutputLimits) -> String {
+    format!(
+        "OUTPUT_LIMITS max_bytes={} max_events={} bytes={} events={}",
+        limits.max_bytes, limits.max_events, limits.bytes, limits.events
+    )
+}
+
+/// `REGISTERS pc=0x401136 sp=0x7ffe10 fp=?`, plus `ref=` with the
+/// instruction reference when the frame has one
+pub fn format_registers(registers: &RegistersResult) -> String {
+    let value = |register: &Option<String>| register.as_deref().map_or("?".to_string(), one_line);
+    let mut line = format!(
+        "REGISTERS pc={} sp={} fp={}",
+        value(&registers.pc),
+        value(&registers.sp),
+        value(&registers.fp)
+    );
+    if let Some(reference) = &registers.instruction_reference {
+        line.push_str(&format!(" ref={}", reference));
+    }
+    line
+}
+
+/// `MODULE 1 libc.so.6 /usr/lib/libc.so.6`, or without the path when the
+/// adapter doesn't give one
+pub fn format_module(module: &ModuleInfo) -> String {
+    let mut line = format!("MODULE {} {}", one_line(&module.id), one_line(&module.name));
+    if let Some(path) = &module.path {
+        line.push_str(&format!(" {}", one_line(path)));
+    }
+    line
+}
+
+/// `THREADS 4 blocked=2`, then `BLOCKED 1 pthread_mutex_lock in update_b
+/// bank.c:31` per blocked thread, `CYCLE 1 2` per potential deadlock and
+/// `SKIPPED 5 6` for threads without a stack
+pub fn format_deadlock(report: &DeadlockReport) -> String {
+    let mut lines = vec![format!(
+        "THREADS {} blocked={}",
+        report.threads,
+        report.blocked.len()
+    )];
+    for thread in &report.blocked {
+        let mut line = format!("BLOCKED {} {}", thread.id, thread.wait_function);
+        if let Some(frame) = &thread.waiting_in {
+            line.push_str(&format!(
+                " in {} {}",
+                frame.name,
+                location(frame.source.as_deref(), frame.line)
+            ));
+        }
+        lines.push(line);
+    }
+    let ids = |ids: &[i64]| ids.iter().map(i64::to_string).collect::<Vec<_>>().join(" ");
+    lines.extend(report.cycles.iter().map(|cycle| format!("CYCLE {}", ids(cycle))));
+    if !report.skipped.is_empty() {
+        lines.push(format!("SKIPPED {}", ids(&report.skipped)));
+    }
+    lines.join("\n")
+}
+
+/// `AT simple.c:12 fn=add`, the source window with `>` on the current line,
+/// then the locals
+pub fn format_context(ctx: &ContextResult, types: bool) -> String {
+    let mut lines = vec![format!(
+        "AT {} fn={}",
+        location(ctx.source.as_deref(), Some(ctx.line)),
+        ctx.function.as_deref().unwrap_or("?")
+    )];
+    for source_line in &ctx.source_lines {
+        let marker = if source_line.is_current { '>' } else { ' ' };
+        lines.push(format!("{}{} {}", marker, source_line.number, source_line.content));
+    }
+    lines.extend(ctx.locals.iter().map(|var| format_variable(var, types)));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::protocol::{OnHitOutput, PresentationHint, WatchValue};
+
+    fn stop() -> StopResult {
+        StopResult {
+            reason: "breakpoint".to_string(),
+            raw_reason: None,
+            description: None,
+            thread_id: Some(1),
+            all_threads_stopped: true,
+            hit_breakpoint_ids: vec![],
+            source: Some("simple.c".to_string()),
+            line: Some(12),
+            column: Some(5),
+            function: Some("add".to_string()),
+            signal_name: None,
+            signal_number: None,
+            exception_text: None,
+            exception: None,
+            on_hit: vec![],
+            watches: vec![],
+        }
+    }
+
+    #[test]
+    fn stops_render_on_one_line() {
+        assert_eq!(format_stop(&stop()), "STOP breakpoint simple.c:12 fn=add");
+
+        let mut with_on_hit = stop();
+        with_on_hit.hit_breakpoint_ids = vec![1, 3];
+        with_on_hit.on_hit = vec![OnHitOutput {
+            command: "print x".to_string(),
+            result: Some(serde_json::json!({ "result": "5" })),
+            error: None,
+        }];
+        assert_eq!(
+            format_stop(&with_on_hit),
+            "STOP breakpoint simple.c:12 fn=add bp=1,3\nONHIT print x => {\"result\":\"5\"}"
+        );
+
+        let mut with_watches = stop();
+        with_watches.watches = vec![
+            WatchValue {
+                id: 1,
+                expression: "n".to_string(),
+                value: Some("3".to_string()),
+                error: None,
+            },
+            WatchValue {
+                id: 2,
+                expression: "missing".to_string(),
+                value: None,
+                error: Some("no such variable".to_string()),
+            },
+        ];
+        assert_eq!(
+            format_stop(&with_watches),
+            "STOP breakpoint simple.c:12 fn=add\nWATCH 1 n => 3\nWATCH 2 missing => ERROR no such variable"
+        );
+    }
+
+    #[test]
+    fn variables_omit_types_unless_requested() {
+        let var = VariableInfo {
+            name: "msg".to_string(),
+            value: "\"a\nb\"".to_string(),
+            type_name: Some("char *".to_string()),
+            variables_reference: 0,
+            presentation_hint: None,
+            elements: None,
+            memory_reference: None,
+        };
+        assert_eq!(format_variable(&var, false), "msg=\"a\\nb\"");
+        assert_eq!(format_variable(&var, true), "msg:char *=\"a\\nb\"");
+    }
+
+    #[test]
+    fn unusual_variables_are_annotated() {
+        let var = VariableInfo {
+            name: "n".to_string(),
+            value: "<optimized out>".to_string(),
+            type_name: None,
+            variables_reference: 0,
+            presentation_hint: Some(PresentationHint {
+                attributes: vec!["readOnly".to_string()],
+                ..Default::default()
+            }),
+            elements: None,
+            memory_reference: None,
+        };
+        assert_eq!(
+            format_variable(&var, false),
+            "n=<optimized out> [optimized out, read-only]"
+        );
+    }
+
+    #[test]
+    fn frames_follow_presentation_hints() {
+        let frame = |hint: Option<&str>| StackFrameInfo {
+            id: 1000,
+            index: Some(2),
+            presentation_hint: hint.map(str::to_string),
+            name: "add".to_string(),
+            source: Some("simple.c".to_string()),
+            line: Some(12),
+            column: Some(5),
+        };
+        assert_eq!(format_frame(2, &frame(None)), "#2 add simple.c:12");
+        assert_eq!(format_frame(2, &frame(Some("subtle"))), "#2 add simple.c:12 subtle");
+        assert_eq!(format_frame(2, &frame(Some("label"))), "#2 LABEL add");
+    }
+
+    #[test]
+    fn breakpoint_warnings_follow_on_their_own_line() {
+        let mut info = BreakpointInfo {
+            id: 2,
+            verified: true,
+            source: Some("simple.c".to_string()),
+            line: Some(12),
+            message: None,
+            reason: None,
+            enabled: true,
+            condition: Some("x >".to_string()),
+            hit_count: None,
+            on_hit: vec![],
+            warning: None,
+        };
+        assert_eq!(format_breakpoint(&info), "BP 2 simple.c:12");
+
+        info.warning = Some("condition failed to evaluate:\nsyntax error".to_string());
+        assert_eq!(
+            format_breakpoint(&info),
+            "BP 2 simple.c:12\nWARN condition failed to evaluate:\\nsyntax error"
+        );
+    }
+
+    #[test]
+    fn breakpoint_details_list_what_is_set() {
+        let details = BreakpointDetails {
+            info: BreakpointInfo {
+                id: 3,
+                verified: false,
+                source: None,
+                line: None,
+                message: Some("no code at line".to_string()),
+                reason: Some("the adapter could not set it and will not retry".to_string()),
+                enabled: true,
+                condition: Some("n > 2".to_string()),
+                hit_count: None,
+                on_hit: vec!["locals".to_string()],
+                warning: None,
+            },
+            location: "simple.c:14".to_string(),
+            hits: 0,
+            adapter_id: Some(7),
+        };
+        assert_eq!(
+            format_breakpoint_details(&details),
+            "BP 3 pending no code at line\nREASON the adapter could not set it and will not retry\n\
+             REQUESTED simple.c:14\nHITS 0\nCONDITION n > 2\nON_HIT locals\nADAPTER_ID 7"
+        );
+    }
+
+    #[test]
+    fn registers_render_on_one_line() {
+        let registers = RegistersResult {
+            pc: Some("0x401136".to_string()),
+            sp: Some("0x7ffe10".to_string()),
+            fp: None,
+            instruction_reference: Some("0x401136".to_string()),
+        };
+        assert_eq!(
+            format_registers(&registers),
+            "REGISTERS pc=0x401136 sp=0x7ffe10 fp=? ref=0x401136"
+        );
+    }
+
+    #[test]
+    fn modules_render_one_per_line() {
+        let module = ModuleInfo {
+            id: "1".to_string(),
+            name: "libc.so.6".to_string(),
+            path: Some("/usr/lib/libc.so.6".to_string()),
+        };
+        assert_eq!(format_module(&module), "MODULE 1 libc.so.6 /usr/lib/libc.so.6");
+
+        let module = ModuleInfo {
+            path: None,
+            ..module
+        };
+        assert_eq!(format_module(&module), "MODULE 1 libc.so.6");
+    }
+}
@@ -0,0 +1,90 @@
+//! Shell completion scripts
+//!
+//! Generated from the clap definitions. Values that depend on the machine
+//! are filled in when the script is generated: the adapters configured in
+//! `config.toml` for `--adapter`, and the installable debuggers for `setup`.
+
+use std::io::Write;
+
+use clap::builder::PossibleValuesParser;
+use clap::Command;
+use clap_complete::Shell;
+
+use crate::common::{config::Config, Result};
+use crate::setup::registry;
+
+/// Print the completion script for `shell` of the CLI defined by `command`
+pub fn print(shell: Shell, command: Command) -> Result<()> {
+    let adapters = Config::load()
+        .map(|config| {
+            let mut names: Vec<String> = config.adapters.into_keys().collect();
+            names.sort();
+            names
+        })
+        .unwrap_or_default();
+    let debuggers: Vec<String> = registry::all_debuggers()
+        .iter()
+        .map(|debugger| debugger.id.to_string())
+        .collect();
+
+    let mut command = with_values(command, &adapters, &debuggers);
+    let name = command.get_name().to_string();
+    // Generated into a buffer, since clap_complete panics on write errors
+    // such as a closed pipe
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, name, &mut script);
+    std::io::stdout().write_all(&script)?;
+    Ok(())
+}
+
+/// Offer `adapters` for every `--adapter` and `debuggers` for `setup`'s
+/// debugger argument, in `command` and all its subcommands
+fn with_values(mut command: Command, adapters: &[String], debuggers: &[String]) -> Command {
+    let has_arg =
+        |command: &Command, id: &str| command.get_arguments().any(|arg| arg.get_id() == id);
+
+    if !adapters.is_empty() && has_arg(&command, "adapter") {
+        command = command.mut_arg("adapter", |arg| {
+            arg.value_parser(PossibleValuesParser::new(adapters.to_vec()))
+        });
+    }
+    if command.get_name() == "setup" && has_arg(&command, "debugger") {
+        command = command.mut_arg("debugger", |arg| {
+            arg.value_parser(PossibleValuesParser::new(debuggers.to_vec()))
+        });
+    }
+
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |subcommand| {
+            with_values(subcommand, adapters, debuggers)
+        });
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    #[test]
+    fn adapter_names_are_offered_in_subcommands() {
+        let command = Command::new("debugger")
+            .subcommand(Command::new("start").arg(Arg::new("adapter").long("adapter")))
+            .subcommand(Command::new("stop"));
+        let command = with_values(command, &["gdb".to_string()], &[]);
+
+        let start = command.find_subcommand("start").unwrap();
+        let adapter = start.get_arguments().find(|arg| arg.get_id() == "adapter").unwrap();
+        let values: Vec<_> = adapter
+            .get_possible_values()
+            .iter()
+            .map(|value| value.get_name().to_string())
+            .collect();
+        assert_eq!(values, vec!["gdb"]);
+    }
+}
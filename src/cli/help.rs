@@ -0,0 +1,191 @@
+//! `debugger help`: help for a command, as text or as Markdown reference
+//!
+//! The Markdown is generated from the live clap definitions, so it always
+//! lists the options and defaults of the binary that prints it.
+
+use clap::{Arg, Command};
+
+use crate::commands::HelpFormat;
+use crate::common::{Error, Result};
+
+/// Print help for the command at `path` (empty for the whole CLI) of the CLI
+/// defined by `command`
+pub fn print(mut command: Command, path: &[String], format: HelpFormat) -> Result<()> {
+    // Building sets each subcommand's full name and propagates global options
+    command.build();
+    let mut target = &mut command;
+    for name in path {
+        let known: Vec<String> = target
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect();
+        target = target.find_subcommand_mut(name).ok_or_else(|| {
+            Error::Config(format!(
+                "Unknown command '{}'. Known commands: {}",
+                name,
+                known.join(", ")
+            ))
+        })?;
+    }
+
+    match format {
+        HelpFormat::Text => target.print_long_help()?,
+        HelpFormat::Markdown => print!("{}", markdown(target)),
+    }
+    Ok(())
+}
+
+/// Markdown reference for `command` and all its subcommands
+fn markdown(command: &Command) -> String {
+    let mut out = String::new();
+    write_command(&mut out, command, true);
+    out
+}
+
+fn write_command(out: &mut String, command: &Command, top: bool) {
+    let name = command.get_bin_name().unwrap_or(command.get_name());
+    out.push_str(&format!("{} `{}`\n\n", if top { "#" } else { "##" }, name));
+
+    if let Some(about) = command.get_long_about().or(command.get_about()) {
+        out.push_str(&format!("{}\n\n", about.to_string().trim_end()));
+    }
+    let aliases: Vec<String> = command
+        .get_visible_aliases()
+        .map(|alias| format!("`{}`", alias))
+        .collect();
+    if !aliases.is_empty() {
+        out.push_str(&format!("Aliases: {}\n\n", aliases.join(", ")));
+    }
+    let usage = command.clone().render_usage().to_string();
+    let usage = usage.trim().trim_start_matches("Usage:").trim();
+    out.push_str(&format!("Usage: `{}`\n\n", usage));
+
+    // Global options are listed once, on the top-level command
+    let arguments: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set() && (top || !arg.is_global_set()))
+        .filter(|arg| !matches!(arg.get_id().as_str(), "help" | "version"))
+        .collect();
+    write_arguments(out, "Arguments", arguments.iter().filter(|arg| arg.is_positional()));
+    write_arguments(out, "Options", arguments.iter().filter(|arg| !arg.is_positional()));
+
+    let subcommands: Vec<&Command> = command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .collect();
+    if !subcommands.is_empty() {
+        out.push_str("Commands:\n\n");
+        for subcommand in &subcommands {
+            let about = subcommand.get_about().map(|about| about.to_string());
+            out.push_str(&format!(
+                "- `{}`: {}\n",
+                subcommand.get_name(),
+                about.unwrap_or_default()
+            ));
+        }
+        out.push('\n');
+    }
+    for subcommand in subcommands {
+        write_command(out, subcommand, false);
+    }
+}
+
+fn write_arguments<'a>(
+    out: &mut String,
+    title: &str,
+    arguments: impl Iterator<Item = &'a &'a Arg>,
+) {
+    let lines: Vec<String> = arguments.map(|arg| format!("- {}", describe(arg))).collect();
+    if !lines.is_empty() {
+        out.push_str(&format!("{}:\n\n{}\n\n", title, lines.join("\n")));
+    }
+}
+
+/// One argument as `` `--name <VALUE>` (required): help. Default: `x`. Values: ... ``
+fn describe(arg: &Arg) -> String {
+    let value = match arg.get_value_names() {
+        Some(names) => names
+            .iter()
+            .map(|name| format!("<{}>", name))
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => format!("<{}>", arg.get_id()),
+    };
+    let mut name = if arg.is_positional() {
+        value
+    } else {
+        let mut flags = Vec::new();
+        if let Some(short) = arg.get_short() {
+            flags.push(format!("-{}", short));
+        }
+        if let Some(long) = arg.get_long() {
+            flags.push(format!("--{}", long));
+        }
+        let mut name = flags.join(", ");
+        if arg.get_action().takes_values() {
+            name = format!("{} {}", name, value);
+        }
+        name
+    };
+    name = format!("`{}`", name);
+    if arg.is_required_set() {
+        name.push_str(" (required)");
+    }
+
+    let mut text = name;
+    if let Some(help) = arg.get_long_help().or(arg.get_help()) {
+        // Help text wraps over lines in the source; keep each entry on one
+        let help = help.to_string();
+        let help: Vec<&str> = help.split_whitespace().collect();
+        text.push_str(&format!(": {}", help.join(" ")));
+    }
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|value| format!("`{}`", value.to_string_lossy()))
+        .collect();
+    if !defaults.is_empty() && arg.get_action().takes_values() {
+        text.push_str(&format!(". Default: {}", defaults.join(", ")));
+    }
+    let values: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| format!("`{}`", value.get_name()))
+        .collect();
+    if !values.is_empty() && arg.get_action().takes_values() {
+        text.push_str(&format!(". Values: {}", values.join(", ")));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_lists_commands_options_and_defaults() {
+        let mut command = Command::new("debugger")
+            .about("LLM-friendly debugger CLI")
+            .subcommand(
+                Command::new("backtrace")
+                    .about("Show the stack trace")
+                    .visible_alias("bt")
+                    .arg(
+                        Arg::new("limit")
+                            .long("limit")
+                            .help("Frames to show")
+                            .default_value("20"),
+                    ),
+            );
+        command.build();
+        let text = markdown(&command);
+
+        assert!(text.starts_with("# `debugger`\n\nLLM-friendly debugger CLI\n\n"));
+        assert!(text.contains("- `backtrace`: Show the stack trace\n"));
+        assert!(text.contains("## `debugger backtrace`\n"));
+        assert!(text.contains("Aliases: `bt`\n"));
+        assert!(text.contains("- `--limit <limit>`: Frames to show. Default: `20`\n"));
+    }
+}
@@ -2,32 +2,129 @@
 //!
 //! Dispatches CLI commands to the daemon and formats output.
 
+mod adapters;
+pub mod agent;
+pub mod completions;
+pub mod help;
+mod run;
 pub mod spawn;
+mod watch;
 
-use crate::commands::{BreakpointCommands, Commands};
-use crate::common::{Error, Result};
+use crate::commands::{
+    parse, AnalyzeCommands, BreakpointCommands, Commands, Endian, MemoryFormat, ThreadCommands,
+    WatchCommands,
+};
+use crate::common::{config::Config, paths, signal, Error, Result};
 use crate::ipc::protocol::{
-    BreakpointInfo, BreakpointLocation, Command, ContextResult, EvaluateContext, EvaluateResult,
-    StackFrameInfo, StatusResult, StopResult, ThreadInfo, VariableInfo,
+    BreakpointDetails, BreakpointInfo, BreakpointLocation, Command, ContextResult, DeadlockReport,
+    EvaluateContext, EvaluateResult, ExceptionInfo, FrameEvaluation, InstructionInfo,
+    MemoryResult, ModuleInfo, OutputLimits, ProgressInfo, RegistersResult, StackFrameInfo,
+    StatusResult, StepKind, StopResult, ThreadInfo, TriageResult, VariableInfo, WatchInfo,
 };
-use crate::ipc::DaemonClient;
+use crate::ipc::{schema, DaemonClient};
 use crate::setup;
 use crate::testing;
 
+/// How command results are rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Readable, labelled output
+    #[default]
+    Human,
+    /// Terse one-record-per-line output for LLM agents (see `agent.rs`)
+    Agent {
+        /// Include variable types
+        types: bool,
+    },
+}
+
+/// Dispatch a CLI command without a daemon (`--no-daemon`)
+///
+/// Only self-contained commands can run this way, since the session ends
+/// when the command does.
+pub async fn dispatch_local(command: Commands, mode: OutputMode) -> Result<()> {
+    match command {
+        Commands::Run { options } => run::run(run::Backend::local()?, options, mode).await,
+        Commands::BuildRun { build, options } => {
+            run::build(&build, mode).await?;
+            run::run(run::Backend::local()?, options, mode).await
+        }
+        _ => Err(Error::Config(
+            "--no-daemon only supports the 'run' and 'build-run' commands".to_string(),
+        )),
+    }
+}
+
 /// Dispatch a CLI command
-pub async fn dispatch(command: Commands) -> Result<()> {
+///
+/// Without a daemon there is no session either, so a command that needs one
+/// fails the same way whether or not the daemon is running. Other commands
+/// report the missing daemon as it is.
+pub async fn dispatch(command: Commands, mode: OutputMode) -> Result<()> {
+    let needs_session = needs_session(&command);
+    dispatch_command(command, mode).await.map_err(|e| match e {
+        Error::DaemonNotRunning if needs_session => Error::SessionNotActive,
+        e => e,
+    })
+}
+
+/// Whether `command` inspects or controls a debug session, so it can't do
+/// anything without one
+fn needs_session(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Breakpoint(_)
+            | Commands::Break { .. }
+            | Commands::Watch(_)
+            | Commands::Continue { .. }
+            | Commands::Next { .. }
+            | Commands::Step { .. }
+            | Commands::Finish { .. }
+            | Commands::StepUntil { .. }
+            | Commands::Pause
+            | Commands::Backtrace { .. }
+            | Commands::Locals { .. }
+            | Commands::Print { .. }
+            | Commands::Eval { .. }
+            | Commands::EvalAll { .. }
+            | Commands::Context { .. }
+            | Commands::Triage { .. }
+            | Commands::Memory { .. }
+            | Commands::Disassemble { .. }
+            | Commands::Registers
+            | Commands::Threads { .. }
+            | Commands::Analyze(_)
+            | Commands::Modules
+            | Commands::Thread { .. }
+            | Commands::Frame { .. }
+            | Commands::Up
+            | Commands::Down
+            | Commands::Await { .. }
+            | Commands::Output { .. }
+            | Commands::Detach
+            | Commands::Restart
+    )
+}
+
+async fn dispatch_command(command: Commands, mode: OutputMode) -> Result<()> {
     match command {
-        Commands::Daemon => {
+        Commands::Daemon { .. } => {
             // Should never happen - daemon mode is handled in main
             unreachable!("Daemon command should be handled in main")
         }
 
+        Commands::Completions { .. } | Commands::Help { .. } => {
+            // Generated in main, which has the full clap definition
+            unreachable!("Completions and help should be handled in main")
+        }
+
         Commands::Start {
             program,
             args,
             adapter,
             stop_on_entry,
             initial_breakpoints,
+            keep_alive,
         } => {
             spawn::ensure_daemon_running().await?;
             let mut client = DaemonClient::connect().await?;
@@ -35,18 +132,27 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             let program = program.canonicalize().unwrap_or(program);
 
             let has_initial_breakpoints = !initial_breakpoints.is_empty();
-
-            let _result = client
-                .send_command(Command::Start {
-                    program: program.clone(),
-                    args,
-                    adapter,
-                    stop_on_entry,
-                    initial_breakpoints: initial_breakpoints.clone(),
-                })
-                .await?;
+            let adapter_selected = adapter.is_none();
+
+            let result = with_progress(client.send_command(Command::Start {
+                program: program.clone(),
+                args,
+                adapter,
+                stop_on_entry,
+                initial_breakpoints: initial_breakpoints.clone(),
+                keep_alive_minutes: keep_alive,
+            }))
+            .await?;
 
             println!("Started debugging: {}", program.display());
+            if adapter_selected {
+                if let Some(adapter) = result["adapter"].as_str() {
+                    println!("Using adapter: {}", adapter);
+                }
+            }
+            if let Some(log) = result["log"].as_str() {
+                println!("Session log: {}", log);
+            }
 
             if has_initial_breakpoints {
                 println!("Set {} initial breakpoint(s)", initial_breakpoints.len());
@@ -63,13 +169,42 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             Ok(())
         }
 
-        Commands::Attach { pid, adapter } => {
+        Commands::Run { options } => run::run(run::Backend::daemon().await?, options, mode).await,
+        Commands::BuildRun { build, options } => {
+            run::build(&build, mode).await?;
+            run::run(run::Backend::daemon().await?, options, mode).await
+        }
+
+        Commands::Attach {
+            pid,
+            adapter,
+            resume,
+            keep_alive,
+        } => {
             spawn::ensure_daemon_running().await?;
             let mut client = DaemonClient::connect().await?;
 
-            client.send_command(Command::Attach { pid, adapter }).await?;
+            // Without --resume, clap has required a pid
+            let command = match pid {
+                Some(pid) if !resume => Command::Attach {
+                    pid,
+                    adapter,
+                    keep_alive_minutes: keep_alive,
+                },
+                pid => Command::Reattach {
+                    pid,
+                    keep_alive_minutes: keep_alive,
+                },
+            };
+            let result = with_progress(client.send_command(command)).await?;
 
-            println!("Attached to process {}", pid);
+            println!("Attached to process {}", result["pid"]);
+            if let Some(breakpoints) = result["breakpoints"].as_u64() {
+                println!("Restored {} breakpoint(s)", breakpoints);
+            }
+            if let Some(log) = result["log"].as_str() {
+                println!("Session log: {}", log);
+            }
             println!("Program is stopped. Use 'debugger continue' to run.");
 
             Ok(())
@@ -80,6 +215,7 @@ pub async fn dispatch(command: Commands) -> Result<()> {
                 location,
                 condition,
                 hit_count,
+                on_hit,
             } => {
                 let mut client = DaemonClient::connect().await?;
                 let loc = BreakpointLocation::parse(&location)?;
@@ -89,11 +225,12 @@ pub async fn dispatch(command: Commands) -> Result<()> {
                         location: loc,
                         condition,
                         hit_count,
+                        on_hit: split_on_hit_commands(on_hit.as_deref()),
                     })
                     .await?;
 
                 let info: BreakpointInfo = serde_json::from_value(result)?;
-                print_breakpoint_added(&info);
+                print_breakpoint_added(&info, mode);
 
                 Ok(())
             }
@@ -133,6 +270,18 @@ pub async fn dispatch(command: Commands) -> Result<()> {
                 Ok(())
             }
 
+            BreakpointCommands::Info { id } => {
+                let mut client = DaemonClient::connect().await?;
+                let result = client.send_command(Command::BreakpointInfo { id }).await?;
+                let details: BreakpointDetails = serde_json::from_value(result)?;
+                if let OutputMode::Agent { .. } = mode {
+                    println!("{}", agent::format_breakpoint_details(&details));
+                } else {
+                    print_breakpoint_details(&details);
+                }
+                Ok(())
+            }
+
             BreakpointCommands::Enable { id } => {
                 let mut client = DaemonClient::connect().await?;
                 client
@@ -152,10 +301,61 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             }
         },
 
+        Commands::Watch(watch_cmd) => match watch_cmd {
+            WatchCommands::Add {
+                expression,
+                function,
+            } => {
+                let mut client = DaemonClient::connect().await?;
+                let result = client
+                    .send_command(Command::WatchAdd {
+                        expression,
+                        function,
+                    })
+                    .await?;
+
+                let watch: WatchInfo = serde_json::from_value(result)?;
+                println!("Watch {}: {}", watch.id, format_watch(&watch));
+                Ok(())
+            }
+
+            WatchCommands::Remove { id, all } => {
+                if id.is_none() && !all {
+                    return Err(Error::Config("Must specify a watch ID or --all".to_string()));
+                }
+                let mut client = DaemonClient::connect().await?;
+                let id = if all { None } else { id };
+                client.send_command(Command::WatchRemove { id }).await?;
+
+                match id {
+                    Some(id) => println!("Watch {} removed", id),
+                    None => println!("All watches removed"),
+                }
+                Ok(())
+            }
+
+            WatchCommands::List => {
+                let mut client = DaemonClient::connect().await?;
+                let result = client.send_command(Command::WatchList).await?;
+                let watches: Vec<WatchInfo> = serde_json::from_value(result["watches"].clone())?;
+
+                if watches.is_empty() {
+                    println!("No watches set");
+                } else {
+                    println!("Watches:");
+                    for watch in &watches {
+                        println!("  {} {}", watch.id, format_watch(watch));
+                    }
+                }
+                Ok(())
+            }
+        },
+
         Commands::Break {
             location,
             condition,
             hit_count,
+            on_hit,
         } => {
             // Shorthand for breakpoint add
             let mut client = DaemonClient::connect().await?;
@@ -166,75 +366,147 @@ pub async fn dispatch(command: Commands) -> Result<()> {
                     location: loc,
                     condition,
                     hit_count,
+                    on_hit: split_on_hit_commands(on_hit.as_deref()),
                 })
                 .await?;
 
             let info: BreakpointInfo = serde_json::from_value(result)?;
-            print_breakpoint_added(&info);
+            print_breakpoint_added(&info, mode);
 
             Ok(())
         }
 
-        Commands::Continue => {
+        Commands::Continue { this_thread } => {
             let mut client = DaemonClient::connect().await?;
-            client.send_command(Command::Continue).await?;
-            println!("Continuing execution...");
-            Ok(())
-        }
-
-        Commands::Next => {
-            let mut client = DaemonClient::connect().await?;
-            client.send_command(Command::Next).await?;
-            println!("Stepping over...");
+            let result = client.send_command(Command::Continue { this_thread }).await?;
+            if result["all_threads_continued"].as_bool().unwrap_or(true) {
+                print_resumed("Continuing execution...", mode);
+            } else {
+                match mode {
+                    OutputMode::Human => println!("Continuing (this thread only)"),
+                    OutputMode::Agent { .. } => println!("OK all_threads_continued=false"),
+                }
+            }
             Ok(())
         }
 
-        Commands::Step => {
+        Commands::Next {
+            count,
+            timeout,
+            this_thread,
+        } => step(StepKind::Over, this_thread, count, timeout, "Stepping over...", mode).await,
+
+        Commands::Step {
+            count,
+            timeout,
+            this_thread,
+        } => step(StepKind::In, this_thread, count, timeout, "Stepping into...", mode).await,
+
+        Commands::Finish {
+            count,
+            timeout,
+            this_thread,
+        } => step(StepKind::Out, this_thread, count, timeout, "Stepping out...", mode).await,
+
+        Commands::StepUntil {
+            expression,
+            max_steps,
+            timeout,
+            into,
+            this_thread,
+        } => {
             let mut client = DaemonClient::connect().await?;
-            client.send_command(Command::StepIn).await?;
-            println!("Stepping into...");
-            Ok(())
-        }
+            let mut result = client
+                .send_command(Command::StepUntil {
+                    expression: expression.clone(),
+                    kind: if into { StepKind::In } else { StepKind::Over },
+                    max_steps,
+                    timeout_secs: timeout,
+                    this_thread,
+                })
+                .await?;
 
-        Commands::Finish => {
-            let mut client = DaemonClient::connect().await?;
-            client.send_command(Command::StepOut).await?;
-            println!("Stepping out...");
-            Ok(())
+            let steps = result["steps"].as_u64().unwrap_or(0);
+            let initial = result["initial"].as_str().unwrap_or("");
+            let value = result["value"].as_str().unwrap_or("");
+            let changed = result["changed"].as_bool().unwrap_or(false);
+            match (mode, changed) {
+                (OutputMode::Agent { .. }, true) => {
+                    println!("STEPS {}/{}", steps, max_steps);
+                    println!("CHANGED {} {} => {}", expression, initial, value);
+                }
+                (OutputMode::Agent { .. }, false) => {
+                    println!("STEPS {}/{}", steps, max_steps);
+                    println!("UNCHANGED {} => {}", expression, value);
+                }
+                (OutputMode::Human, true) => println!(
+                    "{} changed after {} steps: {} -> {}",
+                    expression, steps, initial, value
+                ),
+                (OutputMode::Human, false) => println!(
+                    "{} is still {} after {} of {} steps",
+                    expression, value, steps, max_steps
+                ),
+            }
+            print_await_result(result["stop"].take(), mode)
         }
 
         Commands::Pause => {
             let mut client = DaemonClient::connect().await?;
             client.send_command(Command::Pause).await?;
-            println!("Pausing execution...");
+            print_resumed("Pausing execution...", mode);
             Ok(())
         }
 
-        Commands::Backtrace { limit, locals } => {
+        Commands::Backtrace {
+            limit,
+            locals,
+            user_only,
+        } => {
             let mut client = DaemonClient::connect().await?;
 
             let result = client
                 .send_command(Command::StackTrace {
                     thread_id: None,
                     limit,
+                    user_only,
                 })
                 .await?;
 
             let frames: Vec<StackFrameInfo> = serde_json::from_value(result["frames"].clone())?;
+            let hidden = result["hidden"].as_u64().unwrap_or(0);
 
-            if frames.is_empty() {
+            if frames.is_empty() && hidden == 0 {
                 println!("No stack frames");
             } else {
-                for (i, frame) in frames.iter().enumerate() {
-                    let source = frame.source.as_deref().unwrap_or("?");
-                    let line = frame.line.map(|l| l.to_string()).unwrap_or_else(|| "?".to_string());
-                    println!("#{} {} at {}:{}", i, frame.name, source, line);
+                for (position, frame) in frames.iter().enumerate() {
+                    // Filtered frames keep their stack numbers, for `frame N`
+                    let i = frame.index.unwrap_or(position);
+                    if let OutputMode::Agent { .. } = mode {
+                        println!("{}", agent::format_frame(i, frame));
+                    } else if frame.presentation_hint.as_deref() == Some("label") {
+                        println!("#{} ---- {} ----", i, frame.name);
+                    } else {
+                        let source = frame.source.as_deref().unwrap_or("?");
+                        let line =
+                            frame.line.map(|l| l.to_string()).unwrap_or_else(|| "?".to_string());
+                        let subtle = if frame.presentation_hint.as_deref() == Some("subtle") {
+                            " (subtle)"
+                        } else {
+                            ""
+                        };
+                        println!("#{} {} at {}:{}{}", i, frame.name, source, line, subtle);
+                    }
 
                     if locals {
                         // Get locals for this frame
                         let locals_result = client
                             .send_command(Command::Locals {
                                 frame_id: Some(frame.id),
+                                frame_index: None,
+                                max_string_length: None,
+                                no_truncate: false,
+                                no_cache: false,
                             })
                             .await;
 
@@ -243,83 +515,123 @@ pub async fn dispatch(command: Commands) -> Result<()> {
                                 serde_json::from_value::<Vec<VariableInfo>>(result["variables"].clone())
                             {
                                 for var in vars {
-                                    println!(
-                                        "    {} = {}{}",
-                                        var.name,
-                                        var.value,
-                                        var.type_name
-                                            .map(|t| format!(" ({})", t))
-                                            .unwrap_or_default()
-                                    );
+                                    if let OutputMode::Agent { types } = mode {
+                                        println!("  {}", agent::format_variable(&var, types));
+                                        continue;
+                                    }
+                                    println!("    {}", format_variable(&var));
                                 }
                             }
                         }
                     }
                 }
+                if hidden > 0 {
+                    match mode {
+                        OutputMode::Agent { .. } => println!("HIDDEN {}", hidden),
+                        OutputMode::Human => println!("({} library frames hidden)", hidden),
+                    }
+                }
             }
 
             Ok(())
         }
 
-        Commands::Locals => {
+        Commands::Locals {
+            frame,
+            max_string_length,
+            no_truncate,
+            no_cache,
+        } => {
             let mut client = DaemonClient::connect().await?;
 
             let result = client
-                .send_command(Command::Locals { frame_id: None })
+                .send_command(Command::Locals {
+                    frame_id: None,
+                    frame_index: frame,
+                    max_string_length,
+                    no_truncate,
+                    no_cache,
+                })
                 .await?;
 
             let vars: Vec<VariableInfo> = serde_json::from_value(result["variables"].clone())?;
+            let note = result["note"].as_str();
 
-            if vars.is_empty() {
-                println!("No local variables");
+            if let OutputMode::Agent { types } = mode {
+                for var in &vars {
+                    println!("{}", agent::format_variable(var, types));
+                }
+                if let Some(note) = note {
+                    println!("({})", note);
+                }
+            } else if vars.is_empty() {
+                println!("({})", note.unwrap_or("no local variables"));
             } else {
                 println!("Local variables:");
                 for var in &vars {
-                    println!(
-                        "  {} = {}{}",
-                        var.name,
-                        var.value,
-                        var.type_name
-                            .as_ref()
-                            .map(|t| format!(" ({})", t))
-                            .unwrap_or_default()
-                    );
+                    println!("  {}", format_variable(var));
                 }
             }
 
             Ok(())
         }
 
-        Commands::Print { expression } => {
+        Commands::Print {
+            expression,
+            frame,
+            max_string_length,
+            no_truncate,
+        } => {
             let mut client = DaemonClient::connect().await?;
 
             let result = client
                 .send_command(Command::Evaluate {
                     expression: expression.clone(),
                     frame_id: None,
+                    frame_index: frame,
                     context: EvaluateContext::Watch,
+                    max_string_length,
+                    no_truncate,
                 })
                 .await?;
 
             let eval: EvaluateResult = serde_json::from_value(result)?;
-            println!(
-                "{} = {}{}",
-                expression,
-                eval.result,
-                eval.type_name.map(|t| format!(" ({})", t)).unwrap_or_default()
-            );
+            print_value(expression, eval, mode);
 
             Ok(())
         }
 
-        Commands::Eval { expression } => {
+        Commands::Eval {
+            expression,
+            frame,
+            max_string_length,
+            no_truncate,
+            assign,
+        } => {
             let mut client = DaemonClient::connect().await?;
 
+            if assign {
+                let (target, value) = parse::split_assignment(&expression)?;
+                let result = client
+                    .send_command(Command::Assign {
+                        target: target.clone(),
+                        value,
+                        frame_index: frame,
+                    })
+                    .await?;
+                let eval: EvaluateResult = serde_json::from_value(result)?;
+                print_value(target, eval, mode);
+                return Ok(());
+            }
+
             let result = client
                 .send_command(Command::Evaluate {
                     expression: expression.clone(),
                     frame_id: None,
+                    frame_index: frame,
                     context: EvaluateContext::Repl,
+                    max_string_length,
+                    no_truncate,
                 })
                 .await?;
 
@@ -329,6 +641,41 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             Ok(())
         }
 
+        Commands::EvalAll { expression, limit } => {
+            let mut client = DaemonClient::connect().await?;
+            let result = client
+                .send_command(Command::EvaluateAll { expression, limit })
+                .await?;
+            let frames: Vec<FrameEvaluation> = serde_json::from_value(result["frames"].clone())?;
+
+            if frames.is_empty() {
+                println!("No stack frames");
+            }
+            for frame in &frames {
+                let error = frame.error.as_deref().unwrap_or("unknown");
+                if let OutputMode::Agent { .. } = mode {
+                    let value = match &frame.result {
+                        Some(result) => format!("={}", result),
+                        None => format!("ERROR {}", error),
+                    };
+                    println!("#{} {} {}", frame.index, frame.function, value);
+                    continue;
+                }
+                let value = match &frame.result {
+                    Some(result) => result.clone(),
+                    None => format!("<error: {}>", error),
+                };
+                let source = frame.source.as_deref().unwrap_or("?");
+                let line = frame.line.map(|l| l.to_string()).unwrap_or_else(|| "?".to_string());
+                println!(
+                    "#{} {} at {}:{} = {}",
+                    frame.index, frame.function, source, line, value
+                );
+            }
+
+            Ok(())
+        }
+
         Commands::Context { lines } => {
             let mut client = DaemonClient::connect().await?;
 
@@ -336,49 +683,148 @@ pub async fn dispatch(command: Commands) -> Result<()> {
 
             let ctx: ContextResult = serde_json::from_value(result)?;
 
-            // Print header
-            if let Some(source) = &ctx.source {
-                println!(
-                    "Thread {} stopped at {}:{}",
-                    ctx.thread_id, source, ctx.line
-                );
-            }
-            if let Some(func) = &ctx.function {
-                println!("In function: {}", func);
+            if let OutputMode::Agent { types } = mode {
+                println!("{}", agent::format_context(&ctx, types));
+                return Ok(());
             }
-            println!();
+            print_context(&ctx);
+
+            Ok(())
+        }
+
+        Commands::Triage { limit, lines } => {
+            let mut client = DaemonClient::connect().await?;
+
+            let result = client.send_command(Command::Triage { limit, lines }).await?;
+            let triage: TriageResult = serde_json::from_value(result)?;
 
-            // Print source with line numbers
-            for line in &ctx.source_lines {
-                let marker = if line.is_current { "->" } else { "  " };
-                println!("{} {:>4} | {}", marker, line.number, line.content);
+            if let OutputMode::Agent { types } = mode {
+                println!("{}", agent::format_stop(&triage.stop));
+                for (position, frame) in triage.frames.iter().enumerate() {
+                    println!("{}", agent::format_frame(frame.index.unwrap_or(position), frame));
+                }
+                if let Some(ctx) = &triage.context {
+                    println!("{}", agent::format_context(ctx, types));
+                }
+                for note in &triage.notes {
+                    println!("NOTE {}", note);
+                }
+                return Ok(());
             }
 
-            // Print locals
-            if !ctx.locals.is_empty() {
+            print_stop_result(&triage.stop);
+            if !triage.frames.is_empty() {
                 println!();
-                println!("Locals:");
-                for var in &ctx.locals {
+                println!("Backtrace:");
+                for (position, frame) in triage.frames.iter().enumerate() {
+                    let source = frame.source.as_deref().unwrap_or("?");
+                    let line = frame.line.map(|l| l.to_string()).unwrap_or_else(|| "?".to_string());
                     println!(
-                        "  {} = {}{}",
-                        var.name,
-                        var.value,
-                        var.type_name
-                            .as_ref()
-                            .map(|t| format!(" ({})", t))
-                            .unwrap_or_default()
+                        "  #{} {} at {}:{}",
+                        frame.index.unwrap_or(position),
+                        frame.name,
+                        source,
+                        line
                     );
                 }
             }
+            if let Some(ctx) = &triage.context {
+                println!();
+                print_context(ctx);
+            }
+            for note in &triage.notes {
+                println!("Note: {}", note);
+            }
 
             Ok(())
         }
 
-        Commands::Threads => {
+        Commands::Memory {
+            address,
+            var,
+            count,
+            format,
+            width,
+            endian,
+        } => {
             let mut client = DaemonClient::connect().await?;
 
-            let result = client.send_command(Command::Threads).await?;
+            let command = match (address, var) {
+                (_, Some(name)) => Command::ReadVariableMemory { name, count },
+                (Some(address), None) => Command::ReadMemory { address, count },
+                (None, None) => unreachable!("clap requires an address or --var"),
+            };
+            let result = client.send_command(command).await?;
+
+            let mem: MemoryResult = serde_json::from_value(result)?;
+            print_memory(&mem, format, width, endian);
+
+            Ok(())
+        }
+
+        Commands::Disassemble {
+            address,
+            count,
+            source,
+        } => {
+            let mut client = DaemonClient::connect().await?;
+
+            let result = client
+                .send_command(Command::Disassemble {
+                    address,
+                    count,
+                    source,
+                })
+                .await?;
+
+            let instructions: Vec<InstructionInfo> =
+                serde_json::from_value(result["instructions"].clone())?;
+
+            if instructions.is_empty() {
+                println!("No instructions");
+            } else {
+                print_disassembly(&instructions, source);
+            }
+
+            Ok(())
+        }
+
+        Commands::Registers => {
+            let mut client = DaemonClient::connect().await?;
+            let result = client.send_command(Command::Registers).await?;
+            let registers: RegistersResult = serde_json::from_value(result)?;
+            if matches!(mode, OutputMode::Agent { .. }) {
+                println!("{}", agent::format_registers(&registers));
+                return Ok(());
+            }
+
+            let unavailable = "<unavailable>".to_string();
+            println!("pc  {}", registers.pc.as_ref().unwrap_or(&unavailable));
+            println!("sp  {}", registers.sp.as_ref().unwrap_or(&unavailable));
+            println!("fp  {}", registers.fp.as_ref().unwrap_or(&unavailable));
+            if let Some(reference) = &registers.instruction_reference {
+                println!("instruction reference: {}", reference);
+            }
+
+            Ok(())
+        }
+
+        Commands::Threads {
+            limit,
+            filter,
+            refresh,
+        } => {
+            let mut client = DaemonClient::connect().await?;
+
+            let result = client
+                .send_command(Command::Threads {
+                    limit,
+                    filter,
+                    refresh,
+                })
+                .await?;
             let threads: Vec<ThreadInfo> = serde_json::from_value(result["threads"].clone())?;
+            let total = result["total"].as_u64().unwrap_or(threads.len() as u64);
 
             if threads.is_empty() {
                 println!("No threads");
@@ -387,12 +833,106 @@ pub async fn dispatch(command: Commands) -> Result<()> {
                 for thread in &threads {
                     println!("  {} - {}", thread.id, thread.name);
                 }
+                if result["truncated"].as_bool().unwrap_or(false) {
+                    println!(
+                        "  ... {} more of {} (raise --limit or narrow with --filter)",
+                        total - threads.len() as u64,
+                        total
+                    );
+                }
             }
 
             Ok(())
         }
 
-        Commands::Thread { id } => {
+        Commands::Analyze(AnalyzeCommands::Deadlock { frames }) => {
+            let mut client = DaemonClient::connect().await?;
+
+            let result = client.send_command(Command::AnalyzeDeadlock { frames }).await?;
+            let report: DeadlockReport = serde_json::from_value(result)?;
+            if let OutputMode::Agent { .. } = mode {
+                println!("{}", agent::format_deadlock(&report));
+                return Ok(());
+            }
+
+            println!(
+                "Examined {} threads; {} blocked",
+                report.threads,
+                report.blocked.len()
+            );
+            for thread in &report.blocked {
+                let caller = match &thread.waiting_in {
+                    Some(frame) => format!(
+                        ", called from {} at {}:{}",
+                        frame.name,
+                        frame.source.as_deref().unwrap_or("?"),
+                        frame.line.map(|l| l.to_string()).unwrap_or_else(|| "?".to_string())
+                    ),
+                    None => String::new(),
+                };
+                println!(
+                    "  Thread {} ({}) waits in {}{}",
+                    thread.id, thread.name, thread.wait_function, caller
+                );
+            }
+            if report.cycles.is_empty() {
+                println!("No threads found waiting on each other");
+            } else {
+                println!("Potential deadlocks (threads that may be waiting on each other):");
+                for cycle in &report.cycles {
+                    let mut ids: Vec<String> = cycle.iter().map(i64::to_string).collect();
+                    ids.push(ids[0].clone());
+                    println!("  {}", ids.join(" -> "));
+                }
+            }
+            if !report.skipped.is_empty() {
+                let ids: Vec<String> = report.skipped.iter().map(i64::to_string).collect();
+                println!("No stack for threads: {}", ids.join(", "));
+            }
+
+            Ok(())
+        }
+
+        Commands::Modules => {
+            let mut client = DaemonClient::connect().await?;
+
+            let result = client.send_command(Command::Modules).await?;
+            let modules: Vec<ModuleInfo> = serde_json::from_value(result["modules"].clone())?;
+
+            if let OutputMode::Agent { .. } = mode {
+                for module in &modules {
+                    println!("{}", agent::format_module(module));
+                }
+            } else if modules.is_empty() {
+                println!("No modules reported by the adapter");
+            } else {
+                println!("Modules:");
+                for module in &modules {
+                    match &module.path {
+                        Some(path) => println!("  {} - {} ({})", module.id, module.name, path),
+                        None => println!("  {} - {}", module.id, module.name),
+                    }
+                }
+            }
+
+            Ok(())
+        }
+
+        Commands::Thread {
+            action: Some(ThreadCommands::Kill { ids }),
+            ..
+        } => {
+            let mut client = DaemonClient::connect().await?;
+            client
+                .send_command(Command::TerminateThreads { ids: ids.clone() })
+                .await?;
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            println!("Terminated thread(s) {}", ids.join(", "));
+
+            Ok(())
+        }
+
+        Commands::Thread { id, action: None } => {
             let mut client = DaemonClient::connect().await?;
 
             if let Some(id) = id {
@@ -443,7 +983,45 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             Ok(())
         }
 
-        Commands::Await { timeout } => {
+        Commands::Await {
+            timeout,
+            background: true,
+            notify: Some(path),
+        } => {
+            let mut client = DaemonClient::connect().await?;
+            // The daemon runs elsewhere, so it needs the full path
+            let path = std::path::absolute(&path)?;
+            client
+                .send_command(Command::AwaitNotify {
+                    timeout_secs: timeout,
+                    path: path.clone(),
+                })
+                .await?;
+            println!(
+                "Waiting in the background (timeout: {}s); the result will be written to {}",
+                timeout,
+                path.display()
+            );
+            Ok(())
+        }
+
+        Commands::CancelAwait => {
+            let mut client = DaemonClient::connect().await?;
+            let result = client.send_command(Command::CancelAwait).await?;
+            let cancelled = result["cancelled"].as_u64().unwrap_or(0);
+            println!("Cancelled {} await(s)", cancelled);
+            Ok(())
+        }
+
+        Commands::CancelEval => {
+            let mut client = DaemonClient::connect().await?;
+            let result = client.send_command(Command::CancelEval).await?;
+            let cancelled = result["cancelled"].as_u64().unwrap_or(0);
+            println!("Cancelled {} evaluation(s)", cancelled);
+            Ok(())
+        }
+
+        Commands::Await { timeout, .. } => {
             let mut client = DaemonClient::connect().await?;
 
             println!("Waiting for program to stop (timeout: {}s)...", timeout);
@@ -458,26 +1036,44 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             if result.get("already_stopped").and_then(|v| v.as_bool()).unwrap_or(false) {
                 let reason = result["reason"].as_str().unwrap_or("unknown");
                 println!("Program was already stopped: {}", reason);
-            } else if let Some(reason) = result.get("reason").and_then(|v| v.as_str()) {
-                match reason {
-                    "exited" => {
-                        let code = result["exit_code"].as_i64().unwrap_or(0);
-                        println!("Program exited with code {}", code);
-                    }
-                    "terminated" => {
-                        println!("Program terminated");
-                    }
-                    _ => {
-                        let stop: StopResult = serde_json::from_value(result)?;
-                        print_stop_result(&stop);
-                    }
-                }
+            } else {
+                print_await_result(result, mode)?;
             }
 
             Ok(())
         }
 
-        Commands::Output { follow, tail, clear } => {
+        Commands::Output {
+            follow,
+            raw,
+            tail,
+            clear,
+            set_limit_mb,
+            set_limit_events,
+        } => {
+            if set_limit_mb.is_some() || set_limit_events.is_some() {
+                let mut client = DaemonClient::connect().await?;
+                let result = client
+                    .send_command(Command::SetOutputLimits {
+                        max_bytes_mb: set_limit_mb,
+                        max_events: set_limit_events,
+                    })
+                    .await?;
+                let limits: OutputLimits = serde_json::from_value(result)?;
+                match mode {
+                    OutputMode::Agent { .. } => {
+                        println!("{}", agent::format_output_limits(&limits))
+                    }
+                    OutputMode::Human => println!(
+                        "Output limits: {} MB, {} events ({} bytes in {} events buffered)",
+                        limits.max_bytes / (1024 * 1024),
+                        limits.max_events,
+                        limits.bytes,
+                        limits.events
+                    ),
+                }
+                return Ok(());
+            }
             if follow {
                 use std::io::Write;
 
@@ -488,19 +1084,44 @@ pub async fn dispatch(command: Commands) -> Result<()> {
                 loop {
                     // Clearing after each read turns the daemon's bounded buffer
                     // into a cursor without needing a second streaming protocol.
-                    let result = client
-                        .send_command(Command::GetOutput {
+                    // Raw reads are copied to this connection as they arrive and
+                    // wait for output themselves.
+                    let command = if raw {
+                        Command::FollowOutput { wait_ms: 500 }
+                    } else {
+                        Command::GetOutput {
                             tail: None,
                             clear: true,
-                        })
-                        .await?;
+                        }
+                    };
+                    let result = match client.send_command(command).await {
+                        Ok(result) => result,
+                        // Another client stopped the session
+                        Err(Error::SessionNotActive) => {
+                            print_session_ended(None, mode);
+                            return Ok(());
+                        }
+                        Err(e) => return Err(e),
+                    };
                     let output = result["output"].as_str().unwrap_or("");
                     if !output.is_empty() {
                         print!("{}", output);
                         std::io::stdout().flush()?;
                     }
 
-                    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                    // The last read drained everything the program wrote, so
+                    // the end can be reported right after it
+                    if let Some(ended) = result["session_ended"].as_object() {
+                        let code = ended.get("exit_code").and_then(|c| c.as_i64());
+                        print_session_ended(code, mode);
+                        return Ok(());
+                    }
+
+                    // Raw reads wait in the daemon, but an empty one may
+                    // have returned early, so back off either way
+                    if !raw || output.is_empty() {
+                        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                    }
                 }
             }
 
@@ -519,6 +1140,36 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             Ok(())
         }
 
+        Commands::Events { only } => {
+            use std::io::Write;
+
+            eprintln!("Streaming session events (Ctrl+C to stop)");
+            let mut client = DaemonClient::connect().await?;
+            let mut first = true;
+            loop {
+                let command = Command::FollowEvents {
+                    wait_ms: 500,
+                    only: only.clone(),
+                };
+                let result = match client.send_command(command).await {
+                    Ok(result) => result,
+                    // Another client stopped the session
+                    Err(Error::SessionNotActive) if !first => return Ok(()),
+                    Err(e) => return Err(e),
+                };
+                first = false;
+                if let Some(events) = result["events"].as_array() {
+                    for event in events {
+                        println!("{}", event);
+                    }
+                    std::io::stdout().flush()?;
+                }
+                if !result["session_ended"].is_null() {
+                    return Ok(());
+                }
+            }
+        }
+
         Commands::Status => {
             match DaemonClient::connect().await {
                 Ok(mut client) => {
@@ -558,16 +1209,39 @@ pub async fn dispatch(command: Commands) -> Result<()> {
         }
 
         Commands::Stop => {
-            let mut client = DaemonClient::connect().await?;
-            client.send_command(Command::Stop).await?;
-            println!("Debug session stopped");
+            // Stopping nothing succeeds, so cleanup scripts can stop blindly
+            let stopped = match DaemonClient::connect().await {
+                Ok(mut client) => client.send_command(Command::Stop).await,
+                Err(e) => Err(e),
+            };
+            match stopped {
+                Ok(result) if result["status"] != "not_running" => {
+                    println!("Debug session stopped")
+                }
+                Ok(_) | Err(Error::DaemonNotRunning) => println!("No active session"),
+                Err(e) => return Err(e),
+            }
             Ok(())
         }
 
         Commands::Detach => {
             let mut client = DaemonClient::connect().await?;
-            client.send_command(Command::Detach).await?;
+            let result = client.send_command(Command::Detach).await?;
             println!("Detached from process (process continues running)");
+            if result["resumable"].as_bool().unwrap_or(false) {
+                println!("Use 'debugger attach --resume' to attach again with the same breakpoints");
+            }
+            Ok(())
+        }
+
+        Commands::Reset => {
+            let mut client = DaemonClient::connect().await?;
+            let result = client.send_command(Command::Reset).await?;
+            if result["stopped_session"].as_bool().unwrap_or(false) {
+                println!("Session stopped; daemon is idle");
+            } else {
+                println!("No active session; daemon is idle");
+            }
             Ok(())
         }
 
@@ -578,7 +1252,14 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             Ok(())
         }
 
-        Commands::Logs { lines, follow, clear } => {
+        Commands::WatchRebuild { build, dir } => watch::watch_rebuild(&build, dir, mode).await,
+
+        Commands::Logs {
+            lines,
+            follow,
+            clear,
+            level,
+        } => {
             use crate::common::logging;
 
             let log_path = logging::daemon_log_path();
@@ -612,19 +1293,25 @@ pub async fn dispatch(command: Commands) -> Result<()> {
                         }
                     }
                 } else {
-                    // Read last N lines
-                    let content = std::fs::read_to_string(&path)?;
-                    let all_lines: Vec<&str> = content.lines().collect();
-                    let start = all_lines.len().saturating_sub(lines);
-
-                    println!("Daemon log: {} (last {} lines)", path.display(), lines);
+                    // Read last N lines, including rotated logs if needed
+                    let tail = logging::read_daemon_log(lines, level)?;
+
+                    match level {
+                        Some(level) => println!(
+                            "Daemon log: {} (last {} lines at {} or above)",
+                            path.display(),
+                            lines,
+                            level
+                        ),
+                        None => println!("Daemon log: {} (last {} lines)", path.display(), lines),
+                    }
                     println!("---");
-                    for line in &all_lines[start..] {
+                    for line in &tail {
                         println!("{}", line);
                     }
 
-                    if all_lines.is_empty() {
-                        println!("(log is empty)");
+                    if tail.is_empty() {
+                        println!("(no matching log lines)");
                     }
                 }
             } else {
@@ -639,7 +1326,10 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             version,
             list,
             check,
+            check_updates,
+            repair,
             auto_detect,
+            depth,
             uninstall,
             path,
             force,
@@ -651,7 +1341,10 @@ pub async fn dispatch(command: Commands) -> Result<()> {
                 version,
                 list,
                 check,
+                check_updates,
+                repair,
                 auto_detect,
+                depth,
                 uninstall,
                 path,
                 force,
@@ -661,10 +1354,142 @@ pub async fn dispatch(command: Commands) -> Result<()> {
             setup::run(opts).await
         }
 
-        Commands::Test { path, verbose } => {
-            let result = testing::run_scenario(&path, verbose).await?;
+        Commands::Adapters { features } => adapters::print(features, mode).await,
+
+        Commands::Config { check } => {
+            let path = paths::config_path().ok_or_else(|| {
+                Error::Config("Could not determine the config directory".to_string())
+            })?;
+            if !path.exists() {
+                println!("No config file at {}; defaults are used", path.display());
+                return Ok(());
+            }
+            if !check {
+                println!("{}", path.display());
+                return Ok(());
+            }
+
+            let content = std::fs::read_to_string(&path)?;
+            let problems = Config::check(&content);
+            if problems.is_empty() {
+                println!("✓ {}: no problems found", path.display());
+                return Ok(());
+            }
+
+            println!("✗ {} problem(s) in {}:", problems.len(), path.display());
+            for problem in &problems {
+                println!("  - {}", problem);
+            }
+            std::process::exit(1);
+        }
+
+        Commands::Schema { command } => {
+            let schema = match command {
+                Some(name) => schema::command_schema(&name).ok_or_else(|| {
+                    Error::Config(format!(
+                        "Unknown command '{}'. Known commands: {}",
+                        name,
+                        schema::command_names().join(", ")
+                    ))
+                })?,
+                None => schema::command_schemas(),
+            };
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+            Ok(())
+        }
+
+        Commands::Mcp => crate::mcp::run().await,
+
+        Commands::Replay { path } => {
+            let commands = crate::ipc::client::read_recorded_commands(&path)?;
+            replay(commands, &path.display().to_string()).await
+        }
+
+        Commands::History { replay: rerun } => {
+            let dir = std::env::current_dir()?;
+            let path = paths::history_path(&dir)
+                .ok_or_else(|| Error::Config("No config directory for the history".to_string()))?;
+            let commands = if path.exists() {
+                crate::ipc::client::read_recorded_commands(&path)?
+            } else {
+                Vec::new()
+            };
+            if commands.is_empty() {
+                return Err(Error::Config(format!(
+                    "No command history for {}",
+                    dir.display()
+                )));
+            }
+
+            if rerun {
+                let session = crate::ipc::client::last_session(commands);
+                return replay(session, &format!("the history of {}", dir.display())).await;
+            }
+            let agent = matches!(mode, OutputMode::Agent { .. });
+            for (index, command) in commands.iter().enumerate() {
+                if agent {
+                    println!("{}", parse::format_command(command));
+                } else {
+                    println!("{:>5}  {}", index + 1, parse::format_command(command));
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Exec {
+            script,
+            continue_on_error,
+        } => {
+            // Parse the whole batch up front so a typo doesn't leave it half run
+            let commands = parse::split_script(&script)
+                .into_iter()
+                .map(|text| Ok((text, parse::parse_command(text)?)))
+                .collect::<Result<Vec<_>>>()?;
+
+            spawn::ensure_daemon_running().await?;
+            let mut client = DaemonClient::connect().await?;
+
+            let agent = matches!(mode, OutputMode::Agent { .. });
+            let mut failed = 0;
+            for (text, command) in commands {
+                println!("> {}", text);
+                match client.send_command(command).await {
+                    Ok(result) if agent => println!("{}", result),
+                    Ok(result) => println!("{}", serde_json::to_string_pretty(&result)?),
+                    Err(e) if continue_on_error => {
+                        println!("Error: {}", e);
+                        failed += 1;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            if failed > 0 {
+                // Each failure was already reported above
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+
+        Commands::Test {
+            path,
+            verbose,
+            parallel,
+            shutdown_daemon,
+        } => {
+            if path.is_dir() {
+                let summary = testing::run_suite(&path, parallel as usize, verbose).await?;
+                std::process::exit(if summary.success() { 0 } else { 1 });
+            }
 
-            if result.passed {
+            let result = testing::run_scenario(&path, verbose).await;
+            if shutdown_daemon {
+                if let Ok(mut client) = DaemonClient::connect().await {
+                    let _ = client.send_command(Command::Shutdown).await;
+                }
+            }
+
+            if result?.passed {
                 std::process::exit(0);
             } else {
                 std::process::exit(1);
@@ -673,6 +1498,22 @@ pub async fn dispatch(command: Commands) -> Result<()> {
     }
 }
 
+/// `name = value (type)`, followed by any notes such as `[optimized out]`
+fn format_variable(var: &VariableInfo) -> String {
+    let mut line = format!("{} = {}", var.name, var.value);
+    if let Some(type_name) = &var.type_name {
+        line.push_str(&format!(" ({})", type_name));
+    }
+    let annotations = var.annotations();
+    if !annotations.is_empty() {
+        line.push_str(&format!(" [{}]", annotations.join(", ")));
+    }
+    if let Some(elements) = &var.elements {
+        line.push_str(&format!(" {{{}}}", elements));
+    }
+    line
+}
+
 /// Print the result of a frame navigation command (up/down)
 fn print_frame_nav_result(result: &serde_json::Value) {
     let frame_index = result["selected"].as_u64().unwrap_or(0);
@@ -689,8 +1530,62 @@ fn print_frame_nav_result(result: &serde_json::Value) {
     }
 }
 
-fn print_breakpoint_added(info: &BreakpointInfo) {
-    if info.verified {
+/// Send recorded commands, from `source`, to a fresh session
+async fn replay(commands: Vec<Command>, source: &str) -> Result<()> {
+    spawn::ensure_daemon_running().await?;
+    let mut client = DaemonClient::connect().await?;
+
+    let status: StatusResult =
+        serde_json::from_value(client.send_command(Command::Status).await?)?;
+    if status.session_active {
+        return Err(Error::SessionAlreadyActive);
+    }
+
+    println!("Replaying {} command(s) from {}", commands.len(), source);
+
+    // Keep going after failures: a command that failed in the recorded
+    // session is expected to fail again, and later commands still apply.
+    for command in commands {
+        println!("> {}", serde_json::to_string(&command)?);
+        match client.send_command(command).await {
+            Ok(result) => println!("{}", serde_json::to_string_pretty(&result)?),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Wait for a command that may take a while, e.g. a launch while the adapter
+/// loads symbols, printing the adapter's progress reports to stderr meanwhile
+async fn with_progress<F: std::future::Future>(command: F) -> F::Output {
+    tokio::pin!(command);
+    let mut poll = tokio::time::interval(std::time::Duration::from_millis(500));
+    let mut shown: Option<ProgressInfo> = None;
+    loop {
+        tokio::select! {
+            output = &mut command => return output,
+            _ = poll.tick() => {
+                let progress = match DaemonClient::connect().await {
+                    Ok(mut client) => client.send_command(Command::Progress).await.ok(),
+                    Err(_) => None,
+                };
+                let progress = progress
+                    .and_then(|result| serde_json::from_value(result["progress"].clone()).ok())
+                    .flatten();
+                if let Some(new) = progress.as_ref().filter(|p| Some(*p) != shown.as_ref()) {
+                    eprintln!("{}", new);
+                }
+                shown = progress;
+            }
+        }
+    }
+}
+
+fn print_breakpoint_added(info: &BreakpointInfo, mode: OutputMode) {
+    if let OutputMode::Agent { .. } = mode {
+        println!("{}", agent::format_breakpoint(info));
+    } else if info.verified {
         println!(
             "Breakpoint {} set at {}:{}",
             info.id,
@@ -703,6 +1598,169 @@ fn print_breakpoint_added(info: &BreakpointInfo) {
             info.id,
             info.message.as_ref().map(|m| format!(": {}", m)).unwrap_or_default()
         );
+        if let Some(reason) = &info.reason {
+            println!("  ({})", reason);
+        }
+    }
+    if let (Some(warning), OutputMode::Human) = (&info.warning, mode) {
+        println!("Warning: {}", warning);
+    }
+}
+
+/// Step once and return, or step `count` times and print where the steps
+/// ended up
+async fn step(
+    kind: StepKind,
+    this_thread: bool,
+    count: Option<u32>,
+    timeout: u64,
+    message: &str,
+    mode: OutputMode,
+) -> Result<()> {
+    let mut client = DaemonClient::connect().await?;
+    let Some(count) = count else {
+        client.send_command(kind.command(this_thread)).await?;
+        print_resumed(message, mode);
+        return Ok(());
+    };
+
+    let mut result = client
+        .send_command(Command::StepCount {
+            kind,
+            count,
+            timeout_secs: timeout,
+            this_thread,
+        })
+        .await?;
+    let steps = result["steps"].as_u64().unwrap_or(0);
+    match mode {
+        OutputMode::Agent { .. } => println!("STEPS {}/{}", steps, count),
+        OutputMode::Human if steps < count as u64 => {
+            println!("Stopped after {} of {} steps", steps, count)
+        }
+        OutputMode::Human => println!("Took {} steps", steps),
+    }
+    print_await_result(result["stop"].take(), mode)
+}
+
+/// An await result: a stop, the program ending, or a cancelled wait
+fn print_await_result(result: serde_json::Value, mode: OutputMode) -> Result<()> {
+    let Some(reason) = result.get("reason").and_then(|v| v.as_str()) else {
+        return Ok(());
+    };
+    let agent = matches!(mode, OutputMode::Agent { .. });
+    match reason {
+        "exited" => {
+            let code = result["exit_code"].as_i64().unwrap_or(0);
+            if agent {
+                println!("EXIT {}", code);
+            } else {
+                println!("Program exited with code {}", code);
+            }
+        }
+        "terminated" => {
+            if agent {
+                println!("TERMINATED");
+            } else {
+                println!("Program terminated");
+            }
+        }
+        "cancelled" => {
+            if agent {
+                println!("CANCELLED");
+            } else {
+                println!("Await cancelled; the program is still running");
+            }
+        }
+        _ => {
+            let stop: StopResult = serde_json::from_value(result)?;
+            if agent {
+                println!("{}", agent::format_stop(&stop));
+            } else {
+                print_stop_result(&stop);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `name = value (type)`, or the agent format's variable line
+fn print_value(name: String, eval: EvaluateResult, mode: OutputMode) {
+    if let OutputMode::Agent { types } = mode {
+        let var = VariableInfo {
+            name,
+            value: eval.result,
+            type_name: eval.type_name,
+            variables_reference: eval.variables_reference,
+            presentation_hint: None,
+            elements: None,
+            memory_reference: None,
+        };
+        println!("{}", agent::format_variable(&var, types));
+        return;
+    }
+    println!(
+        "{} = {}{}",
+        name,
+        eval.result,
+        eval.type_name.map(|t| format!(" ({})", t)).unwrap_or_default()
+    );
+}
+
+/// Acknowledge a command that resumed (or is about to pause) the program
+fn print_resumed(message: &str, mode: OutputMode) {
+    match mode {
+        OutputMode::Human => println!("{}", message),
+        OutputMode::Agent { .. } => println!("OK"),
+    }
+}
+
+/// Final line of `output --follow`, matching `run`'s exit lines
+fn print_session_ended(exit_code: Option<i64>, mode: OutputMode) {
+    match (exit_code, mode) {
+        (Some(code), OutputMode::Agent { .. }) => println!("EXIT {}", code),
+        (None, OutputMode::Agent { .. }) => println!("TERMINATED"),
+        (Some(code), OutputMode::Human) => {
+            eprintln!("Session ended: program exited with code {}", code)
+        }
+        (None, OutputMode::Human) => eprintln!("Session ended"),
+    }
+}
+
+fn print_breakpoint_details(details: &BreakpointDetails) {
+    let info = &details.info;
+    let state = match (info.enabled, info.verified) {
+        (false, _) => "disabled",
+        (true, true) => "verified",
+        (true, false) => "pending",
+    };
+    println!("Breakpoint {} ({})", info.id, state);
+    println!("  requested:  {}", details.location);
+    if info.verified {
+        println!(
+            "  resolved:   {}{}",
+            info.source.as_deref().unwrap_or("?"),
+            info.line.map(|line| format!(":{}", line)).unwrap_or_default()
+        );
+    }
+    println!("  hits:       {}", details.hits);
+    if let Some(condition) = &info.condition {
+        println!("  condition:  {}", condition);
+    }
+    if let Some(hit_count) = info.hit_count {
+        println!("  hit count:  break after {} hits", hit_count);
+    }
+    for command in &info.on_hit {
+        println!("  on hit:     {}", command);
+    }
+    if let Some(adapter_id) = details.adapter_id {
+        println!("  adapter id: {}", adapter_id);
+    }
+    if let Some(message) = &info.message {
+        println!("  message:    {}", message);
+    }
+    if let Some(reason) = &info.reason {
+        println!("  reason:     {}", reason);
     }
 }
 
@@ -723,7 +1781,9 @@ fn print_breakpoint(info: &BreakpointInfo) {
     let extras = [
         info.condition.as_ref().map(|c| format!("if {}", c)),
         info.hit_count.map(|n| format!("hits: {}", n)),
+        (!info.on_hit.is_empty()).then(|| format!("on hit: {}", info.on_hit.join("; "))),
         info.message.clone(),
+        info.reason.clone(),
     ]
     .into_iter()
     .flatten()
@@ -737,6 +1797,36 @@ fn print_breakpoint(info: &BreakpointInfo) {
     }
 }
 
+/// Source around the current line, with an arrow on it, then the locals
+fn print_context(ctx: &ContextResult) {
+    // Print header
+    if let Some(source) = &ctx.source {
+        println!(
+            "Thread {} stopped at {}:{}",
+            ctx.thread_id, source, ctx.line
+        );
+    }
+    if let Some(func) = &ctx.function {
+        println!("In function: {}", func);
+    }
+    println!();
+
+    // Print source with line numbers
+    for line in &ctx.source_lines {
+        let marker = if line.is_current { "->" } else { "  " };
+        println!("{} {:>4} | {}", marker, line.number, line.content);
+    }
+
+    // Print locals
+    if !ctx.locals.is_empty() {
+        println!();
+        println!("Locals:");
+        for var in &ctx.locals {
+            println!("  {}", format_variable(var));
+        }
+    }
+}
+
 fn print_stop_result(stop: &StopResult) {
     match stop.reason.as_str() {
         "breakpoint" => {
@@ -745,14 +1835,17 @@ fn print_stop_result(stop: &StopResult) {
                 println!("  Breakpoint IDs: {:?}", stop.hit_breakpoint_ids);
             }
         }
+        "data-breakpoint" => {
+            println!("Stopped at data breakpoint");
+        }
         "step" => {
             println!("Step completed");
         }
-        "exception" | "signal" => {
-            println!(
-                "Stopped: {}",
-                stop.description.as_deref().unwrap_or(&stop.reason)
-            );
+        "exception" => {
+            println!("Stopped: {}", fault_summary(stop));
+            if let Some(exception) = &stop.exception {
+                print_exception(exception);
+            }
         }
         "pause" => {
             println!("Paused");
@@ -760,6 +1853,9 @@ fn print_stop_result(stop: &StopResult) {
         "entry" => {
             println!("Stopped at entry point");
         }
+        "goto" => {
+            println!("Jumped to target");
+        }
         _ => {
             println!("Stopped: {}", stop.reason);
         }
@@ -768,4 +1864,307 @@ fn print_stop_result(stop: &StopResult) {
     if let (Some(source), Some(line)) = (&stop.source, stop.line) {
         println!("  Location: {}:{}", source, line);
     }
+
+    for output in &stop.on_hit {
+        println!();
+        println!("> {}", output.command);
+        match (&output.result, &output.error) {
+            (_, Some(error)) => println!("Error: {}", error),
+            (Some(result), None) => println!(
+                "{}",
+                serde_json::to_string_pretty(result).unwrap_or_else(|_| result.to_string())
+            ),
+            (None, None) => {}
+        }
+    }
+
+    if !stop.watches.is_empty() {
+        println!();
+        for watch in &stop.watches {
+            match (&watch.value, &watch.error) {
+                (Some(value), _) => {
+                    println!("watch {}: {} = {}", watch.id, watch.expression, value)
+                }
+                (None, error) => println!(
+                    "watch {}: {} = <error: {}>",
+                    watch.id,
+                    watch.expression,
+                    error.as_deref().unwrap_or("unknown")
+                ),
+            }
+        }
+    }
+}
+
+/// `expression`, with its function scope when it has one
+fn format_watch(watch: &WatchInfo) -> String {
+    match &watch.function {
+        Some(function) => format!("{} (in {})", watch.expression, function),
+        None => watch.expression.clone(),
+    }
+}
+
+/// "SIGSEGV (segmentation fault) at 0x0" for signals, otherwise the
+/// adapter's exception text
+fn fault_summary(stop: &StopResult) -> String {
+    let text = stop
+        .exception_text
+        .as_deref()
+        .or(stop.description.as_deref())
+        .unwrap_or(&stop.reason);
+
+    let Some(name) = &stop.signal_name else {
+        return text.to_string();
+    };
+    let mut summary = name.clone();
+    if let Some(description) = signal::signal_description(name) {
+        summary.push_str(&format!(" ({})", description));
+    }
+    if let Some(address) = signal::fault_address(text) {
+        summary.push_str(&format!(" at {}", address));
+    }
+    summary
+}
+
+fn print_exception(exception: &ExceptionInfo) {
+    match (&exception.type_name, &exception.message) {
+        (Some(type_name), Some(message)) => println!("  Exception: {}: {}", type_name, message),
+        (Some(type_name), None) => println!("  Exception: {}", type_name),
+        (None, Some(message)) => println!("  Exception: {}", message),
+        (None, None) => {}
+    }
+    if let Some(mode) = &exception.break_mode {
+        println!("  Break mode: {}", mode);
+    }
+    if let Some(stack_trace) = &exception.stack_trace {
+        println!("  Thrown at:");
+        for line in stack_trace.lines() {
+            println!("    {}", line.trim());
+        }
+    }
+}
+
+/// Split a `--on-hit` value into individual commands
+fn split_on_hit_commands(commands: Option<&str>) -> Vec<String> {
+    commands
+        .into_iter()
+        .flat_map(parse::split_script)
+        .map(String::from)
+        .collect()
+}
+
+/// Print disassembled instructions, either with `; file:line` comments or,
+/// in source mode, with each source line printed above its instructions
+fn print_disassembly(instructions: &[InstructionInfo], source: bool) {
+    let mut last_symbol: Option<&str> = None;
+    let mut last_location: Option<(&str, u32)> = None;
+
+    for insn in instructions {
+        if let Some(symbol) = insn.symbol.as_deref() {
+            if last_symbol != Some(symbol) {
+                println!("<{}>:", symbol);
+                last_symbol = Some(symbol);
+            }
+        }
+
+        let location = insn.source.as_deref().zip(insn.line);
+
+        if source {
+            if let Some((path, line)) = location {
+                if last_location != location {
+                    if last_location.map(|(last_path, _)| last_path) != Some(path) {
+                        println!("{}:", path);
+                    }
+                    match &insn.source_text {
+                        Some(text) => println!("  {:>4} | {}", line, text),
+                        None => println!("  {:>4} | <source unavailable>", line),
+                    }
+                }
+            }
+            last_location = location;
+            println!("      {}: {}", insn.address, insn.instruction);
+        } else {
+            match location {
+                Some((path, line)) => {
+                    let file = std::path::Path::new(path)
+                        .file_name()
+                        .map(|f| f.to_string_lossy())
+                        .unwrap_or_else(|| path.into());
+                    println!("  {}: {:<40} ; {}:{}", insn.address, insn.instruction, file, line);
+                }
+                None => println!("  {}: {}", insn.address, insn.instruction),
+            }
+        }
+    }
+}
+
+/// Bytes shown per row of `memory` output
+const MEMORY_ROW_BYTES: usize = 16;
+
+fn print_memory(mem: &MemoryResult, format: MemoryFormat, width: Option<usize>, endian: Endian) {
+    if mem.data.is_empty() {
+        println!("No readable memory at {}", mem.address);
+        return;
+    }
+
+    let base = u64::from_str_radix(
+        mem.address
+            .trim_start_matches("0x")
+            .trim_start_matches("0X"),
+        16,
+    )
+    .ok();
+
+    let word_width = match format {
+        MemoryFormat::Float => Some(4),
+        MemoryFormat::Double => Some(8),
+        _ => width,
+    };
+
+    for (index, row) in mem.data.chunks(MEMORY_ROW_BYTES).enumerate() {
+        let offset = (index * MEMORY_ROW_BYTES) as u64;
+        let label = match base {
+            Some(base) => format!("0x{:x}", base.wrapping_add(offset)),
+            None => format!("+0x{:04x}", offset),
+        };
+        let ascii: String = row
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+
+        match (format, word_width) {
+            (MemoryFormat::Ascii, None) => println!("{}: {}", label, ascii),
+            (MemoryFormat::Decimal, None) => {
+                let bytes: Vec<String> = row.iter().map(|b| format!("{:3}", b)).collect();
+                println!("{}: {}", label, bytes.join(" "));
+            }
+            _ => {
+                let bytes: Vec<String> = row.iter().map(|b| format!("{:02x}", b)).collect();
+                println!("{}: {:<47} |{}|", label, bytes.join(" "), ascii);
+            }
+        }
+
+        // Words never straddle rows because the row size is a multiple of
+        // every supported width; a trailing partial word is left out.
+        if let Some(width) = word_width {
+            let words: Vec<String> = row
+                .chunks_exact(width)
+                .map(|word| format_memory_word(word, format, endian))
+                .collect();
+            if !words.is_empty() {
+                println!("{:indent$}  {}", "", words.join(" "), indent = label.len());
+            }
+        }
+    }
+
+    if mem.unreadable_bytes > 0 {
+        println!("({} bytes unreadable)", mem.unreadable_bytes);
+    }
+}
+
+/// Render one 2/4/8-byte word. Decimal words are shown as signed integers.
+fn format_memory_word(bytes: &[u8], format: MemoryFormat, endian: Endian) -> String {
+    let shift = |value: u64, byte: &u8| (value << 8) | u64::from(*byte);
+    let value = match endian {
+        Endian::Little => bytes.iter().rev().fold(0, shift),
+        Endian::Big => bytes.iter().fold(0, shift),
+    };
+
+    match format {
+        MemoryFormat::Float => f32::from_bits(value as u32).to_string(),
+        MemoryFormat::Double => f64::from_bits(value).to_string(),
+        MemoryFormat::Decimal => {
+            let unused_bits = 64 - bytes.len() as u32 * 8;
+            (((value << unused_bits) as i64) >> unused_bits).to_string()
+        }
+        MemoryFormat::Hex | MemoryFormat::Ascii => {
+            format!("0x{:0digits$x}", value, digits = bytes.len() * 2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fault_summary, format_memory_word, needs_session, split_on_hit_commands};
+    use crate::commands::{Commands, Endian, MemoryFormat};
+    use crate::ipc::protocol::StopResult;
+
+    #[test]
+    fn only_session_commands_report_a_missing_daemon_as_no_session() {
+        assert!(needs_session(&Commands::Registers));
+        assert!(needs_session(&Commands::Restart));
+        assert!(!needs_session(&Commands::Status));
+        assert!(!needs_session(&Commands::Mcp));
+        assert!(!needs_session(&Commands::Logs {
+            lines: 50,
+            follow: false,
+            level: None,
+            clear: false,
+        }));
+    }
+
+    #[test]
+    fn memory_words_honor_endianness() {
+        let bytes = [0x01, 0x02, 0x03, 0x04];
+        assert_eq!(
+            format_memory_word(&bytes, MemoryFormat::Hex, Endian::Little),
+            "0x04030201"
+        );
+        assert_eq!(
+            format_memory_word(&bytes, MemoryFormat::Hex, Endian::Big),
+            "0x01020304"
+        );
+        assert_eq!(
+            format_memory_word(&[0xff, 0xff], MemoryFormat::Decimal, Endian::Little),
+            "-1"
+        );
+    }
+
+    #[test]
+    fn memory_words_decode_floats() {
+        assert_eq!(
+            format_memory_word(&1.5f32.to_le_bytes(), MemoryFormat::Float, Endian::Little),
+            "1.5"
+        );
+        assert_eq!(
+            format_memory_word(&(-2.25f64).to_be_bytes(), MemoryFormat::Double, Endian::Big),
+            "-2.25"
+        );
+    }
+
+    #[test]
+    fn on_hit_commands_split_on_semicolons() {
+        assert_eq!(
+            split_on_hit_commands(Some("locals; backtrace --limit 3;;")),
+            vec!["locals".to_string(), "backtrace --limit 3".to_string()]
+        );
+        assert!(split_on_hit_commands(None).is_empty());
+    }
+
+    #[test]
+    fn signal_stops_name_the_signal_and_fault_address() {
+        let mut stop = StopResult {
+            reason: "exception".to_string(),
+            raw_reason: Some("signal".to_string()),
+            description: Some("signal SIGSEGV".to_string()),
+            thread_id: Some(1),
+            all_threads_stopped: true,
+            hit_breakpoint_ids: vec![],
+            source: None,
+            line: None,
+            column: None,
+            function: None,
+            signal_name: Some("SIGSEGV".to_string()),
+            signal_number: Some(11),
+            exception_text: Some("signal SIGSEGV: invalid address (fault address: 0x0)".to_string()),
+            exception: None,
+            on_hit: vec![],
+            watches: vec![],
+        };
+        assert_eq!(fault_summary(&stop), "SIGSEGV (segmentation fault) at 0x0");
+
+        stop.signal_name = None;
+        stop.exception_text = None;
+        assert_eq!(fault_summary(&stop), "signal SIGSEGV");
+    }
 }
@@ -0,0 +1,251 @@
+//! `debugger run`: a whole debug session in one command
+//!
+//! Starts the program, reports every stop along with the requested
+//! expressions, and continues until the program exits. The session can live
+//! in the background daemon or, with `--no-daemon`, inside this process.
+
+use std::process::Stdio;
+
+use crate::commands::RunOptions;
+use crate::common::{Error, Result};
+use crate::daemon::LocalSession;
+use crate::ipc::protocol::{
+    Command, EvaluateContext, EvaluateResult, StatusResult, StopResult, VariableInfo,
+};
+use crate::ipc::DaemonClient;
+
+use super::{agent, print_stop_result, spawn, OutputMode};
+
+/// Where `run` sends its commands
+pub(super) enum Backend {
+    Daemon(DaemonClient),
+    Local(LocalSession),
+}
+
+impl Backend {
+    /// Connect to the daemon, which must not already have a session
+    pub(super) async fn daemon() -> Result<Self> {
+        spawn::ensure_daemon_running().await?;
+        let mut client = DaemonClient::connect().await?;
+
+        let status: StatusResult =
+            serde_json::from_value(client.send_command(Command::Status).await?)?;
+        if status.session_active {
+            return Err(Error::SessionAlreadyActive);
+        }
+        Ok(Self::Daemon(client))
+    }
+
+    /// Run the session in this process
+    pub(super) fn local() -> Result<Self> {
+        Ok(Self::Local(LocalSession::start()?))
+    }
+
+    async fn send(&mut self, command: Command) -> Result<serde_json::Value> {
+        match self {
+            Self::Daemon(client) => client.send_command(command).await,
+            Self::Local(session) => session.send_command(command).await,
+        }
+    }
+
+    /// End the session, and with it the in-process actor
+    async fn finish(mut self) {
+        if let Err(e) = self.send(Command::Stop).await {
+            tracing::debug!("Stopping session after run: {}", e);
+        }
+        if let Self::Local(session) = self {
+            session.shutdown().await;
+        }
+    }
+}
+
+/// Run a `build-run` build command, failing unless it succeeds. Its output
+/// is passed through so compiler errors are visible; in agent mode it all
+/// goes to stderr, leaving stdout to the session's records.
+pub(super) async fn build(command: &str, mode: OutputMode) -> Result<()> {
+    let stdout = match mode {
+        OutputMode::Human => {
+            println!("Building: {}", command);
+            Stdio::inherit()
+        }
+        OutputMode::Agent { .. } => std::io::stderr().into(),
+    };
+    let status = crate::testing::shell(command)
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .status()
+        .await
+        .map_err(|e| Error::Config(format!("Build command failed to execute: {}", e)))?;
+    if !status.success() {
+        let how = match status.code() {
+            Some(code) => format!("with exit code {}", code),
+            None => "without an exit code".to_string(),
+        };
+        return Err(Error::Config(format!("Build command '{}' failed {}", command, how)));
+    }
+    Ok(())
+}
+
+/// Run the program to completion, then end the session whatever happened
+pub(super) async fn run(mut backend: Backend, options: RunOptions, mode: OutputMode) -> Result<()> {
+    let result = run_to_exit(&mut backend, options, mode).await;
+    backend.finish().await;
+    result
+}
+
+async fn run_to_exit(backend: &mut Backend, options: RunOptions, mode: OutputMode) -> Result<()> {
+    let agent = matches!(mode, OutputMode::Agent { .. });
+    let program = options.program.canonicalize().unwrap_or(options.program);
+
+    backend
+        .send(Command::Start {
+            program: program.clone(),
+            args: options.args,
+            adapter: options.adapter,
+            stop_on_entry: false,
+            initial_breakpoints: options.breakpoints,
+            keep_alive_minutes: None,
+        })
+        .await?;
+    if !agent {
+        println!("Running: {}", program.display());
+    }
+
+    loop {
+        let result = backend
+            .send(Command::Await {
+                timeout_secs: options.timeout,
+            })
+            .await?;
+
+        match result.get("reason").and_then(|v| v.as_str()) {
+            Some("exited") => {
+                let code = result["exit_code"].as_i64().unwrap_or(0);
+                if agent {
+                    println!("EXIT {}", code);
+                } else {
+                    println!("Program exited with code {}", code);
+                }
+                return Ok(());
+            }
+            Some("terminated") => {
+                if agent {
+                    println!("TERMINATED");
+                } else {
+                    println!("Program terminated");
+                }
+                return Ok(());
+            }
+            Some("cancelled") => {
+                return Err(Error::Config(
+                    "Await was cancelled by another client".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        let stop: StopResult = serde_json::from_value(result)?;
+        if agent {
+            println!("{}", agent::format_stop(&stop));
+        } else {
+            print_stop_result(&stop);
+        }
+
+        for expression in &options.print {
+            print_expression(backend, expression, mode).await;
+        }
+
+        backend.send(Command::Continue { this_thread: false }).await?;
+    }
+}
+
+/// Evaluate and print one `--print` expression; failures are reported inline
+/// so the remaining expressions still run.
+async fn print_expression(backend: &mut Backend, expression: &str, mode: OutputMode) {
+    let result = backend
+        .send(Command::Evaluate {
+            expression: expression.to_string(),
+            frame_id: None,
+            frame_index: None,
+            context: EvaluateContext::Watch,
+            max_string_length: None,
+            no_truncate: false,
+        })
+        .await
+        .and_then(|value| Ok(serde_json::from_value::<EvaluateResult>(value)?));
+
+    match (result, mode) {
+        (Ok(eval), OutputMode::Agent { types }) => {
+            let var = VariableInfo {
+                name: expression.to_string(),
+                value: eval.result,
+                type_name: eval.type_name,
+                variables_reference: eval.variables_reference,
+                presentation_hint: None,
+                elements: None,
+                memory_reference: None,
+            };
+            println!("{}", agent::format_variable(&var, types));
+        }
+        (Ok(eval), OutputMode::Human) => println!("  {} = {}", expression, eval.result),
+        (Err(e), OutputMode::Agent { .. }) => println!("ERROR {} {}", expression, e),
+        (Err(e), OutputMode::Human) => println!("  {} = <error: {}>", expression, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::dap::mock::{Action, MockAdapter};
+
+    const AGENT: OutputMode = OutputMode::Agent { types: false };
+
+    #[tokio::test]
+    async fn failed_builds_are_reported_with_their_exit_code() {
+        build("exit 0", AGENT).await.unwrap();
+        let error = build("exit 3", AGENT).await.unwrap_err();
+        let message = error.to_string();
+        assert!(message.ends_with("Build command 'exit 3' failed with exit code 3"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn runs_print_at_each_stop_until_the_program_exits() {
+        // One stop at the breakpoint, then the program exits when continued
+        let adapter = MockAdapter::scripted(|_, command, _| match command {
+            "configurationDone" => Some(vec![
+                Action::Respond(json!({})),
+                Action::Event(
+                    "stopped",
+                    json!({ "reason": "breakpoint", "threadId": 1, "hitBreakpointIds": [1] }),
+                ),
+            ]),
+            "continue" => Some(vec![
+                Action::Respond(json!({ "allThreadsContinued": true })),
+                Action::Event("exited", json!({ "exitCode": 0 })),
+                Action::Event("terminated", json!({})),
+            ]),
+            _ => None,
+        })
+        .await;
+        let backend = Backend::Local(LocalSession::with_config(adapter.config()));
+        let options = RunOptions {
+            program: "/bin/app".into(),
+            args: vec![],
+            adapter: Some("mock".to_string()),
+            breakpoints: vec!["main.c:3".to_string()],
+            print: vec!["x".to_string()],
+            timeout: 10,
+        };
+
+        run(backend, options, AGENT).await.unwrap();
+
+        let received = adapter.received();
+        let commands: Vec<&str> = received.iter().map(|r| r.command.as_str()).collect();
+        let evaluate = received.iter().find(|r| r.command == "evaluate").unwrap();
+        assert_eq!(evaluate.arguments["expression"], "x");
+        assert_eq!(commands.iter().filter(|c| **c == "continue").count(), 1);
+        assert!(commands.contains(&"disconnect"));
+    }
+}
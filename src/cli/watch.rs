@@ -0,0 +1,188 @@
+//! `debugger watch-rebuild`: rebuild and restart on source changes
+//!
+//! Watches the program's source directory and, once edits settle, runs the
+//! build command and restarts the session. Breakpoints live in the session,
+//! so they apply to the rebuilt program as before. Runs until interrupted.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::common::{Error, Result};
+use crate::ipc::protocol::{Command, StatusResult};
+use crate::ipc::DaemonClient;
+
+use super::{run, OutputMode};
+
+/// How long the tree must be quiet before a rebuild starts, so that one save
+/// of several files (or an editor's write-and-rename) triggers one build
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Directories whose contents are build output or tooling state
+const IGNORED_DIRS: &[&str] = &[".git", "target", "node_modules", "__pycache__"];
+
+pub(super) async fn watch_rebuild(
+    build: &str,
+    dir: Option<PathBuf>,
+    mode: OutputMode,
+) -> Result<()> {
+    let agent = matches!(mode, OutputMode::Agent { .. });
+    let mut client = DaemonClient::connect().await?;
+
+    let status: StatusResult = serde_json::from_value(client.send_command(Command::Status).await?)?;
+    if !status.session_active {
+        return Err(Error::SessionNotActive);
+    }
+    let program = status.program.map(PathBuf::from);
+    let dir = dir
+        .or_else(|| {
+            program
+                .as_ref()
+                .and_then(|p| p.parent())
+                .map(Path::to_path_buf)
+        })
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if let Ok(event) = event {
+            let _ = tx.send(event);
+        }
+    })
+    .map_err(|e| Error::Internal(format!("Failed to start file watcher: {}", e)))?;
+    watcher
+        .watch(&dir, RecursiveMode::Recursive)
+        .map_err(|e| Error::Config(format!("Failed to watch '{}': {}", dir.display(), e)))?;
+
+    if agent {
+        println!("WATCHING {}", dir.display());
+    } else {
+        println!("Watching {} for changes (Ctrl+C to stop)", dir.display());
+    }
+
+    loop {
+        let changed = tokio::select! {
+            changed = next_change(&mut rx, program.as_deref()) => changed,
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        let Some(changed) = changed else {
+            return Err(Error::Internal("File watcher stopped".to_string()));
+        };
+        if agent {
+            println!("CHANGED {}", changed.display());
+        } else {
+            println!("\nChanged: {}", changed.display());
+        }
+
+        let built = run::build(build, mode).await;
+
+        // The build writes into the watched tree too; those writes are not
+        // edits, so drop them before watching again
+        tokio::time::sleep(SETTLE_TIME).await;
+        while rx.try_recv().is_ok() {}
+
+        if let Err(e) = built {
+            if agent {
+                println!("ERROR {}", e);
+            } else {
+                println!("{}; waiting for the next change", e);
+            }
+            continue;
+        }
+
+        match client.send_command(Command::Restart).await {
+            Ok(_) if agent => println!("RESTARTED"),
+            Ok(_) => println!("Program rebuilt and restarted"),
+            Err(Error::SessionNotActive) => return Err(Error::SessionNotActive),
+            Err(e) if agent => println!("ERROR {}", e),
+            Err(e) => println!("Restart failed: {}", e),
+        }
+    }
+}
+
+/// Wait for an edit, then for the tree to settle, returning the first
+/// changed path
+async fn next_change(
+    rx: &mut mpsc::UnboundedReceiver<Event>,
+    program: Option<&Path>,
+) -> Option<PathBuf> {
+    let changed = loop {
+        let event = rx.recv().await?;
+        if let Some(path) = source_change(&event, program) {
+            break path;
+        }
+    };
+
+    while let Ok(Some(_)) = tokio::time::timeout(SETTLE_TIME, rx.recv()).await {}
+    Some(changed)
+}
+
+/// The path an event changed, unless it is not a source edit: an access,
+/// a metadata update, the program binary itself or build output
+fn source_change(event: &Event, program: Option<&Path>) -> Option<PathBuf> {
+    let edit = match event.kind {
+        EventKind::Create(_) | EventKind::Remove(_) => true,
+        EventKind::Modify(kind) => !matches!(kind, notify::event::ModifyKind::Metadata(_)),
+        _ => false,
+    };
+    if !edit {
+        return None;
+    }
+
+    event
+        .paths
+        .iter()
+        .find(|path| {
+            Some(path.as_path()) != program
+                && !path.components().any(|part| {
+                    let part = part.as_os_str().to_string_lossy();
+                    IGNORED_DIRS.contains(&part.as_ref())
+                })
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, DataChange, ModifyKind};
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn only_source_edits_count_as_changes() {
+        let program = Path::new("/src/app");
+        let write = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+
+        assert_eq!(
+            source_change(&event(write, "/src/main.c"), Some(program)),
+            Some(PathBuf::from("/src/main.c"))
+        );
+        assert_eq!(
+            source_change(
+                &event(EventKind::Create(CreateKind::File), "/src/util.c"),
+                None
+            ),
+            Some(PathBuf::from("/src/util.c"))
+        );
+        assert_eq!(
+            source_change(&event(write, "/src/app"), Some(program)),
+            None
+        );
+        assert_eq!(
+            source_change(&event(write, "/src/target/debug/app"), None),
+            None
+        );
+        assert_eq!(
+            source_change(
+                &event(EventKind::Access(AccessKind::Any), "/src/main.c"),
+                None
+            ),
+            None
+        );
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! Defines the clap commands for the debugger CLI.
 
-use clap::Subcommand;
+pub mod parse;
+
+use clap::{Args, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Subcommand)]
@@ -16,7 +18,8 @@ pub enum Commands {
         #[arg(last = true)]
         args: Vec<String>,
 
-        /// Debug adapter to use (default: lldb-dap)
+        /// Debug adapter to use (default: chosen from the program type, e.g.
+        /// debugpy for .py, js-debug for .js, Delve for Go binaries)
         #[arg(long)]
         adapter: Option<String>,
 
@@ -24,20 +27,59 @@ pub enum Commands {
         #[arg(long)]
         stop_on_entry: bool,
 
-        /// Set initial breakpoint(s) before program starts (file:line or function name)
+        /// Set initial breakpoint(s) before program starts (file:line, function name, or 0x address)
         /// Can be specified multiple times: --break main --break src/file.c:42
         #[arg(long = "break", short = 'b')]
         initial_breakpoints: Vec<String>,
+
+        /// End the session after this many minutes without a command, in
+        /// place of the daemon's idle timeout (which never ends a session)
+        #[arg(long, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..))]
+        keep_alive: Option<u64>,
+    },
+
+    /// Run a program to completion, printing every stop
+    ///
+    /// Stops at each --break location, prints the --print expressions and
+    /// continues until the program exits. With the global --no-daemon flag the
+    /// session runs inside this process instead of the background daemon.
+    Run {
+        #[command(flatten)]
+        options: RunOptions,
+    },
+
+    /// Build the program with a shell command, then run it like 'run'
+    ///
+    /// The build command's output is shown, and the program is only launched
+    /// when the command succeeds.
+    BuildRun {
+        /// Shell command that builds the program, e.g. "gcc -g -o prog prog.c"
+        #[arg(long)]
+        build: String,
+
+        #[command(flatten)]
+        options: RunOptions,
     },
 
     /// Attach to a running process
     Attach {
         /// Process ID to attach to
-        pid: u32,
+        #[arg(required_unless_present = "resume")]
+        pid: Option<u32>,
 
         /// Debug adapter to use (default: lldb-dap)
-        #[arg(long)]
+        #[arg(long, conflicts_with = "resume")]
         adapter: Option<String>,
+
+        /// Attach again to the process last detached from, restoring its
+        /// breakpoints and watches
+        #[arg(long)]
+        resume: bool,
+
+        /// End the session after this many minutes without a command, in
+        /// place of the daemon's idle timeout (which never ends a session)
+        #[arg(long, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..))]
+        keep_alive: Option<u64>,
     },
 
     /// Breakpoint management
@@ -47,7 +89,9 @@ pub enum Commands {
     /// Shorthand for 'breakpoint add'
     #[command(name = "break", alias = "b")]
     Break {
-        /// Location: file:line or function name
+        /// Location: file:line, function name, or 0x address; or relative to
+        /// the current stop, like file:+N, -N or function+N
+        #[arg(allow_hyphen_values = true)]
         location: String,
 
         /// Condition for the breakpoint
@@ -57,23 +101,103 @@ pub enum Commands {
         /// Hit count (break after N hits)
         #[arg(long)]
         hit_count: Option<u32>,
+
+        /// Commands to run each time the breakpoint is hit, separated by ';'
+        /// (e.g. "locals; backtrace --limit 3"); only ones that inspect the stop
+        #[arg(long)]
+        on_hit: Option<String>,
     },
 
+    /// Watch expressions, evaluated and reported at every stop
+    #[command(subcommand)]
+    Watch(WatchCommands),
+
     /// Continue execution
     #[command(alias = "c")]
-    Continue,
+    Continue {
+        /// Run only the current thread; the others stay stopped (for
+        /// adapters that support it)
+        #[arg(long)]
+        this_thread: bool,
+    },
 
     /// Step over (execute current line, step over function calls)
     #[command(alias = "n")]
-    Next,
+    Next {
+        /// Step N times, waiting for each step; stops early at a breakpoint,
+        /// exception or exit and reports it
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        count: Option<u32>,
+
+        /// Seconds to wait for each step (with --count)
+        #[arg(long, default_value = "300", requires = "count")]
+        timeout: u64,
+
+        /// Run only the current thread; the others stay stopped (for
+        /// adapters that support it)
+        #[arg(long)]
+        this_thread: bool,
+    },
 
     /// Step into (execute current line, step into function calls)
     #[command(alias = "s")]
-    Step,
+    Step {
+        /// Step N times, waiting for each step; stops early at a breakpoint,
+        /// exception or exit and reports it
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        count: Option<u32>,
+
+        /// Seconds to wait for each step (with --count)
+        #[arg(long, default_value = "300", requires = "count")]
+        timeout: u64,
+
+        /// Run only the current thread; the others stay stopped (for
+        /// adapters that support it)
+        #[arg(long)]
+        this_thread: bool,
+    },
 
     /// Step out (run until current function returns)
     #[command(alias = "out")]
-    Finish,
+    Finish {
+        /// Step N times, waiting for each step; stops early at a breakpoint,
+        /// exception or exit and reports it
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        count: Option<u32>,
+
+        /// Seconds to wait for each step (with --count)
+        #[arg(long, default_value = "300", requires = "count")]
+        timeout: u64,
+
+        /// Run only the current thread; the others stay stopped (for
+        /// adapters that support it)
+        #[arg(long)]
+        this_thread: bool,
+    },
+
+    /// Single-step until an expression's value changes (or a condition
+    /// becomes true); breakpoints, exceptions and exit stop it early
+    StepUntil {
+        /// Expression evaluated in the top frame after each step
+        expression: String,
+
+        /// Give up after this many steps
+        #[arg(long, default_value = "100", value_parser = clap::value_parser!(u32).range(1..))]
+        max_steps: u32,
+
+        /// Seconds to wait for each step
+        #[arg(long, default_value = "300")]
+        timeout: u64,
+
+        /// Step into function calls instead of over them
+        #[arg(long)]
+        into: bool,
+
+        /// Run only the current thread; the others stay stopped (for
+        /// adapters that support it)
+        #[arg(long)]
+        this_thread: bool,
+    },
 
     /// Pause execution
     Pause,
@@ -88,22 +212,92 @@ pub enum Commands {
         /// Show local variables for each frame
         #[arg(long)]
         locals: bool,
+
+        /// Hide frames without source or from outside the program's
+        /// directory (libc, runtime internals), keeping their stack numbers
+        #[arg(long)]
+        user_only: bool,
     },
 
     /// Show local variables in current frame
-    Locals,
+    Locals {
+        /// Show this frame (0 = top of stack) without selecting it
+        #[arg(long, value_name = "INDEX")]
+        frame: Option<usize>,
+
+        /// Cut string values off after N characters (0 = no limit; default
+        /// from `output.max_string_length`)
+        #[arg(long, value_name = "N")]
+        max_string_length: Option<usize>,
+
+        /// Show values in full, ignoring the configured truncation limits
+        /// (up to 1 MiB per value and 4 MiB in all)
+        #[arg(long, conflicts_with = "max_string_length")]
+        no_truncate: bool,
+
+        /// Fetch the variables again instead of reusing the ones already
+        /// read at this stop
+        #[arg(long)]
+        no_cache: bool,
+    },
 
     /// Print/evaluate expression
     #[command(alias = "p")]
     Print {
         /// Expression to evaluate
         expression: String,
+
+        /// Evaluate in this frame (0 = top of stack) without selecting it
+        #[arg(long, value_name = "INDEX")]
+        frame: Option<usize>,
+
+        /// Cut a string result off after N characters (0 = no limit)
+        #[arg(long, value_name = "N")]
+        max_string_length: Option<usize>,
+
+        /// Show the result in full, ignoring the configured truncation
+        /// limits (up to 1 MiB)
+        #[arg(long, conflicts_with = "max_string_length")]
+        no_truncate: bool,
     },
 
     /// Evaluate expression (can have side effects)
     Eval {
         /// Expression to evaluate
         expression: String,
+
+        /// Evaluate in this frame (0 = top of stack) without selecting it
+        #[arg(long, value_name = "INDEX")]
+        frame: Option<usize>,
+
+        /// Cut a string result off after N characters (0 = no limit)
+        #[arg(long, value_name = "N")]
+        max_string_length: Option<usize>,
+
+        /// Show the result in full, ignoring the configured truncation
+        /// limits (up to 1 MiB)
+        #[arg(long, conflicts_with = "max_string_length")]
+        no_truncate: bool,
+
+        /// Treat the expression as an assignment `target = value` and make
+        /// it with the adapter's setExpression/setVariable, printing the new
+        /// value
+        #[arg(long, conflicts_with_all = ["max_string_length", "no_truncate"])]
+        assign: bool,
+    },
+
+    /// Evaluate an expression in every frame of the current stack
+    ///
+    /// Shows which frame of a deep call chain holds a value, without walking
+    /// it with `up`/`down`. Frames where the expression can't be evaluated
+    /// report the error.
+    EvalAll {
+        /// Expression to evaluate
+        expression: String,
+
+        /// Number of frames to evaluate in, from the top of the stack
+        #[arg(long, default_value = "20")]
+        limit: usize,
     },
 
     /// Show current position with source context and variables
@@ -114,13 +308,104 @@ pub enum Commands {
         lines: usize,
     },
 
-    /// List all threads
-    Threads,
+    /// Report on the current stop in one go, for a crash: the signal or
+    /// exception, the stopped thread's backtrace, and the source and locals
+    /// where it stopped
+    Triage {
+        /// Maximum number of frames to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+
+        /// Number of context lines to show
+        #[arg(long, default_value = "5")]
+        lines: usize,
+    },
+
+    /// Read raw memory from the debuggee
+    #[command(alias = "x")]
+    Memory {
+        /// Start address (e.g. 0x7ffc1000) or adapter memory reference
+        #[arg(required_unless_present = "var", conflicts_with = "var")]
+        address: Option<String>,
+
+        /// Read at the memory reference the adapter reports for this variable
+        /// in the selected frame, e.g. the bytes behind a pointer
+        #[arg(long, value_name = "NAME")]
+        var: Option<String>,
+
+        /// Number of bytes to read
+        #[arg(long, default_value = "64")]
+        count: u32,
+
+        /// How to render values (float/double imply a width of 4/8)
+        #[arg(long, value_enum, default_value = "hex")]
+        format: MemoryFormat,
+
+        /// Interpret the bytes as 16, 32 or 64-bit words
+        #[arg(long, value_parser = parse_word_width)]
+        width: Option<usize>,
+
+        /// Byte order used to assemble words
+        #[arg(long, value_enum, default_value = "little")]
+        endian: Endian,
+    },
+
+    /// Disassemble instructions at an address or the current frame
+    Disassemble {
+        /// Start address (default: current instruction pointer)
+        address: Option<String>,
+
+        /// Number of instructions to show
+        #[arg(long, default_value = "20")]
+        count: u32,
+
+        /// Interleave source lines above the instructions they produced
+        #[arg(long)]
+        source: bool,
+    },
+
+    /// Show the instruction, stack and frame pointers of the current frame
+    ///
+    /// Register names differ per architecture; this tries the usual ones
+    /// and also prints the instruction reference to pass to `disassemble`
+    /// or `memory`.
+    #[command(alias = "regs")]
+    Registers,
+
+    /// List threads
+    ///
+    /// The list is cached between calls, since enumerating thousands of
+    /// threads can be slow; it is fetched again when a thread starts or with
+    /// --refresh.
+    Threads {
+        /// Show at most this many threads
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Only threads whose name contains this text, ignoring case
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Enumerate the threads again instead of using the cached list
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// Analyze the stopped program's threads
+    #[command(subcommand)]
+    Analyze(AnalyzeCommands),
+
+    /// List the modules (libraries, shared objects...) the program has loaded
+    Modules,
 
     /// Switch to a specific thread
+    #[command(args_conflicts_with_subcommands = true)]
     Thread {
         /// Thread ID to switch to
         id: Option<i64>,
+
+        #[command(subcommand)]
+        action: Option<ThreadCommands>,
     },
 
     /// Navigate to a specific stack frame
@@ -140,14 +425,35 @@ pub enum Commands {
         /// Timeout in seconds
         #[arg(long, default_value = "300")]
         timeout: u64,
+
+        /// Return at once and let the daemon write the result to --notify
+        #[arg(long, requires = "notify")]
+        background: bool,
+
+        /// New file the daemon writes the stop result JSON to (with --background)
+        #[arg(long, requires = "background")]
+        notify: Option<PathBuf>,
     },
 
+    /// Make awaits in progress return at once with reason "cancelled"
+    CancelAwait,
+
+    /// Abort an expression evaluation that is taking too long
+    CancelEval,
+
     /// Get debuggee stdout/stderr output
     Output {
         /// Stream output continuously
         #[arg(long, conflicts_with_all = ["tail", "clear"])]
         follow: bool,
 
+        /// With --follow, receive output as the daemon gets it instead of
+        /// reading the bounded buffer, so none is lost when the program
+        /// writes faster than the buffer keeps; starts from output written
+        /// after connecting
+        #[arg(long, requires = "follow")]
+        raw: bool,
+
         /// Get last N lines of output
         #[arg(long)]
         tail: Option<usize>,
@@ -155,6 +461,29 @@ pub enum Commands {
         /// Clear output buffer
         #[arg(long)]
         clear: bool,
+
+        /// Buffer up to N MB of output for the rest of the session, overriding
+        /// output.max_bytes_mb; lowering it drops the oldest output
+        #[arg(long, value_name = "N", conflicts_with_all = ["follow", "tail", "clear"])]
+        set_limit_mb: Option<usize>,
+
+        /// Buffer up to N output events for the rest of the session,
+        /// overriding output.max_events
+        #[arg(long, value_name = "N", conflicts_with_all = ["follow", "tail", "clear"])]
+        set_limit_events: Option<usize>,
+    },
+
+    /// Stream session events (stops, resumes, output, thread starts and
+    /// exits, program exit) as newline-delimited JSON until the session ends
+    Events {
+        /// Only stream these kinds of event, e.g. `--only stopped,output`
+        #[arg(
+            long,
+            value_name = "KINDS",
+            value_delimiter = ',',
+            value_parser = crate::ipc::protocol::SessionEvent::KINDS
+        )]
+        only: Vec<String>,
     },
 
     /// Get daemon/session status
@@ -169,16 +498,43 @@ pub enum Commands {
     /// Restart program (re-launch with same arguments)
     Restart,
 
+    /// Return the daemon to idle: stop any session and forget its
+    /// breakpoints, watchpoints and stop state
+    ///
+    /// Unlike `shutdown`, the daemon stays running, so the next `start` is
+    /// as fast as the first.
+    Reset,
+
+    /// Rebuild and restart the session whenever the program's sources change
+    ///
+    /// Watches the directory recursively; after each change the build
+    /// command runs and, if it succeeds, the session restarts with its
+    /// breakpoints. Runs until interrupted.
+    WatchRebuild {
+        /// Shell command that builds the program
+        #[arg(long)]
+        build: String,
+
+        /// Directory to watch (default: the program's directory)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+
     /// View daemon logs (for debugging)
     Logs {
         /// Number of lines to show (default: 50)
-        #[arg(long, short = 'n', default_value = "50")]
+        #[arg(long, short = 'n', visible_alias = "tail", default_value = "50")]
         lines: usize,
 
         /// Follow log output (like tail -f)
-        #[arg(long, short)]
+        #[arg(long, short, conflicts_with = "level")]
         follow: bool,
 
+        /// Only show events at this level or more severe (error, warn,
+        /// info, debug, trace)
+        #[arg(long)]
+        level: Option<tracing::Level>,
+
         /// Clear the log file
         #[arg(long)]
         clear: bool,
@@ -186,7 +542,12 @@ pub enum Commands {
 
     /// [Hidden] Run in daemon mode - spawned automatically
     #[command(hide = true)]
-    Daemon,
+    Daemon {
+        /// Also accept commands as HTTP POST requests on this address
+        /// (requires the `http` feature)
+        #[arg(long, value_name = "ADDR")]
+        http: Option<std::net::SocketAddr>,
+    },
 
     /// Install and manage debug adapters
     Setup {
@@ -205,10 +566,23 @@ pub enum Commands {
         #[arg(long)]
         check: bool,
 
+        /// Report installed debuggers that have a newer release
+        #[arg(long)]
+        check_updates: bool,
+
+        /// Reinstall debuggers that are broken or fail verification
+        #[arg(long)]
+        repair: bool,
+
         /// Auto-install debuggers for detected project types
         #[arg(long, name = "auto")]
         auto_detect: bool,
 
+        /// How many directory levels below the current one `--auto` searches
+        /// for projects (0 = current directory only)
+        #[arg(long, default_value_t = crate::setup::detector::DEFAULT_DETECT_DEPTH)]
+        depth: usize,
+
         /// Uninstall a debugger
         #[arg(long)]
         uninstall: bool,
@@ -230,22 +604,146 @@ pub enum Commands {
         json: bool,
     },
 
-    /// Execute a test scenario defined in a YAML file
+    /// List the configured debug adapters
+    Adapters {
+        /// Start each adapter (without launching anything) and show which
+        /// DAP features it supports
+        #[arg(long)]
+        features: bool,
+    },
+
+    /// Show where the config file lives, or check it for problems
+    Config {
+        /// Report typos, unsupported values and missing adapters in the
+        /// config file
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Print JSON Schemas for daemon commands and their results
+    Schema {
+        /// Only print the schema for this command (e.g. breakpoint_add)
+        #[arg(long)]
+        command: Option<String>,
+    },
+
+    /// Serve debugging tools over stdio using the Model Context Protocol
+    Mcp,
+
+    /// Print help for the CLI or one of its commands
+    ///
+    /// `--format markdown` prints a reference of the command and all its
+    /// subcommands, options and defaults, generated from the command
+    /// definitions of this binary.
+    Help {
+        /// Command to show help for, e.g. `breakpoint add`
+        command: Vec<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: HelpFormat,
+    },
+
+    /// Print a shell completion script
+    ///
+    /// For example `debugger completions bash > /etc/bash_completion.d/debugger`.
+    /// Adapter names are taken from the config file when the script is
+    /// generated, so regenerate it after adding adapters.
+    Completions {
+        /// Shell to complete in
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    /// Re-run a command log written with --record against a fresh session
+    Replay {
+        /// Path to the recorded command log
+        path: PathBuf,
+    },
+
+    /// Show the commands issued in this directory, oldest first
+    ///
+    /// Every command typed in a project directory (without --agent) is kept
+    /// in a history file under the config directory.
+    History {
+        /// Re-run the last session's commands, from its last start or
+        /// attach, against a fresh session
+        #[arg(long)]
+        replay: bool,
+    },
+
+    /// Run a batch of commands against one daemon connection
+    ///
+    /// Commands are separated by `;` or newlines and use the same syntax as
+    /// test scenarios, e.g. "break main.c:10; continue; locals; print x".
+    Exec {
+        /// The commands to run
+        script: String,
+
+        /// Keep going after a command fails instead of stopping
+        #[arg(long)]
+        continue_on_error: bool,
+    },
+
+    /// Execute a test scenario defined in a YAML or JSON file, or every
+    /// scenario in a directory
     Test {
-        /// Path to the YAML test scenario file
+        /// Path to the test scenario file (.yml/.yaml, or .json), or a
+        /// directory of them
         path: PathBuf,
 
-        /// Verbose output
-        #[arg(long, short)]
+        /// Show setup output and step details; set by the global
+        /// `--verbose`
+        #[arg(skip)]
         verbose: bool,
+
+        /// Run up to N scenarios of a directory at once, each against its
+        /// own daemon instance
+        #[arg(long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+        parallel: u64,
+
+        /// Shut the daemon down after the scenario (used for the per-scenario
+        /// daemons of `--parallel`)
+        #[arg(long, hide = true)]
+        shutdown_daemon: bool,
     },
 }
 
+/// What to run and report for 'run' and 'build-run'
+#[derive(Args)]
+pub struct RunOptions {
+    /// Path to the executable to debug
+    pub program: PathBuf,
+
+    /// Arguments to pass to the program
+    #[arg(last = true)]
+    pub args: Vec<String>,
+
+    /// Debug adapter to use (default: chosen from the program type, e.g.
+    /// debugpy for .py, js-debug for .js, Delve for Go binaries)
+    #[arg(long)]
+    pub adapter: Option<String>,
+
+    /// Breakpoint location(s): file:line, function name, or 0x address
+    #[arg(long = "break", short = 'b')]
+    pub breakpoints: Vec<String>,
+
+    /// Expression(s) to print at every stop
+    #[arg(long = "print", short = 'p')]
+    pub print: Vec<String>,
+
+    /// Seconds to wait for each stop
+    #[arg(long, default_value = "60")]
+    pub timeout: u64,
+}
+
 #[derive(Subcommand)]
 pub enum BreakpointCommands {
     /// Add a breakpoint
     Add {
-        /// Location: file:line or function name
+        /// Location: file:line, function name, or 0x address; or relative to
+        /// the current stop, like file:+N, -N or function+N
+        #[arg(allow_hyphen_values = true)]
         location: String,
 
         /// Condition for the breakpoint
@@ -255,6 +753,11 @@ pub enum BreakpointCommands {
         /// Hit count (break after N hits)
         #[arg(long)]
         hit_count: Option<u32>,
+
+        /// Commands to run each time the breakpoint is hit, separated by ';'
+        /// (e.g. "locals; backtrace --limit 3"); only ones that inspect the stop
+        #[arg(long)]
+        on_hit: Option<String>,
     },
 
     /// Remove a breakpoint
@@ -270,6 +773,12 @@ pub enum BreakpointCommands {
     /// List all breakpoints
     List,
 
+    /// Show one breakpoint in detail, e.g. to find out why it isn't verified
+    Info {
+        /// Breakpoint ID
+        id: u32,
+    },
+
     /// Enable a breakpoint
     Enable {
         /// Breakpoint ID to enable
@@ -282,3 +791,93 @@ pub enum BreakpointCommands {
         id: u32,
     },
 }
+
+#[derive(Subcommand)]
+pub enum WatchCommands {
+    /// Add a watch expression
+    Add {
+        /// Expression to evaluate at each stop
+        expression: String,
+
+        /// Only evaluate while this function is on the stack, in its
+        /// innermost frame (e.g. to follow a value through recursion)
+        #[arg(long = "in", value_name = "FUNCTION")]
+        function: Option<String>,
+    },
+
+    /// Remove a watch
+    Remove {
+        /// Watch ID to remove
+        id: Option<u32>,
+
+        /// Remove all watches
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// List all watches
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ThreadCommands {
+    /// Terminate threads, leaving the rest of the process running
+    ///
+    /// Needs an adapter that supports the DAP terminateThreads request.
+    Kill {
+        /// IDs of the threads to terminate
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AnalyzeCommands {
+    /// Find threads blocked on locks or waits, and threads that may be
+    /// waiting on each other
+    ///
+    /// A thread is blocked when its stack passes through a function from
+    /// `[analyze] wait_functions` (pthread_mutex_lock, futex, park, ...).
+    /// Two blocked threads are reported as a potential deadlock when each
+    /// waits in a function the other's stack has already passed through.
+    Deadlock {
+        /// Frames to examine at the top of each thread's stack
+        #[arg(long, default_value = "32")]
+        frames: usize,
+    },
+}
+
+/// Output of `help`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HelpFormat {
+    /// The usual `--help` text
+    Text,
+    /// Reference of every command, option and default
+    Markdown,
+}
+
+/// Rendering for `memory` output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MemoryFormat {
+    Hex,
+    Decimal,
+    Ascii,
+    /// 32-bit IEEE 754 floats
+    Float,
+    /// 64-bit IEEE 754 floats
+    Double,
+}
+
+/// Byte order for multi-byte memory values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+fn parse_word_width(s: &str) -> Result<usize, String> {
+    match s {
+        "2" | "4" | "8" => Ok(s.parse().unwrap()),
+        _ => Err("width must be 2, 4 or 8".to_string()),
+    }
+}
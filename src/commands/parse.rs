@@ -0,0 +1,1892 @@
+//! Textual debugger commands
+//!
+//! Parses the gdb-style command strings used by test scenarios, breakpoint
+//! on-hit commands and `debugger exec` into IPC `Command`s, and formats
+//! `Command`s back into that syntax. Every `Command` has a textual form, and
+//! `parse_command(&format_command(c))` gives back `c`.
+//!
+//! Arguments are whitespace-separated. Expressions (`print`, `eval`, `hover`,
+//! `eval-all`, `step-until`) take the rest of the line verbatim, and a
+//! breakpoint's `--on-hit` must be its last option and takes a quoted,
+//! `;`-separated list of commands. `start`'s words, and a `watch add`
+//! expression as a whole, may be double-quoted to hold spaces or `--in`,
+//! with `\"` and `\\` standing for a quote and a backslash inside quotes.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::common::{Error, Result};
+use crate::ipc::protocol::{BreakpointLocation, Command, EvaluateContext, StepKind};
+
+/// Split a script into its commands: one per line or `;`-separated segment,
+/// skipping blank ones. Separators inside double quotes don't split.
+pub fn split_script(script: &str) -> Vec<&str> {
+    let mut commands = Vec::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in script.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '\n' | ';' if !in_quotes => {
+                commands.push(&script[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    commands.push(&script[start..]);
+
+    commands
+        .into_iter()
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+/// Split an assignment `target = value` at its `=`, which must not be part
+/// of a comparison (`==`, `!=`, `<=`, `>=`) or compound assignment (`+=`)
+pub fn split_assignment(text: &str) -> Result<(String, String)> {
+    let bytes = text.as_bytes();
+    let split = (0..bytes.len()).find(|&index| {
+        bytes[index] == b'='
+            && bytes.get(index + 1) != Some(&b'=')
+            && !(index > 0 && b"=!<>+-*/%&|^".contains(&bytes[index - 1]))
+    });
+    let (target, value) = match split {
+        Some(index) => (text[..index].trim(), text[index + 1..].trim()),
+        None => ("", ""),
+    };
+    if target.is_empty() || value.is_empty() {
+        return Err(Error::Config(format!(
+            "Expected an assignment like 'x = 5', got '{}'",
+            text
+        )));
+    }
+    Ok((target.to_string(), value.to_string()))
+}
+
+/// Parse a command string into a Command enum
+pub fn parse_command(s: &str) -> Result<Command> {
+    let s = s.trim();
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    if parts.is_empty() {
+        return Err(Error::Config("Empty command".to_string()));
+    }
+
+    let cmd = parts[0].to_lowercase();
+    let args = &parts[1..];
+    // Everything after the command word, verbatim
+    let rest = s[parts[0].len()..].trim_start();
+
+    match cmd.as_str() {
+        "start" => parse_start(&split_words(rest)?),
+
+        "attach" => {
+            let (pid, options) = args
+                .split_first()
+                .ok_or_else(|| Error::Config("attach requires a process ID".to_string()))?;
+            let pid = pid
+                .parse()
+                .map_err(|_| Error::Config(format!("Invalid process ID: {}", pid)))?;
+            let mut options = Options::new(&cmd, options);
+            let keep_alive_minutes = options.value("--keep-alive")?;
+            let adapter = match options.args.as_slice() {
+                [] => None,
+                ["--adapter", adapter] => Some(adapter.to_string()),
+                _ => {
+                    return Err(Error::Config(
+                        "attach accepts only --adapter <name> and --keep-alive <minutes>"
+                            .to_string(),
+                    ))
+                }
+            };
+            Ok(Command::Attach {
+                pid,
+                adapter,
+                keep_alive_minutes,
+            })
+        }
+
+        "reattach" => {
+            let mut options = Options::new(&cmd, args);
+            let keep_alive_minutes = options.value("--keep-alive")?;
+            let pid = match options.args.as_slice() {
+                [] => None,
+                [pid] => Some(
+                    pid.parse()
+                        .map_err(|_| Error::Config(format!("Invalid process ID: {}", pid)))?,
+                ),
+                _ => {
+                    return Err(Error::Config(
+                        "reattach accepts only a process ID and --keep-alive <minutes>"
+                            .to_string(),
+                    ))
+                }
+            };
+            Ok(Command::Reattach {
+                pid,
+                keep_alive_minutes,
+            })
+        }
+
+        "status" => Ok(Command::Status),
+        "probe-adapter" => match args {
+            [adapter] => Ok(Command::ProbeAdapter {
+                adapter: adapter.to_string(),
+            }),
+            _ => Err(Error::Config(
+                "probe-adapter requires exactly one adapter name".to_string(),
+            )),
+        },
+        "shutdown" => Ok(Command::Shutdown),
+
+        "continue" | "c" => {
+            let mut options = Options::new(&cmd, args);
+            let this_thread = options.flag("--this-thread");
+            options.finish()?;
+            Ok(Command::Continue { this_thread })
+        }
+        "next" | "n" => parse_step(&cmd, args, StepKind::Over),
+        "step" | "s" => parse_step(&cmd, args, StepKind::In),
+        "finish" | "out" => parse_step(&cmd, args, StepKind::Out),
+        "pause" => Ok(Command::Pause),
+        "cancel-await" => Ok(Command::CancelAwait),
+        "cancel-eval" => Ok(Command::CancelEval),
+        "progress" => Ok(Command::Progress),
+
+        "break" | "b" => {
+            if args.is_empty() {
+                return Err(Error::Config(
+                    "break command requires a location".to_string(),
+                ));
+            }
+            // Skip "add" subcommand if present AND there are more args
+            // (otherwise "add" is the function name to break on)
+            let rest = match rest.strip_prefix("add") {
+                Some(after) if args.len() > 1 && after.starts_with(char::is_whitespace) => {
+                    after.trim_start()
+                }
+                _ => rest,
+            };
+            parse_breakpoint_add(rest, "break")
+        }
+
+        "breakpoint" => {
+            if args.is_empty() {
+                return Err(Error::Config(
+                    "breakpoint command requires a subcommand".to_string(),
+                ));
+            }
+
+            match args[0] {
+                "add" => {
+                    if args.len() < 2 {
+                        return Err(Error::Config(
+                            "breakpoint add requires a location".to_string(),
+                        ));
+                    }
+                    parse_breakpoint_add(rest["add".len()..].trim_start(), "breakpoint add")
+                }
+                "remove" => {
+                    if args.len() < 2 {
+                        return Ok(Command::BreakpointRemove { id: None, all: true });
+                    }
+                    if args[1] == "all" || args[1] == "--all" {
+                        return Ok(Command::BreakpointRemove { id: None, all: true });
+                    }
+                    let id: u32 = args[1].parse().map_err(|_| {
+                        Error::Config(format!("Invalid breakpoint ID: {}", args[1]))
+                    })?;
+                    Ok(Command::BreakpointRemove {
+                        id: Some(id),
+                        all: false,
+                    })
+                }
+                "list" => Ok(Command::BreakpointList),
+                "info" => {
+                    if args.len() < 2 {
+                        return Err(Error::Config(
+                            "breakpoint info requires an ID".to_string(),
+                        ));
+                    }
+                    let id: u32 = args[1].parse().map_err(|_| {
+                        Error::Config(format!("Invalid breakpoint ID: {}", args[1]))
+                    })?;
+                    Ok(Command::BreakpointInfo { id })
+                }
+                "enable" => {
+                    if args.len() < 2 {
+                        return Err(Error::Config(
+                            "breakpoint enable requires an ID".to_string(),
+                        ));
+                    }
+                    let id: u32 = args[1].parse().map_err(|_| {
+                        Error::Config(format!("Invalid breakpoint ID: {}", args[1]))
+                    })?;
+                    Ok(Command::BreakpointEnable { id })
+                }
+                "disable" => {
+                    if args.len() < 2 {
+                        return Err(Error::Config(
+                            "breakpoint disable requires an ID".to_string(),
+                        ));
+                    }
+                    let id: u32 = args[1].parse().map_err(|_| {
+                        Error::Config(format!("Invalid breakpoint ID: {}", args[1]))
+                    })?;
+                    Ok(Command::BreakpointDisable { id })
+                }
+                _ => Err(Error::Config(format!(
+                    "Unknown breakpoint subcommand: {}",
+                    args[0]
+                ))),
+            }
+        }
+
+        "triage" => {
+            let mut options = Options::new(&cmd, args);
+            let limit = options.value("--limit")?.unwrap_or(20);
+            let lines = options.value("--lines")?.unwrap_or(5);
+            options.finish()?;
+            Ok(Command::Triage { limit, lines })
+        }
+
+        "context" | "where" => {
+            let lines = match args {
+                [] => 5,
+                [value] => value.parse().map_err(|_| {
+                    Error::Config(format!("{} requires a numeric line count", cmd))
+                })?,
+                [flag, value] if *flag == "--lines" => value.parse().map_err(|_| {
+                    Error::Config(format!("{} --lines requires a number", cmd))
+                })?,
+                _ => {
+                    return Err(Error::Config(format!(
+                        "{} accepts either <lines> or --lines <lines>",
+                        cmd
+                    )))
+                }
+            };
+            Ok(Command::Context { lines })
+        }
+
+        "locals" => {
+            let mut options = Options::new(&cmd, args);
+            let frame_id = options.value("--frame-id")?;
+            let frame_index = options.value("--frame")?;
+            let max_string_length = options.value("--max-string-length")?;
+            let no_truncate = options.flag("--no-truncate");
+            let no_cache = options.flag("--no-cache");
+            options.finish()?;
+            check_one_frame(frame_id, frame_index)?;
+            Ok(Command::Locals {
+                frame_id,
+                frame_index,
+                max_string_length,
+                no_truncate,
+                no_cache,
+            })
+        }
+
+        "backtrace" | "bt" => {
+            let mut options = Options::new(&cmd, args);
+            let limit = options.value("--limit")?.unwrap_or(20);
+            let thread_id = options.value("--thread")?;
+            let user_only = options.flag("--user-only");
+            options.finish()?;
+            Ok(Command::StackTrace {
+                thread_id,
+                limit,
+                user_only,
+            })
+        }
+
+        "eval-all" => {
+            // An optional leading `--limit <n>`, then the expression
+            let (limit, expression) = match args {
+                ["--limit", limit, ..] => {
+                    let limit = limit
+                        .parse()
+                        .map_err(|_| Error::Config(format!("Invalid frame limit: {}", limit)))?;
+                    let after_flag = rest["--limit".len()..].trim_start();
+                    (limit, after_flag[args[1].len()..].trim_start())
+                }
+                _ => (20, rest),
+            };
+            if expression.is_empty() {
+                return Err(Error::Config("eval-all requires an expression".to_string()));
+            }
+            Ok(Command::EvaluateAll {
+                expression: expression.to_string(),
+                limit,
+            })
+        }
+
+        "step-until" => {
+            // Leading `--max-steps <n>`, `--timeout <secs>`, `--this-thread`
+            // and `--into` or `--out`, then the expression
+            let mut kind = StepKind::Over;
+            let mut max_steps = 100;
+            let mut timeout_secs = 300;
+            let mut this_thread = false;
+            let mut expression = rest;
+            let mut leading = args;
+            loop {
+                match leading {
+                    [flag @ ("--into" | "--out" | "--this-thread"), tail @ ..] => {
+                        match *flag {
+                            "--into" => kind = StepKind::In,
+                            "--out" => kind = StepKind::Out,
+                            _ => this_thread = true,
+                        }
+                        expression = expression[flag.len()..].trim_start();
+                        leading = tail;
+                    }
+                    [flag @ ("--max-steps" | "--timeout"), value, tail @ ..] => {
+                        let invalid =
+                            || Error::Config(format!("Invalid value for {}: {}", flag, value));
+                        if *flag == "--max-steps" {
+                            max_steps = value.parse().map_err(|_| invalid())?;
+                        } else {
+                            timeout_secs = value.parse().map_err(|_| invalid())?;
+                        }
+                        let after_flag = expression[flag.len()..].trim_start();
+                        expression = after_flag[value.len()..].trim_start();
+                        leading = tail;
+                    }
+                    _ => break,
+                }
+            }
+            if expression.is_empty() {
+                return Err(Error::Config("step-until requires an expression".to_string()));
+            }
+            if max_steps == 0 {
+                return Err(Error::Config("step-until --max-steps must be at least 1".to_string()));
+            }
+            Ok(Command::StepUntil {
+                expression: expression.to_string(),
+                kind,
+                max_steps,
+                timeout_secs,
+                this_thread,
+            })
+        }
+
+        "scopes" => Ok(Command::Scopes {
+            frame_id: single_number(&cmd, args, "frame ID")?,
+        }),
+
+        "variables" | "vars" => Ok(Command::Variables {
+            reference: single_number(&cmd, args, "variables reference")?,
+        }),
+
+        "memory" | "x" => {
+            if let ["--var", name, options @ ..] = args {
+                let mut options = Options::new(&cmd, options);
+                let count = options.value("--count")?.unwrap_or(64);
+                options.finish()?;
+                return Ok(Command::ReadVariableMemory {
+                    name: name.to_string(),
+                    count,
+                });
+            }
+            let (address, options) = args.split_first().ok_or_else(|| {
+                Error::Config(format!("{} requires an address or --var <name>", cmd))
+            })?;
+            let mut options = Options::new(&cmd, options);
+            let count = options.value("--count")?.unwrap_or(64);
+            options.finish()?;
+            Ok(Command::ReadMemory {
+                address: address.to_string(),
+                count,
+            })
+        }
+
+        "disassemble" | "disas" => {
+            let (address, options) = match args.split_first() {
+                Some((address, options)) if !address.starts_with('-') => {
+                    (Some(address.to_string()), options)
+                }
+                _ => (None, args),
+            };
+            let mut options = Options::new(&cmd, options);
+            let count = options.value("--count")?.unwrap_or(20);
+            let source = options.flag("--source");
+            options.finish()?;
+            Ok(Command::Disassemble {
+                address,
+                count,
+                source,
+            })
+        }
+
+        "registers" | "regs" => Ok(Command::Registers),
+        "analyze" => match args.split_first() {
+            Some((&"deadlock", options)) => {
+                let mut options = Options::new(&cmd, options);
+                let frames = options.value("--frames")?.unwrap_or(32);
+                options.finish()?;
+                Ok(Command::AnalyzeDeadlock { frames })
+            }
+            _ => Err(Error::Config("analyze requires an analysis: deadlock".to_string())),
+        },
+
+        "threads" => {
+            let mut options = Options::new(&cmd, args);
+            let limit = options.value("--limit")?;
+            let filter = options.text("--filter")?;
+            let refresh = options.flag("--refresh");
+            options.finish()?;
+            Ok(Command::Threads {
+                limit,
+                filter,
+                refresh,
+            })
+        }
+        "modules" => Ok(Command::Modules),
+
+        "thread" if args.first() == Some(&"kill") => {
+            if args.len() < 2 {
+                return Err(Error::Config(
+                    "thread kill requires at least one thread ID".to_string(),
+                ));
+            }
+            let ids = args[1..]
+                .iter()
+                .map(|id| {
+                    id.parse()
+                        .map_err(|_| Error::Config(format!("Invalid thread ID: {}", id)))
+                })
+                .collect::<Result<Vec<i64>>>()?;
+            Ok(Command::TerminateThreads { ids })
+        }
+        "thread" => {
+            if args.is_empty() {
+                return Err(Error::Config("thread command requires an ID".to_string()));
+            }
+            let id: i64 = args[0]
+                .parse()
+                .map_err(|_| Error::Config(format!("Invalid thread ID: {}", args[0])))?;
+            Ok(Command::ThreadSelect { id })
+        }
+
+        "frame" => {
+            if args.is_empty() {
+                return Err(Error::Config(
+                    "frame command requires a number".to_string(),
+                ));
+            }
+            let number: usize = args[0]
+                .parse()
+                .map_err(|_| Error::Config(format!("Invalid frame number: {}", args[0])))?;
+            Ok(Command::FrameSelect { number })
+        }
+
+        "up" => Ok(Command::FrameUp),
+        "down" => Ok(Command::FrameDown),
+
+        "watch" => match args.first().copied() {
+            Some("add") if args.len() > 1 => {
+                // `--in <function>` comes last; the expression may contain
+                // spaces, and is quoted if it contains `--in` itself
+                let text = rest["add".len()..].trim_start();
+                let quoted = take_quoted(text)
+                    .map(|(expression, after)| (expression, after.trim_start()))
+                    .filter(|(_, after)| after.is_empty() || find_option(after, "--in") == Some(0));
+                let (expression, text) = match &quoted {
+                    Some((expression, after)) => (Some(expression.as_str()), *after),
+                    None => (None, text),
+                };
+                let (unquoted, function) = match find_option(text, "--in") {
+                    Some(position) => {
+                        let function = text[position + "--in".len()..].trim();
+                        if function.is_empty() || function.contains(char::is_whitespace) {
+                            return Err(Error::Config(
+                                "watch add --in requires one function name".to_string(),
+                            ));
+                        }
+                        (text[..position].trim(), Some(function.to_string()))
+                    }
+                    None => (text, None),
+                };
+                let expression = expression.unwrap_or(unquoted);
+                if expression.is_empty() {
+                    return Err(Error::Config("watch add requires an expression".to_string()));
+                }
+                Ok(Command::WatchAdd {
+                    expression: expression.to_string(),
+                    function,
+                })
+            }
+            Some("add") => Err(Error::Config("watch add requires an expression".to_string())),
+            Some("remove") => match args.get(1) {
+                None | Some(&"all") | Some(&"--all") => Ok(Command::WatchRemove { id: None }),
+                Some(id) => Ok(Command::WatchRemove {
+                    id: Some(
+                        id.parse()
+                            .map_err(|_| Error::Config(format!("Invalid watch ID: {}", id)))?,
+                    ),
+                }),
+            },
+            Some("list") => Ok(Command::WatchList),
+            _ => Err(Error::Config(
+                "watch requires a subcommand: add, remove or list".to_string(),
+            )),
+        },
+
+        "print" | "p" | "eval" | "hover" => {
+            let context = match cmd.as_str() {
+                "eval" => EvaluateContext::Repl,
+                "hover" => EvaluateContext::Hover,
+                _ => EvaluateContext::Watch,
+            };
+            // Leading `--frame-id <id>`, `--frame <index>`,
+            // `--max-string-length <n>`, `--no-truncate` and `--assign`, then
+            // the expression
+            let mut frame_id = None;
+            let mut frame_index = None;
+            let mut max_string_length = None;
+            let mut no_truncate = false;
+            let mut assign = false;
+            let mut expression = rest;
+            let mut leading = args;
+            loop {
+                match leading {
+                    [flag @ ("--no-truncate" | "--assign"), tail @ ..] => {
+                        match *flag {
+                            "--assign" => assign = true,
+                            _ => no_truncate = true,
+                        }
+                        expression = expression[flag.len()..].trim_start();
+                        leading = tail;
+                    }
+                    [
+                        flag @ ("--frame-id" | "--frame" | "--max-string-length"),
+                        value,
+                        tail @ ..,
+                    ] => {
+                        let invalid =
+                            || Error::Config(format!("Invalid value for {}: {}", flag, value));
+                        match *flag {
+                            "--frame-id" => frame_id = Some(value.parse().map_err(|_| invalid())?),
+                            "--frame" => frame_index = Some(value.parse().map_err(|_| invalid())?),
+                            _ => max_string_length = Some(value.parse().map_err(|_| invalid())?),
+                        }
+                        let after_flag = expression[flag.len()..].trim_start();
+                        expression = after_flag[value.len()..].trim_start();
+                        leading = tail;
+                    }
+                    _ => break,
+                }
+            }
+            if expression.is_empty() {
+                return Err(Error::Config(
+                    "print/eval command requires an expression".to_string(),
+                ));
+            }
+            check_one_frame(frame_id, frame_index)?;
+            if assign {
+                if !matches!(context, EvaluateContext::Repl) {
+                    return Err(Error::Config("--assign works only with eval".to_string()));
+                }
+                let (target, value) = split_assignment(expression)?;
+                return Ok(Command::Assign {
+                    target,
+                    value,
+                    frame_index,
+                });
+            }
+            Ok(Command::Evaluate {
+                expression: expression.to_string(),
+                frame_id,
+                frame_index,
+                context,
+                max_string_length,
+                no_truncate,
+            })
+        }
+
+        "await" | "wait" => {
+            let mut options = Options::new(&cmd, args);
+            let timeout_secs = options.value("--timeout")?.unwrap_or(300);
+            let background = options.flag("--background");
+            let notify: Option<PathBuf> = options.value("--notify")?;
+            options.finish()?;
+            match (background, notify) {
+                (false, None) => Ok(Command::Await { timeout_secs }),
+                (true, Some(path)) => Ok(Command::AwaitNotify { timeout_secs, path }),
+                _ => Err(Error::Config(
+                    "await --background and --notify <path> go together".to_string(),
+                )),
+            }
+        }
+
+        "stop" => Ok(Command::Stop),
+        "detach" => Ok(Command::Detach),
+        "restart" => Ok(Command::Restart),
+        "reset" => Ok(Command::Reset),
+
+        "output" => {
+            // Parse the same options accepted by the user-facing CLI.
+            let mut tail: Option<usize> = None;
+            let mut clear = false;
+            let mut max_bytes_mb = None;
+            let mut max_events = None;
+            let mut raw = false;
+            let mut wait_ms = 0;
+            let mut i = 0;
+            while i < args.len() {
+                match args[i] {
+                    "--tail" | "-t" => {
+                        let value = args.get(i + 1).ok_or_else(|| {
+                            Error::Config("output --tail requires a number".to_string())
+                        })?;
+                        tail = Some(value.parse().map_err(|_| {
+                            Error::Config(format!("Invalid output tail value: {}", value))
+                        })?);
+                        i += 2;
+                    }
+                    "--clear" => {
+                        clear = true;
+                        i += 1;
+                    }
+                    "--raw" => {
+                        raw = true;
+                        i += 1;
+                    }
+                    "--wait-ms" => {
+                        wait_ms = args
+                            .get(i + 1)
+                            .and_then(|value| value.parse().ok())
+                            .ok_or_else(|| {
+                                Error::Config("output --wait-ms requires a number".to_string())
+                            })?;
+                        i += 2;
+                    }
+                    option @ ("--set-limit-mb" | "--set-limit-events") => {
+                        let value = args
+                            .get(i + 1)
+                            .and_then(|value| value.parse().ok())
+                            .ok_or_else(|| {
+                                Error::Config(format!("output {} requires a number", option))
+                            })?;
+                        if option == "--set-limit-mb" {
+                            max_bytes_mb = Some(value);
+                        } else {
+                            max_events = Some(value);
+                        }
+                        i += 2;
+                    }
+                    option => {
+                        return Err(Error::Config(format!(
+                            "Unknown output option: {}",
+                            option
+                        )));
+                    }
+                }
+            }
+            if raw {
+                if tail.is_some() || clear || max_bytes_mb.is_some() || max_events.is_some() {
+                    return Err(Error::Config(
+                        "output --raw accepts only --wait-ms".to_string(),
+                    ));
+                }
+                return Ok(Command::FollowOutput { wait_ms });
+            }
+            if max_bytes_mb.is_some() || max_events.is_some() {
+                if tail.is_some() || clear {
+                    return Err(Error::Config(
+                        "output --set-limit-* can't be combined with --tail or --clear"
+                            .to_string(),
+                    ));
+                }
+                return Ok(Command::SetOutputLimits {
+                    max_bytes_mb,
+                    max_events,
+                });
+            }
+            Ok(Command::GetOutput { tail, clear })
+        }
+
+        "events" => {
+            let mut options = Options::new(&cmd, args);
+            let wait_ms = options.value("--wait-ms")?.unwrap_or(0);
+            let only = options.text("--only")?.map(|kinds| {
+                kinds.split(',').map(|kind| kind.trim().to_string()).collect()
+            });
+            options.finish()?;
+            Ok(Command::FollowEvents {
+                wait_ms,
+                only: only.unwrap_or_default(),
+            })
+        }
+
+        _ => Err(Error::Config(format!("Unknown command: {}", cmd))),
+    }
+}
+
+/// Format a command in the syntax accepted by [`parse_command`]
+pub fn format_command(command: &Command) -> String {
+    match command {
+        Command::Start {
+            program,
+            args,
+            adapter,
+            stop_on_entry,
+            initial_breakpoints,
+            keep_alive_minutes,
+        } => {
+            let mut text = format!("start {}", quote_word(&program.to_string_lossy()));
+            if let Some(adapter) = adapter {
+                text.push_str(&format!(" --adapter {}", quote_word(adapter)));
+            }
+            if *stop_on_entry {
+                text.push_str(" --stop-on-entry");
+            }
+            for location in initial_breakpoints {
+                text.push_str(&format!(" --break {}", quote_word(location)));
+            }
+            if let Some(minutes) = keep_alive_minutes {
+                text.push_str(&format!(" --keep-alive {}", minutes));
+            }
+            if !args.is_empty() {
+                let args: Vec<String> = args.iter().map(|arg| quote_word(arg)).collect();
+                text.push_str(&format!(" -- {}", args.join(" ")));
+            }
+            text
+        }
+        Command::Attach {
+            pid,
+            adapter,
+            keep_alive_minutes,
+        } => {
+            let mut text = format!("attach {}", pid);
+            if let Some(adapter) = adapter {
+                text.push_str(&format!(" --adapter {}", adapter));
+            }
+            if let Some(minutes) = keep_alive_minutes {
+                text.push_str(&format!(" --keep-alive {}", minutes));
+            }
+            text
+        }
+        Command::Reattach {
+            pid,
+            keep_alive_minutes,
+        } => {
+            let mut text = "reattach".to_string();
+            if let Some(pid) = pid {
+                text.push_str(&format!(" {}", pid));
+            }
+            if let Some(minutes) = keep_alive_minutes {
+                text.push_str(&format!(" --keep-alive {}", minutes));
+            }
+            text
+        }
+        Command::Detach => "detach".to_string(),
+        Command::Stop => "stop".to_string(),
+        Command::Restart => "restart".to_string(),
+        Command::Reset => "reset".to_string(),
+        Command::Status => "status".to_string(),
+        Command::ProbeAdapter { adapter } => format!("probe-adapter {}", adapter),
+        Command::BreakpointAdd {
+            location,
+            condition,
+            hit_count,
+            on_hit,
+        } => {
+            let mut text = format!("break {}", location);
+            if let Some(condition) = condition {
+                text.push_str(&format!(" --condition {}", condition));
+            }
+            if let Some(hit_count) = hit_count {
+                text.push_str(&format!(" --hit-count {}", hit_count));
+            }
+            if !on_hit.is_empty() {
+                text.push_str(&format!(" --on-hit \"{}\"", on_hit.join("; ")));
+            }
+            text
+        }
+        Command::BreakpointRemove { id: Some(id), .. } => format!("breakpoint remove {}", id),
+        Command::BreakpointRemove { id: None, .. } => "breakpoint remove --all".to_string(),
+        Command::BreakpointList => "breakpoint list".to_string(),
+        Command::BreakpointInfo { id } => format!("breakpoint info {}", id),
+        Command::BreakpointEnable { id } => format!("breakpoint enable {}", id),
+        Command::BreakpointDisable { id } => format!("breakpoint disable {}", id),
+        Command::WatchAdd {
+            expression,
+            function,
+        } => {
+            // Quoted when it would otherwise read back differently
+            let expression = if expression.starts_with('"')
+                || find_option(expression, "--in").is_some()
+            {
+                quote(expression)
+            } else {
+                expression.clone()
+            };
+            match function {
+                Some(function) => format!("watch add {} --in {}", expression, function),
+                None => format!("watch add {}", expression),
+            }
+        }
+        Command::WatchRemove { id: Some(id) } => format!("watch remove {}", id),
+        Command::WatchRemove { id: None } => "watch remove --all".to_string(),
+        Command::WatchList => "watch list".to_string(),
+        Command::Continue { this_thread } => format!("continue{}", this_thread_flag(*this_thread)),
+        Command::Next { this_thread } => format!("next{}", this_thread_flag(*this_thread)),
+        Command::StepCount {
+            kind,
+            count,
+            timeout_secs,
+            this_thread,
+        } => {
+            let name = match kind {
+                StepKind::Over => "next",
+                StepKind::In => "step",
+                StepKind::Out => "finish",
+            };
+            format!(
+                "{} --count {} --timeout {}{}",
+                name,
+                count,
+                timeout_secs,
+                this_thread_flag(*this_thread)
+            )
+        }
+        Command::StepIn { this_thread } => format!("step{}", this_thread_flag(*this_thread)),
+        Command::StepOut { this_thread } => format!("finish{}", this_thread_flag(*this_thread)),
+        Command::Pause => "pause".to_string(),
+        Command::StackTrace {
+            thread_id,
+            limit,
+            user_only,
+        } => {
+            let mut text = format!("backtrace --limit {}", limit);
+            if let Some(thread_id) = thread_id {
+                text.push_str(&format!(" --thread {}", thread_id));
+            }
+            if *user_only {
+                text.push_str(" --user-only");
+            }
+            text
+        }
+        Command::Locals {
+            frame_id,
+            frame_index,
+            max_string_length,
+            no_truncate,
+            no_cache,
+        } => {
+            let mut text = "locals".to_string();
+            if let Some(frame_id) = frame_id {
+                text.push_str(&format!(" --frame-id {}", frame_id));
+            }
+            if let Some(frame_index) = frame_index {
+                text.push_str(&format!(" --frame {}", frame_index));
+            }
+            if let Some(max_string_length) = max_string_length {
+                text.push_str(&format!(" --max-string-length {}", max_string_length));
+            }
+            if *no_truncate {
+                text.push_str(" --no-truncate");
+            }
+            if *no_cache {
+                text.push_str(" --no-cache");
+            }
+            text
+        }
+        Command::Evaluate {
+            expression,
+            frame_id,
+            frame_index,
+            context,
+            max_string_length,
+            no_truncate,
+        } => {
+            let mut text = match context {
+                EvaluateContext::Watch => "print",
+                EvaluateContext::Repl => "eval",
+                EvaluateContext::Hover => "hover",
+            }
+            .to_string();
+            if let Some(frame_id) = frame_id {
+                text.push_str(&format!(" --frame-id {}", frame_id));
+            }
+            if let Some(frame_index) = frame_index {
+                text.push_str(&format!(" --frame {}", frame_index));
+            }
+            if let Some(max_string_length) = max_string_length {
+                text.push_str(&format!(" --max-string-length {}", max_string_length));
+            }
+            if *no_truncate {
+                text.push_str(" --no-truncate");
+            }
+            format!("{} {}", text, expression)
+        }
+        Command::Assign {
+            target,
+            value,
+            frame_index,
+        } => {
+            let mut text = "eval --assign".to_string();
+            if let Some(frame_index) = frame_index {
+                text.push_str(&format!(" --frame {}", frame_index));
+            }
+            format!("{} {} = {}", text, target, value)
+        }
+        Command::EvaluateAll { expression, limit } => {
+            format!("eval-all --limit {} {}", limit, expression)
+        }
+        Command::StepUntil {
+            expression,
+            kind,
+            max_steps,
+            timeout_secs,
+            this_thread,
+        } => {
+            let kind = match kind {
+                StepKind::Over => "",
+                StepKind::In => " --into",
+                StepKind::Out => " --out",
+            };
+            format!(
+                "step-until --max-steps {} --timeout {}{}{} {}",
+                max_steps,
+                timeout_secs,
+                kind,
+                this_thread_flag(*this_thread),
+                expression
+            )
+        }
+        Command::Scopes { frame_id } => format!("scopes {}", frame_id),
+        Command::Variables { reference } => format!("variables {}", reference),
+        Command::ReadMemory { address, count } => {
+            format!("memory {} --count {}", address, count)
+        }
+        Command::ReadVariableMemory { name, count } => {
+            format!("memory --var {} --count {}", name, count)
+        }
+        Command::Disassemble {
+            address,
+            count,
+            source,
+        } => {
+            let mut text = "disassemble".to_string();
+            if let Some(address) = address {
+                text.push_str(&format!(" {}", address));
+            }
+            text.push_str(&format!(" --count {}", count));
+            if *source {
+                text.push_str(" --source");
+            }
+            text
+        }
+        Command::Registers => "registers".to_string(),
+        Command::AnalyzeDeadlock { frames } => format!("analyze deadlock --frames {}", frames),
+        Command::Threads {
+            limit,
+            filter,
+            refresh,
+        } => {
+            let mut text = "threads".to_string();
+            if let Some(limit) = limit {
+                text.push_str(&format!(" --limit {}", limit));
+            }
+            if let Some(filter) = filter {
+                text.push_str(&format!(" --filter {}", filter));
+            }
+            if *refresh {
+                text.push_str(" --refresh");
+            }
+            text
+        }
+        Command::Modules => "modules".to_string(),
+        Command::ThreadSelect { id } => format!("thread {}", id),
+        Command::TerminateThreads { ids } => {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            format!("thread kill {}", ids.join(" "))
+        }
+        Command::FrameSelect { number } => format!("frame {}", number),
+        Command::FrameUp => "up".to_string(),
+        Command::FrameDown => "down".to_string(),
+        Command::Context { lines } => format!("context --lines {}", lines),
+        Command::Triage { limit, lines } => {
+            format!("triage --limit {} --lines {}", limit, lines)
+        }
+        Command::Await { timeout_secs } => format!("await --timeout {}", timeout_secs),
+        Command::CancelAwait => "cancel-await".to_string(),
+        Command::CancelEval => "cancel-eval".to_string(),
+        Command::Progress => "progress".to_string(),
+        Command::AwaitNotify { timeout_secs, path } => format!(
+            "await --timeout {} --background --notify {}",
+            timeout_secs,
+            path.display()
+        ),
+        Command::FollowOutput { wait_ms } => format!("output --raw --wait-ms {}", wait_ms),
+        Command::FollowEvents { wait_ms, only } if only.is_empty() => {
+            format!("events --wait-ms {}", wait_ms)
+        }
+        Command::FollowEvents { wait_ms, only } => {
+            format!("events --wait-ms {} --only {}", wait_ms, only.join(","))
+        }
+        Command::SetOutputLimits {
+            max_bytes_mb,
+            max_events,
+        } => {
+            let mut text = "output".to_string();
+            if let Some(mb) = max_bytes_mb {
+                text.push_str(&format!(" --set-limit-mb {}", mb));
+            }
+            if let Some(events) = max_events {
+                text.push_str(&format!(" --set-limit-events {}", events));
+            }
+            text
+        }
+        Command::GetOutput { tail, clear } => {
+            let mut text = "output".to_string();
+            if let Some(tail) = tail {
+                text.push_str(&format!(" --tail {}", tail));
+            }
+            if *clear {
+                text.push_str(" --clear");
+            }
+            text
+        }
+        Command::Shutdown => "shutdown".to_string(),
+    }
+}
+
+/// `start <program> [--adapter <name>] [--stop-on-entry] [--break <loc>]...
+/// [--keep-alive <minutes>] [-- <args>...]`
+fn parse_start(args: &[String]) -> Result<Command> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (args, program_args) = match args.iter().position(|arg| *arg == "--") {
+        Some(split) => (&args[..split], &args[split + 1..]),
+        None => (&args[..], &[][..]),
+    };
+
+    let mut program = None;
+    let mut adapter = None;
+    let mut stop_on_entry = false;
+    let mut initial_breakpoints = Vec::new();
+    let mut keep_alive_minutes = None;
+    let mut index = 0;
+    while index < args.len() {
+        match args[index] {
+            "--adapter" | "--break" | "-b" => {
+                let value = args.get(index + 1).ok_or_else(|| {
+                    Error::Config(format!("start {} requires a value", args[index]))
+                })?;
+                if args[index] == "--adapter" {
+                    adapter = Some(value.to_string());
+                } else {
+                    initial_breakpoints.push(value.to_string());
+                }
+                index += 2;
+            }
+            "--stop-on-entry" => {
+                stop_on_entry = true;
+                index += 1;
+            }
+            "--keep-alive" => {
+                let minutes = args.get(index + 1).and_then(|value| value.parse().ok());
+                keep_alive_minutes = Some(minutes.ok_or_else(|| {
+                    Error::Config("start --keep-alive requires a number".to_string())
+                })?);
+                index += 2;
+            }
+            option if option.starts_with('-') => {
+                return Err(Error::Config(format!("Unknown start option: {}", option)));
+            }
+            path if program.is_none() => {
+                program = Some(PathBuf::from(path));
+                index += 1;
+            }
+            extra => {
+                return Err(Error::Config(format!(
+                    "Unexpected start argument: {} (pass program arguments after --)",
+                    extra
+                )));
+            }
+        }
+    }
+
+    Ok(Command::Start {
+        program: program
+            .ok_or_else(|| Error::Config("start requires a program".to_string()))?,
+        args: program_args.iter().map(|arg| arg.to_string()).collect(),
+        adapter,
+        stop_on_entry,
+        initial_breakpoints,
+        keep_alive_minutes,
+    })
+}
+
+/// Refuse `--frame-id` with `--frame`, which name a frame two ways
+fn check_one_frame(frame_id: Option<i64>, frame_index: Option<usize>) -> Result<()> {
+    if frame_id.is_some() && frame_index.is_some() {
+        return Err(Error::Config(
+            "--frame and --frame-id can't be used together".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// ` --this-thread` when set, for formatting execution commands
+fn this_thread_flag(this_thread: bool) -> &'static str {
+    if this_thread {
+        " --this-thread"
+    } else {
+        ""
+    }
+}
+
+/// `next`, `step` or `finish`, optionally repeated: `next --count 10` (or
+/// `next 10`) steps ten times, waiting `--timeout` seconds for each step
+fn parse_step(command: &str, args: &[&str], kind: StepKind) -> Result<Command> {
+    let mut options = Options::new(command, args);
+    let count: Option<u32> = options.value("--count")?;
+    let timeout_secs = options.value("--timeout")?.unwrap_or(300);
+    let this_thread = options.flag("--this-thread");
+    let count = match (count, options.args.as_slice()) {
+        (None, [number]) => {
+            let count = single_number(command, &[number], "step count")?;
+            options.args.clear();
+            Some(count)
+        }
+        (count, _) => count,
+    };
+    options.finish()?;
+    match count {
+        None => Ok(kind.command(this_thread)),
+        Some(0) => Err(Error::Config(format!("{} count must be at least 1", command))),
+        Some(count) => Ok(Command::StepCount {
+            kind,
+            count,
+            timeout_secs,
+            this_thread,
+        }),
+    }
+}
+
+/// Parse a breakpoint location and the shared breakpoint options used by the
+/// CLI shorthand and the `breakpoint add` subcommand.
+fn parse_breakpoint_add(rest: &str, command: &str) -> Result<Command> {
+    // `--on-hit` takes the remainder of the line
+    let (rest, on_hit) = match find_option(rest, "--on-hit") {
+        Some(position) => {
+            let commands = rest[position + "--on-hit".len()..].trim();
+            let commands = commands
+                .strip_prefix('"')
+                .and_then(|c| c.strip_suffix('"'))
+                .unwrap_or(commands);
+            let on_hit: Vec<String> = split_script(commands)
+                .into_iter()
+                .map(String::from)
+                .collect();
+            if on_hit.is_empty() {
+                return Err(Error::Config(format!(
+                    "{} --on-hit requires at least one command",
+                    command
+                )));
+            }
+            (&rest[..position], on_hit)
+        }
+        None => (rest, Vec::new()),
+    };
+    let args: Vec<&str> = rest.split_whitespace().collect();
+
+    let mut location_parts = Vec::new();
+    let mut condition = None;
+    let mut hit_count = None;
+    let mut index = 0;
+
+    while index < args.len() {
+        match args[index] {
+            "--condition" | "-c" => {
+                index += 1;
+                let mut condition_parts = Vec::new();
+                while index < args.len() && !args[index].starts_with("--") {
+                    condition_parts.push(args[index]);
+                    index += 1;
+                }
+                if condition_parts.is_empty() {
+                    return Err(Error::Config(format!(
+                        "{} --condition requires an expression",
+                        command
+                    )));
+                }
+                condition = Some(condition_parts.join(" ").trim_matches('"').to_string());
+            }
+            "--hit-count" => {
+                let value = args.get(index + 1).ok_or_else(|| {
+                    Error::Config(format!("{} --hit-count requires a number", command))
+                })?;
+                hit_count = Some(value.parse().map_err(|_| {
+                    Error::Config(format!("Invalid hit count: {}", value))
+                })?);
+                index += 2;
+            }
+            option if option.starts_with('-') && !is_line_offset(option) => {
+                return Err(Error::Config(format!(
+                    "Unknown {} option: {}",
+                    command, option
+                )));
+            }
+            location => {
+                location_parts.push(location);
+                index += 1;
+            }
+        }
+    }
+
+    if location_parts.is_empty() {
+        return Err(Error::Config(format!("{} requires a location", command)));
+    }
+
+    Ok(Command::BreakpointAdd {
+        location: BreakpointLocation::parse(&location_parts.join(" "))?,
+        condition,
+        hit_count,
+        on_hit,
+    })
+}
+
+/// Whether a `-`-prefixed argument is a relative location like `-3` rather than an option
+fn is_line_offset(arg: &str) -> bool {
+    arg.strip_prefix('-').is_some_and(|digits| {
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+/// Split `text` into words at whitespace. Double quotes group a word's
+/// characters, spaces included, and inside them `\"` and `\\` stand for a
+/// quote and a backslash.
+fn split_words(text: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            let (quoted, after) = take_quoted(rest)
+                .ok_or_else(|| Error::Config(format!("Unterminated quote in: {}", text)))?;
+            word.get_or_insert_with(String::new).push_str(&quoted);
+            rest = after;
+            continue;
+        }
+        if c.is_whitespace() {
+            words.extend(word.take());
+        } else {
+            word.get_or_insert_with(String::new).push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// The contents of the double-quoted string `text` starts with, unescaped,
+/// and the rest of `text` after it
+fn take_quoted(text: &str) -> Option<(String, &str)> {
+    let mut quoted = String::new();
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((quoted, &text[index + 2..])),
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => quoted.push(c),
+                Some((_, c)) => {
+                    quoted.push('\\');
+                    quoted.push(c);
+                }
+                None => return None,
+            },
+            c => quoted.push(c),
+        }
+    }
+    None
+}
+
+/// `text` in double quotes, escaped for [`take_quoted`]
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// `word` as it must be written to read back as one word with [`split_words`]
+fn quote_word(word: &str) -> String {
+    if word.is_empty() || word.contains(|c: char| c.is_whitespace() || c == '"' || c == ';') {
+        quote(word)
+    } else {
+        word.to_string()
+    }
+}
+
+/// Byte offset of `option` in `text` when it appears as a whole word
+fn find_option(text: &str, option: &str) -> Option<usize> {
+    text.match_indices(option)
+        .map(|(position, _)| position)
+        .find(|&position| {
+            let before = text[..position].chars().next_back();
+            let after = text[position + option.len()..].chars().next();
+            before.is_none_or(char::is_whitespace) && after.is_none_or(char::is_whitespace)
+        })
+}
+
+/// The single numeric argument of commands like `scopes <frame-id>`
+fn single_number<T: FromStr>(command: &str, args: &[&str], what: &str) -> Result<T> {
+    match args {
+        [value] => value
+            .parse()
+            .map_err(|_| Error::Config(format!("Invalid {}: {}", what, value))),
+        _ => Err(Error::Config(format!("{} requires a {}", command, what))),
+    }
+}
+
+/// `--name <value>` and `--flag` options of one command
+struct Options<'a> {
+    command: &'a str,
+    args: Vec<&'a str>,
+}
+
+impl<'a> Options<'a> {
+    fn new(command: &'a str, args: &[&'a str]) -> Self {
+        Self {
+            command,
+            args: args.to_vec(),
+        }
+    }
+
+    /// Take `name <value>`, parsing the value
+    fn value<T: FromStr>(&mut self, name: &str) -> Result<Option<T>> {
+        let Some(index) = self.args.iter().position(|arg| *arg == name) else {
+            return Ok(None);
+        };
+        let value = self.args.get(index + 1).copied().ok_or_else(|| {
+            Error::Config(format!("{} {} requires a number", self.command, name))
+        })?;
+        let parsed = value.parse().map_err(|_| {
+            Error::Config(format!("{} {} requires a number", self.command, name))
+        })?;
+        self.args.drain(index..index + 2);
+        Ok(Some(parsed))
+    }
+
+    /// Take `name <value>`, keeping the value as text
+    fn text(&mut self, name: &str) -> Result<Option<String>> {
+        let Some(index) = self.args.iter().position(|arg| *arg == name) else {
+            return Ok(None);
+        };
+        let value = self.args.get(index + 1).map(|value| value.to_string()).ok_or_else(|| {
+            Error::Config(format!("{} {} requires a value", self.command, name))
+        })?;
+        self.args.drain(index..index + 2);
+        Ok(Some(value))
+    }
+
+    /// Take `name`, returning whether it was present
+    fn flag(&mut self, name: &str) -> bool {
+        match self.args.iter().position(|arg| *arg == name) {
+            Some(index) => {
+                self.args.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Fail on anything that wasn't taken
+    fn finish(self) -> Result<()> {
+        match self.args.first() {
+            None => Ok(()),
+            Some(arg) => Err(Error::Config(format!(
+                "Unknown {} argument: {}",
+                self.command, arg
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_split_on_newlines_and_semicolons() {
+        assert_eq!(
+            split_script("break main; continue\n\n locals ;"),
+            vec!["break main", "continue", "locals"]
+        );
+        assert_eq!(
+            split_script("break f --on-hit \"locals; bt\"; continue"),
+            vec!["break f --on-hit \"locals; bt\"", "continue"]
+        );
+        assert_eq!(
+            split_script("start ./app -- \"a\\\"; b\"; continue"),
+            vec!["start ./app -- \"a\\\"; b\"", "continue"]
+        );
+    }
+
+    /// One instance of every `Command` variant, with non-default options
+    fn sample_commands() -> Vec<Command> {
+        vec![
+            Command::Start {
+                program: PathBuf::from("./target/debug/app"),
+                args: vec!["--verbose".to_string(), "input.txt".to_string()],
+                adapter: Some("gdb".to_string()),
+                stop_on_entry: true,
+                initial_breakpoints: vec!["main".to_string(), "app.c:12".to_string()],
+                keep_alive_minutes: Some(120),
+            },
+            Command::Attach {
+                pid: 4242,
+                adapter: Some("lldb-dap".to_string()),
+                keep_alive_minutes: Some(30),
+            },
+            Command::Reattach {
+                pid: Some(4242),
+                keep_alive_minutes: Some(30),
+            },
+            Command::Detach,
+            Command::Stop,
+            Command::Restart,
+            Command::Reset,
+            Command::Status,
+            Command::ProbeAdapter {
+                adapter: "lldb-dap".to_string(),
+            },
+            Command::BreakpointAdd {
+                location: BreakpointLocation::parse("app.c:12").unwrap(),
+                condition: Some("x > 5".to_string()),
+                hit_count: Some(3),
+                on_hit: vec!["locals".to_string(), "backtrace --limit 3".to_string()],
+            },
+            Command::BreakpointRemove {
+                id: Some(2),
+                all: false,
+            },
+            Command::BreakpointList,
+            Command::BreakpointInfo { id: 1 },
+            Command::BreakpointEnable { id: 1 },
+            Command::BreakpointDisable { id: 1 },
+            Command::WatchAdd {
+                expression: "node->depth * 2".to_string(),
+                function: Some("walk".to_string()),
+            },
+            Command::WatchRemove { id: Some(2) },
+            Command::WatchList,
+            Command::Continue { this_thread: true },
+            Command::Next { this_thread: false },
+            Command::StepCount {
+                kind: StepKind::In,
+                count: 10,
+                timeout_secs: 30,
+                this_thread: true,
+            },
+            Command::StepIn { this_thread: false },
+            Command::StepOut { this_thread: true },
+            Command::Pause,
+            Command::StackTrace {
+                thread_id: Some(7),
+                limit: 5,
+                user_only: true,
+            },
+            Command::Locals {
+                frame_id: Some(1000),
+                frame_index: None,
+                max_string_length: Some(40),
+                no_truncate: false,
+                no_cache: true,
+            },
+            Command::Locals {
+                frame_id: None,
+                frame_index: Some(2),
+                max_string_length: None,
+                no_truncate: true,
+                no_cache: false,
+            },
+            Command::Evaluate {
+                expression: "strlen(\"a  b\") + 1".to_string(),
+                frame_id: Some(1000),
+                frame_index: None,
+                context: EvaluateContext::Repl,
+                max_string_length: Some(0),
+                no_truncate: true,
+            },
+            Command::Evaluate {
+                expression: "n".to_string(),
+                frame_id: None,
+                frame_index: Some(1),
+                context: EvaluateContext::Watch,
+                max_string_length: None,
+                no_truncate: false,
+            },
+            Command::Assign {
+                target: "counts[2]".to_string(),
+                value: "n == 3".to_string(),
+                frame_index: Some(1),
+            },
+            Command::EvaluateAll {
+                expression: "depth > 3".to_string(),
+                limit: 50,
+            },
+            Command::StepUntil {
+                expression: "i >= 10".to_string(),
+                kind: StepKind::In,
+                max_steps: 500,
+                timeout_secs: 5,
+                this_thread: true,
+            },
+            Command::Scopes { frame_id: 1000 },
+            Command::Variables { reference: 12 },
+            Command::ReadMemory {
+                address: "0x7ffc1000".to_string(),
+                count: 32,
+            },
+            Command::ReadVariableMemory {
+                name: "buffer".to_string(),
+                count: 16,
+            },
+            Command::Disassemble {
+                address: Some("0x401000".to_string()),
+                count: 8,
+                source: true,
+            },
+            Command::Registers,
+            Command::Threads {
+                limit: Some(50),
+                filter: Some("worker".to_string()),
+                refresh: true,
+            },
+            Command::AnalyzeDeadlock { frames: 16 },
+            Command::Modules,
+            Command::ThreadSelect { id: 3 },
+            Command::TerminateThreads { ids: vec![4, 9] },
+            Command::FrameSelect { number: 2 },
+            Command::FrameUp,
+            Command::FrameDown,
+            Command::Context { lines: 7 },
+            Command::Triage {
+                limit: 10,
+                lines: 3,
+            },
+            Command::Await { timeout_secs: 30 },
+            Command::AwaitNotify {
+                timeout_secs: 600,
+                path: PathBuf::from("/tmp/stop.json"),
+            },
+            Command::CancelAwait,
+            Command::CancelEval,
+            Command::Progress,
+            Command::GetOutput {
+                tail: Some(10),
+                clear: true,
+            },
+            Command::SetOutputLimits {
+                max_bytes_mb: Some(64),
+                max_events: Some(100_000),
+            },
+            Command::FollowOutput { wait_ms: 500 },
+            Command::FollowEvents {
+                wait_ms: 500,
+                only: vec!["stopped".to_string(), "output".to_string()],
+            },
+            Command::Shutdown,
+        ]
+    }
+
+    #[test]
+    fn every_command_round_trips_through_text() {
+        let samples = sample_commands();
+
+        let mut covered: Vec<String> = samples
+            .iter()
+            .map(|command| {
+                let value = serde_json::to_value(command).unwrap();
+                value["type"].as_str().unwrap().to_string()
+            })
+            .collect();
+        covered.sort();
+        covered.dedup();
+        let mut all = crate::ipc::schema::command_names();
+        all.sort();
+        assert_eq!(covered, all, "sample_commands() must cover every Command variant");
+
+        // Values that only read back whole when quoted
+        let quoted = vec![
+            Command::Start {
+                program: PathBuf::from("/tmp/my app"),
+                args: vec![
+                    "a b".to_string(),
+                    "say \"hi\"; bye".to_string(),
+                    String::new(),
+                    "back\\slash".to_string(),
+                    "--".to_string(),
+                ],
+                adapter: None,
+                stop_on_entry: false,
+                initial_breakpoints: vec![],
+                keep_alive_minutes: None,
+            },
+            Command::WatchAdd {
+                expression: "x --in y".to_string(),
+                function: None,
+            },
+            Command::WatchAdd {
+                expression: "x --in y".to_string(),
+                function: Some("walk".to_string()),
+            },
+            Command::WatchAdd {
+                expression: "\"a\" --in".to_string(),
+                function: None,
+            },
+        ];
+
+        for command in samples.into_iter().chain(quoted) {
+            let text = format_command(&command);
+            let parsed = parse_command(&text)
+                .unwrap_or_else(|e| panic!("failed to parse {:?}: {}", text, e));
+            assert_eq!(
+                serde_json::to_value(&parsed).unwrap(),
+                serde_json::to_value(&command).unwrap(),
+                "round trip of {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn assignments_split_at_their_own_equals_sign() {
+        let split = |text| split_assignment(text).unwrap();
+        assert_eq!(split("x = 5"), ("x".to_string(), "5".to_string()));
+        assert_eq!(split("ok=a == b"), ("ok".to_string(), "a == b".to_string()));
+        assert_eq!(split("s = \"k=v\""), ("s".to_string(), "\"k=v\"".to_string()));
+        assert!(split_assignment("x == 5").is_err());
+        assert!(split_assignment("x += 5").is_err());
+        assert!(split_assignment("x =").is_err());
+    }
+
+    #[test]
+    fn test_parse_session_commands() {
+        match parse_command("start ./app -b main").unwrap() {
+            Command::Start {
+                program,
+                args,
+                initial_breakpoints,
+                ..
+            } => {
+                assert_eq!(program, PathBuf::from("./app"));
+                assert!(args.is_empty());
+                assert_eq!(initial_breakpoints, vec!["main".to_string()]);
+            }
+            _ => panic!("Expected Start command"),
+        }
+        assert!(parse_command("start").is_err());
+        assert!(parse_command("start ./app extra").is_err());
+        assert!(parse_command("attach nope").is_err());
+        assert!(matches!(
+            parse_command("attach 42 --keep-alive 90").unwrap(),
+            Command::Attach {
+                pid: 42,
+                adapter: None,
+                keep_alive_minutes: Some(90),
+            }
+        ));
+        assert!(parse_command("start ./app --keep-alive soon").is_err());
+        assert!(matches!(
+            parse_command("await").unwrap(),
+            Command::Await { timeout_secs: 300 }
+        ));
+        assert!(matches!(
+            parse_command("next 3").unwrap(),
+            Command::StepCount {
+                kind: StepKind::Over,
+                count: 3,
+                timeout_secs: 300,
+                this_thread: false,
+            }
+        ));
+        assert!(matches!(parse_command("finish").unwrap(), Command::StepOut { .. }));
+        assert!(parse_command("next --count 0").is_err());
+        assert!(parse_command("step 2 3").is_err());
+        assert!(matches!(
+            parse_command("disassemble").unwrap(),
+            Command::Disassemble {
+                address: None,
+                count: 20,
+                source: false
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_simple_commands() {
+        assert!(matches!(parse_command("continue").unwrap(), Command::Continue { .. }));
+        assert!(matches!(parse_command("c").unwrap(), Command::Continue { .. }));
+        assert!(matches!(parse_command("next").unwrap(), Command::Next { .. }));
+        assert!(matches!(parse_command("step").unwrap(), Command::StepIn { .. }));
+        assert!(matches!(parse_command("finish").unwrap(), Command::StepOut { .. }));
+        assert!(matches!(parse_command("pause").unwrap(), Command::Pause));
+    }
+
+    #[test]
+    fn test_parse_break_commands() {
+        let cmd = parse_command("break main").unwrap();
+        assert!(matches!(cmd, Command::BreakpointAdd { .. }));
+
+        let cmd = parse_command("break add main.rs:42").unwrap();
+        assert!(matches!(cmd, Command::BreakpointAdd { .. }));
+
+        let cmd = parse_command("b foo.c:10").unwrap();
+        assert!(matches!(cmd, Command::BreakpointAdd { .. }));
+
+        let cmd = parse_command("break -2 --hit-count 3").unwrap();
+        assert!(matches!(
+            cmd,
+            Command::BreakpointAdd {
+                location: BreakpointLocation::RelativeLine { file: None, offset: -2 },
+                hit_count: Some(3),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_breakpoint_subcommands() {
+        assert!(matches!(
+            parse_command("breakpoint add main").unwrap(),
+            Command::BreakpointAdd { .. }
+        ));
+        assert!(matches!(
+            parse_command("breakpoint list").unwrap(),
+            Command::BreakpointList
+        ));
+        assert!(matches!(
+            parse_command("breakpoint remove 1").unwrap(),
+            Command::BreakpointRemove { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_print_commands() {
+        let cmd = parse_command("print x + y").unwrap();
+        match cmd {
+            Command::Evaluate {
+                expression, context, ..
+            } => {
+                assert_eq!(expression, "x + y");
+                assert!(matches!(context, EvaluateContext::Watch));
+            }
+            _ => panic!("Expected Evaluate command"),
+        }
+
+        let cmd = parse_command("eval counter = counter + 1").unwrap();
+        assert!(matches!(
+            cmd,
+            Command::Evaluate {
+                context: EvaluateContext::Repl,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_break_with_hit_count() {
+        let cmd = parse_command("break factorial --hit-count 3").unwrap();
+        match cmd {
+            Command::BreakpointAdd { hit_count, .. } => {
+                assert_eq!(hit_count, Some(3));
+            }
+            _ => panic!("Expected BreakpointAdd command"),
+        }
+
+        let cmd = parse_command("break main.c:10 --hit-count 5").unwrap();
+        match cmd {
+            Command::BreakpointAdd { hit_count, .. } => {
+                assert_eq!(hit_count, Some(5));
+            }
+            _ => panic!("Expected BreakpointAdd command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_break_with_condition_and_hit_count() {
+        let cmd = parse_command("break foo --condition \"x > 5\" --hit-count 2").unwrap();
+        match cmd {
+            Command::BreakpointAdd { condition, hit_count, .. } => {
+                assert_eq!(condition, Some("x > 5".to_string()));
+                assert_eq!(hit_count, Some(2));
+            }
+            _ => panic!("Expected BreakpointAdd command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_breakpoint_subcommand_options() {
+        let cmd = parse_command("breakpoint add foo.c:10 --condition x > 5 --hit-count 2")
+            .unwrap();
+        match cmd {
+            Command::BreakpointAdd {
+                condition,
+                hit_count,
+                ..
+            } => {
+                assert_eq!(condition, Some("x > 5".to_string()));
+                assert_eq!(hit_count, Some(2));
+            }
+            _ => panic!("Expected BreakpointAdd command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_context_commands() {
+        assert!(matches!(
+            parse_command("context").unwrap(),
+            Command::Context { lines: 5 }
+        ));
+        assert!(matches!(
+            parse_command("where 3").unwrap(),
+            Command::Context { lines: 3 }
+        ));
+        assert!(matches!(
+            parse_command("context --lines 7").unwrap(),
+            Command::Context { lines: 7 }
+        ));
+        assert!(parse_command("context --lines nope").is_err());
+    }
+
+    #[test]
+    fn test_parse_frame_is_named_one_way() {
+        assert!(parse_command("locals --frame 1 --frame-id 1000").is_err());
+        assert!(parse_command("print --frame-id 1000 --frame 1 n").is_err());
+        assert!(parse_command("eval --frame 1 --frame-id 1000 --assign n = 2").is_err());
+    }
+
+    #[test]
+    fn test_parse_backtrace_limit() {
+        assert!(matches!(
+            parse_command("backtrace --limit 3").unwrap(),
+            Command::StackTrace { limit: 3, .. }
+        ));
+        assert!(matches!(
+            parse_command("bt").unwrap(),
+            Command::StackTrace { limit: 20, .. }
+        ));
+        assert!(parse_command("bt --limit x").is_err());
+    }
+
+    #[test]
+    fn test_parse_output_commands() {
+        assert!(matches!(
+            parse_command("output -t 4 --clear").unwrap(),
+            Command::GetOutput {
+                tail: Some(4),
+                clear: true
+            }
+        ));
+        assert!(parse_command("output --tail invalid").is_err());
+        assert!(parse_command("output --follow").is_err());
+    }
+}
@@ -29,6 +29,18 @@ pub struct Config {
     /// Output buffer settings
     #[serde(default)]
     pub output: OutputConfig,
+
+    /// Backtrace settings
+    #[serde(default)]
+    pub backtrace: BacktraceConfig,
+
+    /// `analyze` settings
+    #[serde(default)]
+    pub analyze: AnalyzeConfig,
+
+    /// Client arguments of the DAP `initialize` request, for every adapter
+    #[serde(default)]
+    pub dap: DapConfig,
 }
 
 /// Transport mode for debug adapter communication
@@ -42,22 +54,39 @@ pub enum TransportMode {
     Tcp,
 }
 
-/// TCP adapter spawn style
+/// How a TCP adapter is started and reached
 #[derive(Debug, Deserialize, Clone, Default, PartialEq)]
-pub enum TcpSpawnStyle {
-    /// Adapter accepts --listen flag and waits for connection (Delve)
+pub enum SpawnStyle {
+    /// Adapter accepts `--listen=127.0.0.1:0` and prints the address it
+    /// listens on (Delve)
     #[default]
-    #[serde(rename = "tcp-listen")]
-    TcpListen,
-    /// Adapter receives port as positional argument (js-debug)
-    #[serde(rename = "tcp-port-arg")]
-    TcpPortArg,
+    #[serde(rename = "tcp-listen", alias = "listen-flag")]
+    ListenFlag,
+    /// Adapter receives a free port as its last argument (js-debug's
+    /// `dapDebugServer.js`)
+    #[serde(rename = "tcp-port-arg", alias = "port-arg")]
+    PortArg,
+    /// Adapter is already running at `address`; nothing is spawned
+    #[serde(rename = "connect-existing")]
+    ConnectExisting,
+}
+
+impl SpawnStyle {
+    /// The name used for this style in the config file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ListenFlag => "tcp-listen",
+            Self::PortArg => "tcp-port-arg",
+            Self::ConnectExisting => "connect-existing",
+        }
+    }
 }
 
 /// Configuration for a debug adapter
 #[derive(Debug, Deserialize, Clone)]
 pub struct AdapterConfig {
-    /// Path to the adapter executable
+    /// Path to the adapter executable (unused with `connect-existing`)
+    #[serde(default)]
     pub path: PathBuf,
 
     /// Additional arguments to pass to the adapter
@@ -70,7 +99,83 @@ pub struct AdapterConfig {
 
     /// TCP spawn style (only used when transport is Tcp)
     #[serde(default)]
-    pub spawn_style: TcpSpawnStyle,
+    pub spawn_style: SpawnStyle,
+
+    /// `host:port` of an adapter that is already running, for the
+    /// `connect-existing` spawn style
+    #[serde(default)]
+    pub address: Option<String>,
+
+    /// `adapterID` sent in the DAP `initialize` request, for adapters that
+    /// expect a specific id; defaults to the adapter's name in the config
+    #[serde(default)]
+    pub adapter_id: Option<String>,
+
+    /// Client arguments of the DAP `initialize` request for this adapter,
+    /// taking precedence over the `[dap]` section
+    #[serde(default)]
+    pub dap: DapConfig,
+}
+
+impl AdapterConfig {
+    /// Whether the adapter is reached over TCP without being spawned
+    pub fn connects_to_existing(&self) -> bool {
+        self.transport == TransportMode::Tcp && self.spawn_style == SpawnStyle::ConnectExisting
+    }
+
+    /// The `adapterID` to initialize the adapter configured as `name` with
+    pub fn dap_adapter_id<'a>(&'a self, name: &'a str) -> &'a str {
+        self.adapter_id.as_deref().unwrap_or(name)
+    }
+}
+
+/// Client arguments of the DAP `initialize` request. Options not set keep
+/// the client's defaults; adapters that mishandle one can be told otherwise,
+/// e.g. `supports_variable_paging = false`. Line and column bases, the path
+/// format and `runInTerminal` are left out: the client only speaks 1-based
+/// lines and columns and plain paths, and doesn't serve `runInTerminal`.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct DapConfig {
+    /// `clientID`
+    pub client_id: Option<String>,
+    /// `clientName`
+    pub client_name: Option<String>,
+    /// `locale`, e.g. "en-US"
+    pub locale: Option<String>,
+    /// `supportsVariableType`
+    pub supports_variable_type: Option<bool>,
+    /// `supportsVariablePaging`
+    pub supports_variable_paging: Option<bool>,
+    /// `supportsMemoryReferences`
+    pub supports_memory_references: Option<bool>,
+    /// `supportsProgressReporting`
+    pub supports_progress_reporting: Option<bool>,
+    /// `supportsInvalidatedEvent`
+    pub supports_invalidated_event: Option<bool>,
+}
+
+impl DapConfig {
+    /// These options, with the ones not set taken from `base`
+    pub fn over(&self, base: &DapConfig) -> DapConfig {
+        DapConfig {
+            client_id: self.client_id.clone().or_else(|| base.client_id.clone()),
+            client_name: self.client_name.clone().or_else(|| base.client_name.clone()),
+            locale: self.locale.clone().or_else(|| base.locale.clone()),
+            supports_variable_type: self.supports_variable_type.or(base.supports_variable_type),
+            supports_variable_paging: self
+                .supports_variable_paging
+                .or(base.supports_variable_paging),
+            supports_memory_references: self
+                .supports_memory_references
+                .or(base.supports_memory_references),
+            supports_progress_reporting: self
+                .supports_progress_reporting
+                .or(base.supports_progress_reporting),
+            supports_invalidated_event: self
+                .supports_invalidated_event
+                .or(base.supports_invalidated_event),
+        }
+    }
 }
 
 /// Default settings
@@ -104,6 +209,10 @@ pub struct Timeouts {
     #[serde(default = "default_dap_request")]
     pub dap_request_secs: u64,
 
+    /// Timeout for expression evaluation, which may run debuggee code
+    #[serde(default = "default_eval")]
+    pub eval_secs: u64,
+
     /// Default timeout for await command
     #[serde(default = "default_await")]
     pub await_default_secs: u64,
@@ -114,6 +223,7 @@ impl Default for Timeouts {
         Self {
             dap_initialize_secs: default_dap_initialize(),
             dap_request_secs: default_dap_request(),
+            eval_secs: default_eval(),
             await_default_secs: default_await(),
         }
     }
@@ -125,6 +235,9 @@ fn default_dap_initialize() -> u64 {
 fn default_dap_request() -> u64 {
     30
 }
+fn default_eval() -> u64 {
+    120
+}
 fn default_await() -> u64 {
     300
 }
@@ -135,12 +248,29 @@ pub struct DaemonConfig {
     /// Auto-exit after this many minutes with no active session
     #[serde(default = "default_idle_timeout")]
     pub idle_timeout_minutes: u64,
+
+    /// Rotate the daemon log when it reaches this size (0 = never)
+    #[serde(default = "default_log_max_size")]
+    pub log_max_size_mb: u64,
+
+    /// Rotated daemon logs, and logs of past sessions, to keep besides the
+    /// current ones
+    #[serde(default = "default_log_max_files")]
+    pub log_max_files: usize,
+
+    /// Relaunch the program, with its breakpoints, when the debug adapter
+    /// crashes during a launched session
+    #[serde(default)]
+    pub relaunch_on_crash: bool,
 }
 
 impl Default for DaemonConfig {
     fn default() -> Self {
         Self {
             idle_timeout_minutes: default_idle_timeout(),
+            log_max_size_mb: default_log_max_size(),
+            log_max_files: default_log_max_files(),
+            relaunch_on_crash: false,
         }
     }
 }
@@ -148,6 +278,12 @@ impl Default for DaemonConfig {
 fn default_idle_timeout() -> u64 {
     30
 }
+fn default_log_max_size() -> u64 {
+    10
+}
+fn default_log_max_files() -> usize {
+    3
+}
 
 /// Output buffer configuration
 #[derive(Debug, Deserialize)]
@@ -159,6 +295,16 @@ pub struct OutputConfig {
     /// Maximum total bytes to buffer
     #[serde(default = "default_max_bytes")]
     pub max_bytes_mb: usize,
+
+    /// Maximum bytes of a single evaluate result or variable value; longer
+    /// values are truncated (0 disables the limit)
+    #[serde(default = "default_max_eval_result_bytes")]
+    pub max_eval_result_bytes: usize,
+
+    /// Maximum characters of a single string value; longer strings are cut
+    /// off with an ellipsis (0 disables the limit)
+    #[serde(default)]
+    pub max_string_length: usize,
 }
 
 impl Default for OutputConfig {
@@ -166,6 +312,8 @@ impl Default for OutputConfig {
         Self {
             max_events: default_max_events(),
             max_bytes_mb: default_max_bytes(),
+            max_eval_result_bytes: default_max_eval_result_bytes(),
+            max_string_length: 0,
         }
     }
 }
@@ -176,12 +324,158 @@ fn default_max_events() -> usize {
 fn default_max_bytes() -> usize {
     10
 }
+fn default_max_eval_result_bytes() -> usize {
+    64 * 1024
+}
+
+/// Backtrace configuration
+#[derive(Debug, Deserialize, Default)]
+pub struct BacktraceConfig {
+    /// Source path prefixes whose frames `backtrace --user-only` hides even
+    /// inside the program's directory (vendored code, virtualenvs)
+    #[serde(default)]
+    pub hide_paths: Vec<String>,
+}
+
+/// `analyze` configuration
+#[derive(Debug, Deserialize)]
+pub struct AnalyzeConfig {
+    /// Substrings of the functions a thread blocks in while waiting for a
+    /// lock, condition or other thread; `analyze deadlock` reports threads
+    /// with one of these on their stack. Setting it replaces the defaults.
+    #[serde(default = "default_wait_functions")]
+    pub wait_functions: Vec<String>,
+}
+
+impl Default for AnalyzeConfig {
+    fn default() -> Self {
+        Self {
+            wait_functions: default_wait_functions(),
+        }
+    }
+}
+
+fn default_wait_functions() -> Vec<String> {
+    [
+        // POSIX threads, glibc and macOS internals
+        "pthread_mutex_lock",
+        "pthread_rwlock_rdlock",
+        "pthread_rwlock_wrlock",
+        "pthread_cond_wait",
+        "pthread_cond_timedwait",
+        "pthread_join",
+        "__lll_lock_wait",
+        "futex",
+        "sem_wait",
+        "__psynch_mutexwait",
+        "__psynch_cvwait",
+        // Rust std and parking_lot
+        "lock_contended",
+        "std::thread::park",
+        "Condvar::wait",
+        "parking_lot",
+        // Go
+        "sync.(*Mutex).Lock",
+        "sync.(*RWMutex)",
+        "sync.(*WaitGroup).Wait",
+        "runtime.gopark",
+        // Windows
+        "EnterCriticalSection",
+        "AcquireSRWLock",
+        "SleepConditionVariable",
+        "WaitForSingleObject",
+        "WaitForMultipleObjects",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect()
+}
+
+/// Keys of each settings section, for reporting typos
+const SECTION_KEYS: &[(&str, &[&str])] = &[
+    ("defaults", &["adapter"]),
+    (
+        "timeouts",
+        &[
+            "dap_initialize_secs",
+            "dap_request_secs",
+            "eval_secs",
+            "await_default_secs",
+        ],
+    ),
+    (
+        "daemon",
+        &[
+            "idle_timeout_minutes",
+            "log_max_size_mb",
+            "log_max_files",
+            "relaunch_on_crash",
+        ],
+    ),
+    (
+        "output",
+        &[
+            "max_events",
+            "max_bytes_mb",
+            "max_eval_result_bytes",
+            "max_string_length",
+        ],
+    ),
+    ("backtrace", &["hide_paths"]),
+    ("analyze", &["wait_functions"]),
+    ("dap", DAP_KEYS),
+];
+
+/// Keys of an `[adapters.<name>]` entry
+const ADAPTER_KEYS: &[&str] =
+    &["path", "args", "transport", "spawn_style", "address", "adapter_id", "dap"];
+/// Keys of the `[dap]` section and of an `[adapters.<name>.dap]` table
+const DAP_KEYS: &[&str] = &[
+    "client_id",
+    "client_name",
+    "locale",
+    "supports_variable_type",
+    "supports_variable_paging",
+    "supports_memory_references",
+    "supports_progress_reporting",
+    "supports_invalidated_event",
+];
+const TRANSPORTS: &[&str] = &["stdio", "tcp"];
+const SPAWN_STYLES: &[&str] = &[
+    "tcp-listen",
+    "tcp-port-arg",
+    "connect-existing",
+    "listen-flag",
+    "port-arg",
+];
+
+/// `"a", "b" or "c"`
+fn one_of(options: &[&str]) -> String {
+    let quoted: Vec<String> = options.iter().map(|o| format!("\"{}\"", o)).collect();
+    match quoted.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
 
 impl Config {
     /// Load configuration from the default config file
     ///
-    /// Returns default configuration if file doesn't exist
+    /// Returns default configuration if file doesn't exist. Problems found by
+    /// [`Config::check`] are logged, or listed in the error if the file
+    /// can't be used at all.
     pub fn load() -> Result<Self> {
+        let (config, problems) = Self::load_with_problems()?;
+        for problem in &problems {
+            tracing::warn!("{}", problem);
+        }
+        Ok(config)
+    }
+
+    /// [`Config::load`], returning the problems instead of logging them, for
+    /// callers that load before logging is set up
+    pub fn load_with_problems() -> Result<(Self, Vec<String>)> {
         if let Some(path) = config_path() {
             if path.exists() {
                 let content = std::fs::read_to_string(&path).map_err(|e| {
@@ -190,11 +484,122 @@ impl Config {
                         error: e.to_string(),
                     }
                 })?;
-                return toml::from_str(&content)
-                    .map_err(|e| super::Error::ConfigParse(e.to_string()));
+
+                let problems = Self::check(&content);
+                let config: Self = toml::from_str(&content).map_err(|e| {
+                    let details = if problems.is_empty() {
+                        e.to_string()
+                    } else {
+                        problems.join("; ")
+                    };
+                    super::Error::ConfigParse(format!("{}: {}", path.display(), details))
+                })?;
+                let problems = problems
+                    .iter()
+                    .map(|problem| format!("{}: {}", path.display(), problem))
+                    .collect();
+                return Ok((config, problems));
+            }
+        }
+        Ok((Self::default(), Vec::new()))
+    }
+
+    /// Check config file text, returning human-readable problems: TOML
+    /// errors, unknown keys, bad transport values, and everything
+    /// [`Config::validate`] reports
+    pub fn check(content: &str) -> Vec<String> {
+        let table: toml::Table = match content.parse() {
+            Ok(table) => table,
+            Err(e) => return vec![format!("TOML syntax error: {}", e)],
+        };
+
+        let mut problems = Vec::new();
+        for (key, value) in &table {
+            if key == "adapters" {
+                check_adapters(value, &mut problems);
+                continue;
+            }
+
+            let Some((_, keys)) = SECTION_KEYS.iter().find(|(section, _)| section == key) else {
+                let mut sections = vec!["adapters"];
+                sections.extend(SECTION_KEYS.iter().map(|(section, _)| *section));
+                problems.push(format!(
+                    "Unknown key '{}'; settings go in the sections {}",
+                    key,
+                    sections
+                        .iter()
+                        .map(|s| format!("[{}]", s))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+                continue;
+            };
+            match value.as_table() {
+                Some(section) => {
+                    for name in section.keys() {
+                        if !keys.contains(&name.as_str()) {
+                            problems.push(format!(
+                                "Unknown key '{}.{}' (expected {})",
+                                key,
+                                name,
+                                one_of(keys)
+                            ));
+                        }
+                    }
+                }
+                None => problems.push(format!("'{}' must be a [{}] section", key, key)),
+            }
+        }
+
+        // Only type errors the checks above didn't already explain
+        match toml::from_str::<Self>(content) {
+            Ok(config) => problems.extend(config.validate()),
+            Err(e) if problems.is_empty() => problems.push(e.message().to_string()),
+            Err(_) => {}
+        }
+        problems
+    }
+
+    /// Check the loaded values, returning human-readable problems
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let mut names: Vec<&String> = self.adapters.keys().collect();
+        names.sort();
+        for name in &names {
+            let adapter = &self.adapters[*name];
+            if adapter.connects_to_existing() {
+                if adapter.address.as_deref().is_none_or(str::is_empty) {
+                    problems.push(format!(
+                        "adapters.{} uses spawn_style \"connect-existing\" but has no address",
+                        name
+                    ));
+                }
+            } else if adapter.path.as_os_str().is_empty() {
+                problems.push(format!("adapters.{}.path is empty", name));
+            }
+        }
+
+        for (key, value) in [
+            ("timeouts.dap_initialize_secs", self.timeouts.dap_initialize_secs),
+            ("timeouts.dap_request_secs", self.timeouts.dap_request_secs),
+            ("timeouts.eval_secs", self.timeouts.eval_secs),
+            ("timeouts.await_default_secs", self.timeouts.await_default_secs),
+            ("daemon.idle_timeout_minutes", self.daemon.idle_timeout_minutes),
+        ] {
+            if value == 0 {
+                problems.push(format!("{} must be greater than 0", key));
             }
         }
-        Ok(Self::default())
+
+        if self.get_adapter(&self.defaults.adapter).is_none() {
+            problems.push(format!(
+                "defaults.adapter is '{}', which is not configured in [adapters] or found on PATH",
+                self.defaults.adapter
+            ));
+        }
+
+        problems
     }
 
     /// Get adapter configuration by name
@@ -217,7 +622,10 @@ impl Config {
                     path,
                     args: Vec::new(),
                     transport: TransportMode::default(),
-                    spawn_style: TcpSpawnStyle::default(),
+                    spawn_style: SpawnStyle::default(),
+                    address: None,
+                    adapter_id: None,
+                    dap: DapConfig::default(),
                 });
             }
         }
@@ -230,7 +638,10 @@ impl Config {
                         path: known_path,
                         args: Vec::new(),
                         transport: TransportMode::default(),
-                        spawn_style: TcpSpawnStyle::default(),
+                        spawn_style: SpawnStyle::default(),
+                        address: None,
+                        adapter_id: None,
+                        dap: DapConfig::default(),
                     });
                 }
             }
@@ -240,6 +651,67 @@ impl Config {
     }
 }
 
+/// Check the raw `[adapters]` table for entries that aren't tables, unknown
+/// keys, and unsupported transport values
+fn check_adapters(value: &toml::Value, problems: &mut Vec<String>) {
+    let Some(adapters) = value.as_table() else {
+        problems.push("'adapters' must be a section of [adapters.<name>] tables".to_string());
+        return;
+    };
+
+    for (name, entry) in adapters {
+        let Some(entry) = entry.as_table() else {
+            problems.push(format!(
+                "adapters.{} must be a table, e.g. [adapters.{}] with path = \"...\"",
+                name, name
+            ));
+            continue;
+        };
+
+        for key in entry.keys() {
+            if !ADAPTER_KEYS.contains(&key.as_str()) {
+                problems.push(format!(
+                    "Unknown key 'adapters.{}.{}' (expected {})",
+                    name,
+                    key,
+                    one_of(ADAPTER_KEYS)
+                ));
+            }
+        }
+        if let Some(dap) = entry.get("dap").and_then(|v| v.as_table()) {
+            for key in dap.keys() {
+                if !DAP_KEYS.contains(&key.as_str()) {
+                    problems.push(format!(
+                        "Unknown key 'adapters.{}.dap.{}' (expected {})",
+                        name,
+                        key,
+                        one_of(DAP_KEYS)
+                    ));
+                }
+            }
+        }
+        let connects =
+            entry.get("spawn_style").and_then(|v| v.as_str()) == Some("connect-existing");
+        if !entry.contains_key("path") && !connects {
+            problems.push(format!("adapters.{} has no path", name));
+        }
+
+        for (key, allowed) in [("transport", TRANSPORTS), ("spawn_style", SPAWN_STYLES)] {
+            if let Some(value) = entry.get(key) {
+                if !value.as_str().is_some_and(|v| allowed.contains(&v)) {
+                    problems.push(format!(
+                        "adapters.{}.{} is {}; expected {}",
+                        name,
+                        key,
+                        value,
+                        one_of(allowed)
+                    ));
+                }
+            }
+        }
+    }
+}
+
 /// Returns known system paths where lldb-dap might be installed.
 /// This is especially useful on macOS where the binary might not be in PATH.
 fn known_lldb_paths() -> Vec<PathBuf> {
@@ -295,3 +767,152 @@ pub fn adapter_fallback_names(name: &str) -> Vec<String> {
         _ => vec![name.to_string()],
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_valid_config_has_no_problems() {
+        let content = r#"
+[defaults]
+adapter = "gdb"
+
+[adapters.gdb]
+path = "/usr/bin/gdb"
+args = ["-i=dap"]
+
+[adapters.go]
+path = "dlv"
+args = ["dap"]
+transport = "tcp"
+spawn_style = "tcp-listen"
+
+[timeouts]
+dap_request_secs = 60
+"#;
+        assert_eq!(Config::check(content), Vec::<String>::new());
+    }
+
+    #[test]
+    fn adapter_dap_settings_take_precedence_over_the_dap_section() {
+        let content = r#"
+[defaults]
+adapter = "gdb"
+
+[dap]
+locale = "en-US"
+supports_variable_paging = false
+
+[adapters.gdb]
+path = "/usr/bin/gdb"
+
+[adapters.gdb.dap]
+supports_variable_paging = true
+supports_memory_references = false
+"#;
+        assert_eq!(Config::check(content), Vec::<String>::new());
+
+        let config: Config = toml::from_str(content).unwrap();
+        let dap = config.adapters["gdb"].dap.over(&config.dap);
+        assert_eq!(dap.locale.as_deref(), Some("en-US"));
+        assert_eq!(dap.supports_variable_paging, Some(true));
+        assert_eq!(dap.supports_memory_references, Some(false));
+        assert_eq!(dap.client_id, None);
+
+        // The client can't work with these changed, so they aren't options
+        let unsupported = "[adapters.gdb]\npath = \"gdb\"\n[adapters.gdb.dap]\nlocale = \"en\"\n\
+                           path_format = \"uri\"\n";
+        let problems = Config::check(unsupported);
+        assert!(problems.iter().any(|p| p.contains("path_format")), "{:?}", problems);
+    }
+
+    #[test]
+    fn typos_and_bad_values_are_reported() {
+        let content = r#"
+adapter = "lldb-dap"
+
+[defaults]
+adaptor = "gdb"
+
+[adapters]
+lldb-dap = "/usr/bin/lldb-dap"
+
+[adapters.go]
+path = "dlv"
+transport = "TCP"
+"#;
+        let problems = Config::check(content);
+        assert_eq!(problems.len(), 4, "{:#?}", problems);
+        assert!(problems[0].contains("Unknown key 'adapter'"));
+        assert!(problems[1].contains(r#"adapters.go.transport is "TCP"; expected "stdio" or "tcp""#));
+        assert!(problems[2].contains("adapters.lldb-dap must be a table"));
+        assert!(problems[3].contains("Unknown key 'defaults.adaptor' (expected \"adapter\")"));
+    }
+
+    #[test]
+    fn values_are_validated() {
+        let content = r#"
+[defaults]
+adapter = "missing-adapter-xyz"
+
+[adapters.gdb]
+path = ""
+
+[timeouts]
+dap_initialize_secs = 0
+"#;
+        assert_eq!(
+            Config::check(content),
+            vec![
+                "adapters.gdb.path is empty",
+                "timeouts.dap_initialize_secs must be greater than 0",
+                "defaults.adapter is 'missing-adapter-xyz', which is not configured in [adapters] or found on PATH",
+            ]
+        );
+    }
+
+    #[test]
+    fn spawn_styles_accept_short_names_and_existing_adapters() {
+        let content = r#"
+[adapters.js]
+path = "node"
+transport = "tcp"
+spawn_style = "port-arg"
+
+[adapters.remote]
+transport = "tcp"
+spawn_style = "connect-existing"
+address = "127.0.0.1:4711"
+
+[adapters.nowhere]
+transport = "tcp"
+spawn_style = "connect-existing"
+"#;
+        let config: Config = toml::from_str(content).unwrap();
+        assert_eq!(config.adapters["js"].spawn_style, SpawnStyle::PortArg);
+        assert!(config.adapters["remote"].connects_to_existing());
+
+        let problems = config.validate();
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("adapters.nowhere uses spawn_style \"connect-existing\" but has no address")));
+        assert!(!problems.iter().any(|p| p.contains("adapters.remote")));
+    }
+
+    #[test]
+    fn adapter_id_overrides_the_config_name() {
+        let content = r#"
+[adapters.lldb-dap]
+path = "lldb-dap"
+adapter_id = "lldb"
+
+[adapters.gdb]
+path = "gdb"
+"#;
+        assert_eq!(Config::check(content), Vec::<String>::new());
+        let config: Config = toml::from_str(content).unwrap();
+        assert_eq!(config.adapters["lldb-dap"].dap_adapter_id("lldb-dap"), "lldb");
+        assert_eq!(config.adapters["gdb"].dap_adapter_id("gdb"), "gdb");
+    }
+}
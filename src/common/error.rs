@@ -35,8 +35,11 @@ pub enum Error {
     #[error("Session terminated unexpectedly: {0}")]
     SessionTerminated(String),
 
-    #[error("Program has exited with code {0}")]
-    ProgramExited(i32),
+    #[error("Program has exited{exit_code}")]
+    ProgramExited {
+        /// " with code N", or empty when the adapter didn't report the code
+        exit_code: String,
+    },
 
     // === Adapter Errors ===
     #[error("Debug adapter '{name}' not found. Searched: {searched}")]
@@ -51,6 +54,14 @@ pub enum Error {
     #[error("Debug adapter returned error: {0}")]
     AdapterError(String),
 
+    #[error("Debug adapter '{adapter}' does not support {feature}{hint}")]
+    Unsupported {
+        adapter: String,
+        feature: String,
+        /// What to do instead, as a sentence after the message (or empty)
+        hint: String,
+    },
+
     // === DAP Protocol Errors ===
     #[error("DAP protocol error: {0}")]
     DapProtocol(String),
@@ -58,6 +69,9 @@ pub enum Error {
     #[error("DAP request '{command}' failed: {message}")]
     DapRequestFailed { command: String, message: String },
 
+    #[error("DAP request '{0}' was cancelled")]
+    RequestCancelled(String),
+
     #[error("DAP initialization failed: {0}")]
     DapInitFailed(String),
 
@@ -124,6 +138,24 @@ impl Error {
         }
     }
 
+    /// Create an error for a feature the session's adapter lacks, with an
+    /// optional hint at what to do instead
+    pub fn unsupported(adapter: &str, feature: &str, hint: Option<&str>) -> Self {
+        Self::Unsupported {
+            adapter: adapter.to_string(),
+            feature: feature.to_string(),
+            hint: hint.map(|hint| format!(". {}", hint)).unwrap_or_default(),
+        }
+    }
+
+    /// Create an error for a program that has exited, with its exit code
+    /// if known
+    pub fn program_exited(exit_code: Option<i32>) -> Self {
+        Self::ProgramExited {
+            exit_code: exit_code.map(|code| format!(" with code {}", code)).unwrap_or_default(),
+        }
+    }
+
     /// Create a DAP request failed error
     pub fn dap_request_failed(command: &str, message: &str) -> Self {
         Self::DapRequestFailed {
@@ -163,14 +195,16 @@ impl From<&Error> for IpcError {
             Error::SessionNotActive => "SESSION_NOT_ACTIVE",
             Error::SessionAlreadyActive => "SESSION_ALREADY_ACTIVE",
             Error::AdapterNotFound { .. } => "ADAPTER_NOT_FOUND",
+            Error::Unsupported { .. } => "UNSUPPORTED",
             Error::InvalidLocation(_) => "INVALID_LOCATION",
             Error::BreakpointNotFound { .. } => "BREAKPOINT_NOT_FOUND",
             Error::InvalidState { .. } => "INVALID_STATE",
             Error::ThreadNotFound(_) => "THREAD_NOT_FOUND",
             Error::FrameNotFound(_) => "FRAME_NOT_FOUND",
             Error::Timeout(_) | Error::AwaitTimeout(_) => "TIMEOUT",
-            Error::ProgramExited(_) => "PROGRAM_EXITED",
+            Error::ProgramExited { .. } => "PROGRAM_EXITED",
             Error::DapRequestFailed { .. } => "DAP_REQUEST_FAILED",
+            Error::RequestCancelled(_) => "CANCELLED",
             _ => "INTERNAL_ERROR",
         }
         .to_string();
@@ -3,29 +3,45 @@
 //! Provides structured logging for both CLI and daemon modes.
 //! The daemon logs to a file since it runs in the background.
 
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use tracing::Level;
 use tracing_subscriber::{
     fmt::{self, format::FmtSpan},
     layer::SubscriberExt,
     util::SubscriberInitExt,
-    EnvFilter,
+    EnvFilter, Layer,
 };
 
+use super::config::{Config, DaemonConfig};
 use super::paths;
 
-/// Initialize tracing for the CLI (stdout logging)
+/// Initialize tracing for the CLI (stderr logging)
 ///
-/// Logs are controlled by the `RUST_LOG` environment variable.
+/// Logs are controlled by `-v` (`verbosity` 1: DEBUG, 2 or more: TRACE,
+/// which includes DAP traffic of `--no-daemon` sessions), or else by the
+/// `RUST_LOG` environment variable.
 /// Default level is INFO for this crate, WARN for dependencies.
-pub fn init_cli() {
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-        EnvFilter::new("debugger=info,warn")
-    });
+/// Logs go to stderr so stdout carries only command output, which
+/// `debugger mcp` relies on for its JSON-RPC stream.
+pub fn init_cli(verbosity: u8) {
+    let filter = match verbosity {
+        0 => EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new("debugger=info,warn")),
+        1 => EnvFilter::new("debugger=debug,warn"),
+        _ => EnvFilter::new("debugger=trace,info"),
+    };
 
     tracing_subscriber::registry()
         .with(filter)
         .with(
             fmt::layer()
+                .with_writer(std::io::stderr)
                 .with_target(true)
                 .with_thread_ids(false)
                 .with_file(false)
@@ -42,7 +58,24 @@ pub fn init_cli() {
 /// 2. stderr (inherited from spawning process for early errors)
 ///
 /// Log level controlled by `RUST_LOG`, default is TRACE for daemon to capture DAP messages.
+/// The file is rotated by size per the `[daemon]` log settings, since trace
+/// logging of a long session would otherwise grow without bound. Problems
+/// with the config file are logged once logging is set up.
 pub fn init_daemon() -> Option<PathBuf> {
+    let loaded = Config::load_with_problems();
+    let default = DaemonConfig::default();
+    let settings = loaded.as_ref().map_or(&default, |(config, _)| &config.daemon);
+
+    let log_path = init_daemon_subscriber(settings);
+    SESSION_LOGS_ENABLED.store(true, Ordering::Relaxed);
+    match &loaded {
+        Ok((_, problems)) => problems.iter().for_each(|problem| tracing::warn!("{}", problem)),
+        Err(e) => tracing::warn!("Using default log settings: {}", e),
+    }
+    log_path
+}
+
+fn init_daemon_subscriber(settings: &DaemonConfig) -> Option<PathBuf> {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
         // Default to trace for daemon - we want to see DAP messages
         EnvFilter::new("debugger=trace,info")
@@ -55,15 +88,15 @@ pub fn init_daemon() -> Option<PathBuf> {
             let log_file = log_dir.join("daemon.log");
 
             // Create or append to log file
-            match std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&log_file)
-            {
+            match RotatingFile::open(
+                &log_file,
+                settings.log_max_size_mb * 1024 * 1024,
+                settings.log_max_files,
+            ) {
                 Ok(file) => {
                     // File logging with full details
                     let file_layer = fmt::layer()
-                        .with_writer(file)
+                        .with_writer(Mutex::new(file))
                         .with_ansi(false)
                         .with_target(true)
                         .with_thread_ids(true)
@@ -83,6 +116,8 @@ pub fn init_daemon() -> Option<PathBuf> {
                         .with(filter)
                         .with(file_layer)
                         .with(stderr_layer)
+                        .with(session_layer())
+                        .with(DapCaptureLayer)
                         .init();
 
                     return Some(log_file);
@@ -108,22 +143,416 @@ pub fn init_daemon() -> Option<PathBuf> {
                 .with_file(true)
                 .with_line_number(true),
         )
+        .with(session_layer())
+        .with(DapCaptureLayer)
         .init();
 
     log_path
 }
 
+/// Log file of the current debug session, if any
+static SESSION_LOG: Mutex<Option<File>> = Mutex::new(None);
+
+/// Whether the daemon's subscriber writes to session logs; sessions run
+/// inside the CLI (`--no-daemon`) have none
+static SESSION_LOGS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Layer copying every event to the current session's log
+fn session_layer<S>() -> impl Layer<S>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    fmt::layer()
+        .with_writer(|| SessionLogWriter)
+        .with_ansi(false)
+        .with_target(true)
+        .with_thread_ids(true)
+}
+
+/// Writes to the current session log, or nowhere between sessions
+struct SessionLogWriter;
+
+impl Write for SessionLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match SESSION_LOG.lock().unwrap().as_mut() {
+            Some(file) => file.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match SESSION_LOG.lock().unwrap().as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Start a log for a new debug session, named after `name` (the program, or
+/// the pid when attaching) and the start time. Until [`end_session_log`],
+/// daemon events, DAP traffic included, are copied to it. Only the newest
+/// `max_files` logs of past sessions are kept. Returns its path.
+pub fn start_session_log(name: &str, max_files: usize) -> Option<PathBuf> {
+    if !SESSION_LOGS_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let dir = paths::session_log_dir()?;
+    let path = dir.join(format!("{}-{}.log", name, session_log_timestamp()));
+
+    let opened = std::fs::create_dir_all(&dir).and_then(|_| {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+    });
+    match opened {
+        Ok(file) => {
+            *SESSION_LOG.lock().unwrap() = Some(file);
+            if let Err(e) = prune_session_logs(&dir, &path, max_files) {
+                tracing::warn!("Could not remove old session logs: {}", e);
+            }
+            Some(path)
+        }
+        Err(e) => {
+            tracing::warn!("Could not open session log {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Stop copying events to the session log
+pub fn end_session_log() {
+    SESSION_LOG.lock().unwrap().take();
+}
+
+/// `2026-01-02T030405` in UTC: the log lines' own timestamp, to the
+/// second, without the colons some filesystems refuse
+fn session_log_timestamp() -> String {
+    use fmt::time::FormatTime;
+
+    let mut stamp = String::new();
+    let _ = fmt::time::SystemTime.format_time(&mut fmt::format::Writer::new(&mut stamp));
+    stamp.split('.').next().unwrap_or_default().replace(':', "")
+}
+
+/// Remove all but the newest `keep` session logs other than `current`
+fn prune_session_logs(dir: &Path, current: &Path, keep: usize) -> std::io::Result<()> {
+    let mut logs: Vec<(SystemTime, PathBuf)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let modified = path.metadata().ok()?.modified().ok()?;
+            (path != current && path.extension().is_some_and(|ext| ext == "log"))
+                .then_some((modified, path))
+        })
+        .collect();
+    logs.sort_by(|a, b| b.cmp(a));
+    for (_, path) in logs.into_iter().skip(keep) {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
 /// Get the path to the daemon log file
 pub fn daemon_log_path() -> Option<PathBuf> {
     paths::log_dir().map(|d| d.join("daemon.log"))
 }
 
-/// Truncate the daemon log file (useful before debugging sessions)
+/// Truncate the daemon log file and remove rotated logs (useful before
+/// debugging sessions)
 pub fn truncate_daemon_log() -> std::io::Result<()> {
     if let Some(path) = daemon_log_path() {
         if path.exists() {
             std::fs::write(&path, "")?;
         }
+        for rotated in rotated_paths(&path) {
+            std::fs::remove_file(rotated)?;
+        }
     }
     Ok(())
 }
+
+/// `daemon.log.N`: the Nth most recent rotated log
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Rotated logs that exist, most recent first
+fn rotated_paths(path: &Path) -> Vec<PathBuf> {
+    (1..)
+        .map(|n| rotated_path(path, n))
+        .take_while(|rotated| rotated.exists())
+        .collect()
+}
+
+/// Log file that moves itself to `<path>.1` (shifting older logs up) once
+/// it reaches `max_bytes`, keeping at most `max_files` rotated logs. Older
+/// rotations, left by a higher limit, are removed on open.
+///
+/// The fmt layer writes each event in one call, so events are never split
+/// across files.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, max_bytes: u64, max_files: usize) -> std::io::Result<Self> {
+        for stale in rotated_paths(path).into_iter().skip(max_files) {
+            std::fs::remove_file(stale)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_bytes,
+            max_files,
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            for n in (1..self.max_files).rev() {
+                let older = rotated_path(&self.path, n);
+                if older.exists() {
+                    std::fs::rename(&older, rotated_path(&self.path, n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+
+        self.file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.max_bytes > 0 && self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Captures collecting the DAP traffic logged while they last
+static DAP_CAPTURES: Mutex<Vec<Arc<Mutex<Vec<String>>>>> = Mutex::new(Vec::new());
+
+/// The DAP messages (`DAP >>> {...}` / `DAP <<< {...}`) the daemon logs
+/// while a command runs, to send back to a client that asked for them.
+/// Traffic of other clients' commands running at the same time is included.
+pub struct DapCapture(Arc<Mutex<Vec<String>>>);
+
+impl DapCapture {
+    /// Collect every DAP message logged from now on
+    pub fn start() -> Self {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        DAP_CAPTURES.lock().unwrap().push(messages.clone());
+        Self(messages)
+    }
+
+    /// Stop collecting, returning the messages in the order they were logged
+    pub fn finish(self) -> Vec<String> {
+        DAP_CAPTURES.lock().unwrap().retain(|capture| !Arc::ptr_eq(capture, &self.0));
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+/// Layer copying the DAP messages among the events to every capture
+struct DapCaptureLayer;
+
+impl<S: tracing::Subscriber> Layer<S> for DapCaptureLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        let captures = DAP_CAPTURES.lock().unwrap();
+        if captures.is_empty() {
+            return;
+        }
+        let mut message = MessageField(None);
+        event.record(&mut message);
+        let Some(message) = message.0 else {
+            return;
+        };
+        if message.starts_with("DAP >>> ") || message.starts_with("DAP <<< ") {
+            for capture in captures.iter() {
+                capture.lock().unwrap().push(message.clone());
+            }
+        }
+    }
+}
+
+/// The formatted `message` field of an event
+struct MessageField(Option<String>);
+
+impl tracing::field::Visit for MessageField {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+/// The last `count` lines of the daemon log, oldest first, reading back into
+/// rotated logs as needed. With `level`, only events at that level or more
+/// severe are kept.
+pub fn read_daemon_log(count: usize, level: Option<Level>) -> std::io::Result<Vec<String>> {
+    let Some(path) = daemon_log_path() else {
+        return Ok(Vec::new());
+    };
+
+    let mut lines = Vec::new();
+    for file in std::iter::once(path.clone()).chain(rotated_paths(&path)) {
+        if !file.exists() {
+            continue;
+        }
+        let content = std::fs::read_to_string(&file)?;
+        let mut older = filter_by_level(&content, level);
+        older.append(&mut lines);
+        lines = older;
+        if lines.len() >= count {
+            break;
+        }
+    }
+
+    let start = lines.len().saturating_sub(count);
+    Ok(lines.split_off(start))
+}
+
+/// Lines of log `content` at `level` or more severe. Lines without a level
+/// (the rest of a multi-line message) go with the event they belong to.
+fn filter_by_level(content: &str, level: Option<Level>) -> Vec<String> {
+    let mut keep = true;
+    content
+        .lines()
+        .filter(|line| {
+            if let Some(level) = level {
+                if let Some(line_level) = line_level(line) {
+                    keep = line_level <= level;
+                }
+            }
+            keep
+        })
+        .map(String::from)
+        .collect()
+}
+
+/// Level of a log line, which follows the timestamp
+fn line_level(line: &str) -> Option<Level> {
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_rotates_at_the_size_limit() {
+        let dir = std::env::temp_dir().join(format!("debugger-log-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("daemon.log");
+
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        for event in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(event.as_bytes()).unwrap();
+        }
+
+        let read = |path: &Path| std::fs::read_to_string(path).unwrap();
+        assert_eq!(read(&path), "fourth\n");
+        assert_eq!(read(&rotated_path(&path, 1)), "third\n");
+        assert_eq!(read(&rotated_path(&path, 2)), "second\n");
+        assert!(!rotated_path(&path, 3).exists());
+
+        // A lower limit drops the rotations beyond it
+        RotatingFile::open(&path, 10, 1).unwrap();
+        assert!(rotated_path(&path, 1).exists());
+        assert!(!rotated_path(&path, 2).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dap_traffic_is_captured_while_a_command_runs() {
+        let subscriber = tracing_subscriber::registry().with(DapCaptureLayer);
+        let captured = tracing::subscriber::with_default(subscriber, || {
+            tracing::trace!("DAP >>> {}", "{\"seq\":1}");
+            let capture = DapCapture::start();
+            tracing::trace!("DAP >>> {}", "{\"seq\":2}");
+            tracing::debug!("handling command");
+            tracing::trace!("DAP <<< {}", "{\"seq\":3}");
+            let captured = capture.finish();
+            tracing::trace!("DAP >>> {}", "{\"seq\":4}");
+            captured
+        });
+
+        assert_eq!(captured, ["DAP >>> {\"seq\":2}", "DAP <<< {\"seq\":3}"]);
+    }
+
+    #[test]
+    fn session_logs_are_named_by_utc_start_time() {
+        let stamp = session_log_timestamp();
+        assert_eq!(stamp.len(), "2026-02-14T030405".len());
+        assert_eq!(&stamp[10..11], "T");
+        assert!(!stamp.contains(':'));
+    }
+
+    #[test]
+    fn only_the_newest_session_logs_are_kept() {
+        let dir = std::env::temp_dir().join(format!("debugger-sessions-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let names = ["a.log", "b.log", "c.log", "d.log", "notes.txt"];
+        for (age, name) in names.iter().rev().enumerate() {
+            let file = File::create(dir.join(name)).unwrap();
+            let modified = SystemTime::now() - std::time::Duration::from_secs(60 * age as u64);
+            file.set_modified(modified).unwrap();
+        }
+
+        prune_session_logs(&dir, &dir.join("d.log"), 2).unwrap();
+
+        let mut left: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["b.log", "c.log", "d.log", "notes.txt"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn level_filter_keeps_continuation_lines_with_their_event() {
+        let content = "\
+2026-01-01T00:00:00.000000Z TRACE debugger::dap: DAP >>> {}
+2026-01-01T00:00:00.000001Z  WARN debugger::daemon: adapter said:
+  something odd
+2026-01-01T00:00:00.000002Z DEBUG debugger::daemon: detail
+2026-01-01T00:00:00.000003Z ERROR debugger::daemon: failed";
+
+        assert_eq!(
+            filter_by_level(content, Some(Level::WARN)),
+            [
+                "2026-01-01T00:00:00.000001Z  WARN debugger::daemon: adapter said:",
+                "  something odd",
+                "2026-01-01T00:00:00.000003Z ERROR debugger::daemon: failed",
+            ]
+        );
+        assert_eq!(filter_by_level(content, None).len(), 5);
+    }
+}
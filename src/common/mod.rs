@@ -4,6 +4,7 @@ pub mod config;
 pub mod error;
 pub mod logging;
 pub mod paths;
+pub mod signal;
 
 pub use error::{Error, Result};
 
@@ -23,4 +24,22 @@ pub fn parse_listen_address(line: &str) -> Option<String> {
     } else {
         None
     }
-}
\ No newline at end of file
+}
+
+/// Return the longest valid UTF-8 prefix that fits within `max_bytes`.
+pub fn truncate_utf8_to_bytes(value: &str, max_bytes: usize) -> String {
+    if value.len() <= max_bytes {
+        return value.to_string();
+    }
+
+    let mut end = 0;
+    for (start, character) in value.char_indices() {
+        let character_end = start + character.len_utf8();
+        if character_end > max_bytes {
+            break;
+        }
+        end = character_end;
+    }
+
+    value[..end].to_string()
+}
@@ -4,36 +4,59 @@
 //! Windows: Uses named pipes at \\.\pipe\debugger-cli-<username>
 
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Name used for the IPC socket/pipe
 const SOCKET_NAME: &str = "debugger-cli";
 
+/// Environment variable naming a separate daemon instance. Each instance has
+/// its own socket, and so its own daemon and session; daemons spawned by a
+/// process inherit its instance.
+pub const INSTANCE_ENV: &str = "DEBUGGER_CLI_INSTANCE";
+
+/// The daemon instance selected through [`INSTANCE_ENV`], if any
+pub fn instance() -> Option<String> {
+    std::env::var(INSTANCE_ENV).ok().filter(|name| !name.is_empty())
+}
+
+/// File name of the daemon socket for `instance`
+#[cfg(unix)]
+fn socket_file_name(instance: Option<&str>) -> String {
+    match instance {
+        Some(instance) => format!("daemon-{}.sock", instance),
+        None => "daemon.sock".to_string(),
+    }
+}
+
 /// Get the socket/pipe path for IPC communication
 ///
 /// Platform-specific:
 /// - Unix: `$XDG_RUNTIME_DIR/debugger-cli/daemon.sock` or `/tmp/debugger-cli-<uid>/daemon.sock`
 /// - Windows: Named pipe path (handled by interprocess crate)
+///
+/// A named instance uses `daemon-<instance>.sock` (Unix) or a pipe name
+/// ending in `-<instance>` (Windows).
 #[cfg(unix)]
 pub fn socket_path() -> PathBuf {
+    let file_name = socket_file_name(instance().as_deref());
+
     // Try XDG_RUNTIME_DIR first (preferred on Linux)
     if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
         return PathBuf::from(runtime_dir)
             .join(SOCKET_NAME)
-            .join("daemon.sock");
+            .join(file_name);
     }
 
     // Fallback to /tmp with uid for security
     let uid = unsafe { libc::getuid() };
-    PathBuf::from(format!("/tmp/{}-{}", SOCKET_NAME, uid)).join("daemon.sock")
+    PathBuf::from(format!("/tmp/{}-{}", SOCKET_NAME, uid)).join(file_name)
 }
 
 #[cfg(windows)]
 pub fn socket_path() -> PathBuf {
     // On Windows, we return a path that will be converted to a named pipe
     // The interprocess crate handles the \\.\pipe\ prefix
-    let username = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
-    PathBuf::from(format!("{}-{}", SOCKET_NAME, username))
+    PathBuf::from(socket_name())
 }
 
 /// Get the socket name for interprocess LocalSocketName
@@ -47,7 +70,19 @@ pub fn socket_name() -> String {
 #[cfg(windows)]
 pub fn socket_name() -> String {
     let username = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
-    format!("{}-{}", SOCKET_NAME, username)
+    pipe_name(&username, instance().as_deref())
+}
+
+/// Name of the daemon's pipe for `username` and `instance`, without the
+/// `\\.\pipe\` prefix the interprocess crate adds. Pipe names may not
+/// contain backslashes, so any in the user name are replaced.
+#[cfg(windows)]
+fn pipe_name(username: &str, instance: Option<&str>) -> String {
+    let username = username.replace('\\', "_");
+    match instance {
+        Some(instance) => format!("{}-{}-{}", SOCKET_NAME, username, instance),
+        None => format!("{}-{}", SOCKET_NAME, username),
+    }
 }
 
 /// Ensure the socket directory exists with proper permissions
@@ -114,6 +149,38 @@ pub fn log_dir() -> Option<PathBuf> {
         .map(|dirs| dirs.data_dir().join("logs"))
 }
 
+/// Directory for per-session logs: beside the socket in the runtime
+/// directory on Unix, under the log directory on Windows
+#[cfg(unix)]
+pub fn session_log_dir() -> Option<PathBuf> {
+    socket_path().parent().map(|dir| dir.join("sessions"))
+}
+
+#[cfg(windows)]
+pub fn session_log_dir() -> Option<PathBuf> {
+    log_dir().map(|dir| dir.join("sessions"))
+}
+
+/// File holding the command history of the project in `dir`, under the
+/// configuration directory and keyed by a hash of the directory's path
+pub fn history_path(dir: &Path) -> Option<PathBuf> {
+    config_dir().map(|config| {
+        config
+            .join("history")
+            .join(format!("{:016x}.jsonl", path_hash(dir)))
+    })
+}
+
+/// FNV-1a hash of a path; unlike `DefaultHasher` it is stable across Rust
+/// releases, so a history stays findable after a toolchain upgrade
+fn path_hash(path: &Path) -> u64 {
+    path.to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
 /// Ensure the configuration directory exists
 pub fn ensure_config_dir() -> io::Result<Option<PathBuf>> {
     if let Some(dir) = config_dir() {
@@ -136,6 +203,36 @@ mod tests {
         assert!(!path.as_os_str().is_empty());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_instances_get_their_own_socket() {
+        assert_eq!(socket_file_name(None), "daemon.sock");
+        assert_eq!(socket_file_name(Some("ci-2")), "daemon-ci-2.sock");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_instances_get_their_own_pipe() {
+        assert_eq!(pipe_name("alice", None), "debugger-cli-alice");
+        assert_eq!(pipe_name("alice", Some("ci-2")), "debugger-cli-alice-ci-2");
+        assert_eq!(pipe_name("CORP\\alice", None), "debugger-cli-CORP_alice");
+        assert!(socket_name().starts_with("debugger-cli-"));
+        assert!(session_log_dir().unwrap().starts_with(log_dir().unwrap()));
+    }
+
+    #[test]
+    fn test_history_is_kept_per_directory() {
+        assert_eq!(path_hash(Path::new("")), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(
+            history_path(Path::new("/src/app")),
+            history_path(Path::new("/src/app"))
+        );
+        assert_ne!(
+            history_path(Path::new("/src/app")),
+            history_path(Path::new("/src/lib"))
+        );
+    }
+
     #[test]
     fn test_config_dir_is_valid() {
         let dir = config_dir();
@@ -0,0 +1,95 @@
+//! POSIX signal names as reported by debug adapters
+//!
+//! Adapters describe signal stops in free text ("signal SIGSEGV: invalid
+//! address (fault address: 0x0)"), so these helpers pick the pieces out.
+
+/// Signal names with their number on this platform and a short description
+const SIGNALS: &[(&str, i32, &str)] = &[
+    ("SIGHUP", 1, "hangup"),
+    ("SIGINT", 2, "interrupt"),
+    ("SIGQUIT", 3, "quit"),
+    ("SIGILL", 4, "illegal instruction"),
+    ("SIGTRAP", 5, "trace/breakpoint trap"),
+    ("SIGABRT", 6, "aborted"),
+    #[cfg(target_os = "macos")]
+    ("SIGBUS", 10, "bus error"),
+    #[cfg(not(target_os = "macos"))]
+    ("SIGBUS", 7, "bus error"),
+    ("SIGFPE", 8, "floating point exception"),
+    ("SIGKILL", 9, "killed"),
+    #[cfg(target_os = "macos")]
+    ("SIGUSR1", 30, "user defined signal 1"),
+    #[cfg(not(target_os = "macos"))]
+    ("SIGUSR1", 10, "user defined signal 1"),
+    ("SIGSEGV", 11, "segmentation fault"),
+    #[cfg(target_os = "macos")]
+    ("SIGUSR2", 31, "user defined signal 2"),
+    #[cfg(not(target_os = "macos"))]
+    ("SIGUSR2", 12, "user defined signal 2"),
+    ("SIGPIPE", 13, "broken pipe"),
+    ("SIGALRM", 14, "alarm clock"),
+    ("SIGTERM", 15, "terminated"),
+];
+
+/// Find the first signal name (`SIGSEGV`, `SIGABRT`, ...) in `text`
+pub fn parse_signal_name(text: &str) -> Option<&str> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .find(|word| {
+            word.len() > 3
+                && word.starts_with("SIG")
+                && !word.starts_with("SIGNAL")
+                && word[3..]
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        })
+}
+
+/// Signal number for a signal name on this platform
+pub fn signal_number(name: &str) -> Option<i32> {
+    SIGNALS
+        .iter()
+        .find(|(signal, _, _)| *signal == name)
+        .map(|(_, number, _)| *number)
+}
+
+/// Short description of a signal, e.g. "segmentation fault" for `SIGSEGV`
+pub fn signal_description(name: &str) -> Option<&'static str> {
+    SIGNALS
+        .iter()
+        .find(|(signal, _, _)| *signal == name)
+        .map(|(_, _, description)| *description)
+}
+
+/// The faulting address mentioned in an adapter's fault text, such as
+/// `fault address: 0x10` (lldb) or `address=0x10` (Mach exceptions)
+pub fn fault_address(text: &str) -> Option<&str> {
+    let after = &text[text.find("address")? + "address".len()..];
+    let start = after.find("0x")?;
+    let hex = &after[start..];
+    let end = 2 + hex[2..]
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(hex.len() - 2);
+    (end > 2).then(|| &hex[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signal_details_are_parsed_from_adapter_text() {
+        let lldb = "signal SIGSEGV: invalid address (fault address: 0x0)";
+        assert_eq!(parse_signal_name(lldb), Some("SIGSEGV"));
+        assert_eq!(fault_address(lldb), Some("0x0"));
+        assert_eq!(signal_number("SIGSEGV"), Some(11));
+        assert_eq!(signal_description("SIGSEGV"), Some("segmentation fault"));
+
+        let mach = "EXC_BAD_ACCESS (code=1, address=0x7ffe1c)";
+        assert_eq!(parse_signal_name(mach), None);
+        assert_eq!(fault_address(mach), Some("0x7ffe1c"));
+
+        assert_eq!(parse_signal_name("Program received SIGABRT."), Some("SIGABRT"));
+        assert_eq!(parse_signal_name("SIGNAL"), None);
+        assert_eq!(fault_address("no address here"), None);
+    }
+}
@@ -1,22 +1,22 @@
 //! Session actor - owns the debug session and serializes access to it
 //!
 //! Connection tasks send commands over an mpsc channel; the actor executes
-//! them one at a time, which preserves DAP request ordering. After every
+//! them one at a time, which preserves DAP request ordering. Around every
 //! command and on a periodic tick it reduces pending DAP events and publishes
 //! a state snapshot on a watch channel, so `await` (and any future
 //! subscription) can wait on state changes without occupying the actor.
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use tokio::sync::{mpsc, oneshot, watch};
 
-use crate::common::config::Config;
-use crate::dap::StoppedEventBody;
-use crate::ipc::protocol::{Command, Response};
+use crate::common::{config::Config, logging};
+use crate::dap::{CancelHandle, ExceptionInfoResponseBody, StoppedEventBody};
+use crate::ipc::protocol::{Command, OnHitOutput, Response, WatchValue};
 
 use super::handler;
-use super::session::{DebugSession, SessionState};
+use super::session::{DebugSession, DormantSession, SessionState};
 
 /// How often the actor reduces DAP events when no commands arrive.
 const EVENT_TICK: Duration = Duration::from_millis(100);
@@ -39,6 +39,30 @@ pub struct SessionSnapshot {
     pub stopped_reason: Option<String>,
     pub stopped_thread: Option<i64>,
     pub exit_code: Option<i32>,
+    /// Output of the hit breakpoints' on-hit commands for the current stop
+    pub on_hit: Vec<OnHitOutput>,
+    /// Adapter's `exceptionInfo` for an exception or signal stop
+    pub exception_info: Option<ExceptionInfoResponseBody>,
+    /// Values of the watches in scope at the current stop
+    pub watches: Vec<WatchValue>,
+    /// Idle time after which the daemon ends the session, if overridden
+    pub keep_alive: Option<Duration>,
+}
+
+/// The published view of `session`
+pub(super) fn snapshot_of(session: &DebugSession) -> SessionSnapshot {
+    SessionSnapshot {
+        session_active: true,
+        state: Some(session.state()),
+        last_stop: session.last_stop().cloned(),
+        stopped_reason: session.stopped_reason().map(String::from),
+        stopped_thread: session.stopped_thread(),
+        exit_code: session.exit_code(),
+        on_hit: session.on_hit_output().to_vec(),
+        exception_info: session.exception_info().cloned(),
+        watches: session.watch_values().to_vec(),
+        keep_alive: session.keep_alive(),
+    }
 }
 
 /// Run the session actor until every request sender is dropped.
@@ -49,8 +73,11 @@ pub async fn run(
     config: Arc<Config>,
     mut requests: mpsc::Receiver<ActorRequest>,
     snapshots: watch::Sender<SessionSnapshot>,
+    cancel: Arc<Mutex<Option<CancelHandle>>>,
 ) {
     let mut session: Option<DebugSession> = None;
+    // What `detach` kept of an attached session, for `attach --resume`
+    let mut dormant: Option<DormantSession> = None;
     let mut tick = tokio::time::interval(EVENT_TICK);
     tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
@@ -61,14 +88,22 @@ pub async fn run(
                     break;
                 };
 
-                reduce_events(&mut session).await;
-                let response = handler::handle_command(&mut session, &config, id, command).await;
+                reduce_events(&mut session, &mut dormant, &config).await;
+                let response =
+                    handler::handle_command(&mut session, &mut dormant, &config, id, command).await;
+                // Events the command itself applied, such as a stop found by
+                // a failed resume, are reduced in full before being published
+                reduce_events(&mut session, &mut dormant, &config).await;
                 publish(&snapshots, &session);
+                // Commands start and end sessions, so the handle may change
+                *cancel.lock().unwrap() = session.as_ref().map(DebugSession::cancel_handle);
                 let _ = reply.send(response);
             }
             _ = tick.tick() => {
-                reduce_events(&mut session).await;
+                reduce_events(&mut session, &mut dormant, &config).await;
                 publish(&snapshots, &session);
+                // A relaunch after an adapter crash replaces the client
+                *cancel.lock().unwrap() = session.as_ref().map(DebugSession::cancel_handle);
             }
         }
     }
@@ -76,29 +111,42 @@ pub async fn run(
     tracing::debug!("Session actor shutting down");
     if let Some(mut active) = session.take() {
         let _ = active.stop().await;
+        logging::end_session_log();
     }
 }
 
-async fn reduce_events(session: &mut Option<DebugSession>) {
+async fn reduce_events(
+    session: &mut Option<DebugSession>,
+    dormant: &mut Option<DormantSession>,
+    config: &Config,
+) {
+    let Some(active) = session.as_mut() else {
+        return;
+    };
+    if let Err(e) = active.process_events().await {
+        tracing::warn!("Error processing events: {}", e);
+    }
+    if active.adapter_crashed() && config.daemon.relaunch_on_crash {
+        active.relaunch_after_crash(config).await;
+    }
+    active.recheck_pending_breakpoints().await;
+    active.fetch_exception_info().await;
+    active.evaluate_watches().await;
+
+    // Run on-hit commands before the stop is published, so `await` sees
+    // their output together with the stop itself.
+    let commands = active.take_pending_on_hit();
+    if commands.is_empty() {
+        return;
+    }
+    let output = handler::run_on_hit_commands(session, dormant, config, commands).await;
     if let Some(active) = session.as_mut() {
-        if let Err(e) = active.process_events().await {
-            tracing::warn!("Error processing events: {}", e);
-        }
+        active.set_on_hit_output(output);
     }
 }
 
 fn publish(snapshots: &watch::Sender<SessionSnapshot>, session: &Option<DebugSession>) {
-    let snapshot = match session {
-        Some(active) => SessionSnapshot {
-            session_active: true,
-            state: Some(active.state()),
-            last_stop: active.last_stop().cloned(),
-            stopped_reason: active.stopped_reason().map(String::from),
-            stopped_thread: active.stopped_thread(),
-            exit_code: active.exit_code(),
-        },
-        None => SessionSnapshot::default(),
-    };
+    let snapshot = session.as_ref().map(snapshot_of).unwrap_or_default();
 
     snapshots.send_if_modified(|current| {
         if *current == snapshot {
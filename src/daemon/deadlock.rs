@@ -0,0 +1,203 @@
+//! `analyze deadlock`: a heuristic for spotting threads stuck on each other
+//!
+//! A thread counts as blocked when its stack passes through a known lock or
+//! wait function (`[analyze] wait_functions`). Blocked thread A is taken to
+//! wait for blocked thread B when B's stack, below the point where B waits,
+//! passes through the function A waits in: B got past that point, so it may
+//! hold the lock A is after. Cycles of such threads are reported as
+//! potential deadlocks; without knowing the locks' owners this is a guess.
+
+use crate::dap::{StackFrame, Thread};
+use crate::ipc::protocol::{BlockedThread, DeadlockReport};
+
+use super::session::frame_info;
+
+/// Cycles reported at most; many threads piled up on the same locks can
+/// form a great many
+const MAX_CYCLES: usize = 20;
+
+/// Paths the cycle search extends at most, so a dense graph of blocked
+/// threads can't keep it busy
+const MAX_SEARCH_STEPS: usize = 100_000;
+
+/// A blocked thread, with the index of its outermost wait frame
+struct Blocked<'a> {
+    thread: &'a Thread,
+    frames: &'a [StackFrame],
+    wait: usize,
+}
+
+impl Blocked<'_> {
+    /// The function the thread waits in: the caller of its wait function
+    fn site(&self) -> Option<&str> {
+        self.frames.get(self.wait + 1).map(|frame| frame.name.as_str())
+    }
+
+    /// Whether the stack passes through `function` below the wait site
+    fn has_passed(&self, function: &str) -> bool {
+        self.frames
+            .iter()
+            .skip(self.wait + 2)
+            .any(|frame| frame.name == function)
+    }
+}
+
+/// Find the blocked threads among `stacks`, and cycles among them;
+/// `skipped` lists the threads whose stacks couldn't be fetched
+pub(super) fn analyze(
+    stacks: &[(Thread, Vec<StackFrame>)],
+    skipped: Vec<i64>,
+    wait_functions: &[String],
+) -> DeadlockReport {
+    let is_wait = |frame: &StackFrame| {
+        wait_functions
+            .iter()
+            .any(|function| frame.name.contains(function.as_str()))
+    };
+    let mut blocked: Vec<Blocked> = stacks
+        .iter()
+        .filter_map(|(thread, frames)| {
+            // Wait functions don't call back into the program, so everything
+            // above the outermost one is part of the wait
+            let wait = frames.iter().rposition(is_wait)?;
+            Some(Blocked {
+                thread,
+                frames,
+                wait,
+            })
+        })
+        .collect();
+    blocked.sort_by_key(|b| b.thread.id);
+
+    // For each blocked thread, the blocked threads it may be waiting for
+    let waits_for: Vec<Vec<usize>> = blocked
+        .iter()
+        .map(|waiter| match waiter.site() {
+            Some(site) => (0..blocked.len())
+                .filter(|&other| blocked[other].has_passed(site))
+                .collect(),
+            None => Vec::new(),
+        })
+        .collect();
+    let cycles = find_cycles(&waits_for)
+        .into_iter()
+        .map(|cycle| cycle.into_iter().map(|index| blocked[index].thread.id).collect())
+        .collect();
+
+    DeadlockReport {
+        threads: stacks.len(),
+        blocked: blocked
+            .iter()
+            .map(|b| BlockedThread {
+                id: b.thread.id,
+                name: b.thread.name.clone(),
+                wait_function: b.frames[b.wait].name.clone(),
+                waiting_in: b.frames.get(b.wait + 1).map(|frame| frame_info(frame, b.wait + 1)),
+            })
+            .collect(),
+        cycles,
+        skipped,
+    }
+}
+
+/// The simple cycles of the graph with `edges`, each once, starting from
+/// its lowest node
+fn find_cycles(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut cycles = Vec::new();
+    let mut steps = 0;
+    for start in 0..edges.len() {
+        extend_path(edges, &mut vec![start], &mut cycles, &mut steps);
+    }
+    cycles
+}
+
+/// Extend `path` (from its lowest node) along `edges`, collecting the
+/// cycles that close back to its start
+fn extend_path(
+    edges: &[Vec<usize>],
+    path: &mut Vec<usize>,
+    cycles: &mut Vec<Vec<usize>>,
+    steps: &mut usize,
+) {
+    let start = path[0];
+    let last = path[path.len() - 1];
+    for &next in &edges[last] {
+        if cycles.len() >= MAX_CYCLES || *steps >= MAX_SEARCH_STEPS {
+            return;
+        }
+        *steps += 1;
+        if next == start {
+            cycles.push(path.clone());
+        } else if next > start && !path.contains(&next) {
+            path.push(next);
+            extend_path(edges, path, cycles, steps);
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::config::AnalyzeConfig;
+
+    fn stack(id: i64, functions: &[&str]) -> (Thread, Vec<StackFrame>) {
+        let frames = functions
+            .iter()
+            .enumerate()
+            .map(|(index, name)| StackFrame {
+                id: id * 100 + index as i64,
+                name: name.to_string(),
+                source: None,
+                line: 0,
+                column: 0,
+                module_id: None,
+                instruction_pointer_reference: None,
+                presentation_hint: None,
+            })
+            .collect();
+        let thread = Thread {
+            id,
+            name: format!("thread-{}", id),
+        };
+        (thread, frames)
+    }
+
+    #[test]
+    fn lock_order_inversion_is_reported_as_a_cycle() {
+        let stacks = vec![
+            // Took lock A in update_a, now waits for lock B in update_b
+            stack(
+                1,
+                &[
+                    "futex_wait",
+                    "__lll_lock_wait",
+                    "pthread_mutex_lock",
+                    "update_b",
+                    "update_a",
+                    "worker",
+                ],
+            ),
+            // Took lock B in update_b, now waits for lock A in update_a
+            stack(2, &["pthread_mutex_lock", "update_a", "update_b", "worker"]),
+            stack(3, &["pthread_join", "main"]),
+            stack(4, &["compute", "worker"]),
+        ];
+        let report = analyze(&stacks, vec![5], &AnalyzeConfig::default().wait_functions);
+
+        assert_eq!(report.threads, 4);
+        let blocked: Vec<i64> = report.blocked.iter().map(|b| b.id).collect();
+        assert_eq!(blocked, vec![1, 2, 3]);
+        assert_eq!(report.blocked[0].wait_function, "pthread_mutex_lock");
+        let waiting_in = report.blocked[0].waiting_in.as_ref().unwrap();
+        assert_eq!((waiting_in.name.as_str(), waiting_in.index), ("update_b", Some(3)));
+        assert_eq!(report.cycles, vec![vec![1, 2]]);
+        assert_eq!(report.skipped, vec![5]);
+    }
+
+    #[test]
+    fn cycles_are_found_once_each() {
+        let edges = vec![vec![1], vec![2, 0], vec![0], vec![3]];
+        assert_eq!(find_cycles(&edges), vec![vec![0, 1, 2], vec![0, 1], vec![3]]);
+    }
+}
@@ -0,0 +1,97 @@
+//! Live copies of session events for `debugger events`
+//!
+//! Like the output tap: every subscriber is sent each event as the session
+//! reduces it, in order, as long as it keeps reading; one that falls behind
+//! gets a `lagged` event saying how many it missed. Kept process-wide, so a
+//! subscription carries on when a crashed adapter's session is replaced by
+//! a relaunch; `debugger events` itself ends once the program exits.
+//!
+//! Each subscriber names the kinds of event it wants when subscribing, and
+//! is only sent those, so one watching for stops isn't sent the output.
+
+use tokio::sync::mpsc;
+
+use crate::common::{Error, Result};
+use crate::ipc::protocol::SessionEvent;
+
+use super::tap::{Tap, Tapped};
+
+static TAP: Tap<SessionEvent> = Tap::new();
+
+impl Tapped for SessionEvent {
+    fn lagged(dropped: usize) -> Self {
+        SessionEvent::Lagged { dropped }
+    }
+}
+
+/// Receive the session events of the kinds in `only`, or of every kind if
+/// it is empty, from now on until the receiver is dropped
+pub(super) fn subscribe(only: &[String]) -> Result<mpsc::Receiver<SessionEvent>> {
+    if let Some(kind) = only.iter().find(|kind| !SessionEvent::KINDS.contains(&kind.as_str())) {
+        return Err(Error::Config(format!(
+            "Unknown event kind '{}'; expected one of: {}",
+            kind,
+            SessionEvent::KINDS.join(", ")
+        )));
+    }
+    let only = only.to_vec();
+    Ok(TAP.subscribe(move |event: &SessionEvent| {
+        only.is_empty() || only.iter().any(|wanted| wanted == event.kind())
+    }))
+}
+
+/// Copy the event built by `event` to every subscriber that wants its kind;
+/// it is only built when someone is subscribed
+pub(super) fn send(event: impl FnOnce() -> SessionEvent) {
+    TAP.send(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscribers_get_every_event_in_order() {
+        let mut subscriber = subscribe(&[]).unwrap();
+        for thread_id in [101, 102] {
+            send(|| SessionEvent::Thread {
+                reason: "started".to_string(),
+                thread_id,
+            });
+        }
+        send(|| SessionEvent::Exited { exit_code: 3 });
+
+        // Other tests' sessions may be sending events too
+        let mut received = Vec::new();
+        while let Ok(event) = subscriber.try_recv() {
+            match event {
+                SessionEvent::Thread { thread_id, .. } if thread_id > 100 => {
+                    received.push(thread_id)
+                }
+                SessionEvent::Exited { exit_code: 3 } => received.push(0),
+                _ => {}
+            }
+        }
+        assert_eq!(received, vec![101, 102, 0]);
+    }
+
+    #[test]
+    fn subscribers_get_only_the_kinds_they_asked_for() {
+        let mut subscriber = subscribe(&["exited".to_string()]).unwrap();
+        send(|| SessionEvent::Output {
+            category: "stdout".to_string(),
+            output: "filtered out\n".to_string(),
+        });
+        send(|| SessionEvent::Exited { exit_code: 4 });
+
+        let mut kinds = Vec::new();
+        while let Ok(event) = subscriber.try_recv() {
+            kinds.push(event.kind());
+        }
+        assert!(kinds.contains(&"exited"));
+        assert!(kinds.iter().all(|kind| *kind == "exited"));
+
+        assert!(subscribe(&["breakpoint".to_string()]).is_err());
+        assert!(subscribe(&["lagged".to_string()]).is_err());
+    }
+}
@@ -4,22 +4,34 @@
 
 use serde_json::json;
 
-use crate::common::{config::Config, error::IpcError, Error, Result};
+use crate::common::{
+    config::Config, error::IpcError, logging, truncate_utf8_to_bytes, Error, Result,
+};
+use crate::dap::{Capabilities, StackFrame};
 use crate::ipc::protocol::{
-    BreakpointLocation, Command, ContextResult, EvaluateContext, EvaluateResult, Response,
-    SourceLine, StackFrameInfo, StatusResult, ThreadInfo, VariableInfo,
+    BreakpointLocation, Command, ContextResult, EvaluateContext, EvaluateResult, FrameEvaluation,
+    InstructionInfo, OnHitOutput, PresentationHint, Response, SourceLine, StackFrameInfo,
+    StatusResult, ThreadInfo, TriageResult, VariableInfo,
 };
+use crate::commands::parse::parse_command;
 
-use super::session::DebugSession;
+use super::actor;
+use super::deadlock;
+use super::server::{stop_result_at, StopLocation};
+use super::session::{
+    frame_info, is_user_frame, DebugSession, DormantSession, Locals, SessionState,
+};
 
-/// Handle an IPC command
+/// Handle an IPC command. `dormant` holds what was kept of the last
+/// detached session, for `attach --resume`.
 pub async fn handle_command(
     session: &mut Option<DebugSession>,
+    dormant: &mut Option<DormantSession>,
     config: &Config,
     id: u64,
     command: Command,
 ) -> Response {
-    match handle_command_inner(session, config, command).await {
+    match handle_command_inner(session, dormant, config, command).await {
         Ok(result) => Response::success(id, result),
         Err(e) => Response::error(id, IpcError::from(&e)),
     }
@@ -27,9 +39,18 @@ pub async fn handle_command(
 
 async fn handle_command_inner(
     session: &mut Option<DebugSession>,
+    dormant: &mut Option<DormantSession>,
     config: &Config,
     command: Command,
 ) -> Result<serde_json::Value> {
+    match session.as_ref() {
+        Some(sess) => check_capabilities(&command, sess)?,
+        // Before anything else about the command is checked, so every
+        // command that needs a program gets the same answer without one
+        None if !works_without_session(&command) => return Err(Error::SessionNotActive),
+        None => {}
+    }
+
     match command {
         // === Session Management ===
         Command::Start {
@@ -38,64 +59,150 @@ async fn handle_command_inner(
             adapter,
             stop_on_entry,
             initial_breakpoints,
+            keep_alive_minutes,
         } => {
             if session.is_some() {
                 return Err(Error::SessionAlreadyActive);
             }
-
-            let new_session =
-                DebugSession::launch(config, &program, args, adapter, stop_on_entry, initial_breakpoints).await?;
+            let keep_alive = keep_alive(keep_alive_minutes)?;
+
+            let name = program
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "program".to_string());
+            let log = logging::start_session_log(&name, config.daemon.log_max_files);
+
+            let mut new_session =
+                DebugSession::launch(config, &program, args, adapter, stop_on_entry, initial_breakpoints)
+                    .await
+                    .inspect_err(|_| logging::end_session_log())?;
+            new_session.set_keep_alive(keep_alive);
+            let adapter = new_session.adapter_name().to_string();
             *session = Some(new_session);
 
             Ok(json!({
                 "status": "started",
-                "program": program.display().to_string()
+                "program": program.display().to_string(),
+                "adapter": adapter,
+                "log": log
+            }))
+        }
+
+        Command::Attach {
+            pid,
+            adapter,
+            keep_alive_minutes,
+        } => {
+            if session.is_some() {
+                return Err(Error::SessionAlreadyActive);
+            }
+            let keep_alive = keep_alive(keep_alive_minutes)?;
+
+            let name = format!("pid-{}", pid);
+            let log = logging::start_session_log(&name, config.daemon.log_max_files);
+            let mut new_session = DebugSession::attach(config, pid, adapter)
+                .await
+                .inspect_err(|_| logging::end_session_log())?;
+            new_session.set_keep_alive(keep_alive);
+            *session = Some(new_session);
+
+            Ok(json!({
+                "status": "attached",
+                "pid": pid,
+                "log": log
             }))
         }
 
-        Command::Attach { pid, adapter } => {
+        Command::Reattach {
+            pid,
+            keep_alive_minutes,
+        } => {
             if session.is_some() {
                 return Err(Error::SessionAlreadyActive);
             }
+            let keep_alive = keep_alive(keep_alive_minutes)?;
+            let kept = dormant.as_ref().ok_or_else(|| {
+                Error::Config("No detached session to resume; detach from one first".to_string())
+            })?;
+            if pid.is_some_and(|pid| pid != kept.pid) {
+                return Err(Error::Config(format!(
+                    "The detached session was attached to process {}, not {}",
+                    kept.pid,
+                    pid.unwrap_or_default()
+                )));
+            }
 
-            let new_session = DebugSession::attach(config, pid, adapter).await?;
+            let pid = kept.pid;
+            let breakpoints = kept.breakpoint_count();
+            let name = format!("pid-{}", pid);
+            let log = logging::start_session_log(&name, config.daemon.log_max_files);
+            // Kept until the attach succeeds, so a failed one can be retried
+            let mut new_session = DebugSession::reattach(config, kept.clone())
+                .await
+                .inspect_err(|_| logging::end_session_log())?;
+            new_session.set_keep_alive(keep_alive);
             *session = Some(new_session);
+            *dormant = None;
 
             Ok(json!({
                 "status": "attached",
-                "pid": pid
+                "pid": pid,
+                "log": log,
+                "breakpoints": breakpoints
             }))
         }
 
         Command::Detach => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            let kept = sess.dormant();
             sess.detach().await?;
             *session = None;
+            logging::end_session_log();
 
-            Ok(json!({ "status": "detached" }))
+            let resumable = kept.is_some();
+            if kept.is_some() {
+                *dormant = kept;
+            }
+            Ok(json!({ "status": "detached", "resumable": resumable }))
         }
 
         Command::Stop => {
-            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
-            sess.stop().await?;
-            *session = None;
+            // Stopping twice is not an error: there is just nothing to stop
+            let Some(mut sess) = session.take() else {
+                return Ok(json!({ "status": "not_running" }));
+            };
+            // A failed stop still ends the session, and with it its log
+            let stopped = sess.stop().await;
+            logging::end_session_log();
+            stopped?;
 
             Ok(json!({ "status": "stopped" }))
         }
 
+        Command::Reset => {
+            // Breakpoints, watchpoints and thread/frame state all live in the
+            // session (or what a detach kept of it), so dropping it leaves
+            // nothing behind. A failed stop still drops it: the point is a
+            // clean slate.
+            *dormant = None;
+            let stopped_session = match session.take() {
+                Some(mut sess) => {
+                    if let Err(e) = sess.stop().await {
+                        tracing::warn!("Error stopping session for reset: {}", e);
+                    }
+                    logging::end_session_log();
+                    true
+                }
+                None => false,
+            };
+
+            Ok(json!({ "status": "reset", "stopped_session": stopped_session }))
+        }
+
         Command::Restart => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
-
-            // Check if adapter supports restart
-            if sess.capabilities().supports_restart_request {
-                sess.restart().await?;
-                Ok(json!({ "status": "restarted" }))
-            } else {
-                // Return helpful error message
-                Err(Error::Internal(
-                    "Debug adapter does not support restart. Use 'debugger stop' then 'debugger start' manually.".to_string()
-                ))
-            }
+            sess.restart().await?;
+            Ok(json!({ "status": "restarted" }))
         }
 
         Command::Status => {
@@ -131,32 +238,40 @@ async fn handle_command_inner(
             location,
             condition,
             hit_count,
+            on_hit,
         } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
 
-            // Check capabilities before using advanced features
-            if matches!(location, BreakpointLocation::Function { .. })
-                && !sess.supports_function_breakpoints()
-            {
-                return Err(Error::Internal(
-                    "Debug adapter does not support function breakpoints. Use file:line format instead."
-                        .to_string(),
-                ));
-            }
-
-            if condition.is_some() && !sess.supports_conditional_breakpoints() {
-                return Err(Error::Internal(
-                    "Debug adapter does not support conditional breakpoints.".to_string()
-                ));
+            // Reject malformed on-hit commands now rather than at every hit,
+            // and any that would do more than look at the stop: they run
+            // while the session is still handling it
+            for command in &on_hit {
+                if !inspects_only(&parse_command(command)?) {
+                    return Err(Error::Config(format!(
+                        "'{}' can't run on a breakpoint hit; on-hit commands may only \
+                         inspect the stop (print, eval, locals, backtrace, context, memory, \
+                         registers, watch list, ...)",
+                        command
+                    )));
+                }
             }
 
-            if hit_count.is_some() && !sess.supports_hit_conditional_breakpoints() {
-                return Err(Error::Internal(
-                    "Debug adapter does not support hit count conditions.".to_string()
-                ));
-            }
+            // Try the condition once so a syntax error shows up now rather than as
+            // an adapter-specific failure at every hit. It may also fail only
+            // because its variables are out of scope here, so it is a warning.
+            let warning = match &condition {
+                Some(condition) if sess.state() == SessionState::Stopped => sess
+                    .evaluate(condition, None, "watch")
+                    .await
+                    .err()
+                    .map(|e| format!("condition failed to evaluate at the current stop: {}", e)),
+                _ => None,
+            };
 
-            let info = sess.add_breakpoint(location, condition, hit_count).await?;
+            let mut info = sess
+                .add_breakpoint(location, condition, hit_count, on_hit)
+                .await?;
+            info.warning = warning;
             Ok(serde_json::to_value(info)?)
         }
 
@@ -182,6 +297,11 @@ async fn handle_command_inner(
             Ok(json!({ "breakpoints": breakpoints }))
         }
 
+        Command::BreakpointInfo { id } => {
+            let sess = session.as_ref().ok_or(Error::SessionNotActive)?;
+            Ok(serde_json::to_value(sess.breakpoint_details(id)?)?)
+        }
+
         Command::BreakpointEnable { id } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
             sess.enable_breakpoint(id).await?;
@@ -194,28 +314,51 @@ async fn handle_command_inner(
             Ok(json!({ "disabled": id }))
         }
 
+        // === Watches ===
+        Command::WatchAdd {
+            expression,
+            function,
+        } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            Ok(serde_json::to_value(sess.add_watch(expression, function))?)
+        }
+
+        Command::WatchRemove { id } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            sess.remove_watch(id)?;
+            Ok(match id {
+                Some(id) => json!({ "removed": id }),
+                None => json!({ "removed": "all" }),
+            })
+        }
+
+        Command::WatchList => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            Ok(json!({ "watches": sess.watches() }))
+        }
+
         // === Execution Control ===
-        Command::Continue => {
+        Command::Continue { this_thread } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
-            sess.continue_execution().await?;
-            Ok(json!({ "status": "running" }))
+            let all_threads_continued = sess.continue_execution(this_thread).await?;
+            Ok(json!({ "status": "running", "all_threads_continued": all_threads_continued }))
         }
 
-        Command::Next => {
+        Command::Next { this_thread } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
-            sess.next().await?;
+            sess.next(this_thread).await?;
             Ok(json!({ "status": "stepping" }))
         }
 
-        Command::StepIn => {
+        Command::StepIn { this_thread } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
-            sess.step_in().await?;
+            sess.step_in(this_thread).await?;
             Ok(json!({ "status": "stepping" }))
         }
 
-        Command::StepOut => {
+        Command::StepOut { this_thread } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
-            sess.step_out().await?;
+            sess.step_out(this_thread).await?;
             Ok(json!({ "status": "stepping" }))
         }
 
@@ -226,47 +369,58 @@ async fn handle_command_inner(
         }
 
         // === State Inspection ===
-        Command::StackTrace { thread_id, limit } => {
+        Command::StackTrace {
+            thread_id,
+            limit,
+            user_only,
+        } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
             let frames = sess.stack_trace(thread_id, limit).await?;
+            let roots = sess.user_code_roots();
 
             let frame_infos: Vec<StackFrameInfo> = frames
                 .iter()
-                .map(|f| StackFrameInfo {
-                    id: f.id,
-                    name: f.name.clone(),
-                    source: f.source.as_ref().and_then(|s| s.path.clone()),
-                    line: Some(f.line),
-                    column: Some(f.column),
+                .enumerate()
+                .filter(|(_, f)| {
+                    !user_only || is_user_frame(f, &roots, &config.backtrace.hide_paths)
                 })
+                .map(|(index, f)| frame_info(f, index))
                 .collect();
 
-            Ok(json!({ "frames": frame_infos }))
+            let hidden = frames.len() - frame_infos.len();
+            Ok(json!({ "frames": frame_infos, "hidden": hidden }))
         }
 
-        Command::Locals { frame_id } => {
+        Command::Locals {
+            frame_id,
+            frame_index,
+            max_string_length,
+            no_truncate,
+            no_cache,
+        } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
-            let vars = sess.get_locals(frame_id).await?;
+            if no_cache {
+                sess.clear_variable_cache();
+            }
+            let frame_id = resolve_frame(sess, frame_id, frame_index).await?;
+            let locals = sess.get_locals(frame_id).await?;
 
-            let var_infos: Vec<VariableInfo> = vars
-                .iter()
-                .map(|v| VariableInfo {
-                    name: v.name.clone(),
-                    value: v.value.clone(),
-                    type_name: v.type_name.clone(),
-                    variables_reference: v.variables_reference,
-                })
-                .collect();
+            let limits = ValueLimits::new(config, max_string_length, no_truncate);
+            let var_infos = local_infos(&locals, limits);
 
-            Ok(json!({ "variables": var_infos }))
+            Ok(json!({ "variables": var_infos, "note": locals.note() }))
         }
 
         Command::Evaluate {
             expression,
             frame_id,
+            frame_index,
             context,
+            max_string_length,
+            no_truncate,
         } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            let frame_id = resolve_frame(sess, frame_id, frame_index).await?;
             let ctx_str = match context {
                 EvaluateContext::Watch => "watch",
                 EvaluateContext::Repl => "repl",
@@ -275,12 +429,68 @@ async fn handle_command_inner(
             let result = sess.evaluate(&expression, frame_id, ctx_str).await?;
 
             Ok(serde_json::to_value(EvaluateResult {
-                result: result.result,
+                result: ValueLimits::new(config, max_string_length, no_truncate)
+                    .apply(&result.result, result.type_name.as_deref()),
                 type_name: result.type_name,
                 variables_reference: result.variables_reference,
             })?)
         }
 
+        Command::Assign {
+            target,
+            value,
+            frame_index,
+        } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            let frame_id = resolve_frame(sess, None, frame_index).await?;
+            let result = sess.assign(&target, &value, frame_id).await?;
+
+            Ok(serde_json::to_value(EvaluateResult {
+                result: ValueLimits::new(config, None, false)
+                    .apply(&result.value, result.type_name.as_deref()),
+                type_name: result.type_name,
+                variables_reference: result.variables_reference,
+            })?)
+        }
+
+        Command::EvaluateAll { expression, limit } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            let frames = sess.stack_trace(None, limit).await?;
+
+            let limits = ValueLimits::new(config, None, false);
+            let mut evaluations = Vec::with_capacity(frames.len());
+            for (index, frame) in frames.iter().enumerate() {
+                // Failing in a frame where the names don't exist is the
+                // expected case, so it is reported rather than returned; a
+                // hung or cancelled evaluation would only repeat per frame
+                let (result, type_name, error) =
+                    match sess.evaluate(&expression, Some(frame.id), "watch").await {
+                        Ok(eval) => (
+                            Some(limits.apply(&eval.result, eval.type_name.as_deref())),
+                            eval.type_name,
+                            None,
+                        ),
+                        Err(
+                            e @ (Error::ProgramExited { .. }
+                            | Error::RequestCancelled(_)
+                            | Error::Timeout(_)),
+                        ) => return Err(e),
+                        Err(e) => (None, None, Some(e.to_string())),
+                    };
+                evaluations.push(FrameEvaluation {
+                    index,
+                    function: frame.name.clone(),
+                    source: frame.source.as_ref().and_then(|s| s.path.clone()),
+                    line: Some(frame.line),
+                    result,
+                    type_name,
+                    error,
+                });
+            }
+
+            Ok(json!({ "expression": expression, "frames": evaluations }))
+        }
+
         Command::Scopes { frame_id } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
             let scopes = sess.get_scopes(Some(frame_id)).await?;
@@ -293,24 +503,78 @@ async fn handle_command_inner(
 
             let var_infos: Vec<VariableInfo> = vars
                 .iter()
-                .map(|v| VariableInfo {
-                    name: v.name.clone(),
-                    value: v.value.clone(),
-                    type_name: v.type_name.clone(),
-                    variables_reference: v.variables_reference,
-                })
+                .map(|v| variable_info(v, ValueLimits::new(config, None, false)))
                 .collect();
 
             Ok(json!({ "variables": var_infos }))
         }
 
+        Command::ReadMemory { address, count } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            let result = sess.read_memory(&address, count).await?;
+            Ok(serde_json::to_value(result)?)
+        }
+
+        Command::ReadVariableMemory { name, count } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            let reference = sess.variable_memory_reference(&name).await?;
+            let result = sess.read_memory(&reference, count).await?;
+            Ok(serde_json::to_value(result)?)
+        }
+
+        Command::Disassemble {
+            address,
+            count,
+            source,
+        } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            let raw = sess.disassemble(address.as_deref(), count).await?;
+
+            // Adapters may omit an instruction's location when it matches the
+            // previous instruction, so carry the last one forward.
+            let mut current_source: Option<String> = None;
+            let mut instructions: Vec<InstructionInfo> = raw
+                .into_iter()
+                .map(|insn| {
+                    if let Some(path) = insn.location.as_ref().and_then(|s| s.path.clone()) {
+                        current_source = Some(path);
+                    }
+                    InstructionInfo {
+                        address: insn.address,
+                        instruction: insn.instruction,
+                        bytes: insn.instruction_bytes,
+                        symbol: insn.symbol,
+                        source: insn.line.and(current_source.clone()),
+                        line: insn.line,
+                        source_text: None,
+                    }
+                })
+                .collect();
+
+            if source {
+                attach_source_text(&mut instructions);
+            }
+
+            Ok(json!({ "instructions": instructions }))
+        }
+
+        Command::Registers => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            Ok(serde_json::to_value(sess.registers().await?)?)
+        }
+
         // === Thread/Frame Management ===
-        Command::Threads => {
+        Command::Threads {
+            limit,
+            filter,
+            refresh,
+        } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
-            let threads = sess.get_threads().await?;
+            let threads = sess.get_threads(refresh).await?;
 
-            let thread_infos: Vec<ThreadInfo> = threads
-                .iter()
+            let (shown, total) = select_threads(&threads, filter.as_deref(), limit);
+            let thread_infos: Vec<ThreadInfo> = shown
+                .into_iter()
                 .map(|t| ThreadInfo {
                     id: t.id,
                     name: t.name.clone(),
@@ -318,7 +582,33 @@ async fn handle_command_inner(
                 })
                 .collect();
 
-            Ok(json!({ "threads": thread_infos }))
+            Ok(json!({
+                "truncated": thread_infos.len() < total,
+                "total": total,
+                "threads": thread_infos,
+            }))
+        }
+
+        Command::AnalyzeDeadlock { frames } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            let mut stacks = Vec::new();
+            let mut skipped = Vec::new();
+            for (thread, frames) in sess.all_thread_stacks(frames).await? {
+                match frames {
+                    Ok(frames) => stacks.push((thread, frames)),
+                    Err(e) => {
+                        tracing::debug!(thread = thread.id, "No stack to analyze: {}", e);
+                        skipped.push(thread.id);
+                    }
+                }
+            }
+            let report = deadlock::analyze(&stacks, skipped, &config.analyze.wait_functions);
+            Ok(serde_json::to_value(report)?)
+        }
+
+        Command::Modules => {
+            let sess = session.as_ref().ok_or(Error::SessionNotActive)?;
+            Ok(json!({ "modules": sess.modules() }))
         }
 
         Command::ThreadSelect { id } => {
@@ -327,6 +617,12 @@ async fn handle_command_inner(
             Ok(json!({ "selected": id }))
         }
 
+        Command::TerminateThreads { ids } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            sess.terminate_threads(&ids).await?;
+            Ok(json!({ "terminated": ids }))
+        }
+
         Command::FrameSelect { number } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
             let frame = sess.select_frame(number).await?;
@@ -354,43 +650,70 @@ async fn handle_command_inner(
             // Preserve the frame selected by `frame`, `up`, or `down`.
             let frame_index = sess.get_current_frame_index();
             let frame = sess.select_frame(frame_index).await?;
+            let thread_id = sess.stopped_thread().unwrap_or(1);
 
-            // Read source file
-            let source_path = frame
-                .source
-                .as_ref()
-                .and_then(|s| s.path.as_ref())
-                .ok_or_else(|| Error::Internal("No source file available".to_string()))?;
-
-            let source_lines = read_source_context(source_path, frame.line, lines)?;
+            let result = frame_context(sess, &frame, thread_id, lines, config).await?;
+            Ok(serde_json::to_value(result)?)
+        }
 
-            // Get locals
-            let vars = sess.get_locals(Some(frame.id)).await.unwrap_or_default();
-            let locals: Vec<VariableInfo> = vars
-                .iter()
-                .map(|v| VariableInfo {
-                    name: v.name.clone(),
-                    value: v.value.clone(),
-                    type_name: v.type_name.clone(),
-                    variables_reference: v.variables_reference,
-                })
-                .collect();
+        Command::Triage { limit, lines } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            if sess.state() != SessionState::Stopped {
+                return Err(Error::invalid_state("triage", &sess.state().to_string()));
+            }
 
-            let result = ContextResult {
-                thread_id: sess.stopped_thread().unwrap_or(1),
-                source: Some(source_path.clone()),
-                line: frame.line,
-                column: Some(frame.column),
-                function: Some(frame.name.clone()),
-                source_lines,
-                locals,
+            // Everything comes from the thread that stopped and its top frame,
+            // whichever thread or frame has been selected since. Parts that
+            // fail are left out with a note, so a crash still gets whatever
+            // could be gathered.
+            let snapshot = actor::snapshot_of(sess);
+            let mut notes = Vec::new();
+            let frames = match sess.stack_trace(sess.stopped_thread(), limit).await {
+                Ok(frames) => frames,
+                Err(e) => {
+                    notes.push(format!("No backtrace: {}", e));
+                    Vec::new()
+                }
+            };
+            let frame_infos: Vec<StackFrameInfo> =
+                frames.iter().enumerate().map(|(index, f)| frame_info(f, index)).collect();
+
+            let location = frame_infos.first().map(StopLocation::of).unwrap_or_default();
+            let stop = stop_result_at(&snapshot, location);
+
+            let context = match frames.first() {
+                Some(top) => {
+                    let thread_id = stop.thread_id.unwrap_or(1);
+                    match frame_context(sess, top, thread_id, lines, config).await {
+                        Ok(context) => Some(context),
+                        Err(e) => {
+                            notes.push(format!("No context: {}", e));
+                            None
+                        }
+                    }
+                }
+                None => None,
             };
 
-            Ok(serde_json::to_value(result)?)
+            Ok(serde_json::to_value(TriageResult {
+                stop,
+                frames: frame_infos,
+                context,
+                notes,
+            })?)
         }
 
         // === Async ===
-        Command::Await { .. } => {
+        Command::Await { .. }
+        | Command::AwaitNotify { .. }
+        | Command::StepCount { .. }
+        | Command::StepUntil { .. }
+        | Command::CancelAwait
+        | Command::CancelEval
+        | Command::Progress
+        | Command::ProbeAdapter { .. }
+        | Command::FollowOutput { .. }
+        | Command::FollowEvents { .. } => {
             // Await is handled by the connection task in the server, which
             // waits on state snapshots so it never occupies the session actor.
             // Reaching this arm means a bug in command routing.
@@ -400,6 +723,16 @@ async fn handle_command_inner(
         }
 
         // === Output ===
+        Command::SetOutputLimits {
+            max_bytes_mb,
+            max_events,
+        } => {
+            let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
+            let max_bytes = max_bytes_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+            sess.set_output_limits(max_events, max_bytes);
+            Ok(serde_json::to_value(sess.output_limits())?)
+        }
+
         Command::GetOutput { tail, clear } => {
             let sess = session.as_mut().ok_or(Error::SessionNotActive)?;
             // Make output visible immediately instead of waiting for the daemon's
@@ -424,10 +757,16 @@ async fn handle_command_inner(
                 })
                 .collect();
 
+            // Lets a client following output stop once the program is gone
+            // instead of polling forever
+            let session_ended = (sess.state() == SessionState::Exited)
+                .then(|| json!({ "exit_code": sess.exit_code() }));
+
             Ok(json!({
                 "output": output,
                 "count": events.len(),
                 "events": event_details,
+                "session_ended": session_ended,
             }))
         }
 
@@ -439,28 +778,400 @@ async fn handle_command_inner(
     }
 }
 
-/// Create a JSON response for frame navigation commands
-fn create_frame_response(frame: &crate::dap::StackFrame, index: usize) -> serde_json::Value {
-    let frame_info = StackFrameInfo {
-        id: frame.id,
-        name: frame.name.clone(),
-        source: frame.source.as_ref().and_then(|s| s.path.clone()),
-        line: Some(frame.line),
-        column: Some(frame.column),
+/// Run the on-hit commands of the breakpoints that caused the current stop.
+///
+/// Execution stops early if a command resumes the program, since the
+/// remaining commands would no longer see the stop they were written for.
+pub async fn run_on_hit_commands(
+    session: &mut Option<DebugSession>,
+    dormant: &mut Option<DormantSession>,
+    config: &Config,
+    commands: Vec<String>,
+) -> Vec<OnHitOutput> {
+    let mut output = Vec::with_capacity(commands.len());
+
+    for command in commands {
+        let result = match parse_command(&command) {
+            Ok(parsed) => handle_command_inner(session, dormant, config, parsed).await,
+            Err(e) => Err(e),
+        };
+        output.push(match result {
+            Ok(value) => OnHitOutput {
+                command,
+                result: Some(value),
+                error: None,
+            },
+            Err(e) => OnHitOutput {
+                command,
+                result: None,
+                error: Some(e.to_string()),
+            },
+        });
+
+        if session.as_ref().map(|s| s.state()) != Some(SessionState::Stopped) {
+            break;
+        }
+    }
+
+    output
+}
+
+/// Bytes of a value shown with `--no-truncate`, so one huge value still fits
+/// in an IPC message
+const NO_TRUNCATE_MAX_BYTES: usize = 1024 * 1024;
+
+/// Bytes of all the values of a `--no-truncate` response together. The IPC
+/// message limit is 10 MiB; the rest is left for JSON escaping and for the
+/// names, types and truncation notes around the values.
+const NO_TRUNCATE_TOTAL_BYTES: usize = 4 * 1024 * 1024;
+
+/// Caps on a rendered evaluate result or variable value
+#[derive(Debug, Clone, Copy)]
+struct ValueLimits {
+    /// Characters of a string value (0 = unlimited)
+    string_chars: usize,
+    /// Bytes of any value (0 = unlimited)
+    bytes: usize,
+    /// Bytes of all the values in one response (0 = unlimited)
+    total_bytes: usize,
+}
+
+impl ValueLimits {
+    /// The configured limits, with a command's own string length taking
+    /// precedence, or only the hard ceiling when `no_truncate` is set
+    fn new(config: &Config, max_string_length: Option<usize>, no_truncate: bool) -> Self {
+        if no_truncate {
+            return Self {
+                string_chars: 0,
+                bytes: NO_TRUNCATE_MAX_BYTES,
+                total_bytes: NO_TRUNCATE_TOTAL_BYTES,
+            };
+        }
+        Self {
+            string_chars: max_string_length.unwrap_or(config.output.max_string_length),
+            bytes: config.output.max_eval_result_bytes,
+            total_bytes: 0,
+        }
+    }
+
+    /// These limits for a value when `remaining` bytes of the response's
+    /// total are left. A value past the total keeps a byte, so it still
+    /// says how much was cut.
+    fn within(self, remaining: usize) -> Self {
+        if self.total_bytes == 0 {
+            return self;
+        }
+        Self {
+            bytes: self.bytes.min(remaining).max(1),
+            ..self
+        }
+    }
+
+    fn apply(self, value: &str, type_name: Option<&str>) -> String {
+        let value = if is_string_value(value, type_name) {
+            truncate_string(value, self.string_chars)
+        } else {
+            value.to_string()
+        };
+        truncate_value(&value, self.bytes)
+    }
+}
+
+/// Whether `command` can run with no debug session: the commands that create,
+/// end or report on one, and those the connection layer serves itself
+fn works_without_session(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Start { .. }
+            | Command::Attach { .. }
+            | Command::Reattach { .. }
+            | Command::Stop
+            | Command::Reset
+            | Command::ProbeAdapter { .. }
+            | Command::Status
+            | Command::Shutdown
+            | Command::Await { .. }
+            | Command::AwaitNotify { .. }
+            | Command::StepCount { .. }
+            | Command::StepUntil { .. }
+            | Command::CancelAwait
+            | Command::CancelEval
+            | Command::Progress
+            | Command::FollowOutput { .. }
+            | Command::FollowEvents { .. }
+    )
+}
+
+/// Whether `command` only reads the state of a stop, so it can run as an
+/// on-hit command without resuming, ending or reselecting anything
+fn inspects_only(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Status
+            | Command::BreakpointList
+            | Command::BreakpointInfo { .. }
+            | Command::WatchList
+            | Command::StackTrace { .. }
+            | Command::Locals { .. }
+            | Command::Evaluate { .. }
+            | Command::EvaluateAll { .. }
+            | Command::Scopes { .. }
+            | Command::Variables { .. }
+            | Command::ReadMemory { .. }
+            | Command::ReadVariableMemory { .. }
+            | Command::Disassemble { .. }
+            | Command::Registers
+            | Command::Modules
+            | Command::Threads { .. }
+            | Command::AnalyzeDeadlock { .. }
+            | Command::Context { .. }
+            | Command::Triage { .. }
+    )
+}
+
+/// Fail `command` up front if it needs a capability the session's adapter
+/// didn't report, rather than letting the request fail opaquely
+fn check_capabilities(command: &Command, sess: &DebugSession) -> Result<()> {
+    match missing_capability(command, sess.capabilities()) {
+        Some((feature, hint)) => Err(Error::unsupported(sess.adapter_name(), feature, hint)),
+        None => Ok(()),
+    }
+}
+
+/// The feature `command` needs that `capabilities` lack, with a hint at what
+/// to do instead
+fn missing_capability(
+    command: &Command,
+    capabilities: &Capabilities,
+) -> Option<(&'static str, Option<&'static str>)> {
+    let resumes_one_thread = match command {
+        Command::Continue { this_thread }
+        | Command::Next { this_thread }
+        | Command::StepIn { this_thread }
+        | Command::StepOut { this_thread } => *this_thread,
+        _ => false,
     };
+    match command {
+        Command::Restart if !capabilities.supports_restart_request => Some((
+            "restart",
+            Some("Use 'debugger stop' then 'debugger start' instead"),
+        )),
+        Command::BreakpointAdd {
+            location: BreakpointLocation::Function { .. },
+            ..
+        } if !capabilities.supports_function_breakpoints => {
+            Some(("function breakpoints", Some("Use file:line format instead")))
+        }
+        Command::BreakpointAdd {
+            location: BreakpointLocation::Address { .. },
+            ..
+        } if !capabilities.supports_instruction_breakpoints => {
+            Some(("instruction breakpoints", Some("Use file:line format instead")))
+        }
+        Command::BreakpointAdd {
+            condition: Some(_), ..
+        } if !capabilities.supports_conditional_breakpoints => {
+            Some(("conditional breakpoints", None))
+        }
+        Command::BreakpointAdd {
+            hit_count: Some(_), ..
+        } if !capabilities.supports_hit_conditional_breakpoints => {
+            Some(("hit count conditions", None))
+        }
+        Command::ReadMemory { .. } | Command::ReadVariableMemory { .. }
+            if !capabilities.supports_read_memory_request =>
+        {
+            Some(("reading memory", None))
+        }
+        Command::Disassemble { .. } if !capabilities.supports_disassemble_request => {
+            Some(("disassembly", None))
+        }
+        Command::Evaluate {
+            context: EvaluateContext::Hover,
+            ..
+        } if !capabilities.supports_evaluate_for_hovers => {
+            Some(("hover evaluation", Some("Use 'print' instead")))
+        }
+        Command::Assign { .. }
+            if !capabilities.supports_set_expression && !capabilities.supports_set_variable =>
+        {
+            Some(("assigning variables", Some("Try 'debugger eval \"x = 5\"' instead")))
+        }
+        Command::TerminateThreads { .. } if !capabilities.supports_terminate_threads_request => {
+            Some((
+                "terminating threads",
+                Some("Only the whole process can be stopped, with 'debugger stop'"),
+            ))
+        }
+        _ if resumes_one_thread && !capabilities.supports_single_thread_execution_requests => {
+            Some(("running a single thread", Some("Run without --this-thread")))
+        }
+        _ => None,
+    }
+}
+
+/// Validate a `--keep-alive` override of the idle timeout
+fn keep_alive(minutes: Option<u64>) -> Result<Option<std::time::Duration>> {
+    let Some(minutes) = minutes else {
+        return Ok(None);
+    };
+    if minutes == 0 {
+        return Err(Error::Config("--keep-alive must be at least 1 minute".to_string()));
+    }
+    let secs = minutes.checked_mul(60).ok_or_else(|| {
+        Error::Config(format!("--keep-alive of {} minutes is too long", minutes))
+    })?;
+    Ok(Some(std::time::Duration::from_secs(secs)))
+}
+
+/// Convert an adapter variable, capping its value at `limits`
+fn variable_info(var: &crate::dap::Variable, limits: ValueLimits) -> VariableInfo {
+    VariableInfo {
+        name: var.name.clone(),
+        value: limits.apply(&var.value, var.type_name.as_deref()),
+        type_name: var.type_name.clone(),
+        variables_reference: var.variables_reference,
+        presentation_hint: var.presentation_hint.as_ref().map(|hint| PresentationHint {
+            kind: hint.kind.clone(),
+            attributes: hint.attributes.clone(),
+            visibility: hint.visibility.clone(),
+        }),
+        elements: None,
+        memory_reference: var.memory_reference.clone(),
+    }
+}
+
+/// Convert a frame's locals, with the element summaries of large containers
+fn local_infos(locals: &Locals, limits: ValueLimits) -> Vec<VariableInfo> {
+    let mut remaining = limits.total_bytes;
+    locals
+        .variables
+        .iter()
+        .zip(&locals.elements)
+        .map(|(var, elements)| {
+            let value = variable_info(var, limits.within(remaining));
+            remaining = remaining.saturating_sub(value.value.len());
+            let elements = elements
+                .as_deref()
+                .map(|e| truncate_value(e, limits.within(remaining).bytes));
+            remaining = remaining.saturating_sub(elements.as_ref().map_or(0, String::len));
+            VariableInfo { elements, ..value }
+        })
+        .collect()
+}
+
+/// Cap an evaluate result or variable value at `max_bytes` (0 = unlimited),
+/// noting the original size so the reader knows data is missing.
+fn truncate_value(value: &str, max_bytes: usize) -> String {
+    if max_bytes == 0 || value.len() <= max_bytes {
+        return value.to_string();
+    }
+    format!(
+        "{}... (truncated, {} bytes)",
+        truncate_utf8_to_bytes(value, max_bytes),
+        value.len()
+    )
+}
+
+/// Whether a value is a string, by its type or, for adapters without
+/// types, by its quotes. Only strings are shortened: cutting a number or a
+/// struct summary would change what it says, not just its length.
+fn is_string_value(value: &str, type_name: Option<&str>) -> bool {
+    let string_type = type_name.is_some_and(|t| {
+        let t = t.trim_start_matches('&').to_ascii_lowercase();
+        t == "str"
+            || t.ends_with(" str")
+            || t.ends_with("string")
+            || t.contains("basic_string<")
+            || t.contains("char *")
+            || t.contains("char*")
+    });
+    string_type || value.starts_with('"') || value.starts_with('\'')
+}
 
+/// Keep the first `max_chars` characters of a string value (0 = unlimited),
+/// noting its full length. A quoted string keeps its closing quote after
+/// the ellipsis, so it still reads as a string.
+fn truncate_string(value: &str, max_chars: usize) -> String {
+    let length = value.chars().count();
+    if max_chars == 0 || length <= max_chars {
+        return value.to_string();
+    }
+    let kept: String = value.chars().take(max_chars).collect();
+    let closing = value
+        .chars()
+        .last()
+        .filter(|quote| matches!(quote, '"' | '\'') && kept.contains(*quote));
+    match closing {
+        Some(quote) => format!("{}...{} ({} chars)", kept, quote, length),
+        None => format!("{}... ({} chars)", kept, length),
+    }
+}
+
+/// The frame a command targets: one given by index, else by ID, else
+/// (`None`) the selected frame
+async fn resolve_frame(
+    sess: &mut DebugSession,
+    frame_id: Option<i64>,
+    frame_index: Option<usize>,
+) -> Result<Option<i64>> {
+    match frame_index {
+        Some(index) => Ok(Some(sess.frame_at(index).await?.id)),
+        None => Ok(frame_id),
+    }
+}
+
+/// Create a JSON response for frame navigation commands
+fn create_frame_response(frame: &crate::dap::StackFrame, index: usize) -> serde_json::Value {
     json!({
         "selected": index,
-        "frame": frame_info
+        "frame": frame_info(frame, index)
     })
 }
 
-/// Read source file and return lines around the current position
-fn read_source_context(path: &str, current_line: u32, context: usize) -> Result<Vec<SourceLine>> {
-    let content = std::fs::read_to_string(path).map_err(|e| Error::FileRead {
+/// Read a source file reported by the adapter
+fn read_source_file(path: &str) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| Error::FileRead {
         path: path.to_string(),
         error: e.to_string(),
-    })?;
+    })
+}
+
+/// Source around `frame` and its locals, as `context` reports them
+async fn frame_context(
+    sess: &mut DebugSession,
+    frame: &StackFrame,
+    thread_id: i64,
+    lines: usize,
+    config: &Config,
+) -> Result<ContextResult> {
+    let source_path = frame
+        .source
+        .as_ref()
+        .and_then(|s| s.path.as_ref())
+        .ok_or_else(|| Error::Internal("No source file available".to_string()))?;
+
+    let source_lines = read_source_context(source_path, frame.line, lines)?;
+
+    let locals = match sess.get_locals(Some(frame.id)).await {
+        Ok(locals) => local_infos(&locals, ValueLimits::new(config, None, false)),
+        Err(_) => Vec::new(),
+    };
+
+    Ok(ContextResult {
+        thread_id,
+        source: Some(source_path.clone()),
+        line: frame.line,
+        column: Some(frame.column),
+        function: Some(frame.name.clone()),
+        source_lines,
+        locals,
+    })
+}
+
+/// Read source file and return lines around the current position
+fn read_source_context(path: &str, current_line: u32, context: usize) -> Result<Vec<SourceLine>> {
+    let content = read_source_file(path)?;
 
     let lines: Vec<&str> = content.lines().collect();
     if lines.is_empty() {
@@ -486,6 +1197,28 @@ fn read_source_context(path: &str, current_line: u32, context: usize) -> Result<
     Ok(result)
 }
 
+/// Fill in `source_text` for instructions with line info, reading each
+/// file once. Unreadable files are skipped rather than failing the listing.
+fn attach_source_text(instructions: &mut [InstructionInfo]) {
+    let mut files: std::collections::HashMap<String, Option<Vec<String>>> =
+        std::collections::HashMap::new();
+
+    for insn in instructions.iter_mut() {
+        let (Some(path), Some(line)) = (&insn.source, insn.line) else {
+            continue;
+        };
+        let lines = files.entry(path.clone()).or_insert_with(|| {
+            read_source_file(path)
+                .ok()
+                .map(|content| content.lines().map(str::to_string).collect())
+        });
+        insn.source_text = lines
+            .as_ref()
+            .and_then(|lines| lines.get((line as usize).checked_sub(1)?))
+            .cloned();
+    }
+}
+
 /// Return the last `line_count` lines while preserving a trailing newline.
 fn tail_output_lines(output: &str, line_count: usize) -> String {
     if line_count == 0 || output.is_empty() {
@@ -501,9 +1234,71 @@ fn tail_output_lines(output: &str, line_count: usize) -> String {
     result
 }
 
+/// The first `limit` of `threads` whose name contains `filter`, ignoring
+/// case, and how many matched in all
+fn select_threads<'a>(
+    threads: &'a [crate::dap::Thread],
+    filter: Option<&str>,
+    limit: Option<usize>,
+) -> (Vec<&'a crate::dap::Thread>, usize) {
+    let filter = filter.map(str::to_lowercase);
+    let matching: Vec<&crate::dap::Thread> = threads
+        .iter()
+        .filter(|thread| {
+            filter
+                .as_deref()
+                .is_none_or(|filter| thread.name.to_lowercase().contains(filter))
+        })
+        .collect();
+    let total = matching.len();
+    let shown = matching.into_iter().take(limit.unwrap_or(usize::MAX)).collect();
+    (shown, total)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::tail_output_lines;
+    use super::{
+        is_string_value, keep_alive, tail_output_lines, truncate_string, truncate_value,
+    };
+    use crate::common::Error;
+    use std::time::Duration;
+
+    #[test]
+    fn keep_alive_is_validated() {
+        assert_eq!(keep_alive(None).unwrap(), None);
+        assert_eq!(keep_alive(Some(2)).unwrap(), Some(Duration::from_secs(120)));
+        assert!(matches!(keep_alive(Some(0)), Err(Error::Config(_))));
+        assert!(matches!(keep_alive(Some(u64::MAX)), Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn only_string_values_are_shortened_to_a_length() {
+        assert!(is_string_value("\"hello\"", None));
+        assert!(is_string_value("'hello'", Some("str")));
+        assert!(is_string_value("0x4006f4 \"hello\"", Some("const char *")));
+        assert!(is_string_value("\"hi\"", Some("&'static str")));
+        assert!(is_string_value("size=5", Some("std::__cxx11::basic_string<char>")));
+        assert!(!is_string_value("{x:1}", Some("struct Point")));
+        assert!(!is_string_value("42", Some("int")));
+
+        assert_eq!(truncate_string("\"abcdef\"", 4), "\"abc...\" (8 chars)");
+        assert_eq!(truncate_string("\"ab\"", 4), "\"ab\"");
+        assert_eq!(truncate_string("\"\u{e9}\u{e9}\u{e9}\"", 2), "\"\u{e9}...\" (5 chars)");
+        assert_eq!(truncate_string("'abcdef'", 4), "'abc...' (8 chars)");
+        assert_eq!(truncate_string("0x4006f4 \"abcdef\"", 12), "0x4006f4 \"ab...\" (17 chars)");
+        assert_eq!(truncate_string("size=12345", 6), "size=1... (10 chars)");
+        assert_eq!(truncate_string("\"abcdef\"", 0), "\"abcdef\"");
+    }
+
+    #[test]
+    fn long_values_are_truncated_on_a_char_boundary() {
+        assert_eq!(truncate_value("short", 16), "short");
+        assert_eq!(truncate_value("unlimited", 0), "unlimited");
+        assert_eq!(
+            truncate_value("ab\u{e9}cd", 3),
+            "ab... (truncated, 6 bytes)"
+        );
+    }
 
     #[test]
     fn tail_output_is_line_based_across_dap_chunks() {
@@ -522,4 +1317,368 @@ mod tests {
         assert_eq!(context.len(), 2);
         assert!(!context.iter().any(|line| line.is_current));
     }
+
+    #[test]
+    fn disassembly_source_text_comes_from_the_instruction_line() {
+        let directory = tempfile::tempdir().unwrap();
+        let source = directory.path().join("loop.c");
+        std::fs::write(&source, "int main() {\n  return 0;\n}\n").unwrap();
+        let path = source.to_str().unwrap().to_string();
+
+        let instruction = |line: Option<u32>| super::InstructionInfo {
+            address: "0x1000".to_string(),
+            instruction: "nop".to_string(),
+            bytes: None,
+            symbol: None,
+            source: line.map(|_| path.clone()),
+            line,
+            source_text: None,
+        };
+        let mut instructions = vec![instruction(Some(2)), instruction(Some(40)), instruction(None)];
+
+        super::attach_source_text(&mut instructions);
+
+        assert_eq!(instructions[0].source_text.as_deref(), Some("  return 0;"));
+        assert!(instructions[1].source_text.is_none());
+        assert!(instructions[2].source_text.is_none());
+    }
+
+    #[tokio::test]
+    async fn on_hit_commands_may_only_inspect_the_stop() {
+        use super::super::mock_session::launch_on;
+        use crate::commands::parse::parse_command;
+        use crate::dap::mock::MockAdapter;
+
+        let adapter = MockAdapter::start().await;
+        let config = adapter.config();
+        let mut session = Some(launch_on(&adapter).await);
+
+        for on_hit in ["continue", "locals; stop", "await", "next"] {
+            let command = parse_command(&format!("break main.c:5 --on-hit \"{}\"", on_hit));
+            let error =
+                super::handle_command_inner(&mut session, &mut None, &config, command.unwrap())
+                    .await
+                    .unwrap_err();
+            assert!(matches!(error, Error::Config(_)), "{}: {}", on_hit, error);
+            assert!(error.to_string().contains("can't run on a breakpoint hit"), "{}", error);
+        }
+        assert_eq!(session.as_ref().unwrap().list_breakpoints().len(), 1);
+
+        let command = parse_command("break main.c:5 --on-hit \"locals; print x; backtrace\"");
+        super::handle_command_inner(&mut session, &mut None, &config, command.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(session.as_ref().unwrap().list_breakpoints().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn terminating_the_stopped_thread_moves_commands_to_another() {
+        use super::super::mock_session::{launch_on, process_until, stop_at_start};
+        use super::SessionState;
+        use crate::commands::parse::parse_command;
+        use crate::dap::mock::{Action, MockAdapter};
+        use crate::ipc::protocol::Command;
+        use serde_json::json;
+
+        // Thread 1 stops; thread 2 is all that's left once it's terminated
+        let adapter = MockAdapter::scripted(|connection, command, arguments| match command {
+            "threads" => Some(vec![Action::Respond(
+                json!({ "threads": [{ "id": 2, "name": "worker" }] }),
+            )]),
+            _ => stop_at_start(connection, command, arguments),
+        })
+        .await;
+        let config = adapter.config();
+        let mut session = Some(launch_on(&adapter).await);
+        let stopped = |session: &super::DebugSession| session.state() == SessionState::Stopped;
+        process_until(session.as_mut().unwrap(), stopped).await;
+
+        let commands = [
+            parse_command("backtrace").unwrap(),
+            Command::TerminateThreads { ids: vec![1] },
+            parse_command("backtrace").unwrap(),
+        ];
+        for command in commands {
+            super::handle_command_inner(&mut session, &mut None, &config, command)
+                .await
+                .unwrap();
+        }
+
+        let traced: Vec<_> = adapter
+            .received()
+            .into_iter()
+            .filter(|request| request.command == "stackTrace")
+            .map(|request| request.arguments["threadId"].clone())
+            .collect();
+        assert_eq!(traced, vec![json!(1), json!(2)]);
+    }
+
+    #[tokio::test]
+    async fn triage_reports_the_stopped_thread_whatever_is_selected() {
+        use super::super::mock_session::{launch_on, process_until, stop_at_start};
+        use super::SessionState;
+        use crate::commands::parse::parse_command;
+        use crate::dap::mock::{Action, MockAdapter};
+        use serde_json::json;
+
+        // Thread 1 faults; thread 2 sits idle and gets selected afterwards
+        let source = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/e2e/hello_world.c");
+        let adapter = MockAdapter::scripted(move |connection, command, arguments| {
+            match command {
+                "threads" => Some(vec![Action::Respond(json!({
+                    "threads": [{ "id": 1, "name": "main" }, { "id": 2, "name": "worker" }],
+                }))]),
+                "stackTrace" => {
+                    let (id, name) = match arguments["threadId"].as_i64() {
+                        Some(1) => (1000, "fault"),
+                        _ => (2000, "idle"),
+                    };
+                    Some(vec![Action::Respond(json!({
+                        "stackFrames": [{
+                            "id": id,
+                            "name": name,
+                            "line": 3,
+                            "column": 1,
+                            "source": { "path": source },
+                        }],
+                    }))])
+                }
+                _ => stop_at_start(connection, command, arguments),
+            }
+        })
+        .await;
+        let config = adapter.config();
+        let mut session = Some(launch_on(&adapter).await);
+        let stopped = |session: &super::DebugSession| session.state() == SessionState::Stopped;
+        process_until(session.as_mut().unwrap(), stopped).await;
+
+        let mut results = Vec::new();
+        for line in ["thread 2", "triage", "continue", "triage"] {
+            let command = parse_command(line).unwrap();
+            let mut dormant = None;
+            let result = super::handle_command_inner(&mut session, &mut dormant, &config, command);
+            results.push(result.await);
+        }
+
+        let triage = results[1].as_ref().unwrap();
+        assert_eq!(triage["stop"]["thread_id"], 1);
+        assert_eq!(triage["stop"]["function"], "fault");
+        assert_eq!(triage["frames"][0]["name"], "fault");
+        assert_eq!(triage["context"]["function"], "fault");
+        assert_eq!(triage["context"]["thread_id"], 1);
+        assert!(triage.get("notes").is_none());
+
+        // Once running there is nothing to triage
+        assert!(results[3].is_err());
+    }
+
+    #[test]
+    fn threads_are_filtered_by_name_then_limited() {
+        let thread = |id: i64, name: &str| crate::dap::Thread {
+            id,
+            name: name.to_string(),
+        };
+        let threads = vec![
+            thread(1, "main"),
+            thread(2, "tokio-worker-0"),
+            thread(3, "Tokio-Worker-1"),
+            thread(4, "tokio-worker-2"),
+        ];
+
+        let (shown, total) = super::select_threads(&threads, Some("WORKER"), Some(2));
+        assert_eq!(shown.iter().map(|t| t.id).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(total, 3);
+
+        let (shown, total) = super::select_threads(&threads, None, None);
+        assert_eq!((shown.len(), total), (4, 4));
+    }
+
+    #[tokio::test]
+    async fn commands_needing_a_session_are_refused_alike_without_one() {
+        use crate::commands::parse::parse_command;
+        use crate::common::{config::Config, Error};
+
+        let config = Config::default();
+        let texts = ["locals", "backtrace", "continue", "print x", "threads", "memory 0x0"];
+        for text in texts {
+            let command = parse_command(text).unwrap();
+            let result = super::handle_command_inner(&mut None, &mut None, &config, command).await;
+            assert!(matches!(result, Err(Error::SessionNotActive)), "{}", text);
+        }
+
+        let status = parse_command("status").unwrap();
+        let result = super::handle_command_inner(&mut None, &mut None, &config, status).await;
+        assert_eq!(result.unwrap()["session_active"], false);
+
+        let stop = parse_command("stop").unwrap();
+        let result = super::handle_command_inner(&mut None, &mut None, &config, stop).await;
+        assert_eq!(result.unwrap()["status"], "not_running");
+    }
+
+    #[test]
+    fn commands_needing_a_missing_capability_are_refused() {
+        use crate::ipc::protocol::Command;
+
+        let mut capabilities = crate::dap::Capabilities::default();
+        let restart = super::missing_capability(&Command::Restart, &capabilities);
+        assert_eq!(restart.map(|(feature, _)| feature), Some("restart"));
+        let one_thread = Command::Next { this_thread: true };
+        assert!(super::missing_capability(&one_thread, &capabilities).is_some());
+        let all_threads = Command::Next { this_thread: false };
+        assert!(super::missing_capability(&all_threads, &capabilities).is_none());
+
+        capabilities.supports_restart_request = true;
+        capabilities.supports_single_thread_execution_requests = true;
+        assert!(super::missing_capability(&Command::Restart, &capabilities).is_none());
+        assert!(super::missing_capability(&one_thread, &capabilities).is_none());
+    }
+
+    #[test]
+    fn no_truncate_lifts_the_configured_limits_up_to_the_ceiling() {
+        let mut config = crate::common::config::Config::default();
+        config.output.max_string_length = 4;
+        config.output.max_eval_result_bytes = 8;
+        let value = format!("\"{}\"", "a".repeat(20));
+
+        let limits = super::ValueLimits::new(&config, None, false);
+        assert_ne!(limits.apply(&value, Some("char *")), value);
+
+        let limits = super::ValueLimits::new(&config, None, true);
+        assert_eq!(limits.apply(&value, Some("char *")), value);
+
+        let huge = "x".repeat(super::NO_TRUNCATE_MAX_BYTES + 1);
+        assert!(limits.apply(&huge, None).ends_with("(truncated, 1048577 bytes)"));
+    }
+
+    #[test]
+    fn no_truncate_keeps_all_the_locals_within_a_total() {
+        let config = crate::common::config::Config::default();
+        let variable = |index: usize| crate::dap::Variable {
+            name: format!("buffer{}", index),
+            value: "x".repeat(super::NO_TRUNCATE_MAX_BYTES),
+            type_name: None,
+            variables_reference: 0,
+            presentation_hint: None,
+            indexed_variables: None,
+            memory_reference: None,
+        };
+        let count = 2 * super::NO_TRUNCATE_TOTAL_BYTES / super::NO_TRUNCATE_MAX_BYTES;
+        let locals = super::Locals {
+            variables: (0..count).map(variable).collect(),
+            has_scopes: true,
+            elements: vec![None; count],
+        };
+
+        let infos = super::local_infos(&locals, super::ValueLimits::new(&config, None, true));
+        let total: usize = infos.iter().map(|info| info.value.len()).sum();
+        assert!(total < super::NO_TRUNCATE_TOTAL_BYTES + 1024);
+        assert_eq!(infos[0].value.len(), super::NO_TRUNCATE_MAX_BYTES);
+        assert!(infos[count - 1].value.ends_with("(truncated, 1048576 bytes)"));
+    }
+
+    #[tokio::test]
+    async fn handler_results_match_their_output_schemas() {
+        use super::super::mock_session::{launch_on, process_until, stop_at_start};
+        use super::SessionState;
+        use crate::commands::parse::parse_command;
+        use crate::dap::mock::{Action, MockAdapter};
+        use crate::ipc::schema::check_output;
+        use serde_json::json;
+
+        let source = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/e2e/hello_world.c");
+        let adapter = MockAdapter::scripted(move |connection, command, arguments| {
+            if command != "stackTrace" {
+                return stop_at_start(connection, command, arguments);
+            }
+            Some(vec![Action::Respond(json!({
+                "stackFrames": [
+                    { "id": 1000, "name": "main", "line": 3, "column": 1,
+                      "source": { "path": source } },
+                ],
+            }))])
+        })
+        .await;
+        let config = adapter.config();
+        let mut session = Some(launch_on(&adapter).await);
+        let stopped = |session: &super::DebugSession| session.state() == SessionState::Stopped;
+        process_until(session.as_mut().unwrap(), stopped).await;
+
+        let lines = [
+            "status",
+            "break main.c:5",
+            "breakpoint list",
+            "breakpoint info 2",
+            "breakpoint disable 2",
+            "breakpoint enable 2",
+            "watch add x",
+            "watch list",
+            "backtrace",
+            "locals",
+            "print x",
+            "eval --assign x = 2",
+            "eval-all x",
+            "scopes 1000",
+            "variables 1",
+            "threads",
+            "thread 1",
+            "frame 0",
+            "context",
+            "triage",
+            "modules",
+            "output",
+            "output --set-limit-events 100",
+            "watch remove",
+            "breakpoint remove 2",
+            "continue",
+            "pause",
+            "stop",
+        ];
+        for line in lines {
+            let command = parse_command(line).unwrap();
+            let name = serde_json::to_value(&command).unwrap()["type"].clone();
+            let mut dormant = None;
+            let result = super::handle_command_inner(&mut session, &mut dormant, &config, command);
+            let value = result.await.unwrap_or_else(|e| panic!("{}: {}", line, e));
+            if let Err(mismatch) = check_output(name.as_str().unwrap(), &value) {
+                panic!("'{}' returned {}\n{}", line, value, mismatch);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn evaluating_in_every_frame_stops_at_the_first_timeout() {
+        use super::super::mock_session::{process_until, stop_at_start};
+        use super::SessionState;
+        use crate::commands::parse::parse_command;
+        use crate::dap::mock::{Action, MockAdapter};
+        use serde_json::json;
+
+        // Three frames, and an adapter that never answers an evaluation
+        let adapter = MockAdapter::scripted(|connection, command, arguments| match command {
+            "stackTrace" => {
+                let frames: Vec<_> = (0..3)
+                    .map(|index| json!({ "id": 1000 + index, "name": "f", "line": 1, "column": 1 }))
+                    .collect();
+                Some(vec![Action::Respond(json!({ "stackFrames": frames }))])
+            }
+            "evaluate" => Some(vec![]),
+            _ => stop_at_start(connection, command, arguments),
+        })
+        .await;
+        let mut config = adapter.config();
+        config.timeouts.eval_secs = 1;
+        let program = std::path::Path::new("/bin/app");
+        let adapter_name = Some("mock".to_string());
+        let launched =
+            super::DebugSession::launch(&config, program, vec![], adapter_name, false, vec![]);
+        let mut session = Some(launched.await.unwrap());
+        let stopped = |session: &super::DebugSession| session.state() == SessionState::Stopped;
+        process_until(session.as_mut().unwrap(), stopped).await;
+
+        let command = parse_command("eval-all x").unwrap();
+        let result = super::handle_command_inner(&mut session, &mut None, &config, command).await;
+        assert!(matches!(result, Err(super::Error::Timeout(_))), "{:?}", result);
+        let evaluations = adapter.received().iter().filter(|r| r.command == "evaluate").count();
+        assert_eq!(evaluations, 1);
+    }
 }
@@ -0,0 +1,350 @@
+//! HTTP front end for the daemon (`http` feature)
+//!
+//! A deliberately small HTTP/1.1 server: each `POST` carries one JSON-encoded
+//! `Command` and gets back the same result value the IPC path returns. Errors
+//! use the IPC error shape (`{"code", "message"}`) with a non-2xx status.
+//! Every connection handles a single request and is then closed.
+//!
+//! Since browsers can reach loopback addresses too, requests must look like
+//! they came from a local tool rather than a web page: a `Content-Type` of
+//! `application/json` (which a cross-site form can't send without a
+//! preflight), no `Origin` header, and a loopback `Host` so that a
+//! DNS-rebound name can't reach the daemon.
+
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::ipc::protocol::Command;
+
+use super::server::{self, Shared};
+
+/// Largest request head (request line plus headers) we accept
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Largest request body we accept
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Accept HTTP connections until the daemon shuts down.
+pub(super) async fn serve(listener: TcpListener, shared: Shared) {
+    if let Ok(addr) = listener.local_addr() {
+        if !addr.ip().is_loopback() {
+            tracing::warn!(
+                "HTTP front end is listening on non-loopback address {}; anyone who can reach it can control the debugger",
+                addr
+            );
+        }
+    }
+
+    let mut shutdown = shared.shutdown_signal();
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => {
+                if *shutdown.borrow() {
+                    break;
+                }
+            }
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    tracing::debug!("HTTP connection from {}", peer);
+                    tokio::spawn(handle_connection(stream, shared.clone()));
+                }
+                Err(e) => tracing::error!("HTTP accept error: {}", e),
+            },
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, shared: Shared) {
+    let (status, body, shutdown) =
+        match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+            Ok(Ok(request)) => respond(request, &shared).await,
+            Ok(Err(error)) => (error.status, error_body("INVALID_REQUEST", &error.message), false),
+            Err(_) => (408, error_body("INVALID_REQUEST", "request timed out"), false),
+        };
+
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason_phrase(status),
+        body.len(),
+        body
+    );
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        tracing::debug!("Failed to write HTTP response: {}", e);
+    }
+    let _ = stream.shutdown().await;
+
+    if shutdown {
+        shared.request_shutdown();
+    }
+}
+
+/// Run a parsed request, returning the status, JSON body and whether the
+/// daemon should shut down once the response is sent.
+async fn respond(request: HttpRequest, shared: &Shared) -> (u16, serde_json::Value, bool) {
+    if request.method != "POST" {
+        return (405, error_body("INVALID_REQUEST", "only POST is supported"), false);
+    }
+    if let Err(error) = check_request_is_local(&request) {
+        return (error.status, error_body("INVALID_REQUEST", &error.message), false);
+    }
+
+    let command: Command = match serde_json::from_slice(&request.body) {
+        Ok(command) => command,
+        Err(e) => return (400, error_body("INVALID_REQUEST", &e.to_string()), false),
+    };
+
+    let shutdown = matches!(command, Command::Shutdown);
+    let response = server::execute(0, command, shared).await;
+    if response.success {
+        (200, response.result.unwrap_or_else(|| json!({})), shutdown)
+    } else {
+        let error = response.error.map(|e| json!({ "code": e.code, "message": e.message }));
+        (500, error.unwrap_or_else(|| json!({})), false)
+    }
+}
+
+/// Refuse requests a web page could have made: see the module docs.
+fn check_request_is_local(request: &HttpRequest) -> Result<(), RequestError> {
+    if request.has_origin {
+        return Err(RequestError::new(403, "requests with an Origin header are refused"));
+    }
+    match request.host.as_deref() {
+        Some(host) if is_loopback_host(host) => {}
+        Some(host) => {
+            return Err(RequestError::new(403, format!("Host '{}' is not a loopback address", host)))
+        }
+        None => return Err(RequestError::new(400, "missing Host header")),
+    }
+    let is_json = request.content_type.as_deref().is_some_and(|content_type| {
+        let media_type = content_type.split(';').next().unwrap_or_default();
+        media_type.trim().eq_ignore_ascii_case("application/json")
+    });
+    if !is_json {
+        return Err(RequestError::new(415, "Content-Type must be application/json"));
+    }
+    Ok(())
+}
+
+/// Whether a `Host` header value names this machine
+fn is_loopback_host(host: &str) -> bool {
+    // Strip the port, taking care not to split an IPv6 literal
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+fn error_body(code: &str, message: &str) -> serde_json::Value {
+    json!({ "code": code, "message": message })
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    }
+}
+
+/// A received HTTP request
+struct HttpRequest {
+    method: String,
+    host: Option<String>,
+    content_type: Option<String>,
+    has_origin: bool,
+    body: Vec<u8>,
+}
+
+/// The parts of a request head we look at
+#[derive(Debug, Default)]
+struct RequestHead {
+    method: String,
+    content_length: usize,
+    host: Option<String>,
+    content_type: Option<String>,
+    has_origin: bool,
+}
+
+/// Why a request could not be read, with the status to answer it with
+#[derive(Debug)]
+struct RequestError {
+    status: u16,
+    message: String,
+}
+
+impl RequestError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<HttpRequest, RequestError> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let head_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buffer.len() > MAX_HEAD_BYTES {
+            return Err(RequestError::new(413, "request headers too large"));
+        }
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| RequestError::new(400, e.to_string()))?;
+        if read == 0 {
+            return Err(RequestError::new(400, "connection closed before end of headers"));
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = std::str::from_utf8(&buffer[..head_end])
+        .map_err(|_| RequestError::new(400, "request headers are not UTF-8"))?;
+    let head = parse_head(head)?;
+    let content_length = head.content_length;
+    if content_length > MAX_BODY_BYTES {
+        return Err(RequestError::new(413, "request body too large"));
+    }
+
+    let mut body = buffer.split_off(head_end + 4);
+    while body.len() < content_length {
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| RequestError::new(400, e.to_string()))?;
+        if read == 0 {
+            return Err(RequestError::new(400, "connection closed before end of body"));
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    Ok(HttpRequest {
+        method: head.method,
+        host: head.host,
+        content_type: head.content_type,
+        has_origin: head.has_origin,
+        body,
+    })
+}
+
+/// Parse the request line and headers.
+fn parse_head(head: &str) -> Result<RequestHead, RequestError> {
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    let method = request_line
+        .split_whitespace()
+        .next()
+        .filter(|method| !method.is_empty())
+        .ok_or_else(|| RequestError::new(400, "missing request line"))?;
+
+    let mut parsed = RequestHead {
+        method: method.to_string(),
+        ..Default::default()
+    };
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(RequestError::new(400, format!("malformed header: {}", line)));
+        };
+        let (name, value) = (name.trim(), value.trim());
+        if name.eq_ignore_ascii_case("content-length") {
+            parsed.content_length = value
+                .parse()
+                .map_err(|_| RequestError::new(400, "invalid Content-Length"))?;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(RequestError::new(400, "chunked request bodies are not supported"));
+        } else if name.eq_ignore_ascii_case("host") {
+            parsed.host = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-type") {
+            parsed.content_type = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("origin") {
+            parsed.has_origin = true;
+        }
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn head_parsing_reads_method_and_content_length() {
+        let head =
+            parse_head("POST / HTTP/1.1\r\nHost: localhost\r\ncontent-length: 20").unwrap();
+        assert_eq!(head.method, "POST");
+        assert_eq!(head.content_length, 20);
+        assert_eq!(head.host.as_deref(), Some("localhost"));
+
+        let head = parse_head("GET /status HTTP/1.1").unwrap();
+        assert_eq!(head.method, "GET");
+        assert_eq!(head.content_length, 0);
+
+        assert_eq!(parse_head("POST / HTTP/1.1\r\nbogus").unwrap_err().status, 400);
+        assert_eq!(
+            parse_head("POST / HTTP/1.1\r\nTransfer-Encoding: chunked")
+                .unwrap_err()
+                .status,
+            400
+        );
+    }
+
+    fn request(head: &str) -> HttpRequest {
+        let head = parse_head(head).unwrap();
+        HttpRequest {
+            method: head.method,
+            host: head.host,
+            content_type: head.content_type,
+            has_origin: head.has_origin,
+            body: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn only_local_json_requests_are_accepted() {
+        let status = |head: &str| check_request_is_local(&request(head)).map_err(|e| e.status);
+
+        let json = "Content-Type: application/json; charset=utf-8";
+        for host in ["127.0.0.1:8712", "localhost", "[::1]:8712", "LOCALHOST:80"] {
+            assert_eq!(status(&format!("POST / HTTP/1.1\r\nHost: {}\r\n{}", host, json)), Ok(()));
+        }
+
+        // DNS rebinding: the name resolved to loopback but isn't one
+        let rebound = format!("POST / HTTP/1.1\r\nHost: evil.example:8712\r\n{}", json);
+        assert_eq!(status(&rebound), Err(403));
+        assert_eq!(status(&format!("POST / HTTP/1.1\r\n{}", json)), Err(400));
+        // A page's fetch or form post
+        assert_eq!(
+            status(&format!(
+                "POST / HTTP/1.1\r\nHost: localhost\r\nOrigin: https://evil.example\r\n{}",
+                json
+            )),
+            Err(403)
+        );
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: text/plain"),
+            Err(415)
+        );
+        assert_eq!(status("POST / HTTP/1.1\r\nHost: localhost"), Err(415));
+    }
+}
@@ -0,0 +1,114 @@
+//! In-process sessions for `--no-daemon`
+//!
+//! Runs the session actor inside the CLI process and hands it commands
+//! directly, so a one-shot run needs no background daemon, socket or IPC.
+//! The session lives only as long as the `LocalSession`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::common::{config::Config, Result};
+use crate::ipc::protocol::Command;
+
+use super::server::{self, Shared};
+
+/// A debug session driven from the current process
+pub struct LocalSession {
+    shared: Shared,
+    actor_task: JoinHandle<()>,
+    next_id: u64,
+}
+
+impl LocalSession {
+    /// Start the session actor in this process
+    pub fn start() -> Result<Self> {
+        Ok(Self::with_config(Config::load()?))
+    }
+
+    /// Start the session actor with `config` in place of the user's
+    pub(crate) fn with_config(config: Config) -> Self {
+        let (shared, actor_task) = Shared::spawn(Arc::new(config));
+        Self {
+            shared,
+            actor_task,
+            next_id: 1,
+        }
+    }
+
+    /// Run a command, with the same semantics as sending it to the daemon
+    pub async fn send_command(&mut self, command: Command) -> Result<serde_json::Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        server::execute(id, command, &self.shared)
+            .await
+            .into_result()
+    }
+
+    /// Stop any running session and wait for the actor to finish
+    pub async fn shutdown(self) {
+        let Self {
+            shared, actor_task, ..
+        } = self;
+        drop(shared);
+        if tokio::time::timeout(Duration::from_secs(10), actor_task)
+            .await
+            .is_err()
+        {
+            tracing::warn!("Session actor did not shut down in time");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dap::mock::MockAdapter;
+    use crate::ipc::protocol::StatusResult;
+
+    use super::super::mock_session::stop_at_start;
+
+    #[tokio::test]
+    async fn commands_run_in_process_as_on_the_daemon() {
+        let adapter = MockAdapter::scripted(stop_at_start).await;
+        let mut session = LocalSession::with_config(adapter.config());
+
+        let status: StatusResult =
+            serde_json::from_value(session.send_command(Command::Status).await.unwrap())
+                .unwrap();
+        assert!(!status.session_active);
+
+        session
+            .send_command(Command::Start {
+                program: "/bin/app".into(),
+                args: vec![],
+                adapter: Some("mock".to_string()),
+                stop_on_entry: false,
+                initial_breakpoints: vec!["main.c:3".to_string()],
+                keep_alive_minutes: None,
+            })
+            .await
+            .unwrap();
+        let stop = session
+            .send_command(Command::Await { timeout_secs: 10 })
+            .await
+            .unwrap();
+        assert_eq!(stop["reason"], "breakpoint");
+
+        let error = session
+            .send_command(Command::Attach {
+                pid: 1,
+                adapter: None,
+                keep_alive_minutes: None,
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, crate::common::Error::SessionAlreadyActive), "{}", error);
+
+        session.send_command(Command::Stop).await.unwrap();
+        session.shutdown().await;
+        let commands: Vec<String> = adapter.received().into_iter().map(|r| r.command).collect();
+        assert!(commands.contains(&"disconnect".to_string()));
+    }
+}
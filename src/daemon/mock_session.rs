@@ -0,0 +1,49 @@
+//! Sessions on the mock adapter, for the daemon's tests
+
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::dap::mock::{Action, MockAdapter};
+
+use super::session::DebugSession;
+
+/// Launch `/bin/app` on the mock adapter, with a breakpoint at main.c:3
+pub(super) async fn launch_on(adapter: &MockAdapter) -> DebugSession {
+    let config = adapter.config();
+    let breakpoints = vec!["main.c:3".to_string()];
+    let adapter = Some("mock".to_string());
+    let program = Path::new("/bin/app");
+    DebugSession::launch(&config, program, vec![], adapter, false, breakpoints)
+        .await
+        .unwrap()
+}
+
+/// Script for a mock adapter whose program stops at a breakpoint on thread
+/// 1 as soon as it is configured
+pub(super) fn stop_at_start(_: usize, command: &str, _: &Value) -> Option<Vec<Action>> {
+    (command == "configurationDone").then(|| {
+        vec![
+            Action::Respond(json!({})),
+            Action::Event(
+                "stopped",
+                json!({ "reason": "breakpoint", "threadId": 1, "allThreadsStopped": true }),
+            ),
+        ]
+    })
+}
+
+/// Process events until `done` holds, as the actor would on its ticks
+pub(super) async fn process_until(
+    session: &mut DebugSession,
+    done: impl Fn(&DebugSession) -> bool,
+) {
+    for _ in 0..200 {
+        session.process_events().await.unwrap();
+        if done(session) {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("session never reached the expected state");
+}
@@ -4,11 +4,24 @@
 //! persistent debug sessions across CLI invocations.
 
 mod actor;
+mod deadlock;
+mod event_tap;
 mod handler;
+#[cfg(feature = "http")]
+mod http;
+mod local;
+#[cfg(test)]
+mod mock_session;
+mod output_tap;
 mod server;
 mod session;
+mod tap;
 
-use crate::common::Result;
+use std::net::SocketAddr;
+
+pub use local::LocalSession;
+
+use crate::common::{Error, Result};
 
 /// Run in daemon mode
 ///
@@ -18,7 +31,16 @@ use crate::common::Result;
 /// 2. Accepts CLI commands and translates them to DAP requests
 /// 3. Buffers events when no client is connected
 /// 4. Manages the debug session lifecycle
-pub async fn run() -> Result<()> {
+///
+/// With `http`, the same commands are also accepted as HTTP POST requests on
+/// that address; this needs the `http` feature.
+pub async fn run(http: Option<SocketAddr>) -> Result<()> {
+    if http.is_some() && !cfg!(feature = "http") {
+        return Err(Error::Config(
+            "HTTP front end is not compiled in; rebuild with `--features http`".to_string(),
+        ));
+    }
+
     tracing::info!(
         version = env!("CARGO_PKG_VERSION"),
         pid = std::process::id(),
@@ -26,5 +48,5 @@ pub async fn run() -> Result<()> {
     );
 
     let mut daemon = server::Daemon::new().await?;
-    daemon.run().await
+    daemon.run(http).await
 }
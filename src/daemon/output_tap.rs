@@ -0,0 +1,63 @@
+//! Live copies of the debuggee's output for `output --follow --raw`
+//!
+//! The session keeps output in a bounded buffer that drops the oldest events
+//! once full. A follower subscribed here is sent every event as the session
+//! receives it instead, so output written faster than the buffer keeps still
+//! reaches it whole, as long as the follower keeps reading; one that falls
+//! behind is told how much it missed with a `[debugger]` line. Kept
+//! process-wide, like adapter progress, so a follower carries on when a
+//! crashed adapter's session is replaced by a relaunch.
+
+use tokio::sync::mpsc;
+
+use super::session::OutputEvent;
+use super::tap::{Tap, Tapped};
+
+static TAP: Tap<OutputEvent> = Tap::new();
+
+impl Tapped for OutputEvent {
+    fn lagged(dropped: usize) -> Self {
+        OutputEvent {
+            category: "console".to_string(),
+            output: format!(
+                "[debugger] {} output event(s) dropped; the follower fell behind\n",
+                dropped
+            ),
+        }
+    }
+}
+
+/// Receive every output event from now on, until the receiver is dropped
+pub(super) fn subscribe() -> mpsc::Receiver<OutputEvent> {
+    TAP.subscribe(|_| true)
+}
+
+/// Copy an output event to every follower
+pub(super) fn send(category: &str, output: &str) {
+    TAP.send(|| OutputEvent {
+        category: category.to_string(),
+        output: output.to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn followers_get_every_event() {
+        let mut follower = subscribe();
+        for index in 0..3 {
+            send("stdout", &format!("tap-test {}\n", index));
+        }
+
+        // Other tests' sessions may be writing output too
+        let mut received = Vec::new();
+        while let Ok(event) = follower.try_recv() {
+            if event.output.starts_with("tap-test") {
+                received.push(event.output);
+            }
+        }
+        assert_eq!(received, vec!["tap-test 0\n", "tap-test 1\n", "tap-test 2\n"]);
+    }
+}
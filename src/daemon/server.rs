@@ -5,6 +5,8 @@
 //! (see `actor.rs`); `await` is handled here by waiting on state snapshots so
 //! it never blocks other clients.
 
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -12,24 +14,40 @@ use interprocess::local_socket::traits::tokio::Listener as ListenerTrait;
 use serde_json::json;
 use tokio::io::BufReader;
 use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
 
-use crate::common::{config::Config, error::IpcError, paths, Error, Result};
+use crate::common::{config::Config, error::IpcError, logging, paths, signal, Error, Result};
+use crate::dap::{progress, CancelHandle, ExceptionInfoResponseBody, StoppedEventBody};
 use crate::ipc::{
-    protocol::{Command, Request, Response, StackFrameInfo, StopResult},
+    protocol::{
+        Command, EvaluateContext, ExceptionInfo, ProgressInfo, Request, Response, SessionEvent,
+        StackFrameInfo, StepKind, StopResult,
+    },
     transport,
 };
 
 use super::actor::{self, ActorRequest, SessionSnapshot};
-use super::session::SessionState;
+use super::event_tap;
+use super::output_tap;
+use super::session::{normalize_stop_reason, DebugSession, OutputEvent, SessionState};
 
 /// Handles shared by every connection task.
 #[derive(Clone)]
-struct Shared {
+pub(super) struct Shared {
+    config: Arc<Config>,
     requests: mpsc::Sender<ActorRequest>,
     snapshots: watch::Receiver<SessionSnapshot>,
     shutdown_tx: Arc<watch::Sender<bool>>,
     shutdown_rx: watch::Receiver<bool>,
     last_activity: Arc<Mutex<Instant>>,
+    /// Task waiting for a stop on behalf of `await --background`
+    background_await: Arc<Mutex<Option<JoinHandle<()>>>>,
+    /// Bumped to cancel the awaits in progress; each await subscribes while
+    /// it waits, so the receiver count is the number of waiters
+    cancel_await: Arc<watch::Sender<u64>>,
+    /// Cancels the session's adapter requests without going through the
+    /// actor, which is busy while one of them is in flight
+    dap_cancel: Arc<Mutex<Option<CancelHandle>>>,
 }
 
 /// Main daemon server
@@ -41,37 +59,47 @@ pub struct Daemon {
 impl Daemon {
     /// Create a new daemon instance
     pub async fn new() -> Result<Self> {
-        let config = Arc::new(Config::load()?);
-        Ok(Self { config })
+        match crate::setup::first_run::write_default_config_if_missing().await {
+            Ok(Some(path)) => tracing::info!("Wrote default config to {}", path.display()),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to write default config: {}", e),
+        }
+        // Problems with the file were logged by `logging::init_daemon`
+        let (config, _) = Config::load_with_problems()?;
+        Ok(Self {
+            config: Arc::new(config),
+        })
     }
 
     /// Run the daemon main loop
-    pub async fn run(&mut self) -> Result<()> {
+    pub async fn run(&mut self, http: Option<std::net::SocketAddr>) -> Result<()> {
         // Create the IPC listener
         let listener = transport::create_listener().await?;
         tracing::info!("Daemon listening on {}", paths::socket_name());
 
-        let idle_timeout = Duration::from_secs(self.config.daemon.idle_timeout_minutes * 60);
-
-        let (request_tx, request_rx) = mpsc::channel(32);
-        let (snapshot_tx, snapshot_rx) = watch::channel(SessionSnapshot::default());
-        let (shutdown_tx, shutdown_rx) = watch::channel(false);
-
-        let actor_task = tokio::spawn(actor::run(self.config.clone(), request_rx, snapshot_tx));
+        let idle_timeout =
+            Duration::from_secs(self.config.daemon.idle_timeout_minutes.saturating_mul(60));
 
-        let shared = Shared {
-            requests: request_tx,
-            snapshots: snapshot_rx,
-            shutdown_tx: Arc::new(shutdown_tx),
-            shutdown_rx,
-            last_activity: Arc::new(Mutex::new(Instant::now())),
-        };
+        let (shared, actor_task) = Shared::spawn(self.config.clone());
         let mut shutdown_rx = shared.shutdown_rx.clone();
 
+        #[cfg(feature = "http")]
+        if let Some(addr) = http {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            tracing::info!("HTTP front end listening on {}", addr);
+            tokio::spawn(super::http::serve(listener, shared.clone()));
+        }
+        #[cfg(not(feature = "http"))]
+        let _ = http;
+
         loop {
             // Check for idle timeout
-            let idle = !shared.snapshots.borrow().session_active
-                && shared.last_activity.lock().unwrap().elapsed() > idle_timeout;
+            let timeout = {
+                let awaiting = shared.cancel_await.receiver_count() > 0;
+                idle_limit(&shared.snapshots.borrow(), idle_timeout, awaiting)
+            };
+            let idle = timeout
+                .is_some_and(|timeout| shared.last_activity.lock().unwrap().elapsed() > timeout);
             if idle {
                 tracing::info!("Idle timeout reached, shutting down daemon");
                 break;
@@ -108,6 +136,21 @@ impl Daemon {
     }
 }
 
+/// How long the daemon may go without a request before shutting down, if
+/// at all. An active session keeps the daemon alive unless it was started
+/// with `--keep-alive`, and then only while nothing is awaiting it.
+fn idle_limit(
+    snapshot: &SessionSnapshot,
+    idle_timeout: Duration,
+    awaiting: bool,
+) -> Option<Duration> {
+    match (snapshot.session_active, snapshot.keep_alive) {
+        (false, _) => Some(idle_timeout),
+        (true, Some(keep_alive)) if !awaiting => Some(keep_alive),
+        (true, _) => None,
+    }
+}
+
 /// Run one iteration of the select loop, returns true if should break
 #[cfg(unix)]
 async fn run_select_loop(
@@ -184,6 +227,11 @@ async fn run_select_loop(
 async fn handle_client(stream: transport::platform::Stream, mut shared: Shared) {
     let (reader, mut writer) = tokio::io::split(stream);
     let mut reader = BufReader::new(reader);
+    // Copy of the session's output, from this connection's first FollowOutput
+    let mut followed_output = None;
+    // Copy of the session's events, from this connection's first FollowEvents
+    // and filtered as it asked
+    let mut followed_events = None;
 
     // Read and process commands until client disconnects
     loop {
@@ -236,23 +284,31 @@ async fn handle_client(stream: transport::platform::Stream, mut shared: Shared)
         tracing::debug!("Received command: {:?}", request.command);
         *shared.last_activity.lock().unwrap() = Instant::now();
 
-        let mut shutdown_after_reply = false;
-        let response = match request.command {
-            Command::Shutdown => {
-                shutdown_after_reply = true;
-                Response::ok(request.id)
+        let shutdown_after_reply = matches!(request.command, Command::Shutdown);
+        let capture = request.trace_dap.then(logging::DapCapture::start);
+        let mut response = match request.command {
+            Command::FollowOutput { wait_ms } => {
+                let output = followed_output.get_or_insert_with(output_tap::subscribe);
+                follow_output(request.id, output, wait_ms, &shared).await
             }
-            // Await waits on state snapshots so a stopped/exited transition can
-            // be observed without occupying the session actor; other clients
-            // stay free to send pause/continue while this connection waits.
-            Command::Await { timeout_secs } => {
-                match await_stop(timeout_secs, &shared).await {
-                    Ok(result) => Response::success(request.id, result),
+            Command::FollowEvents { wait_ms, only } => {
+                let subscribed = match followed_events.take() {
+                    Some(events) => Ok(events),
+                    None => event_tap::subscribe(&only),
+                };
+                match subscribed {
+                    Ok(events) => {
+                        let events = followed_events.insert(events);
+                        follow_events(request.id, events, wait_ms, &shared).await
+                    }
                     Err(e) => Response::error(request.id, IpcError::from(&e)),
                 }
             }
-            command => dispatch(request.id, command, &shared).await,
+            command => execute(request.id, command, &shared).await,
         };
+        if let Some(capture) = capture {
+            response.dap_trace = capture.finish();
+        }
 
         if send_response(&mut writer, &response).await.is_err() {
             break;
@@ -260,12 +316,302 @@ async fn handle_client(stream: transport::platform::Stream, mut shared: Shared)
         *shared.last_activity.lock().unwrap() = Instant::now();
 
         if shutdown_after_reply {
-            let _ = shared.shutdown_tx.send(true);
+            shared.request_shutdown();
             break;
         }
     }
 }
 
+/// Execute one command on behalf of a front end (IPC or HTTP).
+///
+/// `Shutdown` is only acknowledged here; the caller requests the shutdown
+/// after its reply has been delivered.
+pub(super) async fn execute(id: u64, command: Command, shared: &Shared) -> Response {
+    *shared.last_activity.lock().unwrap() = Instant::now();
+
+    match command {
+        Command::Shutdown => Response::ok(id),
+        // Await waits on state snapshots so a stopped/exited transition can
+        // be observed without occupying the session actor; other clients
+        // stay free to send pause/continue while this connection waits.
+        Command::Await { timeout_secs } => match await_stop(timeout_secs, shared).await {
+            Ok(result) => Response::success(id, result),
+            Err(e) => Response::error(id, IpcError::from(&e)),
+        },
+        // Each step waits for its stop the same way, between actor commands,
+        // so other clients can still pause or inspect
+        Command::StepCount {
+            kind,
+            count,
+            timeout_secs,
+            this_thread,
+        } => step_count(id, (kind, this_thread), count, timeout_secs, shared).await,
+        Command::StepUntil {
+            expression,
+            kind,
+            max_steps,
+            timeout_secs,
+            this_thread,
+        } => {
+            let step = (kind, this_thread);
+            step_until(id, expression, step, max_steps, timeout_secs, shared).await
+        }
+        Command::CancelEval => match shared.cancel_evaluate().await {
+            Ok(cancelled) => Response::success(id, json!({ "cancelled": cancelled })),
+            Err(e) => Response::error(id, IpcError::from(&e)),
+        },
+        Command::FollowOutput { .. } => Response::error(
+            id,
+            IpcError::from(&Error::Config(
+                "Following raw output needs a daemon connection; use get_output".to_string(),
+            )),
+        ),
+        Command::FollowEvents { .. } => Response::error(
+            id,
+            IpcError::from(&Error::Config(
+                "Following session events needs a daemon connection; use await".to_string(),
+            )),
+        ),
+        Command::CancelAwait => {
+            let cancelled = shared.cancel_awaits();
+            Response::success(id, json!({ "cancelled": cancelled }))
+        }
+        // Read from the DAP layer directly: a launch still loading symbols
+        // keeps the actor busy
+        Command::Progress => {
+            let progress = progress::latest().map(|progress| ProgressInfo {
+                title: progress.title,
+                message: progress.message,
+                percentage: progress.percentage,
+            });
+            Response::success(id, json!({ "progress": progress }))
+        }
+        // Spawns and initializes an adapter of its own, which can take a
+        // while, so it doesn't hold up the session's commands
+        Command::ProbeAdapter { adapter } => {
+            match DebugSession::probe_capabilities(&shared.config, &adapter).await {
+                Ok(capabilities) => Response::success(
+                    id,
+                    json!({ "adapter": adapter, "capabilities": capabilities }),
+                ),
+                Err(e) => Response::error(id, IpcError::from(&e)),
+            }
+        }
+        Command::AwaitNotify { timeout_secs, path } => {
+            match shared.start_background_await(timeout_secs, path.clone()) {
+                Ok(()) => Response::success(
+                    id,
+                    json!({ "status": "waiting", "notify": path.display().to_string() }),
+                ),
+                Err(e) => Response::error(id, IpcError::from(&e)),
+            }
+        }
+        command => dispatch(id, command, shared).await,
+    }
+}
+
+/// The output copied to a follower since its last read, waiting up to
+/// `wait_ms` for some while the session lasts. A stopped program writes
+/// nothing, but waiting then too keeps a follower from polling in a loop.
+async fn follow_output(
+    id: u64,
+    output: &mut mpsc::Receiver<OutputEvent>,
+    wait_ms: u64,
+    shared: &Shared,
+) -> Response {
+    let mut events = Vec::new();
+    let waiting = {
+        let snapshot = shared.snapshots.borrow();
+        snapshot.session_active && snapshot.state != Some(SessionState::Exited)
+    };
+    if output.is_empty() && waiting {
+        tokio::select! {
+            Some(event) = output.recv() => events.push(event),
+            _ = tokio::time::sleep(Duration::from_millis(wait_ms)) => {}
+        }
+    }
+
+    // Output is copied before the snapshot shows the end of the session, so
+    // draining after reading the snapshot leaves nothing behind
+    let (active, exit_code) = {
+        let snapshot = shared.snapshots.borrow();
+        let exited = snapshot.state == Some(SessionState::Exited);
+        (snapshot.session_active && !exited, exited.then_some(snapshot.exit_code))
+    };
+    while let Ok(event) = output.try_recv() {
+        events.push(event);
+    }
+    if events.is_empty() && !active && exit_code.is_none() {
+        return Response::error(id, IpcError::from(&Error::SessionNotActive));
+    }
+
+    let text: String = events.iter().map(|event| event.output.as_str()).collect();
+    let details: Vec<_> = events
+        .iter()
+        .map(|event| json!({ "category": event.category, "output": event.output }))
+        .collect();
+    let session_ended = exit_code.map(|code| json!({ "exit_code": code }));
+    Response::success(
+        id,
+        json!({ "output": text, "events": details, "session_ended": session_ended }),
+    )
+}
+
+/// The session events sent to a subscriber since its last read, waiting up
+/// to `wait_ms` for some while the session lasts
+async fn follow_events(
+    id: u64,
+    subscription: &mut mpsc::Receiver<SessionEvent>,
+    wait_ms: u64,
+    shared: &Shared,
+) -> Response {
+    let mut events = Vec::new();
+    let waiting = {
+        let snapshot = shared.snapshots.borrow();
+        snapshot.session_active && snapshot.state != Some(SessionState::Exited)
+    };
+    if subscription.is_empty() && waiting {
+        tokio::select! {
+            Some(event) = subscription.recv() => events.push(event),
+            _ = tokio::time::sleep(Duration::from_millis(wait_ms)) => {}
+        }
+    }
+
+    // Events are sent before the snapshot shows the end of the session, so
+    // draining after reading the snapshot leaves nothing behind
+    let (active, exit_code) = {
+        let snapshot = shared.snapshots.borrow();
+        let exited = snapshot.state == Some(SessionState::Exited);
+        (snapshot.session_active && !exited, exited.then_some(snapshot.exit_code))
+    };
+    while let Ok(event) = subscription.try_recv() {
+        events.push(event);
+    }
+    if events.is_empty() && !active && exit_code.is_none() {
+        return Response::error(id, IpcError::from(&Error::SessionNotActive));
+    }
+
+    let session_ended = exit_code.map(|code| json!({ "exit_code": code }));
+    Response::success(id, json!({ "events": events, "session_ended": session_ended }))
+}
+
+impl Shared {
+    /// Spawn the session actor and return the handles for talking to it.
+    ///
+    /// The actor task ends, stopping any session, once every clone of the
+    /// returned handles has been dropped.
+    pub(super) fn spawn(config: Arc<Config>) -> (Self, JoinHandle<()>) {
+        let (request_tx, request_rx) = mpsc::channel(32);
+        let (snapshot_tx, snapshot_rx) = watch::channel(SessionSnapshot::default());
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let dap_cancel = Arc::new(Mutex::new(None));
+
+        let actor_task = tokio::spawn(actor::run(
+            config.clone(),
+            request_rx,
+            snapshot_tx,
+            dap_cancel.clone(),
+        ));
+
+        let shared = Self {
+            config,
+            requests: request_tx,
+            snapshots: snapshot_rx,
+            shutdown_tx: Arc::new(shutdown_tx),
+            shutdown_rx,
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            background_await: Arc::new(Mutex::new(None)),
+            cancel_await: Arc::new(watch::channel(0).0),
+            dap_cancel,
+        };
+        (shared, actor_task)
+    }
+
+    /// Cancel the evaluate requests in flight, returning how many there were
+    async fn cancel_evaluate(&self) -> Result<usize> {
+        let handle = self.dap_cancel.lock().unwrap().clone();
+        match handle {
+            Some(handle) => handle.cancel_command("evaluate").await,
+            None => Err(Error::SessionNotActive),
+        }
+    }
+
+    /// Cancel every await in progress, returning how many there were
+    fn cancel_awaits(&self) -> usize {
+        let waiting = self.cancel_await.receiver_count();
+        self.cancel_await.send_modify(|generation| *generation += 1);
+        waiting
+    }
+
+    /// Wait for the next stop in a task of its own and write the result to
+    /// `path`, which must not exist yet. A background await already in
+    /// progress is replaced.
+    fn start_background_await(&self, timeout_secs: u64, path: PathBuf) -> Result<()> {
+        // Never replace or follow an existing file: the daemon may be able
+        // to write places the client can't. This also keeps a result left
+        // from an earlier wait from looking like this one's.
+        if std::fs::symlink_metadata(&path).is_ok() {
+            return Err(Error::Config(format!(
+                "Cannot use '{}' for --notify: it already exists; remove it or pick a new path",
+                path.display()
+            )));
+        }
+
+        let shared = self.clone();
+        let task = tokio::spawn(async move {
+            let mut shutdown_rx = shared.shutdown_rx.clone();
+            let result = tokio::select! {
+                result = await_stop(timeout_secs, &shared) => result,
+                // Holding `shared` would keep the session actor alive
+                _ = shutdown_rx.wait_for(|shutdown| *shutdown) => return,
+            };
+            let value = match result {
+                Ok(value) => value,
+                Err(e) => json!({ "error": IpcError::from(&e) }),
+            };
+            if let Err(e) = write_notify_file(&path, &value) {
+                tracing::error!("Failed to write await result to {}: {}", path.display(), e);
+            }
+        });
+
+        if let Some(previous) = self.background_await.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Ask the daemon main loop to shut down.
+    pub(super) fn request_shutdown(&self) {
+        let _ = self.shutdown_tx.send(true);
+    }
+
+    /// Receiver that flips to `true` when the daemon is shutting down.
+    #[cfg(feature = "http")]
+    pub(super) fn shutdown_signal(&self) -> watch::Receiver<bool> {
+        self.shutdown_rx.clone()
+    }
+}
+
+/// Write an await result where a polling client will only ever see the
+/// complete file, refusing to replace or follow anything already at `path`
+/// or at the `.partial` file beside it
+fn write_notify_file(path: &Path, value: &serde_json::Value) -> std::io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let json = serde_json::to_vec_pretty(value).map_err(std::io::Error::other)?;
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&partial)?;
+    let written = file.write_all(&json).and_then(|()| {
+        // Unlike a rename, linking fails if something appeared at `path`
+        std::fs::hard_link(&partial, path)
+    });
+    let _ = std::fs::remove_file(&partial);
+    written
+}
+
 async fn send_response(
     writer: &mut (impl tokio::io::AsyncWrite + Unpin),
     response: &Response,
@@ -303,6 +649,7 @@ fn daemon_stopping_response(id: u64) -> Response {
 /// Wait for the session to stop by watching state snapshots.
 async fn await_stop(timeout_secs: u64, shared: &Shared) -> Result<serde_json::Value> {
     let mut snapshots = shared.snapshots.clone();
+    let mut cancel = shared.cancel_await.subscribe();
     let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_secs);
 
     loop {
@@ -332,13 +679,138 @@ async fn await_stop(timeout_secs: u64, shared: &Shared) -> Result<serde_json::Va
             return Err(Error::AwaitTimeout(timeout_secs));
         }
 
-        match tokio::time::timeout(remaining, snapshots.changed()).await {
-            Ok(Ok(())) => {}
-            Ok(Err(_)) => {
-                return Err(Error::Internal("daemon is shutting down".to_string()));
+        tokio::select! {
+            changed = tokio::time::timeout(remaining, snapshots.changed()) => match changed {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) => {
+                    return Err(Error::Internal("daemon is shutting down".to_string()));
+                }
+                Err(_) => return Err(Error::AwaitTimeout(timeout_secs)),
+            },
+            _ = cancel.changed() => return Ok(json!({ "reason": "cancelled" })),
+        }
+    }
+}
+
+/// Step up to `count` times, stopping early at any stop that isn't the end
+/// of a step, and report the last stop with the number of steps taken.
+/// `step` is the kind of step and whether only the current thread runs.
+async fn step_count(
+    id: u64,
+    step: (StepKind, bool),
+    count: u32,
+    timeout_secs: u64,
+    shared: &Shared,
+) -> Response {
+    let mut steps = 0;
+    let mut stop = json!(null);
+    while steps < count {
+        stop = match step_once(id, step, timeout_secs, shared).await {
+            Ok(stop) => stop,
+            // Once stepping has started, report how far it got instead
+            Err(_) if steps > 0 => break,
+            Err(response) => return response,
+        };
+        steps += 1;
+        if stop["reason"] != "step" {
+            break;
+        }
+    }
+    Response::success(id, json!({ "steps": steps, "count": count, "stop": stop }))
+}
+
+/// Step until `expression`'s value in the top frame differs from its value
+/// before the first step, for at most `max_steps` steps. Like
+/// [`step_count`], any stop other than a finished step ends it early.
+async fn step_until(
+    id: u64,
+    expression: String,
+    step: (StepKind, bool),
+    max_steps: u32,
+    timeout_secs: u64,
+    shared: &Shared,
+) -> Response {
+    let initial = match evaluate_top(id, &expression, shared).await {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+
+    let mut steps = 0;
+    let mut value = initial.clone();
+    let mut stop = json!(null);
+    while steps < max_steps {
+        stop = match step_once(id, step, timeout_secs, shared).await {
+            Ok(stop) => stop,
+            Err(_) if steps > 0 => break,
+            Err(response) => return response,
+        };
+        steps += 1;
+        if stop["reason"] != "step" {
+            break;
+        }
+        // Stepping out of the expression's scope counts as a change
+        value = match evaluate_top(id, &expression, shared).await {
+            Ok(value) => value,
+            Err(response) => {
+                let message = response.error.map(|e| e.message).unwrap_or_default();
+                format!("<error: {}>", message)
             }
-            Err(_) => return Err(Error::AwaitTimeout(timeout_secs)),
+        };
+        if value != initial {
+            break;
+        }
+    }
+
+    Response::success(
+        id,
+        json!({
+            "expression": expression,
+            "steps": steps,
+            "max_steps": max_steps,
+            "changed": value != initial,
+            "initial": initial,
+            "value": value,
+            "stop": stop,
+        }),
+    )
+}
+
+/// Step once and wait for the stop, or the failed step's response
+async fn step_once(
+    id: u64,
+    (kind, this_thread): (StepKind, bool),
+    timeout_secs: u64,
+    shared: &Shared,
+) -> std::result::Result<serde_json::Value, Response> {
+    let response = dispatch(id, kind.command(this_thread), shared).await;
+    if !response.success {
+        return Err(response);
+    }
+    await_stop(timeout_secs, shared)
+        .await
+        .map_err(|e| Response::error(id, IpcError::from(&e)))
+}
+
+/// `expression`'s value in the top frame, or the failed evaluation's response
+async fn evaluate_top(
+    id: u64,
+    expression: &str,
+    shared: &Shared,
+) -> std::result::Result<String, Response> {
+    let command = Command::Evaluate {
+        expression: expression.to_string(),
+        frame_id: None,
+        frame_index: Some(0),
+        context: EvaluateContext::Watch,
+        max_string_length: None,
+        no_truncate: false,
+    };
+    let response = dispatch(id, command, shared).await;
+    match &response.result {
+        Some(result) if response.success => {
+            Ok(result["result"].as_str().unwrap_or_default().to_string())
         }
+        _ => Err(response),
     }
 }
 
@@ -347,45 +819,152 @@ async fn build_stop_result(
     snapshot: &SessionSnapshot,
     shared: &Shared,
 ) -> Result<serde_json::Value> {
-    let (source, line, column) = fetch_stop_location(shared).await;
-
-    let result = match &snapshot.last_stop {
-        Some(body) => StopResult {
-            reason: body.reason.clone(),
-            description: body.description.clone(),
-            thread_id: body.thread_id,
-            all_threads_stopped: body.all_threads_stopped,
-            hit_breakpoint_ids: body.hit_breakpoint_ids.clone(),
-            source,
-            line,
-            column,
-        },
+    Ok(serde_json::to_value(stop_result(snapshot, shared).await)?)
+}
+
+async fn stop_result(snapshot: &SessionSnapshot, shared: &Shared) -> StopResult {
+    let location = fetch_stop_location(shared).await;
+    stop_result_at(snapshot, location)
+}
+
+/// The stop result for a snapshot of a stopped session, at `location`
+pub(super) fn stop_result_at(snapshot: &SessionSnapshot, location: StopLocation) -> StopResult {
+    match &snapshot.last_stop {
+        Some(body) => {
+            let fault = FaultDetails::from_stop(body, snapshot.exception_info.as_ref());
+            let reason = normalize_stop_reason(&body.reason);
+            StopResult {
+                reason: reason.to_string(),
+                raw_reason: (reason != body.reason).then(|| body.reason.clone()),
+                description: body.description.clone(),
+                thread_id: body.thread_id,
+                all_threads_stopped: body.all_threads_stopped,
+                hit_breakpoint_ids: body.hit_breakpoint_ids.clone(),
+                source: location.source,
+                line: location.line,
+                column: location.column,
+                function: location.function,
+                signal_name: fault.signal_name,
+                signal_number: fault.signal_number,
+                exception_text: fault.exception_text,
+                exception: snapshot.exception_info.as_ref().map(exception_info),
+                on_hit: snapshot.on_hit.clone(),
+                watches: snapshot.watches.clone(),
+            }
+        }
         // Stopped without an adapter event (attach, stop-on-entry).
         None => StopResult {
             reason: snapshot
                 .stopped_reason
                 .clone()
                 .unwrap_or_else(|| "unknown".to_string()),
+            raw_reason: None,
             description: None,
             thread_id: snapshot.stopped_thread,
             all_threads_stopped: true,
             hit_breakpoint_ids: vec![],
-            source,
-            line,
-            column,
+            source: location.source,
+            line: location.line,
+            column: location.column,
+            function: location.function,
+            signal_name: None,
+            signal_number: None,
+            exception_text: None,
+            exception: None,
+            on_hit: vec![],
+            watches: snapshot.watches.clone(),
         },
-    };
+    }
+}
+
+/// Signal or exception behind a stop, from the stopped event and the
+/// adapter's `exceptionInfo` response
+#[derive(Debug, Default, PartialEq)]
+struct FaultDetails {
+    signal_name: Option<String>,
+    signal_number: Option<i32>,
+    exception_text: Option<String>,
+}
+
+impl FaultDetails {
+    fn from_stop(body: &StoppedEventBody, info: Option<&ExceptionInfoResponseBody>) -> Self {
+        if normalize_stop_reason(&body.reason) != "exception" {
+            return Self::default();
+        }
+
+        // Most specific text first: exceptionInfo, then the event's own text
+        let texts: Vec<&str> = [
+            info.and_then(|i| i.description.as_deref()),
+            body.text.as_deref(),
+            body.description.as_deref(),
+            info.map(|i| i.exception_id.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let signal_name = texts
+            .iter()
+            .find_map(|text| signal::parse_signal_name(text))
+            .map(String::from);
+        Self {
+            signal_number: signal_name.as_deref().and_then(signal::signal_number),
+            signal_name,
+            exception_text: texts.first().map(|text| text.to_string()),
+        }
+    }
+}
+
+/// Flatten the adapter's `exceptionInfo` response for the stop result
+fn exception_info(info: &ExceptionInfoResponseBody) -> ExceptionInfo {
+    let details = info.details.as_ref();
+    ExceptionInfo {
+        id: info.exception_id.clone(),
+        description: info.description.clone(),
+        break_mode: info.break_mode.clone(),
+        type_name: details.and_then(|d| d.full_type_name.clone().or_else(|| d.type_name.clone())),
+        message: details.and_then(|d| d.message.clone()),
+        stack_trace: details.and_then(|d| d.stack_trace.clone()),
+    }
+}
+
+/// Where the program stopped, taken from the top stack frame
+#[derive(Default)]
+pub(super) struct StopLocation {
+    source: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    function: Option<String>,
+}
+
+impl StopLocation {
+    pub(super) fn of(frame: &StackFrameInfo) -> Self {
+        // Report just the filename, matching the pre-actor await output.
+        let source = frame.source.as_ref().map(|path| {
+            std::path::Path::new(path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(path)
+                .to_string()
+        });
 
-    Ok(serde_json::to_value(result)?)
+        StopLocation {
+            source,
+            line: frame.line,
+            column: frame.column,
+            function: Some(frame.name.clone()),
+        }
+    }
 }
 
-/// Ask the actor for the top stack frame and extract filename/line/column.
-async fn fetch_stop_location(shared: &Shared) -> (Option<String>, Option<u32>, Option<u32>) {
+/// Ask the actor for the top stack frame and extract its location.
+async fn fetch_stop_location(shared: &Shared) -> StopLocation {
     let response = dispatch(
         0,
         Command::StackTrace {
             thread_id: None,
             limit: 1,
+            user_only: false,
         },
         shared,
     )
@@ -397,21 +976,215 @@ async fn fetch_stop_location(shared: &Shared) -> (Option<String>, Option<u32>, O
         .map(serde_json::from_value)
     {
         Some(Ok(frames)) if response.success => frames,
-        _ => return (None, None, None),
+        _ => return StopLocation::default(),
     };
 
-    let Some(frame) = frames.first() else {
-        return (None, None, None);
-    };
+    frames.first().map(StopLocation::of).unwrap_or_default()
+}
 
-    // Report just the filename, matching the pre-actor await output.
-    let source = frame.source.as_ref().map(|path| {
-        std::path::Path::new(path)
-            .file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or(path)
-            .to_string()
-    });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dap::ExceptionDetails;
+
+    fn stopped(reason: &str, description: Option<&str>) -> StoppedEventBody {
+        StoppedEventBody {
+            reason: reason.to_string(),
+            description: description.map(String::from),
+            thread_id: Some(1),
+            all_threads_stopped: true,
+            hit_breakpoint_ids: vec![],
+            text: None,
+        }
+    }
+
+    #[test]
+    fn sessions_keep_the_daemon_alive_unless_given_a_keep_alive() {
+        let idle = Duration::from_secs(600);
+        let keep_alive = Duration::from_secs(60);
+        let session = |keep_alive| SessionSnapshot {
+            session_active: true,
+            keep_alive,
+            ..Default::default()
+        };
 
-    (source, frame.line, frame.column)
+        assert_eq!(idle_limit(&SessionSnapshot::default(), idle, false), Some(idle));
+        assert_eq!(idle_limit(&session(None), idle, false), None);
+        assert_eq!(idle_limit(&session(Some(keep_alive)), idle, false), Some(keep_alive));
+        // Someone waiting on the session counts as using it
+        assert_eq!(idle_limit(&session(Some(keep_alive)), idle, true), None);
+    }
+
+    #[test]
+    fn fault_details_prefer_exception_info() {
+        let info = ExceptionInfoResponseBody {
+            exception_id: "signal".to_string(),
+            description: Some("signal SIGSEGV: invalid address (fault address: 0x0)".to_string()),
+            break_mode: Some("always".to_string()),
+            details: None,
+        };
+        let fault =
+            FaultDetails::from_stop(&stopped("exception", Some("signal SIGSEGV")), Some(&info));
+        assert_eq!(fault.signal_name.as_deref(), Some("SIGSEGV"));
+        assert_eq!(fault.signal_number, Some(11));
+        assert_eq!(fault.exception_text, info.description);
+
+        let fault = FaultDetails::from_stop(&stopped("breakpoint", Some("SIGSEGV")), None);
+        assert_eq!(fault, FaultDetails::default());
+    }
+
+    #[test]
+    fn exception_info_is_flattened() {
+        let info = ExceptionInfoResponseBody {
+            exception_id: "Error".to_string(),
+            description: Some("boom".to_string()),
+            break_mode: Some("unhandled".to_string()),
+            details: Some(ExceptionDetails {
+                message: Some("boom".to_string()),
+                type_name: Some("TypeError".to_string()),
+                full_type_name: None,
+                evaluate_name: None,
+                stack_trace: Some("at main (app.js:3:9)".to_string()),
+                inner_exception: vec![],
+            }),
+        };
+        let exception = exception_info(&info);
+        assert_eq!(exception.id, "Error");
+        assert_eq!(exception.type_name.as_deref(), Some("TypeError"));
+        assert_eq!(exception.message.as_deref(), Some("boom"));
+        assert_eq!(
+            exception.stack_trace.as_deref(),
+            Some("at main (app.js:3:9)")
+        );
+    }
+
+    /// Shared handles over a running session, without an actor behind them
+    fn running_session() -> (Shared, mpsc::Receiver<ActorRequest>, watch::Sender<SessionSnapshot>) {
+        let (requests, request_rx) = mpsc::channel(1);
+        let (snapshot_tx, snapshots) = watch::channel(SessionSnapshot {
+            session_active: true,
+            state: Some(SessionState::Running),
+            ..Default::default()
+        });
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let shared = Shared {
+            config: Arc::new(Config::default()),
+            requests,
+            snapshots,
+            shutdown_tx: Arc::new(shutdown_tx),
+            shutdown_rx,
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            background_await: Arc::new(Mutex::new(None)),
+            cancel_await: Arc::new(watch::channel(0).0),
+            dap_cancel: Arc::new(Mutex::new(None)),
+        };
+        (shared, request_rx, snapshot_tx)
+    }
+
+    #[tokio::test]
+    async fn a_stop_found_by_a_failed_resume_is_reduced_before_it_is_published() {
+        use crate::dap::mock::{Action, MockAdapter};
+        use serde_json::json;
+
+        // The step fails because the program faulted just before it
+        let adapter = MockAdapter::scripted(|connection, command, arguments| match command {
+            "initialize" => Some(vec![Action::Respond(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsExceptionInfoRequest": true,
+            }))]),
+            "next" => Some(vec![
+                Action::Event("stopped", json!({ "reason": "exception", "threadId": 1 })),
+                Action::Fail("the thread is not stopped"),
+            ]),
+            "exceptionInfo" => Some(vec![Action::Respond(
+                json!({ "exceptionId": "SIGSEGV", "breakMode": "always" }),
+            )]),
+            _ => super::super::mock_session::stop_at_start(connection, command, arguments),
+        })
+        .await;
+        let (shared, actor_task) = Shared::spawn(Arc::new(adapter.config()));
+
+        let start = Command::Start {
+            program: "/bin/app".into(),
+            args: vec![],
+            adapter: Some("mock".to_string()),
+            stop_on_entry: false,
+            initial_breakpoints: vec!["main.c:3".to_string()],
+            keep_alive_minutes: None,
+        };
+        assert!(execute(1, start, &shared).await.success);
+        let mut snapshots = shared.snapshots.clone();
+        let stopped = |snapshot: &SessionSnapshot| snapshot.state == Some(SessionState::Stopped);
+        tokio::time::timeout(Duration::from_secs(5), snapshots.wait_for(stopped))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let next = Command::Next { this_thread: false };
+        assert!(!execute(2, next, &shared).await.success);
+        let snapshot = shared.snapshots.borrow().clone();
+        assert_eq!(snapshot.last_stop.unwrap().reason, "exception");
+        assert_eq!(snapshot.exception_info.unwrap().exception_id, "SIGSEGV");
+
+        drop((shared, snapshots));
+        actor_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn adapters_are_probed_without_the_session_actor() {
+        let adapter = crate::dap::mock::MockAdapter::start().await;
+        // Nothing serves the actor's requests, as if it were busy
+        let (mut shared, _requests, _snapshots) = running_session();
+        shared.config = Arc::new(adapter.config());
+
+        let probe = Command::ProbeAdapter {
+            adapter: "mock".to_string(),
+        };
+        let response = tokio::time::timeout(Duration::from_secs(5), execute(1, probe, &shared))
+            .await
+            .unwrap();
+        let result = response.result.unwrap();
+        assert_eq!(result["capabilities"]["supportsSetVariable"], true);
+    }
+
+    #[tokio::test]
+    async fn cancel_await_ends_blocking_and_background_awaits() {
+        let (shared, _requests, _snapshots) = running_session();
+        let notify = std::env::temp_dir().join(format!("debugger-await-{}.json", std::process::id()));
+
+        let blocking = tokio::spawn({
+            let shared = shared.clone();
+            async move { await_stop(300, &shared).await }
+        });
+        shared.start_background_await(300, notify.clone()).unwrap();
+        while shared.cancel_await.receiver_count() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(shared.cancel_awaits(), 2);
+        let result = blocking.await.unwrap().unwrap();
+        assert_eq!(result["reason"], "cancelled");
+
+        let task = shared.background_await.lock().unwrap().take().unwrap();
+        task.await.unwrap();
+        let written: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&notify).unwrap()).unwrap();
+        std::fs::remove_file(&notify).unwrap();
+        assert_eq!(written["reason"], "cancelled");
+    }
+
+    #[tokio::test]
+    async fn background_await_refuses_an_existing_notify_file() {
+        let (shared, _requests, _snapshots) = running_session();
+        let notify =
+            std::env::temp_dir().join(format!("debugger-existing-{}.json", std::process::id()));
+        std::fs::write(&notify, "keep me").unwrap();
+
+        let error = shared.start_background_await(300, notify.clone()).unwrap_err();
+        let contents = std::fs::read_to_string(&notify).unwrap();
+        std::fs::remove_file(&notify).unwrap();
+        assert!(matches!(error, Error::Config(_)));
+        assert_eq!(contents, "keep me");
+        assert!(shared.background_await.lock().unwrap().is_none());
+    }
 }
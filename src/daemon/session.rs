@@ -8,12 +8,16 @@ use std::path::{Path, PathBuf};
 
 use tokio::sync::mpsc;
 
-use crate::common::{config::{adapter_fallback_names, Config, TransportMode}, Error, Result};
+use crate::common::{
+    config::{adapter_fallback_names, AdapterConfig, Config, TransportMode},
+    truncate_utf8_to_bytes, Error, Result,
+};
 use crate::dap::{
-    self, Breakpoint, Capabilities, DapClient, Event, FunctionBreakpoint, LaunchArguments,
-    AttachArguments, Scope, SourceBreakpoint, StackFrame, StoppedEventBody, Thread, Variable,
+    self, Breakpoint, CancelHandle, Capabilities, DapClient, Event, ExceptionInfoResponseBody, FunctionBreakpoint, InstructionBreakpoint,
+    LaunchArguments, AttachArguments, Scope, SourceBreakpoint, StackFrame, StoppedEventBody, Thread, Variable,
 };
-use crate::ipc::protocol::{BreakpointInfo, BreakpointLocation};
+use crate::ipc::protocol::{BreakpointDetails, BreakpointInfo, BreakpointLocation, MemoryResult, ModuleInfo, OnHitOutput, OutputLimits, RegistersResult, SessionEvent, StackFrameInfo, WatchInfo, WatchValue};
+use crate::setup::detector::{detect_program_type, ProjectType};
 
 /// Debug session state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -49,7 +53,43 @@ struct StoredBreakpoint {
     enabled: bool,
     verified: bool,
     actual_line: Option<u32>,
+    /// The adapter's message about it
     message: Option<String>,
+    /// Why it isn't bound yet, going by the adapter's DAP `reason`
+    reason: Option<String>,
+    /// Breakpoint ID assigned by the adapter, used to match `hitBreakpointIds`
+    adapter_id: Option<u32>,
+    /// Commands to run when this breakpoint is hit
+    on_hit: Vec<String>,
+    /// Stops at this breakpoint so far
+    hits: u32,
+}
+
+impl StoredBreakpoint {
+    /// A new, enabled breakpoint the adapter has not seen yet
+    fn new(id: u32, location: BreakpointLocation) -> Self {
+        Self {
+            id,
+            location,
+            condition: None,
+            hit_count: None,
+            enabled: true,
+            verified: false,
+            actual_line: None,
+            message: None,
+            reason: None,
+            adapter_id: None,
+            on_hit: Vec::new(),
+            hits: 0,
+        }
+    }
+
+    /// Take the adapter's message about the breakpoint, and why it isn't
+    /// bound yet
+    fn record_message(&mut self, bp: &Breakpoint) {
+        self.message = bp.message.clone();
+        self.reason = pending_reason(bp);
+    }
 }
 
 /// Output event for buffering
@@ -92,18 +132,7 @@ impl OutputBuffer {
             return;
         }
         let output_bytes = output.len();
-
-        while self.current_bytes + output_bytes > self.max_bytes && !self.events.is_empty() {
-            if let Some(removed) = self.events.pop_front() {
-                self.current_bytes = self.current_bytes.saturating_sub(removed.output.len());
-            }
-        }
-
-        while self.events.len() >= self.max_events && !self.events.is_empty() {
-            if let Some(removed) = self.events.pop_front() {
-                self.current_bytes = self.current_bytes.saturating_sub(removed.output.len());
-            }
-        }
+        self.make_room(output_bytes, 1);
 
         self.events.push_back(OutputEvent {
             category: category.to_string(),
@@ -112,6 +141,26 @@ impl OutputBuffer {
         self.current_bytes += output_bytes;
     }
 
+    /// Drop the oldest events until `bytes` more bytes in `events` more
+    /// events fit within the limits
+    fn make_room(&mut self, bytes: usize, events: usize) {
+        while self.current_bytes + bytes > self.max_bytes
+            || self.events.len() + events > self.max_events
+        {
+            let Some(removed) = self.events.pop_front() else {
+                break;
+            };
+            self.current_bytes = self.current_bytes.saturating_sub(removed.output.len());
+        }
+    }
+
+    /// Change the limits, dropping the oldest events that no longer fit
+    fn set_limits(&mut self, max_events: usize, max_bytes: usize) {
+        self.max_events = max_events;
+        self.max_bytes = max_bytes;
+        self.make_room(0, 0);
+    }
+
     fn take(&mut self, clear: bool) -> Vec<OutputEvent> {
         let result = self.events.iter().cloned().collect();
 
@@ -124,24 +173,270 @@ impl OutputBuffer {
     }
 }
 
-/// Return the longest valid UTF-8 prefix that fits within `max_bytes`.
-fn truncate_utf8_to_bytes(value: &str, max_bytes: usize) -> String {
-    if value.len() <= max_bytes {
-        return value.to_string();
+/// Resolve a bare adapter name from the config (`path = "lldb-dap"`) on
+/// PATH, so one config works wherever the adapter happens to be installed.
+/// Paths with a directory part are used as given.
+fn resolve_adapter_path(name: &str, path: &Path) -> Result<PathBuf> {
+    let is_bare_name = path.components().count() == 1 && path.parent() == Some(Path::new(""));
+    if !is_bare_name || path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+
+    which::which(path).map_err(|_| {
+        Error::adapter_not_found(name, &[format!("{} on PATH", path.display())])
+    })
+}
+
+/// Start the adapter, or connect to it, over its configured transport
+async fn spawn_client(adapter_config: &AdapterConfig) -> Result<DapClient> {
+    match adapter_config.transport {
+        TransportMode::Stdio => DapClient::spawn(&adapter_config.path, &adapter_config.args).await,
+        TransportMode::Tcp => {
+            DapClient::spawn_tcp(
+                &adapter_config.path,
+                &adapter_config.args,
+                &adapter_config.spawn_style,
+                adapter_config.address.as_deref(),
+            )
+            .await
+        }
+    }
+}
+
+/// `[adapters]` names of the adapters that debug a kind of program, most
+/// preferred first; native programs (`None`) can use any native debugger
+fn adapter_candidates(program_type: Option<ProjectType>) -> &'static [&'static str] {
+    match program_type {
+        Some(ProjectType::Python) => &["debugpy", "python"],
+        Some(ProjectType::JavaScript | ProjectType::TypeScript) => &["js-debug"],
+        Some(ProjectType::Go) => &["go", "delve", "dlv"],
+        _ => &["lldb-dap", "lldb", "codelldb", "gdb"],
+    }
+}
+
+/// Pick the adapter for a program when none was requested. The default
+/// adapter is kept when it suits the program; otherwise the first configured
+/// adapter that does. With none configured, the preferred one is still named
+/// so that the "not found" error points at what to install.
+fn select_adapter(config: &Config, program: &Path) -> String {
+    let program_type = detect_program_type(program);
+    let candidates = adapter_candidates(program_type);
+    let default = &config.defaults.adapter;
+
+    // Native programs keep any default that isn't meant for another language
+    let is_language_adapter = [ProjectType::Python, ProjectType::JavaScript, ProjectType::Go]
+        .into_iter()
+        .any(|other| adapter_candidates(Some(other)).contains(&default.as_str()));
+    let selected = if candidates.contains(&default.as_str())
+        || (program_type.is_none() && !is_language_adapter)
+    {
+        default.clone()
+    } else {
+        candidates
+            .iter()
+            .find(|name| config.adapters.contains_key(**name))
+            .unwrap_or(&candidates[0])
+            .to_string()
+    };
+
+    tracing::info!(
+        program = %program.display(),
+        ?program_type,
+        adapter = %selected,
+        "Selected adapter"
+    );
+    selected
+}
+
+/// `outFiles` globs for a compiled JavaScript program that has a source map,
+/// so js-debug can resolve breakpoints in `.ts` files before their scripts load
+fn source_map_out_files(program: &Path) -> Option<Vec<String>> {
+    if program.extension().is_none_or(|ext| ext != "js") {
+        return None;
+    }
+
+    let mut map = program.as_os_str().to_owned();
+    map.push(".map");
+    let has_map = Path::new(&map).exists()
+        || std::fs::read_to_string(program)
+            .is_ok_and(|content| content.contains("//# sourceMappingURL="));
+    if !has_map {
+        return None;
+    }
+
+    let dir = program.parent().filter(|dir| !dir.as_os_str().is_empty())?;
+    Some(vec![
+        format!("{}/**/*.js", dir.display()),
+        "!**/node_modules/**".to_string(),
+    ])
+}
+
+/// Function to break on when emulating stop-on-entry, for adapters whose own
+/// stop-on-entry support is missing or inconsistent. GDB's
+/// `stopAtBeginningOfMainSubprogram` varies between builds and the
+/// cdt-gdb-adapter bridge used for some cuda-gdb installs ignores it, while a
+/// breakpoint on `main` stops every one of them at the start of the program.
+fn emulated_entry_symbol(adapter_name: &str) -> Option<&'static str> {
+    match adapter_name {
+        "gdb" | "cuda-gdb" => Some("main"),
+        _ => None,
+    }
+}
+
+/// The canonical name of an adapter's stop reason, so the same kind of stop
+/// reads the same whichever adapter reported it: `breakpoint`,
+/// `data-breakpoint`, `step`, `exception`, `pause`, `entry` or `goto`.
+/// Reasons outside that set are kept as the adapter gave them.
+pub(crate) fn normalize_stop_reason(reason: &str) -> &str {
+    let key = reason.trim().to_ascii_lowercase().replace(['_', ' '], "-");
+    match key.as_str() {
+        "breakpoint" | "breakpoint-hit" | "function-breakpoint" | "instruction-breakpoint" => {
+            "breakpoint"
+        }
+        "data-breakpoint" | "watchpoint" | "watchpoint-trigger" | "watchpoint-scope" => {
+            "data-breakpoint"
+        }
+        "step" | "instruction-step" | "end-stepping-range" | "function-finished"
+        | "location-reached" => "step",
+        "exception" | "signal" | "signal-received" | "panic" => "exception",
+        "pause" | "paused" | "interrupt" => "pause",
+        "entry" | "entry-point" => "entry",
+        "goto" => "goto",
+        _ => reason,
     }
+}
+
+/// Whether a stop reason means the program faulted
+fn is_fault_reason(reason: &str) -> bool {
+    normalize_stop_reason(reason) == "exception"
+}
+
+/// Whether a frame belongs to `function`. Adapters decorate frame names
+/// with parameters (`fib(int)`), offsets (`fib + 12`) and module paths
+/// (`app::fib`, `app.fib`), so only the bare name has to match.
+fn frame_is_in_function(frame_name: &str, function: &str) -> bool {
+    let name = frame_name.split(['(', ' ']).next().unwrap_or(frame_name);
+    name == function
+        || name
+            .strip_suffix(function)
+            .is_some_and(|path| path.ends_with("::") || path.ends_with('.'))
+}
 
-    let mut end = 0;
-    for (start, character) in value.char_indices() {
-        let character_end = start + character.len_utf8();
-        if character_end > max_bytes {
-            break;
+/// How the adapter suggests showing a frame: "label" for a separator that
+/// isn't a real frame, "subtle" for a frame, or a frame in a source, it
+/// deemphasizes; `None` for ordinary frames
+pub(super) fn frame_presentation(frame: &StackFrame) -> Option<&'static str> {
+    match frame.presentation_hint.as_deref() {
+        Some("label") => Some("label"),
+        Some("subtle") => Some("subtle"),
+        _ if frame
+            .source
+            .as_ref()
+            .is_some_and(|s| s.presentation_hint.as_deref() == Some("deemphasize")) =>
+        {
+            Some("subtle")
         }
-        end = character_end;
+        _ => None,
+    }
+}
+
+/// A frame as reported to clients, at `index` on its stack
+pub(super) fn frame_info(frame: &StackFrame, index: usize) -> StackFrameInfo {
+    StackFrameInfo {
+        id: frame.id,
+        index: Some(index),
+        presentation_hint: frame_presentation(frame).map(str::to_string),
+        name: frame.name.clone(),
+        source: frame.source.as_ref().and_then(|s| s.path.clone()),
+        line: Some(frame.line),
+        column: Some(frame.column),
+    }
+}
+
+/// Whether a frame is application code for `backtrace --user-only`: the
+/// adapter doesn't present it as subtle or a label, and its source file lies
+/// under one of `roots` and outside every `hidden_paths` prefix. Frames
+/// without source (libc, runtime internals) never are.
+pub(super) fn is_user_frame(
+    frame: &StackFrame,
+    roots: &[PathBuf],
+    hidden_paths: &[String],
+) -> bool {
+    if frame_presentation(frame).is_some() {
+        return false;
+    }
+    let Some(path) = frame.source.as_ref().and_then(|s| s.path.as_deref()) else {
+        return false;
+    };
+    if hidden_paths.iter().any(|prefix| path.starts_with(prefix.as_str())) {
+        return false;
+    }
+    roots.iter().any(|root| Path::new(path).starts_with(root))
+}
+
+/// `[0]=a, [1]=b, ... (94 more), [98]=y, [99]=z` for a container of
+/// `count` elements. Windows that overlap, or that the adapter filled with
+/// fewer elements than asked, still count towards the elided total.
+fn format_element_summary(count: i64, head: &[Variable], tail: &[Variable]) -> String {
+    let element = |v: &Variable| format!("{}={}", v.name, v.value);
+    let mut parts: Vec<String> = head.iter().map(element).collect();
+    let elided = count - (head.len() + tail.len()) as i64;
+    if elided > 0 {
+        parts.push(format!("... ({} more)", elided));
     }
+    parts.extend(tail.iter().map(element));
+    parts.join(", ")
+}
+
+/// What the DAP `reason` of an unverified breakpoint implies, or a guess
+/// when the adapter explains it neither way
+fn pending_reason(bp: &Breakpoint) -> Option<String> {
+    let explanation = match bp.reason.as_deref() {
+        _ if bp.verified => return None,
+        Some("pending") => "waiting for its code to load, e.g. a library or module not loaded yet",
+        Some("failed") => "the adapter could not set it and will not retry",
+        _ if bp.message.is_some() => return None,
+        _ => "the adapter gave no reason; the file may not be part of the program, or its \
+              code may not be loaded yet",
+    };
+    Some(explanation.to_string())
+}
 
-    value[..end].to_string()
+/// Breakpoint info for an instruction breakpoint, reported by address
+fn instruction_breakpoint_info(bp: &StoredBreakpoint) -> BreakpointInfo {
+    BreakpointInfo {
+        id: bp.id,
+        verified: bp.verified,
+        source: Some(bp.location.to_string()),
+        line: None,
+        message: bp.message.clone(),
+        reason: bp.reason.clone(),
+        enabled: bp.enabled,
+        condition: bp.condition.clone(),
+        hit_count: bp.hit_count,
+        on_hit: bp.on_hit.clone(),
+        warning: None,
+    }
 }
 
+/// Containers with more indexed elements than this are summarized in
+/// `locals` by their first and last elements
+const SUMMARY_MIN_ELEMENTS: i64 = 16;
+/// Elements shown from each end of a summarized container
+const SUMMARY_EDGE_ELEMENTS: i64 = 3;
+
+/// Names tried for each pointer register: the generic aliases GDB and LLDB
+/// understand first, then the x86-64 and x86 names for adapters that only
+/// know the architecture's own. AArch64's own pc and sp are the generic
+/// names, so only its frame pointer, x29, is listed.
+const PC_NAMES: &[&str] = &["$pc", "$rip", "$eip"];
+const SP_NAMES: &[&str] = &["$sp", "$rsp", "$esp"];
+const FP_NAMES: &[&str] = &["$fp", "$rbp", "$ebp", "$x29"];
+
+/// Relaunches allowed per session, so an adapter that crashes on every
+/// launch isn't restarted forever
+const MAX_RELAUNCHES: u32 = 3;
+
 /// Debug session managing a DAP connection
 pub struct DebugSession {
     /// DAP client connection
@@ -158,10 +453,20 @@ pub struct DebugSession {
     adapter_name: String,
     /// Whether we launched (vs attached)
     launched: bool,
+    /// Process attached to, for sessions started by attaching
+    attached_pid: Option<u32>,
+    /// Program arguments, for relaunching after an adapter crash
+    args: Vec<String>,
+    /// The adapter's connection was lost before the program ended
+    adapter_crashed: bool,
+    /// Times the program was relaunched after an adapter crash
+    relaunches: u32,
     /// All breakpoints by source file
     source_breakpoints: HashMap<PathBuf, Vec<StoredBreakpoint>>,
     /// Function breakpoints
     function_breakpoints: Vec<StoredBreakpoint>,
+    /// Instruction (address) breakpoints
+    instruction_breakpoints: Vec<StoredBreakpoint>,
     /// Next breakpoint ID
     next_bp_id: u32,
     /// Cached threads
@@ -176,16 +481,93 @@ pub struct DebugSession {
     last_stop: Option<StoppedEventBody>,
     /// Hit breakpoint IDs from last stop
     hit_breakpoints: Vec<u32>,
+    /// On-hit commands of the last stop's breakpoints, waiting to be run
+    pending_on_hit: Vec<String>,
+    /// Output of the on-hit commands run for the current stop
+    on_hit_output: Vec<OnHitOutput>,
+    /// Expressions evaluated at every stop
+    watches: Vec<WatchInfo>,
+    next_watch_id: u32,
+    /// Whether the watches still need evaluating for the current stop
+    watches_pending: bool,
+    /// Whether a module loaded since unverified breakpoints were last sent
+    breakpoints_recheck_pending: bool,
+    /// Modules the adapter reported loading, in load order
+    modules: Vec<dap::Module>,
+    /// Watch values for the current stop
+    watch_values: Vec<WatchValue>,
+    /// Whether the last stop was an exception or signal whose details have
+    /// not been requested yet
+    exception_info_pending: bool,
+    /// Adapter's exception details for the current stop
+    exception_info: Option<ExceptionInfoResponseBody>,
     /// Current frame index (0 = top of stack)
     current_frame_index: usize,
     /// Current frame ID (for variable inspection)
     current_frame: Option<i64>,
     /// Cached stack frames for current stop
     cached_frames: Vec<StackFrame>,
+    /// Variables already fetched at the current stop, by `variablesReference`
+    variable_cache: HashMap<i64, Vec<Variable>>,
+    /// Idle time after which the daemon ends this session, from
+    /// `--keep-alive`; without it an active session never times out
+    keep_alive: Option<std::time::Duration>,
     /// Bounded output buffer
     output_buffer: OutputBuffer,
     /// Exit code if program exited
     exit_code: Option<i32>,
+    /// Function breakpoint standing in for stop-on-entry, removed before
+    /// execution resumes from the first stop
+    entry_breakpoint: Option<EntryBreakpoint>,
+}
+
+/// A function breakpoint set to emulate stop-on-entry
+struct EntryBreakpoint {
+    /// Adapter's ID for the breakpoint, to recognise the stop it causes
+    adapter_id: Option<u32>,
+}
+
+/// What `detach` keeps of an attached session, so that `attach --resume`
+/// can attach to the same process again with the same breakpoints
+#[derive(Clone)]
+pub struct DormantSession {
+    pub pid: u32,
+    adapter_name: String,
+    breakpoints: Vec<StoredBreakpoint>,
+    next_bp_id: u32,
+    watches: Vec<WatchInfo>,
+    next_watch_id: u32,
+}
+
+impl DormantSession {
+    /// Number of breakpoints a reattach restores
+    pub fn breakpoint_count(&self) -> usize {
+        self.breakpoints.len()
+    }
+}
+
+/// Local variables of a frame
+pub struct Locals {
+    pub variables: Vec<Variable>,
+    /// Whether the adapter reported any scopes for the frame at all
+    pub has_scopes: bool,
+    /// First and last elements of each large container, by variable index
+    pub elements: Vec<Option<String>>,
+}
+
+impl Locals {
+    /// Why there are no variables, when there are none. An empty scope is
+    /// normal (nothing declared yet, or optimized out), whereas no scopes at
+    /// all usually means the adapter has no debug info for the frame.
+    pub fn note(&self) -> Option<&'static str> {
+        if !self.variables.is_empty() {
+            None
+        } else if self.has_scopes {
+            Some("no local variables; optimized out?")
+        } else {
+            Some("the adapter reported no scopes for this frame; missing debug info?")
+        }
+    }
 }
 
 impl DebugSession {
@@ -199,12 +581,47 @@ impl DebugSession {
         stop_on_entry: bool,
         initial_breakpoints: Vec<String>,
     ) -> Result<Self> {
-        let adapter_name = adapter_name.unwrap_or_else(|| config.defaults.adapter.clone());
+        let breakpoints = initial_breakpoints
+            .iter()
+            .zip(1..)
+            .map(|(location, id)| {
+                let location = BreakpointLocation::parse(location)?;
+                if matches!(
+                    location,
+                    BreakpointLocation::RelativeLine { .. } | BreakpointLocation::FunctionOffset { .. }
+                ) {
+                    return Err(Error::InvalidLocation(format!(
+                        "{} can only be resolved once the program is running; add it with \
+                         'break' after it stops",
+                        location
+                    )));
+                }
+                Ok(StoredBreakpoint::new(id, location))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::launch_with_breakpoints(config, program, args, adapter_name, stop_on_entry, breakpoints)
+            .await
+    }
+
+    /// Launch a program with breakpoints set before it starts. Disabled
+    /// breakpoints are kept but not sent to the adapter.
+    async fn launch_with_breakpoints(
+        config: &Config,
+        program: &Path,
+        args: Vec<String>,
+        adapter_name: Option<String>,
+        stop_on_entry: bool,
+        breakpoints: Vec<StoredBreakpoint>,
+    ) -> Result<Self> {
+        let adapter_name = adapter_name.unwrap_or_else(|| select_adapter(config, program));
 
-        let adapter_config = config.get_adapter(&adapter_name).ok_or_else(|| {
+        let mut adapter_config = config.get_adapter(&adapter_name).ok_or_else(|| {
             let searched = adapter_fallback_names(&adapter_name);
             Error::adapter_not_found(&adapter_name, &searched)
         })?;
+        if !adapter_config.connects_to_existing() {
+            adapter_config.path = resolve_adapter_path(&adapter_name, &adapter_config.path)?;
+        }
 
         tracing::info!(
             program = %program.display(),
@@ -217,26 +634,33 @@ impl DebugSession {
         );
 
         tracing::debug!("Spawning DAP adapter process");
-        let mut client = match adapter_config.transport {
-            TransportMode::Stdio => {
-                DapClient::spawn(&adapter_config.path, &adapter_config.args).await?
-            }
-            TransportMode::Tcp => {
-                DapClient::spawn_tcp(&adapter_config.path, &adapter_config.args, &adapter_config.spawn_style).await?
-            }
-        };
+        let mut client = spawn_client(&adapter_config).await?;
 
         // Take the event receiver
         // Initialize the adapter with timeout
         let init_timeout = std::time::Duration::from_secs(config.timeouts.dap_initialize_secs);
         let request_timeout = std::time::Duration::from_secs(config.timeouts.dap_request_secs);
         client.set_request_timeout(request_timeout);
+        client.set_eval_timeout(std::time::Duration::from_secs(config.timeouts.eval_secs));
 
         // Initialize the adapter with timeout
         tracing::debug!(timeout_secs = init_timeout.as_secs(), "Sending DAP initialize request");
-        let capabilities = client.initialize_with_timeout(&adapter_name, init_timeout).await?;
+        let adapter_id = adapter_config.dap_adapter_id(&adapter_name);
+        let dap = adapter_config.dap.over(&config.dap);
+        let capabilities = client
+            .initialize_with_timeout(adapter_id, &dap, init_timeout)
+            .await?;
         tracing::debug!(?capabilities, "DAP adapter initialized");
 
+        let entry_symbol = emulated_entry_symbol(&adapter_name)
+            .filter(|_| stop_on_entry && capabilities.supports_function_breakpoints);
+        if let Some(symbol) = entry_symbol {
+            tracing::debug!(symbol, "Emulating stop-on-entry with a function breakpoint");
+        }
+        // The adapter's own stop-on-entry is left off when it is emulated, so
+        // the program doesn't stop twice
+        let native_stop_on_entry = stop_on_entry && entry_symbol.is_none();
+
         // Launch the program (DAP: launch must come before initialized event)
         let cwd = std::env::current_dir()
             .ok()
@@ -244,22 +668,25 @@ impl DebugSession {
 
         // Build launch arguments - adapter-specific fields
         // Only set adapter-specific fields when actually using that adapter
-        let is_python = adapter_name == "debugpy";
+        let is_python = adapter_name == "debugpy" || adapter_name == "python";
         let is_go = adapter_name == "go"
             || adapter_name == "delve"
             || adapter_name == "dlv";
         let is_js_debug = adapter_name == "js-debug";
-        // Enable source maps for js-debug when debugging TS files or compiled JS with sibling .ts
+        // js-debug maps `.ts` breakpoints to the emitted JavaScript itself,
+        // given source maps are on and it knows where the output lives
+        let out_files = if is_js_debug { source_map_out_files(program) } else { None };
         let is_typescript_source = program.extension().map(|e| e == "ts").unwrap_or(false)
             || (program.extension().map(|e| e == "js").unwrap_or(false)
-                && program.with_extension("ts").exists());
+                && program.with_extension("ts").exists())
+            || out_files.is_some();
 
         let launch_args = LaunchArguments {
             program: program.to_string_lossy().into_owned(),
             args: args.clone(),
             cwd,
             env: None,
-            stop_on_entry,
+            stop_on_entry: native_stop_on_entry,
             // lldb-dap specific
             init_commands: None,
             pre_run_commands: None,
@@ -271,13 +698,13 @@ impl DebugSession {
             // Delve (Go) specific - use "exec" for precompiled binaries
             mode: if is_go { Some("exec".to_string()) } else { None },
             // Delve uses stopAtEntry instead of stopOnEntry
-            stop_at_entry: if is_go && stop_on_entry { Some(true) } else { None },
+            stop_at_entry: if is_go && native_stop_on_entry { Some(true) } else { None },
             // GDB-based adapters (gdb, cuda-gdb) use stopAtBeginningOfMainSubprogram
-            stop_at_beginning_of_main_subprogram: if (adapter_name == "gdb" || adapter_name == "cuda-gdb") && stop_on_entry { Some(true) } else { None },
+            stop_at_beginning_of_main_subprogram: if (adapter_name == "gdb" || adapter_name == "cuda-gdb") && native_stop_on_entry { Some(true) } else { None },
             // js-debug specific - type selects the debugger (pwa-node for Node.js)
             type_attr: if is_js_debug { Some("pwa-node".to_string()) } else { None },
             source_maps: if is_js_debug && is_typescript_source { Some(true) } else { None },
-            out_files: None,
+            out_files,
             runtime_executable: None,
             runtime_args: None,
             skip_files: None,
@@ -307,63 +734,66 @@ impl DebugSession {
         // This is required for adapters that don't support stopOnEntry (e.g., cdt-gdb-adapter)
         let mut source_breakpoints = HashMap::new();
         let mut function_breakpoints = Vec::new();
-        let mut next_bp_id = 1;
+        let mut instruction_breakpoints = Vec::new();
+        let next_bp_id = breakpoints.iter().map(|bp| bp.id).max().unwrap_or(0) + 1;
+        let mut entry_breakpoint = None;
 
-        if !initial_breakpoints.is_empty() {
-            tracing::debug!(count = initial_breakpoints.len(), "Setting initial breakpoints");
+        if !breakpoints.is_empty() || entry_symbol.is_some() {
+            tracing::debug!(count = breakpoints.len(), "Setting initial breakpoints");
 
             // Group breakpoints by type (source vs function)
             let mut source_bps: HashMap<PathBuf, Vec<dap::SourceBreakpoint>> = HashMap::new();
             let mut function_bps: Vec<dap::FunctionBreakpoint> = Vec::new();
+            let mut instruction_bps: Vec<InstructionBreakpoint> = Vec::new();
 
-            for bp_str in &initial_breakpoints {
-                let location = BreakpointLocation::parse(bp_str)?;
-                let bp_id = next_bp_id;
-                next_bp_id += 1;
+            for stored in breakpoints {
+                let condition = stored.condition.clone();
+                let hit_condition = stored.hit_count.map(|n| n.to_string());
 
-                match &location {
+                match &stored.location {
                     BreakpointLocation::Line { file, line } => {
-                        source_bps
-                            .entry(file.clone())
-                            .or_default()
-                            .push(dap::SourceBreakpoint {
-                                line: *line,
-                                column: None,
-                                condition: None,
-                                hit_condition: None,
-                                log_message: None,
-                            });
+                        let file = file.clone();
+                        if stored.enabled {
+                            source_bps
+                                .entry(file.clone())
+                                .or_default()
+                                .push(dap::SourceBreakpoint {
+                                    line: *line,
+                                    column: None,
+                                    condition,
+                                    hit_condition,
+                                    log_message: None,
+                                });
+                        }
                         source_breakpoints
-                            .entry(file.clone())
+                            .entry(file)
                             .or_insert_with(Vec::new)
-                            .push(StoredBreakpoint {
-                                id: bp_id,
-                                location,
-                                condition: None,
-                                hit_count: None,
-                                enabled: true,
-                                verified: false,
-                                actual_line: None,
-                                message: None,
-                            });
+                            .push(stored);
                     }
                     BreakpointLocation::Function { name } => {
-                        function_bps.push(dap::FunctionBreakpoint {
-                            name: name.clone(),
-                            condition: None,
-                            hit_condition: None,
-                        });
-                        function_breakpoints.push(StoredBreakpoint {
-                            id: bp_id,
-                            location,
-                            condition: None,
-                            hit_count: None,
-                            enabled: true,
-                            verified: false,
-                            actual_line: None,
-                            message: None,
-                        });
+                        if stored.enabled {
+                            function_bps.push(dap::FunctionBreakpoint {
+                                name: name.clone(),
+                                condition,
+                                hit_condition,
+                            });
+                        }
+                        function_breakpoints.push(stored);
+                    }
+                    BreakpointLocation::Address { addr } => {
+                        if stored.enabled {
+                            instruction_bps.push(InstructionBreakpoint {
+                                instruction_reference: format!("0x{:x}", addr),
+                                offset: None,
+                                condition,
+                                hit_condition,
+                            });
+                        }
+                        instruction_breakpoints.push(stored);
                     }
+                    // Rejected in `launch` and resolved before being stored otherwise
+                    BreakpointLocation::RelativeLine { .. }
+                    | BreakpointLocation::FunctionOffset { .. } => {}
                 }
             }
 
@@ -371,14 +801,25 @@ impl DebugSession {
             for (file, bps) in source_bps {
                 let results = client.set_breakpoints(&file, bps).await?;
                 if let Some(stored_bps) = source_breakpoints.get_mut(&file) {
-                    for (stored, result) in stored_bps.iter_mut().zip(results.iter()) {
+                    let enabled = stored_bps.iter_mut().filter(|bp| bp.enabled);
+                    for (stored, result) in enabled.zip(results.iter()) {
                         stored.verified = result.verified;
+                        stored.adapter_id = result.id;
                         stored.actual_line = result.line;
-                        stored.message = result.message.clone();
+                        stored.record_message(result);
                     }
                 }
             }
 
+            // The entry breakpoint goes last, after those stored for the user
+            if let Some(symbol) = entry_symbol {
+                function_bps.push(dap::FunctionBreakpoint {
+                    name: symbol.to_string(),
+                    condition: None,
+                    hit_condition: None,
+                });
+            }
+
             // Set function breakpoints
             if !function_bps.is_empty() {
                 if !capabilities.supports_function_breakpoints {
@@ -388,10 +829,35 @@ impl DebugSession {
                     ));
                 }
                 let results = client.set_function_breakpoints(function_bps).await?;
-                for (stored, result) in function_breakpoints.iter_mut().zip(results.iter()) {
+                let enabled = function_breakpoints.iter_mut().filter(|bp| bp.enabled);
+                for (stored, result) in enabled.zip(results.iter()) {
                     stored.verified = result.verified;
+                    stored.adapter_id = result.id;
                     stored.actual_line = result.line;
-                    stored.message = result.message.clone();
+                    stored.record_message(result);
+                }
+                if entry_symbol.is_some() {
+                    entry_breakpoint = Some(EntryBreakpoint {
+                        adapter_id: results
+                            .get(function_breakpoints.iter().filter(|bp| bp.enabled).count())
+                            .and_then(|bp| bp.id),
+                    });
+                }
+            }
+
+            // Set instruction breakpoints
+            if !instruction_bps.is_empty() {
+                if !capabilities.supports_instruction_breakpoints {
+                    return Err(Error::Internal(
+                        "Debug adapter does not support instruction breakpoints.".to_string(),
+                    ));
+                }
+                let results = client.set_instruction_breakpoints(instruction_bps).await?;
+                let enabled = instruction_breakpoints.iter_mut().filter(|bp| bp.enabled);
+                for (stored, result) in enabled.zip(results.iter()) {
+                    stored.verified = result.verified;
+                    stored.adapter_id = result.id;
+                    stored.record_message(result);
                 }
             }
         }
@@ -408,13 +874,14 @@ impl DebugSession {
 
         // Initial state: Stopped if stop_on_entry requested, otherwise Running
         // Note: If initial breakpoints are set, the program will stop when it hits them
-        let initial_state = if stop_on_entry {
+        // An emulated entry stop is only known once its breakpoint is hit
+        let initial_state = if native_stop_on_entry {
             SessionState::Stopped
         } else {
             SessionState::Running
         };
 
-        Ok(Self {
+        let mut session = Self {
             client,
             events_rx,
             state: initial_state,
@@ -422,8 +889,13 @@ impl DebugSession {
             program: program.to_path_buf(),
             adapter_name,
             launched: true,
+            attached_pid: None,
+            args,
+            adapter_crashed: false,
+            relaunches: 0,
             source_breakpoints,
             function_breakpoints,
+            instruction_breakpoints,
             next_bp_id,
             threads: Vec::new(),
             selected_thread: None,
@@ -431,15 +903,61 @@ impl DebugSession {
             stopped_reason: None,
             last_stop: None,
             hit_breakpoints: Vec::new(),
+            pending_on_hit: Vec::new(),
+            on_hit_output: Vec::new(),
+            watches: Vec::new(),
+            next_watch_id: 1,
+            watches_pending: false,
+            breakpoints_recheck_pending: false,
+            modules: Vec::new(),
+            watch_values: Vec::new(),
+            exception_info_pending: false,
+            exception_info: None,
             current_frame_index: 0,
             current_frame: None,
             cached_frames: Vec::new(),
+            variable_cache: HashMap::new(),
+            keep_alive: None,
             output_buffer: OutputBuffer::new(
                 config.output.max_events,
                 config.output.max_bytes_mb * 1024 * 1024,
             ),
             exit_code: None,
-        })
+            entry_breakpoint,
+        };
+
+        if entry_symbol.is_some() {
+            session.wait_for_entry_stop(request_timeout).await;
+        }
+        Ok(session)
+    }
+
+    /// Start the adapter `adapter_name` and initialize it, without launching
+    /// or attaching to anything, to see which features it reports. The
+    /// adapter is disconnected again straight away.
+    pub async fn probe_capabilities(config: &Config, adapter_name: &str) -> Result<Capabilities> {
+        let mut adapter_config = config.get_adapter(adapter_name).ok_or_else(|| {
+            let searched = adapter_fallback_names(adapter_name);
+            Error::adapter_not_found(adapter_name, &searched)
+        })?;
+        if !adapter_config.connects_to_existing() {
+            adapter_config.path = resolve_adapter_path(adapter_name, &adapter_config.path)?;
+        }
+
+        tracing::info!(adapter = %adapter_name, "Probing adapter capabilities");
+        let mut client = spawn_client(&adapter_config).await?;
+        let request_timeout = std::time::Duration::from_secs(config.timeouts.dap_request_secs);
+        client.set_request_timeout(request_timeout);
+        let init_timeout = std::time::Duration::from_secs(config.timeouts.dap_initialize_secs);
+        let adapter_id = adapter_config.dap_adapter_id(adapter_name);
+        let dap = adapter_config.dap.over(&config.dap);
+        let capabilities = client
+            .initialize_with_timeout(adapter_id, &dap, init_timeout)
+            .await;
+        if let Err(e) = client.terminate().await {
+            tracing::debug!("Stopping probed adapter {}: {}", adapter_name, e);
+        }
+        capabilities
     }
 
     /// Create a new debug session by attaching to a process
@@ -450,10 +968,13 @@ impl DebugSession {
     ) -> Result<Self> {
         let adapter_name = adapter_name.unwrap_or_else(|| config.defaults.adapter.clone());
 
-        let adapter_config = config.get_adapter(&adapter_name).ok_or_else(|| {
+        let mut adapter_config = config.get_adapter(&adapter_name).ok_or_else(|| {
             let searched = adapter_fallback_names(&adapter_name);
             Error::adapter_not_found(&adapter_name, &searched)
         })?;
+        if !adapter_config.connects_to_existing() {
+            adapter_config.path = resolve_adapter_path(&adapter_name, &adapter_config.path)?;
+        }
 
         tracing::info!(
             pid,
@@ -462,27 +983,28 @@ impl DebugSession {
             "Attaching to process"
         );
 
-        let mut client = match adapter_config.transport {
-            TransportMode::Stdio => {
-                DapClient::spawn(&adapter_config.path, &adapter_config.args).await?
-            }
-            TransportMode::Tcp => {
-                DapClient::spawn_tcp(&adapter_config.path, &adapter_config.args, &adapter_config.spawn_style).await?
-            }
-        };
+        let mut client = spawn_client(&adapter_config).await?;
 
         // Get configured timeouts
         let init_timeout = std::time::Duration::from_secs(config.timeouts.dap_initialize_secs);
         let request_timeout = std::time::Duration::from_secs(config.timeouts.dap_request_secs);
         client.set_request_timeout(request_timeout);
+        client.set_eval_timeout(std::time::Duration::from_secs(config.timeouts.eval_secs));
 
-        let capabilities = client.initialize_with_timeout(&adapter_name, init_timeout).await?;
+        let adapter_id = adapter_config.dap_adapter_id(&adapter_name);
+        let dap = adapter_config.dap.over(&config.dap);
+        let capabilities = client
+            .initialize_with_timeout(adapter_id, &dap, init_timeout)
+            .await?;
 
-        // Attach to the process (DAP: attach must come before initialized event)
+        // Attach to the process (DAP: attach must come before initialized
+        // event). It stays stopped, so breakpoints can be set before the
+        // first `continue`.
         client
             .attach(AttachArguments {
                 pid,
                 wait_for: None,
+                stop_on_entry: Some(true),
             })
             .await?;
 
@@ -497,7 +1019,7 @@ impl DebugSession {
             .take_event_receiver()
             .ok_or_else(|| Error::Internal("Failed to get event receiver".to_string()))?;
 
-        Ok(Self {
+        let mut session = Self {
             client,
             events_rx,
             state: SessionState::Stopped, // Attached processes start stopped
@@ -505,8 +1027,13 @@ impl DebugSession {
             program: PathBuf::from(format!("pid:{}", pid)),
             adapter_name,
             launched: false,
+            attached_pid: Some(pid),
+            args: Vec::new(),
+            adapter_crashed: false,
+            relaunches: 0,
             source_breakpoints: HashMap::new(),
             function_breakpoints: Vec::new(),
+            instruction_breakpoints: Vec::new(),
             next_bp_id: 1,
             threads: Vec::new(),
             selected_thread: None,
@@ -514,14 +1041,56 @@ impl DebugSession {
             stopped_reason: Some("attach".to_string()),
             last_stop: None,
             hit_breakpoints: Vec::new(),
+            pending_on_hit: Vec::new(),
+            on_hit_output: Vec::new(),
+            watches: Vec::new(),
+            next_watch_id: 1,
+            watches_pending: false,
+            breakpoints_recheck_pending: false,
+            modules: Vec::new(),
+            watch_values: Vec::new(),
+            exception_info_pending: false,
+            exception_info: None,
             current_frame_index: 0,
             current_frame: None,
             cached_frames: Vec::new(),
+            variable_cache: HashMap::new(),
+            keep_alive: None,
             output_buffer: OutputBuffer::new(
                 config.output.max_events,
                 config.output.max_bytes_mb * 1024 * 1024,
             ),
             exit_code: None,
+            entry_breakpoint: None,
+        };
+        // Apply what the adapter reported during configurationDone: the
+        // stopped thread, or a continued event if it resumed the process
+        // regardless
+        session.drain_pending_events();
+        Ok(session)
+    }
+
+    /// Attach again to the process of a detached session, restoring its
+    /// breakpoints and watches
+    pub async fn reattach(config: &Config, dormant: DormantSession) -> Result<Self> {
+        let mut session = Self::attach(config, dormant.pid, Some(dormant.adapter_name)).await?;
+        session.next_bp_id = dormant.next_bp_id;
+        session.watches = dormant.watches;
+        session.next_watch_id = dormant.next_watch_id;
+        session.restore_breakpoints(dormant.breakpoints).await;
+        Ok(session)
+    }
+
+    /// What to keep of this session when detaching, if it can be resumed:
+    /// only attached processes have a pid to attach to again
+    pub fn dormant(&self) -> Option<DormantSession> {
+        Some(DormantSession {
+            pid: self.attached_pid?,
+            adapter_name: self.adapter_name.clone(),
+            breakpoints: self.stored_breakpoints(),
+            next_bp_id: self.next_bp_id,
+            watches: self.watches.clone(),
+            next_watch_id: self.next_watch_id,
         })
     }
 
@@ -540,6 +1109,18 @@ impl DebugSession {
         &self.program
     }
 
+    /// Directories holding the program's own code: the program's directory.
+    /// Clients send the program's absolute path, so this doesn't depend on
+    /// where the daemon happened to be started.
+    pub fn user_code_roots(&self) -> Vec<PathBuf> {
+        self.program
+            .parent()
+            .filter(|dir| dir.is_absolute())
+            .map(Path::to_path_buf)
+            .into_iter()
+            .collect()
+    }
+
     /// Get adapter name
     pub fn adapter_name(&self) -> &str {
         &self.adapter_name
@@ -560,6 +1141,16 @@ impl DebugSession {
         self.exit_code
     }
 
+    /// Idle time after which the daemon ends this session
+    pub fn keep_alive(&self) -> Option<std::time::Duration> {
+        self.keep_alive
+    }
+
+    /// Override the daemon's idle timeout for this session
+    pub fn set_keep_alive(&mut self, keep_alive: Option<std::time::Duration>) {
+        self.keep_alive = keep_alive;
+    }
+
     /// Process pending events
     pub async fn process_events(&mut self) -> Result<Vec<Event>> {
         let mut events = Vec::new();
@@ -584,40 +1175,78 @@ impl DebugSession {
     fn handle_event(&mut self, event: &Event) {
         match event {
             Event::Stopped(body) => {
+                let mut body = body.clone();
+                if self.is_entry_stop(&body) {
+                    // Only the internal breakpoint is hidden: a user's own
+                    // breakpoint at the entry point still counts as hit
+                    if let Some(EntryBreakpoint { adapter_id: Some(id) }) = &self.entry_breakpoint {
+                        body.hit_breakpoint_ids.retain(|hit| hit != id);
+                    }
+                    if body.hit_breakpoint_ids.is_empty() {
+                        body.reason = "entry".to_string();
+                    }
+                }
                 self.state = SessionState::Stopped;
                 self.stopped_thread = body.thread_id;
                 self.selected_thread = body.thread_id;
-                self.stopped_reason = Some(body.reason.clone());
+                self.stopped_reason = Some(normalize_stop_reason(&body.reason).to_string());
                 self.last_stop = Some(body.clone());
                 self.hit_breakpoints = body.hit_breakpoint_ids.clone();
+                self.pending_on_hit = self.on_hit_commands(&body.hit_breakpoint_ids);
+                self.count_hits(&body.hit_breakpoint_ids);
+                self.on_hit_output.clear();
+                self.watches_pending = !self.watches.is_empty();
+                self.watch_values.clear();
+                self.exception_info_pending = is_fault_reason(&body.reason);
+                self.exception_info = None;
                 // Reset frame tracking on stop - user starts at top of stack
                 self.current_frame = None;
                 self.current_frame_index = 0;
                 self.cached_frames.clear();
+                self.variable_cache.clear();
                 tracing::debug!("Stopped: {:?}", body);
+                super::event_tap::send(|| {
+                    let reason = normalize_stop_reason(&body.reason);
+                    SessionEvent::Stopped {
+                        reason: reason.to_string(),
+                        raw_reason: (reason != body.reason).then(|| body.reason.clone()),
+                        description: body.description.clone(),
+                        thread_id: body.thread_id,
+                        all_threads_stopped: body.all_threads_stopped,
+                        hit_breakpoint_ids: body.hit_breakpoint_ids.clone(),
+                    }
+                });
             }
-            Event::Continued { thread_id, .. } => {
-                self.state = SessionState::Running;
-                self.selected_thread = None;
-                self.stopped_thread = None;
-                self.stopped_reason = None;
-                self.last_stop = None;
-                self.hit_breakpoints.clear();
-                self.current_frame = None;
-                self.current_frame_index = 0;
-                self.cached_frames.clear();
+            Event::Continued {
+                thread_id,
+                all_threads_continued,
+            } => {
+                self.enter_running();
                 tracing::debug!("Continued: thread {}", thread_id);
+                super::event_tap::send(|| SessionEvent::Continued {
+                    thread_id: *thread_id,
+                    all_threads_continued: *all_threads_continued,
+                });
             }
             Event::Exited(body) => {
                 self.state = SessionState::Exited;
                 self.selected_thread = None;
                 self.exit_code = Some(body.exit_code);
                 tracing::info!("Program exited with code {}", body.exit_code);
+                super::event_tap::send(|| SessionEvent::Exited {
+                    exit_code: body.exit_code,
+                });
             }
             Event::Terminated(_) => {
+                // The reader task tells a crash from the adapter ending
+                // after the program did
+                if self.client.adapter_crashed() {
+                    self.adapter_crashed = true;
+                }
                 self.state = SessionState::Exited;
                 self.selected_thread = None;
                 tracing::info!("Session terminated");
+                super::event_tap::send(|| SessionEvent::Terminated);
             }
             Event::Output(body) => {
                 let category = body.category.clone().unwrap_or_else(|| "console".to_string());
@@ -625,8 +1254,15 @@ impl DebugSession {
             }
             Event::Thread(body) => {
                 tracing::debug!("Thread {}: {}", body.thread_id, body.reason);
+                super::event_tap::send(|| SessionEvent::Thread {
+                    reason: body.reason.clone(),
+                    thread_id: body.thread_id,
+                });
                 // Update thread list if needed
-                if body.reason == "exited" {
+                if body.reason == "started" {
+                    // Fetched again when next needed
+                    self.threads.clear();
+                } else if body.reason == "exited" {
                     self.threads.retain(|t| t.id != body.thread_id);
                     // Clear selected thread if it was the one that exited
                     if self.selected_thread == Some(body.thread_id) {
@@ -636,18 +1272,119 @@ impl DebugSession {
             }
             Event::Breakpoint { reason, breakpoint } => {
                 tracing::debug!("Breakpoint {}: {:?}", reason, breakpoint);
-                // Update breakpoint status if we get change notifications
-                if let Some(bp_id) = breakpoint.id {
-                    self.update_breakpoint_from_event(bp_id, breakpoint);
+                self.update_breakpoint_from_event(breakpoint);
+            }
+            Event::Module { reason, module } => {
+                tracing::debug!("Module {}: {}", reason, module.name);
+                self.modules.retain(|known| known.id != module.id);
+                if reason != "removed" {
+                    self.modules.push(module.clone());
+                    if self.has_unverified_breakpoints() {
+                        self.breakpoints_recheck_pending = true;
+                    }
+                }
+            }
+            Event::Invalidated { areas, thread_id } => {
+                tracing::debug!("Invalidated {:?} (thread {:?})", areas, thread_id);
+                let covers = |area: &str| {
+                    areas.is_empty() || areas.iter().any(|a| a == "all" || a == area)
+                };
+                let current_thread = self.selected_thread.or(self.stopped_thread);
+                if covers("stacks") && thread_id.is_none_or(|id| Some(id) == current_thread) {
+                    // Frame IDs may have changed; the selection is kept by index
+                    self.cached_frames.clear();
+                    self.current_frame = None;
+                }
+                if covers("variables") {
+                    self.variable_cache.clear();
+                    if self.state == SessionState::Stopped {
+                        self.watches_pending = !self.watches.is_empty();
+                    }
+                }
+            }
+            Event::LoadedSource { reason, source } => {
+                tracing::debug!("Source {}: {:?}", reason, source.path);
+                // Only a source with pending breakpoints of its own is worth re-sending
+                let pending_here = source.path.as_ref().is_some_and(|path| {
+                    self.source_breakpoints
+                        .get(Path::new(path))
+                        .is_some_and(|bps| bps.iter().any(|bp| bp.enabled && !bp.verified))
+                });
+                if reason != "removed" && pending_here {
+                    self.breakpoints_recheck_pending = true;
                 }
             }
             _ => {}
         }
     }
 
+    /// Wait for the program to reach the emulated stop-on-entry breakpoint,
+    /// so `start` returns stopped just as it does with native stop-on-entry
+    async fn wait_for_entry_stop(&mut self, timeout: std::time::Duration) {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.state == SessionState::Running {
+            match tokio::time::timeout_at(deadline, self.events_rx.recv()).await {
+                Ok(Some(event)) => self.handle_event(&event),
+                Ok(None) => break,
+                Err(_) => {
+                    tracing::warn!("Program did not reach the entry breakpoint in time");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Handle for cancelling this session's adapter requests while the actor
+    /// is busy waiting on one
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.client.cancel_handle()
+    }
+
+    /// Whether a stop was caused by the emulated stop-on-entry breakpoint
+    fn is_entry_stop(&self, body: &StoppedEventBody) -> bool {
+        match &self.entry_breakpoint {
+            Some(EntryBreakpoint { adapter_id: Some(id) }) => body.hit_breakpoint_ids.contains(id),
+            // Without an ID, the first breakpoint stop that names no user
+            // breakpoint is taken to be the entry stop
+            Some(EntryBreakpoint { adapter_id: None }) => {
+                normalize_stop_reason(&body.reason) == "breakpoint"
+                    && body.hit_breakpoint_ids.is_empty()
+            }
+            None => false,
+        }
+    }
+
+    /// Remove the emulated stop-on-entry breakpoint, leaving the user's
+    /// function breakpoints in place
+    async fn clear_entry_breakpoint(&mut self) -> Result<()> {
+        if self.entry_breakpoint.take().is_some() {
+            let breakpoints = self.collect_function_breakpoints();
+            let results = self.client.set_function_breakpoints(breakpoints).await?;
+            self.update_function_breakpoint_status(&results);
+        }
+        Ok(())
+    }
+
     /// Update breakpoint status from a breakpoint event
-    fn update_breakpoint_from_event(&mut self, _id: u32, bp: &dap::Breakpoint) {
-        // Try to match by line/source to update verification status
+    fn update_breakpoint_from_event(&mut self, bp: &dap::Breakpoint) {
+        // Match by the adapter's ID, which it sends for every breakpoint it knows
+        if let Some(id) = bp.id {
+            let stored = self
+                .source_breakpoints
+                .values_mut()
+                .flatten()
+                .chain(&mut self.function_breakpoints)
+                .chain(&mut self.instruction_breakpoints)
+                .find(|stored| stored.adapter_id == Some(id));
+            if let Some(stored) = stored {
+                stored.verified = bp.verified;
+                stored.actual_line = bp.line.or(stored.actual_line);
+                stored.record_message(bp);
+                return;
+            }
+        }
+
+        // Otherwise try to match by line/source
         if let (Some(source), Some(line)) = (&bp.source, bp.line) {
             if let Some(path) = &source.path {
                 let path = PathBuf::from(path);
@@ -657,7 +1394,7 @@ impl DebugSession {
                             if *stored_line == line || stored.actual_line == Some(line) {
                                 stored.verified = bp.verified;
                                 stored.actual_line = bp.line;
-                                stored.message = bp.message.clone();
+                                stored.record_message(bp);
                                 break;
                             }
                         }
@@ -667,58 +1404,335 @@ impl DebugSession {
         }
     }
 
-    /// Buffer output for later retrieval.
-    fn buffer_output(&mut self, category: &str, output: &str) {
-        self.output_buffer.push(category, output);
+    fn has_unverified_breakpoints(&self) -> bool {
+        self.source_breakpoints
+            .values()
+            .flatten()
+            .chain(&self.function_breakpoints)
+            .any(|bp| bp.enabled && !bp.verified)
     }
 
-    /// Add a breakpoint
-    pub async fn add_breakpoint(
-        &mut self,
-        location: BreakpointLocation,
-        condition: Option<String>,
-        hit_count: Option<u32>,
-    ) -> Result<BreakpointInfo> {
-        let bp_id = self.next_bp_id;
-        self.next_bp_id += 1;
+    /// Send unverified breakpoints again after a module loaded, for adapters
+    /// that don't bind them to new code on their own
+    pub async fn recheck_pending_breakpoints(&mut self) {
+        if !std::mem::take(&mut self.breakpoints_recheck_pending) {
+            return;
+        }
 
-        match &location {
-            BreakpointLocation::Line { file, line: _ } => {
-                // Add to our tracking
-                let stored = StoredBreakpoint {
-                    id: bp_id,
-                    location: location.clone(),
-                    condition: condition.clone(),
-                    hit_count,
-                    enabled: true,
-                    verified: false,
-                    actual_line: None,
-                    message: None,
-                };
+        let files: Vec<PathBuf> = self
+            .source_breakpoints
+            .iter()
+            .filter(|(_, bps)| bps.iter().any(|bp| bp.enabled && !bp.verified))
+            .map(|(file, _)| file.clone())
+            .collect();
+        for file in files {
+            let source_bps = self.collect_source_breakpoints(&file);
+            match self.client.set_breakpoints(&file, source_bps).await {
+                Ok(results) => self.update_source_breakpoint_status(&file, &results),
+                Err(e) => tracing::debug!("Re-sending breakpoints in {}: {}", file.display(), e),
+            }
+        }
 
-                self.source_breakpoints
-                    .entry(file.clone())
-                    .or_default()
-                    .push(stored);
+        // Re-sending function breakpoints would drop the entry breakpoint
+        let function_pending =
+            self.function_breakpoints.iter().any(|bp| bp.enabled && !bp.verified);
+        if function_pending && self.entry_breakpoint.is_none() {
+            let function_bps = self.collect_function_breakpoints();
+            match self.client.set_function_breakpoints(function_bps).await {
+                Ok(results) => self.update_function_breakpoint_status(&results),
+                Err(e) => tracing::debug!("Re-sending function breakpoints: {}", e),
+            }
+        }
+    }
 
-                // Send to adapter
-                let source_bps = self.collect_source_breakpoints(file);
-                let results = match self.client.set_breakpoints(file, source_bps).await {
-                    Ok(results) => results,
-                    Err(error) => {
-                        if let Some(breakpoints) = self.source_breakpoints.get_mut(file) {
-                            breakpoints.retain(|breakpoint| breakpoint.id != bp_id);
-                        }
-                        return Err(error);
-                    }
-                };
+    /// Collect the on-hit commands of the breakpoints the adapter reported as hit
+    fn on_hit_commands(&self, adapter_ids: &[u32]) -> Vec<String> {
+        self.source_breakpoints
+            .values()
+            .flatten()
+            .chain(&self.function_breakpoints)
+            .chain(&self.instruction_breakpoints)
+            .filter(|bp| bp.adapter_id.is_some_and(|id| adapter_ids.contains(&id)))
+            .flat_map(|bp| bp.on_hit.iter().cloned())
+            .collect()
+    }
 
-                // Update verification status
-                self.update_source_breakpoint_status(file, &results);
+    fn count_hits(&mut self, adapter_ids: &[u32]) {
+        self.source_breakpoints
+            .values_mut()
+            .flatten()
+            .chain(&mut self.function_breakpoints)
+            .chain(&mut self.instruction_breakpoints)
+            .filter(|bp| bp.adapter_id.is_some_and(|id| adapter_ids.contains(&id)))
+            .for_each(|bp| bp.hits += 1);
+    }
 
-                // Find our breakpoint in results
-                let info = self.get_breakpoint_info(bp_id)?;
-                Ok(info)
+    /// Take the on-hit commands that still need to run for the current stop
+    pub fn take_pending_on_hit(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.pending_on_hit)
+    }
+
+    /// Record the output of the on-hit commands for the current stop
+    pub fn set_on_hit_output(&mut self, output: Vec<OnHitOutput>) {
+        self.on_hit_output = output;
+    }
+
+    /// Output of the on-hit commands run for the current stop
+    pub fn on_hit_output(&self) -> &[OnHitOutput] {
+        &self.on_hit_output
+    }
+
+    /// Add a watch, evaluated from the next stop on
+    pub fn add_watch(&mut self, expression: String, function: Option<String>) -> WatchInfo {
+        let watch = WatchInfo {
+            id: self.next_watch_id,
+            expression,
+            function,
+        };
+        self.next_watch_id += 1;
+        self.watches.push(watch.clone());
+        watch
+    }
+
+    /// Remove one watch, or all of them
+    pub fn remove_watch(&mut self, id: Option<u32>) -> Result<()> {
+        match id {
+            Some(id) => {
+                let index = self
+                    .watches
+                    .iter()
+                    .position(|w| w.id == id)
+                    .ok_or_else(|| Error::Config(format!("No watch with ID {}", id)))?;
+                self.watches.remove(index);
+            }
+            None => self.watches.clear(),
+        }
+        self.watch_values.retain(|v| id.is_some_and(|id| v.id != id));
+        Ok(())
+    }
+
+    pub fn watches(&self) -> &[WatchInfo] {
+        &self.watches
+    }
+
+    /// Values of the watches in scope at the current stop
+    pub fn watch_values(&self) -> &[WatchValue] {
+        &self.watch_values
+    }
+
+    /// Evaluate the watches for the current stop, once per stop. A watch
+    /// scoped to a function is skipped unless that function is on the stack,
+    /// and is evaluated in its innermost frame, so a recursive call shows
+    /// the deepest activation.
+    pub async fn evaluate_watches(&mut self) {
+        if !std::mem::take(&mut self.watches_pending) {
+            return;
+        }
+
+        // Scoped watches need the whole stack, not just the cached top frames
+        let frames = if self.watches.iter().any(|w| w.function.is_some()) {
+            let stack = match self.get_thread_id().await {
+                Ok(thread_id) => self.client.stack_trace(thread_id, 0).await,
+                Err(e) => Err(e),
+            };
+            match stack {
+                Ok(frames) => frames,
+                Err(e) => {
+                    tracing::debug!("Stack trace for scoped watches failed: {}", e);
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let mut values = Vec::new();
+        for watch in self.watches.clone() {
+            let frame_id = match &watch.function {
+                Some(function) => {
+                    match frames.iter().find(|f| frame_is_in_function(&f.name, function)) {
+                        Some(frame) => Some(frame.id),
+                        None => continue,
+                    }
+                }
+                None => None,
+            };
+            let (value, error) = match self.evaluate(&watch.expression, frame_id, "watch").await {
+                Ok(result) => (Some(result.result), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            values.push(WatchValue {
+                id: watch.id,
+                expression: watch.expression,
+                value,
+                error,
+            });
+        }
+        self.watch_values = values;
+    }
+
+    /// Ask the adapter for details of the exception or signal behind the
+    /// current stop, once per stop. Adapters without `exceptionInfo`, or
+    /// that fail the request, leave only the stopped event's text.
+    pub async fn fetch_exception_info(&mut self) {
+        if !std::mem::take(&mut self.exception_info_pending)
+            || !self.capabilities.supports_exception_info_request
+        {
+            return;
+        }
+        let Some(thread_id) = self.stopped_thread else {
+            return;
+        };
+        match self.client.exception_info(thread_id).await {
+            Ok(info) => self.exception_info = Some(info),
+            Err(e) => tracing::debug!("exceptionInfo request failed: {}", e),
+        }
+    }
+
+    /// Adapter's exception details for the current stop, if fetched
+    pub fn exception_info(&self) -> Option<&ExceptionInfoResponseBody> {
+        self.exception_info.as_ref()
+    }
+
+    /// Buffer output for later retrieval.
+    fn buffer_output(&mut self, category: &str, output: &str) {
+        super::output_tap::send(category, output);
+        super::event_tap::send(|| SessionEvent::Output {
+            category: category.to_string(),
+            output: output.to_string(),
+        });
+        self.output_buffer.push(category, output);
+    }
+
+    /// Turn a relative location into the file and line it points at now,
+    /// so the stored breakpoint stays put when the program moves on
+    async fn resolve_location(
+        &mut self,
+        location: BreakpointLocation,
+    ) -> Result<BreakpointLocation> {
+        match location {
+            BreakpointLocation::RelativeLine { file, offset } => {
+                let frame = self.frame_at(self.current_frame_index).await?;
+                let path = frame.source.as_ref().and_then(|s| s.path.clone());
+                let path = path.ok_or_else(|| {
+                    Error::InvalidLocation(format!(
+                        "{} has no source location to offset from",
+                        frame.name
+                    ))
+                })?;
+                if let Some(file) = &file {
+                    if !Path::new(&path).ends_with(file) {
+                        return Err(Error::InvalidLocation(format!(
+                            "stopped in {}, not {}",
+                            path,
+                            file.display()
+                        )));
+                    }
+                }
+                let line = i64::from(frame.line) + i64::from(offset);
+                let line = u32::try_from(line).ok().filter(|&line| line > 0).ok_or_else(|| {
+                    Error::InvalidLocation(format!(
+                        "{:+} from line {} is before the start of the file",
+                        offset, frame.line
+                    ))
+                })?;
+                Ok(BreakpointLocation::Line { file: PathBuf::from(path), line })
+            }
+            BreakpointLocation::FunctionOffset { name, offset } => {
+                let (file, start) = self.function_start(&name).await?;
+                Ok(BreakpointLocation::Line { file, line: start + offset })
+            }
+            location => Ok(location),
+        }
+    }
+
+    /// Where a breakpoint on `name` would stop, found by briefly adding one
+    async fn function_start(&mut self, name: &str) -> Result<(PathBuf, u32)> {
+        if !self.supports_function_breakpoints() {
+            return Err(Error::Internal(
+                "Debug adapter does not support function breakpoints, needed to find where \
+                 a function starts. Use file:line format instead."
+                    .to_string(),
+            ));
+        }
+
+        let mut probe = self.collect_function_breakpoints();
+        probe.push(FunctionBreakpoint {
+            name: name.to_string(),
+            condition: None,
+            hit_condition: None,
+        });
+        let probed = self.client.set_function_breakpoints(probe).await;
+
+        // Put the real function breakpoints back whether or not the probe worked
+        let restored = self
+            .client
+            .set_function_breakpoints(self.collect_function_breakpoints())
+            .await?;
+        self.update_function_breakpoint_status(&restored);
+
+        let found = probed?.pop().filter(|bp| bp.verified).and_then(|bp| {
+            let path = bp.source?.path?;
+            Some((PathBuf::from(path), bp.line?))
+        });
+        found.ok_or_else(|| {
+            Error::InvalidLocation(format!("cannot find the source line where {} starts", name))
+        })
+    }
+
+    /// Add a breakpoint
+    pub async fn add_breakpoint(
+        &mut self,
+        location: BreakpointLocation,
+        condition: Option<String>,
+        hit_count: Option<u32>,
+        on_hit: Vec<String>,
+    ) -> Result<BreakpointInfo> {
+        let location = self.resolve_location(location).await?;
+        let bp_id = self.next_bp_id;
+        self.next_bp_id += 1;
+
+        match &location {
+            BreakpointLocation::Line { file, line: _ } => {
+                // Add to our tracking
+                let stored = StoredBreakpoint {
+                    id: bp_id,
+                    location: location.clone(),
+                    condition: condition.clone(),
+                    hit_count,
+                    enabled: true,
+                    verified: false,
+                    actual_line: None,
+                    message: None,
+                    reason: None,
+                    adapter_id: None,
+                    on_hit,
+                    hits: 0,
+                };
+
+                self.source_breakpoints
+                    .entry(file.clone())
+                    .or_default()
+                    .push(stored);
+
+                // Send to adapter
+                let source_bps = self.collect_source_breakpoints(file);
+                let results = match self.client.set_breakpoints(file, source_bps).await {
+                    Ok(results) => results,
+                    Err(error) => {
+                        if let Some(breakpoints) = self.source_breakpoints.get_mut(file) {
+                            breakpoints.retain(|breakpoint| breakpoint.id != bp_id);
+                        }
+                        return Err(error);
+                    }
+                };
+
+                // Update verification status
+                self.update_source_breakpoint_status(file, &results);
+
+                // Find our breakpoint in results
+                let info = self.get_breakpoint_info(bp_id)?;
+                Ok(info)
             }
             BreakpointLocation::Function { name: _ } => {
                 let stored = StoredBreakpoint {
@@ -730,6 +1744,10 @@ impl DebugSession {
                     verified: false,
                     actual_line: None,
                     message: None,
+                    reason: None,
+                    adapter_id: None,
+                    on_hit,
+                    hits: 0,
                 };
 
                 self.function_breakpoints.push(stored);
@@ -751,6 +1769,121 @@ impl DebugSession {
                 let info = self.get_breakpoint_info(bp_id)?;
                 Ok(info)
             }
+            BreakpointLocation::Address { addr: _ } => {
+                let stored = StoredBreakpoint {
+                    id: bp_id,
+                    location: location.clone(),
+                    condition: condition.clone(),
+                    hit_count,
+                    enabled: true,
+                    verified: false,
+                    actual_line: None,
+                    message: None,
+                    reason: None,
+                    adapter_id: None,
+                    on_hit,
+                    hits: 0,
+                };
+
+                self.instruction_breakpoints.push(stored);
+
+                // Instruction breakpoints are also replaced as a whole set
+                let instruction_bps = self.collect_instruction_breakpoints();
+                let results = match self.client.set_instruction_breakpoints(instruction_bps).await {
+                    Ok(results) => results,
+                    Err(error) => {
+                        self.instruction_breakpoints
+                            .retain(|breakpoint| breakpoint.id != bp_id);
+                        return Err(error);
+                    }
+                };
+
+                self.update_instruction_breakpoint_status(&results);
+
+                let info = self.get_breakpoint_info(bp_id)?;
+                Ok(info)
+            }
+            BreakpointLocation::RelativeLine { .. } | BreakpointLocation::FunctionOffset { .. } => {
+                Err(Error::Internal(format!("unresolved breakpoint location {}", location)))
+            }
+        }
+    }
+
+    /// Every breakpoint, in the order they were added
+    fn stored_breakpoints(&self) -> Vec<StoredBreakpoint> {
+        let mut breakpoints: Vec<StoredBreakpoint> = self
+            .source_breakpoints
+            .values()
+            .flatten()
+            .chain(&self.function_breakpoints)
+            .chain(&self.instruction_breakpoints)
+            .cloned()
+            .collect();
+        breakpoints.sort_by_key(|bp| bp.id);
+        breakpoints
+    }
+
+    /// Set breakpoints kept from an earlier session. The adapter's view of
+    /// them starts over; any it rejects are reported in the output buffer
+    /// and stay listed as unverified.
+    async fn restore_breakpoints(&mut self, breakpoints: Vec<StoredBreakpoint>) {
+        for mut stored in breakpoints {
+            stored.verified = false;
+            stored.adapter_id = None;
+            stored.actual_line = None;
+            stored.message = None;
+            stored.reason = None;
+            stored.hits = 0;
+            match &stored.location {
+                BreakpointLocation::Line { file, .. } => self
+                    .source_breakpoints
+                    .entry(file.clone())
+                    .or_default()
+                    .push(stored),
+                BreakpointLocation::Function { .. } => self.function_breakpoints.push(stored),
+                BreakpointLocation::Address { .. } => self.instruction_breakpoints.push(stored),
+                // Resolved to a line before they were stored
+                BreakpointLocation::RelativeLine { .. }
+                | BreakpointLocation::FunctionOffset { .. } => {}
+            }
+        }
+
+        let files: Vec<PathBuf> = self.source_breakpoints.keys().cloned().collect();
+        for file in files {
+            let source_bps = self.collect_source_breakpoints(&file);
+            match self.client.set_breakpoints(&file, source_bps).await {
+                Ok(results) => self.update_source_breakpoint_status(&file, &results),
+                Err(e) => self.buffer_output(
+                    "console",
+                    &format!(
+                        "[debugger] Could not restore breakpoints in {}: {}\n",
+                        file.display(),
+                        e
+                    ),
+                ),
+            }
+        }
+
+        if !self.function_breakpoints.is_empty() {
+            let func_bps = self.collect_function_breakpoints();
+            match self.client.set_function_breakpoints(func_bps).await {
+                Ok(results) => self.update_function_breakpoint_status(&results),
+                Err(e) => self.buffer_output(
+                    "console",
+                    &format!("[debugger] Could not restore function breakpoints: {}\n", e),
+                ),
+            }
+        }
+
+        if !self.instruction_breakpoints.is_empty() {
+            let instruction_bps = self.collect_instruction_breakpoints();
+            match self.client.set_instruction_breakpoints(instruction_bps).await {
+                Ok(results) => self.update_instruction_breakpoint_status(&results),
+                Err(e) => self.buffer_output(
+                    "console",
+                    &format!("[debugger] Could not restore address breakpoints: {}\n", e),
+                ),
+            }
         }
     }
 
@@ -798,24 +1931,52 @@ impl DebugSession {
             .collect()
     }
 
+    /// Collect instruction breakpoints
+    fn collect_instruction_breakpoints(&self) -> Vec<InstructionBreakpoint> {
+        self.instruction_breakpoints
+            .iter()
+            .filter(|bp| bp.enabled)
+            .map(|bp| InstructionBreakpoint {
+                instruction_reference: bp.location.to_string(),
+                offset: None,
+                condition: bp.condition.clone(),
+                hit_condition: bp.hit_count.map(|n| n.to_string()),
+            })
+            .collect()
+    }
+
     /// Update source breakpoint status from adapter response
     fn update_source_breakpoint_status(&mut self, file: &Path, results: &[Breakpoint]) {
         if let Some(stored) = self.source_breakpoints.get_mut(file) {
-            // Match by line number (best effort)
-            for (stored_bp, result) in stored.iter_mut().zip(results.iter()) {
+            // The adapter was sent only the enabled ones, in order
+            let enabled = stored.iter_mut().filter(|bp| bp.enabled);
+            for (stored_bp, result) in enabled.zip(results.iter()) {
                 stored_bp.verified = result.verified;
+                stored_bp.adapter_id = result.id;
                 stored_bp.actual_line = result.line;
-                stored_bp.message = result.message.clone();
+                stored_bp.record_message(result);
             }
         }
     }
 
     /// Update function breakpoint status from adapter response
     fn update_function_breakpoint_status(&mut self, results: &[Breakpoint]) {
-        for (stored_bp, result) in self.function_breakpoints.iter_mut().zip(results.iter()) {
+        let enabled = self.function_breakpoints.iter_mut().filter(|bp| bp.enabled);
+        for (stored_bp, result) in enabled.zip(results.iter()) {
             stored_bp.verified = result.verified;
+            stored_bp.adapter_id = result.id;
             stored_bp.actual_line = result.line;
-            stored_bp.message = result.message.clone();
+            stored_bp.record_message(result);
+        }
+    }
+
+    /// Update instruction breakpoint status from adapter response
+    fn update_instruction_breakpoint_status(&mut self, results: &[Breakpoint]) {
+        let enabled = self.instruction_breakpoints.iter_mut().filter(|bp| bp.enabled);
+        for (stored_bp, result) in enabled.zip(results.iter()) {
+            stored_bp.verified = result.verified;
+            stored_bp.adapter_id = result.id;
+            stored_bp.record_message(result);
         }
     }
 
@@ -833,9 +1994,12 @@ impl DebugSession {
                         _ => None,
                     }),
                     message: bp.message.clone(),
+                    reason: bp.reason.clone(),
                     enabled: bp.enabled,
                     condition: bp.condition.clone(),
                     hit_count: bp.hit_count,
+                    on_hit: bp.on_hit.clone(),
+                    warning: None,
                 });
             }
         }
@@ -851,12 +2015,20 @@ impl DebugSession {
                 },
                 line: bp.actual_line,
                 message: bp.message.clone(),
+                reason: bp.reason.clone(),
                 enabled: bp.enabled,
                 condition: bp.condition.clone(),
                 hit_count: bp.hit_count,
+                on_hit: bp.on_hit.clone(),
+                warning: None,
             });
         }
 
+        // Search instruction breakpoints
+        if let Some(bp) = self.instruction_breakpoints.iter().find(|bp| bp.id == id) {
+            return Ok(instruction_breakpoint_info(bp));
+        }
+
         Err(Error::BreakpointNotFound { id })
     }
 
@@ -893,6 +2065,17 @@ impl DebugSession {
             return Ok(());
         }
 
+        // Try instruction breakpoints
+        if let Some(pos) = self.instruction_breakpoints.iter().position(|bp| bp.id == id) {
+            let removed = self.instruction_breakpoints.remove(pos);
+            let instruction_bps = self.collect_instruction_breakpoints();
+            if let Err(error) = self.client.set_instruction_breakpoints(instruction_bps).await {
+                self.instruction_breakpoints.insert(pos, removed);
+                return Err(error);
+            }
+            return Ok(());
+        }
+
         Err(Error::BreakpointNotFound { id })
     }
 
@@ -909,9 +2092,49 @@ impl DebugSession {
         self.client.set_function_breakpoints(vec![]).await?;
         self.function_breakpoints.clear();
 
+        // Clear instruction breakpoints (only ever set on adapters that support them)
+        if !self.instruction_breakpoints.is_empty() {
+            self.client.set_instruction_breakpoints(vec![]).await?;
+            self.instruction_breakpoints.clear();
+        }
+
         Ok(())
     }
 
+    /// Modules the adapter has reported loading and not unloading
+    pub fn modules(&self) -> Vec<ModuleInfo> {
+        self.modules
+            .iter()
+            .map(|module| ModuleInfo {
+                id: match &module.id {
+                    serde_json::Value::String(id) => id.clone(),
+                    id => id.to_string(),
+                },
+                name: module.name.clone(),
+                path: module.path.clone(),
+            })
+            .collect()
+    }
+
+    /// One breakpoint with its requested location, hits and adapter ID
+    pub fn breakpoint_details(&self, id: u32) -> Result<BreakpointDetails> {
+        let info = self.get_breakpoint_info(id)?;
+        let stored = self
+            .source_breakpoints
+            .values()
+            .flatten()
+            .chain(&self.function_breakpoints)
+            .chain(&self.instruction_breakpoints)
+            .find(|bp| bp.id == id)
+            .ok_or(Error::BreakpointNotFound { id })?;
+        Ok(BreakpointDetails {
+            info,
+            location: stored.location.to_string(),
+            hits: stored.hits,
+            adapter_id: stored.adapter_id,
+        })
+    }
+
     /// List all breakpoints
     pub fn list_breakpoints(&self) -> Vec<BreakpointInfo> {
         let mut result = Vec::new();
@@ -927,9 +2150,12 @@ impl DebugSession {
                         _ => None,
                     }),
                     message: bp.message.clone(),
+                    reason: bp.reason.clone(),
                     enabled: bp.enabled,
                     condition: bp.condition.clone(),
                     hit_count: bp.hit_count,
+                    on_hit: bp.on_hit.clone(),
+                    warning: None,
                 });
             }
         }
@@ -944,96 +2170,82 @@ impl DebugSession {
                 },
                 line: bp.actual_line,
                 message: bp.message.clone(),
+                reason: bp.reason.clone(),
                 enabled: bp.enabled,
                 condition: bp.condition.clone(),
                 hit_count: bp.hit_count,
+                on_hit: bp.on_hit.clone(),
+                warning: None,
             });
         }
 
+        for bp in &self.instruction_breakpoints {
+            result.push(instruction_breakpoint_info(bp));
+        }
+
         result
     }
 
-    /// Continue execution
-    pub async fn continue_execution(&mut self) -> Result<()> {
+    /// Continue execution; with `single_thread`, only the current thread
+    /// runs and the others stay stopped. Returns whether the adapter
+    /// resumed every thread.
+    pub async fn continue_execution(&mut self, single_thread: bool) -> Result<bool> {
+        // Also applies any pending events, so no state update is lost
         self.ensure_stopped()?;
 
-        // Process any pending events before sending continue request
-        // This ensures we don't lose state updates while clearing the queue
-        self.drain_pending_events();
-
+        self.clear_entry_breakpoint().await?;
         let thread_id = self.get_thread_id().await?;
-        self.client.continue_execution(thread_id).await?;
-        self.state = SessionState::Running;
-        self.selected_thread = None;
-        self.stopped_thread = None;
-        self.stopped_reason = None;
-        self.last_stop = None;
-        self.current_frame = None;
-        self.current_frame_index = 0;
-        self.cached_frames.clear();
+        let all_threads_continued =
+            match self.client.continue_execution(thread_id, single_thread).await {
+                Ok(all_threads_continued) => all_threads_continued,
+                Err(error) => return Err(self.resume_error(error)),
+            };
+        self.enter_running();
 
-        Ok(())
+        Ok(all_threads_continued)
     }
 
-    /// Step over (next)
-    pub async fn next(&mut self) -> Result<()> {
+    /// Step over (next), like `continue_execution` for `single_thread`
+    pub async fn next(&mut self, single_thread: bool) -> Result<()> {
+        // Also applies any pending events, so no state update is lost
         self.ensure_stopped()?;
 
-        // Process any pending events before sending step request
-        self.drain_pending_events();
-
+        self.clear_entry_breakpoint().await?;
         let thread_id = self.get_thread_id().await?;
-        self.client.next(thread_id).await?;
-        self.state = SessionState::Running;
-        self.selected_thread = None;
-        self.stopped_thread = None;
-        self.stopped_reason = None;
-        self.last_stop = None;
-        self.current_frame = None;
-        self.current_frame_index = 0;
-        self.cached_frames.clear();
+        if let Err(error) = self.client.next(thread_id, single_thread).await {
+            return Err(self.resume_error(error));
+        }
+        self.enter_running();
 
         Ok(())
     }
 
     /// Step into
-    pub async fn step_in(&mut self) -> Result<()> {
+    pub async fn step_in(&mut self, single_thread: bool) -> Result<()> {
+        // Also applies any pending events, so no state update is lost
         self.ensure_stopped()?;
 
-        // Process any pending events before sending step request
-        self.drain_pending_events();
-
+        self.clear_entry_breakpoint().await?;
         let thread_id = self.get_thread_id().await?;
-        self.client.step_in(thread_id).await?;
-        self.state = SessionState::Running;
-        self.selected_thread = None;
-        self.stopped_thread = None;
-        self.stopped_reason = None;
-        self.last_stop = None;
-        self.current_frame = None;
-        self.current_frame_index = 0;
-        self.cached_frames.clear();
+        if let Err(error) = self.client.step_in(thread_id, single_thread).await {
+            return Err(self.resume_error(error));
+        }
+        self.enter_running();
 
         Ok(())
     }
 
     /// Step out
-    pub async fn step_out(&mut self) -> Result<()> {
+    pub async fn step_out(&mut self, single_thread: bool) -> Result<()> {
+        // Also applies any pending events, so no state update is lost
         self.ensure_stopped()?;
 
-        // Process any pending events before sending step request
-        self.drain_pending_events();
-
+        self.clear_entry_breakpoint().await?;
         let thread_id = self.get_thread_id().await?;
-        self.client.step_out(thread_id).await?;
-        self.state = SessionState::Running;
-        self.selected_thread = None;
-        self.stopped_thread = None;
-        self.stopped_reason = None;
-        self.last_stop = None;
-        self.current_frame = None;
-        self.current_frame_index = 0;
-        self.cached_frames.clear();
+        if let Err(error) = self.client.step_out(thread_id, single_thread).await {
+            return Err(self.resume_error(error));
+        }
+        self.enter_running();
 
         Ok(())
     }
@@ -1065,9 +2277,31 @@ impl DebugSession {
         self.client.stack_trace(thread_id, limit as i64).await
     }
 
+    /// The top `limit` frames of every thread, from a fresh thread list, or
+    /// the error the adapter gave for a thread's stack
+    pub async fn all_thread_stacks(
+        &mut self,
+        limit: usize,
+    ) -> Result<Vec<(Thread, Result<Vec<StackFrame>>)>> {
+        self.ensure_stopped()?;
+        let threads = self.get_threads(true).await?;
+        let mut stacks = Vec::with_capacity(threads.len());
+        for thread in threads {
+            let frames = self.client.stack_trace(thread.id, limit as i64).await;
+            stacks.push((thread, frames));
+        }
+        Ok(stacks)
+    }
+
     /// Get threads
-    pub async fn get_threads(&mut self) -> Result<Vec<Thread>> {
-        self.threads = self.client.threads().await?;
+    ///
+    /// Enumerating thousands of threads can be slow, so the list is reused
+    /// until a thread starts or `refresh` is set; exited threads are dropped
+    /// from it as the adapter reports them.
+    pub async fn get_threads(&mut self, refresh: bool) -> Result<Vec<Thread>> {
+        if refresh || self.threads.is_empty() {
+            self.threads = self.client.threads().await?;
+        }
         Ok(self.threads.clone())
     }
 
@@ -1075,44 +2309,81 @@ impl DebugSession {
     pub async fn get_scopes(&mut self, frame_id: Option<i64>) -> Result<Vec<Scope>> {
         self.ensure_stopped()?;
 
-        // Auto-fetch top frame if no frame specified and current_frame is not set
-        let frame_id = match frame_id.or(self.current_frame) {
+        let frame_id = match frame_id {
             Some(id) => id,
-            None => {
-                // Fetch stack trace to get the top frame
-                let thread_id = self.get_thread_id().await?;
-                let frames = self.client.stack_trace(thread_id, 1).await?;
-                let frame = frames.first().ok_or_else(|| {
-                    Error::Internal("No stack frames available".to_string())
-                })?;
-                self.current_frame = Some(frame.id);
-                frame.id
-            }
+            None => self.selected_frame_id().await?,
         };
 
         self.client.scopes(frame_id).await
     }
 
-    /// Get variables
+    /// Get variables, reusing the ones fetched earlier at this stop
     pub async fn get_variables(&mut self, reference: i64) -> Result<Vec<Variable>> {
         self.ensure_stopped()?;
-        self.client.variables(reference).await
+        if let Some(variables) = self.variable_cache.get(&reference) {
+            return Ok(variables.clone());
+        }
+        let variables = self.client.variables(reference).await?;
+        self.variable_cache.insert(reference, variables.clone());
+        Ok(variables)
+    }
+
+    /// Forget the variables fetched at this stop, e.g. after something may
+    /// have changed them
+    pub fn clear_variable_cache(&mut self) {
+        self.variable_cache.clear();
     }
 
     /// Get local variables for current frame
-    pub async fn get_locals(&mut self, frame_id: Option<i64>) -> Result<Vec<Variable>> {
+    pub async fn get_locals(&mut self, frame_id: Option<i64>) -> Result<Locals> {
         let scopes = self.get_scopes(frame_id).await?;
 
-        // Find the "Locals" scope
-        let locals_scope = scopes.iter().find(|s| s.name == "Locals" || s.name == "Local");
+        // Find the "Locals" scope, falling back to the first scope
+        let scope = scopes
+            .iter()
+            .find(|s| s.name == "Locals" || s.name == "Local")
+            .or(scopes.first());
 
-        if let Some(scope) = locals_scope {
-            self.get_variables(scope.variables_reference).await
-        } else if let Some(scope) = scopes.first() {
-            // Fall back to first scope
-            self.get_variables(scope.variables_reference).await
-        } else {
-            Ok(Vec::new())
+        let variables = match scope {
+            Some(scope) => self.get_variables(scope.variables_reference).await?,
+            None => Vec::new(),
+        };
+
+        let mut elements = Vec::with_capacity(variables.len());
+        for variable in &variables {
+            elements.push(self.summarize_elements(variable).await);
+        }
+        Ok(Locals {
+            variables,
+            has_scopes: !scopes.is_empty(),
+            elements,
+        })
+    }
+
+    /// The first and last few elements of a container too large to read
+    /// whole, fetched as two bounded windows. Smaller containers, and
+    /// adapters that fail the windowed request, get no summary.
+    async fn summarize_elements(&mut self, variable: &Variable) -> Option<String> {
+        let count = variable.indexed_variables?;
+        if variable.variables_reference == 0 || count <= SUMMARY_MIN_ELEMENTS {
+            return None;
+        }
+
+        let reference = variable.variables_reference;
+        let head = self
+            .client
+            .indexed_variables(reference, 0, SUMMARY_EDGE_ELEMENTS)
+            .await;
+        let tail = self
+            .client
+            .indexed_variables(reference, count - SUMMARY_EDGE_ELEMENTS, SUMMARY_EDGE_ELEMENTS)
+            .await;
+        match (head, tail) {
+            (Ok(head), Ok(tail)) => Some(format_element_summary(count, &head, &tail)),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::debug!(variable = %variable.name, "Summarizing elements failed: {}", e);
+                None
+            }
         }
     }
 
@@ -1125,22 +2396,157 @@ impl DebugSession {
     ) -> Result<dap::EvaluateResponseBody> {
         self.ensure_stopped()?;
 
-        // Auto-fetch top frame if no frame specified and current_frame is not set
-        let frame_id = match frame_id.or(self.current_frame) {
+        let frame_id = match frame_id {
             Some(id) => Some(id),
+            None => self.selected_frame_id().await.ok(),
+        };
+        // Watches and clipboard copies can call functions too; only a
+        // hover is taken to leave the program alone
+        if context != "hover" {
+            self.variable_cache.clear();
+        }
+        self.client.evaluate(expression, frame_id, context).await
+    }
+
+    /// Assign `value` to `target` in frame `frame_id` (default: the selected
+    /// frame): with setExpression where the adapter has it, otherwise with
+    /// setVariable on the variable of that name in the frame's scopes
+    pub async fn assign(
+        &mut self,
+        target: &str,
+        value: &str,
+        frame_id: Option<i64>,
+    ) -> Result<dap::SetValueResponseBody> {
+        self.ensure_stopped()?;
+
+        let frame_id = match frame_id {
+            Some(id) => id,
+            None => self.selected_frame_id().await?,
+        };
+        if self.capabilities.supports_set_expression {
+            // Assigning one expression can change others read at this stop
+            self.variable_cache.clear();
+            return self.client.set_expression(target, value, Some(frame_id)).await;
+        }
+
+        let scopes = self.get_scopes(Some(frame_id)).await?;
+        for scope in scopes.iter().filter(|scope| !scope.expensive) {
+            let variables = self.get_variables(scope.variables_reference).await?;
+            if variables.iter().any(|variable| variable.name == target) {
+                self.variable_cache.clear();
+                return self
+                    .client
+                    .set_variable(scope.variables_reference, target, value)
+                    .await;
+            }
+        }
+        let hint = format!("Try 'debugger eval \"{} = {}\"' instead", target, value);
+        Err(Error::unsupported(
+            self.adapter_name(),
+            &format!("assigning '{}', which is not a variable of the frame's scopes", target),
+            Some(&hint),
+        ))
+    }
+
+    /// Memory reference of the variable `name` in the selected frame: the
+    /// one the adapter reported with the variable in the frame's scopes, or
+    /// failing that, with the result of evaluating `name`
+    pub async fn variable_memory_reference(&mut self, name: &str) -> Result<String> {
+        let scopes = self.get_scopes(None).await?;
+        for scope in scopes.iter().filter(|scope| !scope.expensive) {
+            let variables = self.get_variables(scope.variables_reference).await?;
+            if let Some(reference) = variables
+                .iter()
+                .find(|variable| variable.name == name)
+                .and_then(|variable| variable.memory_reference.clone())
+            {
+                return Ok(reference);
+            }
+        }
+
+        let result = self.evaluate(name, None, "watch").await?;
+        result.memory_reference.ok_or_else(|| {
+            Error::Internal(format!(
+                "The debug adapter reported no memory reference for '{}'; \
+                 pass an address instead",
+                name
+            ))
+        })
+    }
+
+    /// Read `count` bytes of debuggee memory starting at `address`
+    pub async fn read_memory(&mut self, address: &str, count: u32) -> Result<MemoryResult> {
+        use base64::Engine;
+
+        self.ensure_stopped()?;
+
+        let response = self
+            .client
+            .read_memory(address, None, u64::from(count))
+            .await?;
+
+        let data = match response.data.as_deref() {
+            Some(encoded) => base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(|e| {
+                    Error::DapProtocol(format!("Invalid readMemory data: {}", e))
+                })?,
+            None => Vec::new(),
+        };
+
+        Ok(MemoryResult {
+            address: response.address,
+            data,
+            unreadable_bytes: response.unreadable_bytes,
+        })
+    }
+
+    /// Disassemble `count` instructions at `address`, or at the selected
+    /// frame's instruction pointer when no address is given
+    pub async fn disassemble(
+        &mut self,
+        address: Option<&str>,
+        count: u32,
+    ) -> Result<Vec<dap::DisassembledInstruction>> {
+        self.ensure_stopped()?;
+
+        let memory_reference = match address {
+            Some(address) => address.to_string(),
             None => {
-                // Fetch stack trace to get the top frame
-                let thread_id = self.get_thread_id().await?;
-                let frames = self.client.stack_trace(thread_id, 1).await?;
-                if let Some(frame) = frames.first() {
-                    self.current_frame = Some(frame.id);
-                    Some(frame.id)
-                } else {
-                    None
-                }
+                let frame = self.select_frame(self.current_frame_index).await?;
+                frame.instruction_pointer_reference.ok_or_else(|| {
+                    Error::Internal(
+                        "Current frame has no instruction pointer; pass an address".to_string(),
+                    )
+                })?
             }
         };
-        self.client.evaluate(expression, frame_id, context).await
+
+        self.client
+            .disassemble(&memory_reference, i64::from(count))
+            .await
+    }
+
+    /// Read the selected frame's instruction, stack and frame pointers
+    pub async fn registers(&mut self) -> Result<RegistersResult> {
+        let frame = self.select_frame(self.current_frame_index).await?;
+
+        Ok(RegistersResult {
+            pc: self.evaluate_register(PC_NAMES, frame.id).await,
+            sp: self.evaluate_register(SP_NAMES, frame.id).await,
+            fp: self.evaluate_register(FP_NAMES, frame.id).await,
+            instruction_reference: frame.instruction_pointer_reference,
+        })
+    }
+
+    /// Value of the first of `names` the adapter can evaluate in the frame
+    async fn evaluate_register(&mut self, names: &[&str], frame_id: i64) -> Option<String> {
+        for name in names {
+            if let Ok(result) = self.evaluate(name, Some(frame_id), "watch").await {
+                return Some(result.result);
+            }
+        }
+        None
     }
 
     /// Get buffered output
@@ -1148,6 +2554,27 @@ impl DebugSession {
         self.output_buffer.take(clear)
     }
 
+    /// Change the output buffer's limits, keeping the current one for those
+    /// not given, and drop the oldest output that no longer fits
+    pub fn set_output_limits(&mut self, max_events: Option<usize>, max_bytes: Option<usize>) {
+        let buffer = &mut self.output_buffer;
+        buffer.set_limits(
+            max_events.unwrap_or(buffer.max_events),
+            max_bytes.unwrap_or(buffer.max_bytes),
+        );
+    }
+
+    /// The output buffer's limits and how much of them is used
+    pub fn output_limits(&self) -> OutputLimits {
+        let buffer = &self.output_buffer;
+        OutputLimits {
+            max_events: buffer.max_events,
+            max_bytes: buffer.max_bytes,
+            events: buffer.events.len(),
+            bytes: buffer.current_bytes,
+        }
+    }
+
     /// Detach from the debuggee (keep it running)
     pub async fn detach(&mut self) -> Result<()> {
         self.state = SessionState::Terminating;
@@ -1165,19 +2592,102 @@ impl DebugSession {
 
     /// Restart the debug session using the DAP restart request.
     ///
-    /// Note: The caller (handler) should check `supports_restart_request` capability
-    /// before calling this method. If the adapter doesn't support restart, the
-    /// user should be instructed to use 'debugger stop' then 'debugger start'.
+    /// The handler refuses the command up front for adapters without
+    /// `supports_restart_request`.
     pub async fn restart(&mut self) -> Result<()> {
         self.client.restart(false).await?;
+        // Clear frame/stop state since we're restarting, and the modules,
+        // which the adapter reports afresh as the new run loads them
+        self.enter_running();
+        self.modules.clear();
+        Ok(())
+    }
+
+    /// Switch to Running, forgetting everything that described the last
+    /// stop, so no frame, variable or on-hit output from it can leak into
+    /// the next one. Every transition to Running goes through here, whether
+    /// a command resumed the program or the adapter reported it.
+    fn enter_running(&mut self) {
         self.state = SessionState::Running;
-        // Clear frame/stop state since we're restarting
+        self.selected_thread = None;
         self.stopped_thread = None;
         self.stopped_reason = None;
+        self.last_stop = None;
+        self.exception_info_pending = false;
+        self.exception_info = None;
+        self.hit_breakpoints.clear();
+        self.pending_on_hit.clear();
+        self.on_hit_output.clear();
+        self.watches_pending = false;
+        self.watch_values.clear();
         self.current_frame = None;
         self.current_frame_index = 0;
         self.cached_frames.clear();
-        Ok(())
+        self.variable_cache.clear();
+    }
+
+    /// Whether the adapter crashed during a launched session, leaving it to
+    /// be relaunched with [`DebugSession::relaunch_after_crash`]
+    pub fn adapter_crashed(&self) -> bool {
+        self.adapter_crashed && self.launched
+    }
+
+    /// Relaunch the program with a new adapter after a crash, keeping the
+    /// breakpoints and buffered output. The outcome is reported in the
+    /// output buffer, where `output` shows it. Gives up after
+    /// [`MAX_RELAUNCHES`] crashes.
+    pub async fn relaunch_after_crash(&mut self, config: &Config) {
+        self.adapter_crashed = false;
+        if self.relaunches >= MAX_RELAUNCHES {
+            self.buffer_output(
+                "console",
+                &format!(
+                    "[debugger] {} crashed again; not relaunching after {} attempts\n",
+                    self.adapter_name, MAX_RELAUNCHES
+                ),
+            );
+            return;
+        }
+
+        let breakpoints = self.stored_breakpoints();
+
+        tracing::info!(program = %self.program.display(), "Relaunching after adapter crash");
+        let relaunched = Self::launch_with_breakpoints(
+            config,
+            &self.program,
+            self.args.clone(),
+            Some(self.adapter_name.clone()),
+            false,
+            breakpoints,
+        )
+        .await;
+
+        match relaunched {
+            Ok(mut session) => {
+                session.next_bp_id = self.next_bp_id;
+                session.watches = std::mem::take(&mut self.watches);
+                session.next_watch_id = self.next_watch_id;
+                session.relaunches = self.relaunches + 1;
+                session.keep_alive = self.keep_alive;
+                std::mem::swap(&mut session.output_buffer, &mut self.output_buffer);
+                *self = session;
+                self.buffer_output(
+                    "console",
+                    &format!(
+                        "[debugger] {} crashed; relaunched the program with its breakpoints\n",
+                        self.adapter_name
+                    ),
+                );
+            }
+            Err(e) => {
+                tracing::error!("Relaunch after adapter crash failed: {}", e);
+                self.relaunches += 1;
+                self.buffer_output(
+                    "console",
+                    &format!("[debugger] {} crashed; relaunch failed: {}\n", self.adapter_name, e),
+                );
+            }
+        }
     }
 
     /// Select a thread for debugging operations
@@ -1201,6 +2711,30 @@ impl DebugSession {
         Ok(())
     }
 
+    /// Terminate the threads `ids`, forgetting the selection if it was one
+    /// of them
+    pub async fn terminate_threads(&mut self, ids: &[i64]) -> Result<()> {
+        self.client.terminate_threads(ids).await?;
+
+        self.threads.clear();
+        let killed = |thread: Option<i64>| thread.is_some_and(|id| ids.contains(&id));
+        let selected_killed = killed(self.selected_thread);
+        let stopped_killed = killed(self.stopped_thread);
+        if selected_killed {
+            self.selected_thread = None;
+        }
+        // Commands fall back to the stopped thread without a selected one
+        if stopped_killed {
+            self.stopped_thread = None;
+        }
+        if selected_killed || stopped_killed {
+            self.current_frame_index = 0;
+            self.current_frame = None;
+            self.cached_frames.clear();
+        }
+        Ok(())
+    }
+
     /// Get the currently selected thread (for UI display)
     pub fn get_selected_thread(&self) -> Option<i64> {
         self.selected_thread.or(self.stopped_thread)
@@ -1208,6 +2742,26 @@ impl DebugSession {
 
     /// Select a stack frame by index (0 = top/innermost)
     pub async fn select_frame(&mut self, frame_index: usize) -> Result<StackFrame> {
+        let frame = self.frame_at(frame_index).await?;
+        self.current_frame_index = frame_index;
+        self.current_frame = Some(frame.id);
+        Ok(frame)
+    }
+
+    /// ID of the selected frame, looked up again after a stop or after the
+    /// adapter invalidated the stack
+    async fn selected_frame_id(&mut self) -> Result<i64> {
+        if let Some(id) = self.current_frame {
+            return Ok(id);
+        }
+        let frame = self.frame_at(self.current_frame_index).await?;
+        self.current_frame = Some(frame.id);
+        Ok(frame.id)
+    }
+
+    /// The frame at `frame_index` (0 = top of stack), leaving the selected
+    /// frame alone
+    pub async fn frame_at(&mut self, frame_index: usize) -> Result<StackFrame> {
         self.ensure_stopped()?;
 
         // Fetch frames if not cached or if requesting beyond cache
@@ -1218,14 +2772,10 @@ impl DebugSession {
             self.cached_frames = self.client.stack_trace(thread_id, needed as i64).await?;
         }
 
-        if frame_index >= self.cached_frames.len() {
-            return Err(Error::FrameNotFound(frame_index));
-        }
-
-        self.current_frame_index = frame_index;
-        self.current_frame = Some(self.cached_frames[frame_index].id);
-
-        Ok(self.cached_frames[frame_index].clone())
+        self.cached_frames
+            .get(frame_index)
+            .cloned()
+            .ok_or(Error::FrameNotFound(frame_index))
     }
 
     /// Move up the stack (to caller frame)
@@ -1273,10 +2823,14 @@ impl DebugSession {
         }
 
         let mut function_previous_enabled = None;
+        let mut instruction_previous_enabled = None;
         if source_breakpoint.is_none() {
             if let Some(bp) = self.function_breakpoints.iter_mut().find(|bp| bp.id == id) {
                 function_previous_enabled = Some(bp.enabled);
                 bp.enabled = enabled;
+            } else if let Some(bp) = self.instruction_breakpoints.iter_mut().find(|bp| bp.id == id) {
+                instruction_previous_enabled = Some(bp.enabled);
+                bp.enabled = enabled;
             } else {
                 return Err(Error::BreakpointNotFound { id });
             }
@@ -1311,6 +2865,18 @@ impl DebugSession {
                 }
             };
             self.update_function_breakpoint_status(&results);
+        } else if let Some(previous_enabled) = instruction_previous_enabled {
+            let instruction_bps = self.collect_instruction_breakpoints();
+            let results = match self.client.set_instruction_breakpoints(instruction_bps).await {
+                Ok(results) => results,
+                Err(error) => {
+                    if let Some(bp) = self.instruction_breakpoints.iter_mut().find(|bp| bp.id == id) {
+                        bp.enabled = previous_enabled;
+                    }
+                    return Err(error);
+                }
+            };
+            self.update_instruction_breakpoint_status(&results);
         }
 
         Ok(())
@@ -1326,27 +2892,33 @@ impl DebugSession {
         self.capabilities.supports_function_breakpoints
     }
 
-    /// Check if adapter supports conditional breakpoints
-    pub fn supports_conditional_breakpoints(&self) -> bool {
-        self.capabilities.supports_conditional_breakpoints
-    }
-
-    /// Check if adapter supports hit conditional breakpoints
-    pub fn supports_hit_conditional_breakpoints(&self) -> bool {
-        self.capabilities.supports_hit_conditional_breakpoints
-    }
-
     /// Ensure we're in stopped state for inspection commands
+    ///
+    /// The actor reduces queued events just before every command, running
+    /// on-hit commands, fetching exception info and evaluating watches as
+    /// it does, so a program that exited since the last command is already
+    /// reported as exited here. Events aren't drained again, which would
+    /// skip that for a stop.
     fn ensure_stopped(&self) -> Result<()> {
         match self.state {
             SessionState::Stopped => Ok(()),
-            SessionState::Exited => {
-                Err(Error::ProgramExited(self.exit_code.unwrap_or(0)))
-            }
+            SessionState::Exited => Err(Error::program_exited(self.exit_code)),
             _ => Err(Error::invalid_state("inspect", &self.state.to_string())),
         }
     }
 
+    /// The error for a failed resume request: the program may have exited
+    /// while the request was in flight, in which case the adapter's error is
+    /// only a symptom of that. A stop drained here is reduced in full by the
+    /// actor once the command is done.
+    fn resume_error(&mut self, error: Error) -> Error {
+        self.drain_pending_events();
+        match self.state {
+            SessionState::Exited => Error::program_exited(self.exit_code),
+            _ => error,
+        }
+    }
+
     /// Get a thread ID (preferring selected > stopped > first)
     async fn get_thread_id(&mut self) -> Result<i64> {
         // Prefer explicitly selected thread
@@ -1373,7 +2945,69 @@ impl DebugSession {
 
 #[cfg(test)]
 mod tests {
-    use super::OutputBuffer;
+    use super::{
+        format_element_summary, frame_is_in_function, frame_presentation, is_user_frame,
+        normalize_stop_reason, pending_reason, resolve_adapter_path, select_adapter,
+        source_map_out_files, Locals, OutputBuffer, SessionState,
+    };
+    use super::super::mock_session::{launch_on, process_until, stop_at_start};
+    use crate::dap::mock::{Action, MockAdapter};
+    use crate::dap::{Breakpoint, Source, StackFrame, Variable};
+    use crate::common::config::Config;
+    use serde_json::json;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn adapters_are_selected_by_program_type() {
+        let config: Config = toml::from_str(
+            r#"
+[defaults]
+adapter = "gdb"
+
+[adapters.gdb]
+path = "/usr/bin/gdb"
+
+[adapters.python]
+path = "/usr/bin/python3"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(select_adapter(&config, Path::new("app.py")), "python");
+        assert_eq!(select_adapter(&config, Path::new("/no/such/binary")), "gdb");
+        // Unconfigured, but named so the error says what is missing
+        assert_eq!(select_adapter(&config, Path::new("app.js")), "js-debug");
+    }
+
+    #[test]
+    fn compiled_typescript_gets_out_files() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+        let out_files = source_map_out_files(&fixtures.join("dist/simple.js")).unwrap();
+        assert_eq!(
+            out_files[0],
+            format!("{}/**/*.js", fixtures.join("dist").display())
+        );
+        // Plain JavaScript and the TypeScript source itself need no globs
+        assert_eq!(source_map_out_files(&fixtures.join("simple.js")), None);
+        assert_eq!(source_map_out_files(&fixtures.join("simple.ts")), None);
+    }
+
+    #[test]
+    fn bare_adapter_names_are_resolved_on_path() {
+        #[cfg(unix)]
+        {
+            let sh = resolve_adapter_path("sh", Path::new("sh")).unwrap();
+            assert!(sh.is_absolute(), "{}", sh.display());
+        }
+
+        // Paths with a directory part are left alone, even if missing
+        let relative = Path::new("./bin/lldb-dap");
+        assert_eq!(resolve_adapter_path("lldb", relative).unwrap(), relative);
+
+        let error = resolve_adapter_path("x", Path::new("no-such-adapter-xyz")).unwrap_err();
+        assert!(error.to_string().contains("no-such-adapter-xyz on PATH"), "{}", error);
+    }
 
     #[test]
     fn clearing_output_resets_byte_accounting() {
@@ -1401,10 +3035,409 @@ mod tests {
         assert_eq!(buffer.current_bytes, 4);
     }
 
+    #[test]
+    fn multibyte_output_never_exceeds_the_byte_budget() {
+        let mut buffer = OutputBuffer::new(100, 10);
+        for chunk in ["日本語", "ü", "ééééééé", "🦀🦀🦀"] {
+            buffer.push("stdout", chunk);
+            let buffered: usize = buffer.events.iter().map(|e| e.output.len()).sum();
+            assert_eq!(buffer.current_bytes, buffered);
+            assert!(buffer.current_bytes <= 10, "{} bytes buffered", buffer.current_bytes);
+        }
+
+        // Each 4-byte crab fits whole; a third would cross the budget
+        let output = buffer.take(false);
+        assert_eq!(output.last().unwrap().output, "🦀🦀");
+    }
+
+    #[test]
+    fn lowering_output_limits_drops_the_oldest_output() {
+        let mut buffer = OutputBuffer::new(4, 16);
+        for chunk in ["aaaa", "bbbb", "cccc"] {
+            buffer.push("stdout", chunk);
+        }
+
+        buffer.set_limits(4, 8);
+        let output: Vec<_> = buffer.take(false).into_iter().map(|e| e.output).collect();
+        assert_eq!(output, vec!["bbbb", "cccc"]);
+        assert_eq!(buffer.current_bytes, 8);
+
+        buffer.set_limits(1, 8);
+        assert_eq!(buffer.take(false)[0].output, "cccc");
+        assert_eq!(buffer.current_bytes, 4);
+    }
+
     #[test]
     fn zero_sized_buffers_discard_output() {
         let mut buffer = OutputBuffer::new(0, 32);
         buffer.push("stdout", "discard me");
         assert!(buffer.take(false).is_empty());
     }
+
+    #[test]
+    fn watch_scopes_match_decorated_frame_names() {
+        assert!(frame_is_in_function("fib", "fib"));
+        assert!(frame_is_in_function("fib(int)", "fib"));
+        assert!(frame_is_in_function("app::fib + 12", "fib"));
+        assert!(frame_is_in_function("app.fib", "fib"));
+        assert!(frame_is_in_function("app::fib", "app::fib"));
+        assert!(!frame_is_in_function("fibonacci", "fib"));
+        assert!(!frame_is_in_function("not_fib", "fib"));
+    }
+
+    #[test]
+    fn user_frames_have_source_under_the_program() {
+        let frame = |path: Option<&str>| StackFrame {
+            id: 1,
+            name: "main".to_string(),
+            source: path.map(|path| Source {
+                path: Some(path.to_string()),
+                ..Default::default()
+            }),
+            line: 1,
+            column: 1,
+            module_id: None,
+            instruction_pointer_reference: None,
+            presentation_hint: None,
+        };
+        let roots = [PathBuf::from("/work/app")];
+        let hidden = ["/work/app/vendor/".to_string()];
+
+        assert!(is_user_frame(&frame(Some("/work/app/src/main.c")), &roots, &hidden));
+        assert!(!is_user_frame(&frame(None), &roots, &hidden));
+        assert!(!is_user_frame(&frame(Some("/usr/lib/libc.c")), &roots, &hidden));
+        assert!(!is_user_frame(&frame(Some("/work/app/vendor/zlib.c")), &roots, &hidden));
+        // Whole path components only
+        assert!(!is_user_frame(&frame(Some("/work/application/x.c")), &roots, &hidden));
+
+        // The adapter's own hints win over the path
+        let mut subtle = frame(Some("/work/app/src/main.c"));
+        subtle.presentation_hint = Some("subtle".to_string());
+        assert!(!is_user_frame(&subtle, &roots, &hidden));
+        let mut deemphasized = frame(Some("/work/app/src/main.c"));
+        if let Some(source) = deemphasized.source.as_mut() {
+            source.presentation_hint = Some("deemphasize".to_string());
+        }
+        assert_eq!(frame_presentation(&deemphasized), Some("subtle"));
+        assert!(!is_user_frame(&deemphasized, &roots, &hidden));
+    }
+
+    #[test]
+    fn large_containers_show_both_ends() {
+        let element = |index: i64| Variable {
+            name: format!("[{}]", index),
+            value: (index * 10).to_string(),
+            type_name: None,
+            variables_reference: 0,
+            presentation_hint: None,
+            indexed_variables: None,
+            memory_reference: None,
+        };
+        let head: Vec<Variable> = (0..3).map(element).collect();
+        let tail: Vec<Variable> = (9997..10000).map(element).collect();
+
+        assert_eq!(
+            format_element_summary(10000, &head, &tail),
+            "[0]=0, [1]=10, [2]=20, ... (9994 more), [9997]=99970, [9998]=99980, [9999]=99990"
+        );
+        assert_eq!(
+            format_element_summary(6, &head[..2], &tail[..1]),
+            "[0]=0, [1]=10, ... (3 more), [9997]=99970"
+        );
+    }
+
+    #[test]
+    fn empty_locals_explain_themselves() {
+        let variable = Variable {
+            name: "x".to_string(),
+            value: "1".to_string(),
+            type_name: None,
+            variables_reference: 0,
+            presentation_hint: None,
+            indexed_variables: None,
+            memory_reference: None,
+        };
+        let found = Locals { variables: vec![variable], has_scopes: true, elements: vec![None] };
+        let empty = Locals { variables: Vec::new(), has_scopes: true, elements: Vec::new() };
+        let no_scopes = Locals { variables: Vec::new(), has_scopes: false, elements: Vec::new() };
+
+        assert_eq!(found.note(), None);
+        assert!(empty.note().unwrap().contains("optimized out"));
+        assert!(no_scopes.note().unwrap().contains("no scopes"));
+    }
+
+    #[test]
+    fn unverified_breakpoints_are_explained() {
+        let mut bp = Breakpoint {
+            id: Some(1),
+            verified: false,
+            message: None,
+            source: None,
+            line: None,
+            column: None,
+            reason: Some("pending".to_string()),
+        };
+        assert!(pending_reason(&bp).unwrap().contains("waiting for its code to load"));
+
+        // The adapter's own text stays in `message`, apart from the reason
+        bp.message = Some("no such file".to_string());
+        bp.reason = Some("failed".to_string());
+        assert!(pending_reason(&bp).unwrap().contains("will not retry"));
+        bp.reason = None;
+        assert_eq!(pending_reason(&bp), None);
+
+        bp.message = None;
+        assert!(pending_reason(&bp).unwrap().contains("gave no reason"));
+
+        bp.verified = true;
+        assert_eq!(pending_reason(&bp), None);
+    }
+
+    #[test]
+    fn stop_reasons_are_normalized_across_adapters() {
+        assert_eq!(normalize_stop_reason("breakpoint-hit"), "breakpoint");
+        assert_eq!(normalize_stop_reason("function breakpoint"), "breakpoint");
+        assert_eq!(normalize_stop_reason("instruction-step"), "step");
+        assert_eq!(normalize_stop_reason("end-stepping-range"), "step");
+        assert_eq!(normalize_stop_reason("signal"), "exception");
+        assert_eq!(normalize_stop_reason("Exception"), "exception");
+        assert_eq!(normalize_stop_reason("data breakpoint"), "data-breakpoint");
+        assert_eq!(normalize_stop_reason("attach"), "attach");
+    }
+
+    #[tokio::test]
+    async fn adapter_crash_relaunches_but_a_program_exit_does_not() {
+        // The first adapter dies under the program; the second outlives it
+        let adapter = MockAdapter::scripted(|connection, command, _| match (connection, command) {
+            (0, "configurationDone") => Some(vec![Action::Respond(json!({})), Action::Hangup]),
+            (1, "configurationDone") => Some(vec![
+                Action::Respond(json!({})),
+                Action::Event("exited", json!({ "exitCode": 0 })),
+                Action::Event("terminated", json!({})),
+                Action::Hangup,
+            ]),
+            _ => None,
+        })
+        .await;
+        let config = adapter.config();
+
+        let mut session = launch_on(&adapter).await;
+        let keep_alive = Some(std::time::Duration::from_secs(600));
+        session.set_keep_alive(keep_alive);
+        process_until(&mut session, |session| session.state() == SessionState::Exited).await;
+        assert!(session.adapter_crashed());
+
+        session.relaunch_after_crash(&config).await;
+        assert_eq!(session.relaunches, 1);
+        assert_eq!(session.state, SessionState::Running);
+        assert_eq!(session.keep_alive(), keep_alive);
+        let relaunched = adapter.received();
+        let breakpoints = relaunched
+            .iter()
+            .find(|request| request.connection == 1 && request.command == "setBreakpoints")
+            .expect("breakpoints set again on the new adapter");
+        assert_eq!(breakpoints.arguments["breakpoints"][0]["line"], 3);
+
+        process_until(&mut session, |session| session.state() == SessionState::Exited).await;
+        assert!(!session.adapter_crashed());
+        assert_eq!(session.exit_code, Some(0));
+        let output = session.get_output(false);
+        assert!(output.iter().any(|event| event.output.contains("relaunched the program")));
+    }
+
+    #[tokio::test]
+    async fn a_program_that_exits_mid_step_is_reported_exited() {
+        // The first program exits with a code; the second only terminates
+        let adapter = MockAdapter::scripted(|connection, command, arguments| {
+            match (connection, command) {
+                (0, "next") => Some(vec![
+                    Action::Event("exited", json!({ "exitCode": 3 })),
+                    Action::Event("terminated", json!({})),
+                    Action::Fail("process exited"),
+                ]),
+                (1, "next") => Some(vec![
+                    Action::Event("terminated", json!({})),
+                    Action::Fail("process exited"),
+                ]),
+                _ => stop_at_start(connection, command, arguments),
+            }
+        })
+        .await;
+
+        for expected in ["Program has exited with code 3", "Program has exited"] {
+            let mut session = launch_on(&adapter).await;
+            process_until(&mut session, |session| session.state() == SessionState::Stopped).await;
+
+            let error = session.next(false).await.unwrap_err();
+            assert_eq!(error.to_string(), expected);
+            let error = session.stack_trace(None, 1).await.unwrap_err();
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_user_breakpoint_at_the_emulated_entry_stop_is_still_hit() {
+        // The entry breakpoint is 99; the user's main.c:3 is 1
+        let adapter = MockAdapter::scripted(|_, command, _| match command {
+            "setFunctionBreakpoints" => Some(vec![Action::Respond(json!({
+                "breakpoints": [{ "id": 99, "verified": true }],
+            }))]),
+            "configurationDone" => Some(vec![
+                Action::Respond(json!({})),
+                Action::Event(
+                    "stopped",
+                    json!({ "reason": "breakpoint", "threadId": 1, "hitBreakpointIds": [1, 99] }),
+                ),
+            ]),
+            _ => None,
+        })
+        .await;
+        let mut config = adapter.config();
+        let mock = config.adapters.remove("mock").unwrap();
+        config.adapters.insert("gdb".to_string(), mock);
+
+        let breakpoints = vec!["main.c:3".to_string()];
+        let adapter_name = Some("gdb".to_string());
+        let program = Path::new("/bin/app");
+        let session =
+            super::DebugSession::launch(&config, program, vec![], adapter_name, true, breakpoints)
+                .await
+                .unwrap();
+
+        assert_eq!(session.state(), SessionState::Stopped);
+        assert_eq!(session.hit_breakpoints, vec![1]);
+        assert_eq!(session.stopped_reason.as_deref(), Some("breakpoint"));
+    }
+
+    #[tokio::test]
+    async fn an_attached_process_waits_for_breakpoints_before_running() {
+        // The process is stopped on attach, and hits breakpoint 1 once continued
+        let adapter = MockAdapter::scripted(|_, command, _| match command {
+            "configurationDone" => Some(vec![
+                Action::Respond(json!({})),
+                Action::Event("stopped", json!({ "reason": "entry", "threadId": 1 })),
+            ]),
+            "continue" => Some(vec![
+                Action::Respond(json!({ "allThreadsContinued": true })),
+                Action::Event(
+                    "stopped",
+                    json!({ "reason": "breakpoint", "threadId": 1, "hitBreakpointIds": [1] }),
+                ),
+            ]),
+            _ => None,
+        })
+        .await;
+        let config = adapter.config();
+        let mut session = super::DebugSession::attach(&config, 4242, Some("mock".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(session.state(), SessionState::Stopped);
+        let attach = adapter.received().into_iter().find(|r| r.command == "attach").unwrap();
+        assert_eq!(attach.arguments["pid"], 4242);
+        assert_eq!(attach.arguments["stopOnEntry"], true);
+
+        let location = crate::ipc::protocol::BreakpointLocation::parse("main.c:5").unwrap();
+        let added = session.add_breakpoint(location, None, None, vec![]).await.unwrap();
+        session.continue_execution(false).await.unwrap();
+        process_until(&mut session, |session| session.state() == SessionState::Stopped).await;
+
+        assert_eq!(session.stopped_reason(), Some("breakpoint"));
+        assert_eq!(session.hit_breakpoints, vec![1]);
+        assert!(session.list_breakpoints().iter().any(|bp| bp.id == added.id && bp.verified));
+        let commands: Vec<String> = adapter.received().into_iter().map(|r| r.command).collect();
+        let position = |name: &str| commands.iter().position(|c| c == name).unwrap();
+        assert!(position("setBreakpoints") < position("continue"));
+    }
+
+    #[tokio::test]
+    async fn results_for_enabled_breakpoints_skip_disabled_ones() {
+        let adapter = MockAdapter::start().await;
+        let mut session = launch_on(&adapter).await;
+        for spec in ["main.c:5", "main.c:7"] {
+            let location = crate::ipc::protocol::BreakpointLocation::parse(spec).unwrap();
+            session.add_breakpoint(location, None, None, vec![]).await.unwrap();
+        }
+
+        // The mock numbers breakpoints 1-6 up to here, then 7 and 8 for the
+        // lines 5 and 7 still enabled
+        session.disable_breakpoint(1).await.unwrap();
+        let stored: Vec<&super::StoredBreakpoint> =
+            session.source_breakpoints.values().flatten().collect();
+        let adapter_id = |id: u32| stored.iter().find(|bp| bp.id == id).unwrap().adapter_id;
+        let line = |id: u32| stored.iter().find(|bp| bp.id == id).unwrap().actual_line;
+        assert_eq!((adapter_id(2), line(2)), (Some(7), Some(5)));
+        assert_eq!((adapter_id(3), line(3)), (Some(8), Some(7)));
+    }
+
+    #[tokio::test]
+    async fn modules_are_forgotten_on_restart() {
+        let adapter = MockAdapter::scripted(|_, command, _| match command {
+            "initialize" => Some(vec![Action::Respond(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsRestartRequest": true,
+            }))]),
+            "configurationDone" => Some(vec![
+                Action::Respond(json!({})),
+                Action::Event(
+                    "module",
+                    json!({ "reason": "new", "module": { "id": 1, "name": "libc.so.6" } }),
+                ),
+                Action::Event("stopped", json!({ "reason": "entry", "threadId": 1 })),
+            ]),
+            _ => None,
+        })
+        .await;
+        let mut session = launch_on(&adapter).await;
+        process_until(&mut session, |session| session.state() == SessionState::Stopped).await;
+        assert_eq!(session.modules().len(), 1);
+
+        session.restart().await.unwrap();
+        assert!(session.modules().is_empty());
+    }
+
+    #[tokio::test]
+    async fn only_variables_of_the_frame_are_assigned_without_set_expression() {
+        let adapter = MockAdapter::scripted(stop_at_start).await;
+        let mut session = launch_on(&adapter).await;
+        process_until(&mut session, |session| session.state() == SessionState::Stopped).await;
+
+        session.assign("x", "2", None).await.unwrap();
+        let set = adapter.received().into_iter().find(|r| r.command == "setVariable");
+        assert_eq!(set.unwrap().arguments["name"], "x");
+
+        let error = session.assign("x + 1", "2", None).await.unwrap_err();
+        assert!(matches!(error, crate::common::Error::Unsupported { .. }), "{:?}", error);
+        assert!(error.to_string().contains("debugger eval \"x + 1 = 2\""));
+    }
+
+    #[tokio::test]
+    async fn evaluations_other_than_hovers_invalidate_cached_variables() {
+        let adapter = MockAdapter::scripted(stop_at_start).await;
+        let mut session = launch_on(&adapter).await;
+        process_until(&mut session, |session| session.state() == SessionState::Stopped).await;
+        let fetches = || {
+            adapter.received().iter().filter(|request| request.command == "variables").count()
+        };
+
+        session.get_variables(1).await.unwrap();
+        session.get_variables(1).await.unwrap();
+        assert_eq!(fetches(), 1);
+
+        session.evaluate("x", None, "hover").await.unwrap();
+        session.get_variables(1).await.unwrap();
+        assert_eq!(fetches(), 1);
+
+        for (context, expected) in [("watch", 2), ("repl", 3), ("clipboard", 4)] {
+            session.evaluate("bump()", None, context).await.unwrap();
+            session.get_variables(1).await.unwrap();
+            assert_eq!(fetches(), expected, "after a {} evaluation", context);
+        }
+    }
+
+    #[tokio::test]
+    async fn user_code_is_what_lies_beside_the_program() {
+        let adapter = MockAdapter::start().await;
+        let session = launch_on(&adapter).await;
+        assert_eq!(session.user_code_roots(), vec![PathBuf::from("/bin")]);
+    }
 }
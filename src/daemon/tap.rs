@@ -0,0 +1,130 @@
+//! Fan-out of live session data to followers
+//!
+//! The output and event taps are both built on [`Tap`]: each follower has a
+//! bounded channel of its own, so one that stops reading can't make the
+//! daemon hold everything sent since. A follower more than [`CAPACITY`]
+//! items behind loses the newest ones, and gets a marker saying how many
+//! as soon as it has room again.
+
+use std::sync::Mutex;
+
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// Items a follower can fall behind by before they are dropped
+pub(super) const CAPACITY: usize = 1024;
+
+/// Something a tap carries
+pub(super) trait Tapped: Clone + Send {
+    /// The marker telling a follower it missed `dropped` items
+    fn lagged(dropped: usize) -> Self;
+}
+
+pub(super) struct Tap<T> {
+    followers: Mutex<Vec<Follower<T>>>,
+}
+
+/// A follower, with which items it wants and how many it missed since it
+/// last got one
+struct Follower<T> {
+    wants: Box<dyn Fn(&T) -> bool + Send>,
+    sender: mpsc::Sender<T>,
+    dropped: usize,
+}
+
+impl<T: Tapped> Tap<T> {
+    pub(super) const fn new() -> Self {
+        Self {
+            followers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Receive the items `wants` accepts from now on, until the receiver is
+    /// dropped
+    pub(super) fn subscribe(
+        &self,
+        wants: impl Fn(&T) -> bool + Send + 'static,
+    ) -> mpsc::Receiver<T> {
+        let (sender, receiver) = mpsc::channel(CAPACITY);
+        self.followers.lock().unwrap().push(Follower {
+            wants: Box::new(wants),
+            sender,
+            dropped: 0,
+        });
+        receiver
+    }
+
+    /// Copy the item built by `item` to every follower that wants it; it is
+    /// only built when someone is following. Dropped followers are pruned.
+    pub(super) fn send(&self, item: impl FnOnce() -> T) {
+        let mut followers = self.followers.lock().unwrap();
+        if followers.is_empty() {
+            return;
+        }
+        let item = item();
+        followers.retain_mut(|follower| {
+            if !(follower.wants)(&item) {
+                return !follower.sender.is_closed();
+            }
+            follower.deliver(&item)
+        });
+    }
+}
+
+impl<T: Tapped> Follower<T> {
+    /// Queue `item`, or count it as dropped if the follower is too far
+    /// behind. Returns whether the follower is still listening.
+    fn deliver(&mut self, item: &T) -> bool {
+        if self.dropped > 0 {
+            match self.sender.try_send(T::lagged(self.dropped)) {
+                Ok(()) => self.dropped = 0,
+                Err(TrySendError::Full(_)) => {
+                    self.dropped += 1;
+                    return true;
+                }
+                Err(TrySendError::Closed(_)) => return false,
+            }
+        }
+        match self.sender.try_send(item.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.dropped += 1;
+                true
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Tapped for String {
+        fn lagged(dropped: usize) -> Self {
+            format!("lagged {}", dropped)
+        }
+    }
+
+    #[test]
+    fn followers_that_fall_behind_are_told_what_they_missed() {
+        let tap = Tap::new();
+        let mut follower = tap.subscribe(|item: &String| item != "unwanted");
+        for index in 0..CAPACITY + 2 {
+            tap.send(|| index.to_string());
+        }
+        tap.send(|| "unwanted".to_string());
+
+        for index in 0..CAPACITY {
+            assert_eq!(follower.try_recv().unwrap(), index.to_string());
+        }
+        assert!(follower.try_recv().is_err());
+
+        tap.send(|| "caught up".to_string());
+        assert_eq!(follower.try_recv().unwrap(), "lagged 2");
+        assert_eq!(follower.try_recv().unwrap(), "caught up");
+
+        drop(follower);
+        tap.send(|| "pruned".to_string());
+        assert!(tap.followers.lock().unwrap().is_empty());
+    }
+}
@@ -23,7 +23,7 @@
 use std::collections::HashMap;
 use std::path::Path;
 use std::process::Stdio;
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -36,13 +36,22 @@ use tokio::net::TcpStream;
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
 use tokio::sync::{mpsc, oneshot, Mutex};
 
+use crate::common::config::{DapConfig, SpawnStyle};
 use crate::common::{Error, Result};
 
 use super::codec;
+use super::progress;
 use super::types::*;
 
 /// Pending response waiters, keyed by request sequence number
-type PendingResponses = Arc<Mutex<HashMap<i64, oneshot::Sender<std::result::Result<ResponseMessage, Error>>>>>;
+type PendingResponses = Arc<Mutex<HashMap<i64, PendingRequest>>>;
+
+/// A request waiting for its response
+struct PendingRequest {
+    /// DAP command of the request, so it can be found to cancel
+    command: String,
+    tx: oneshot::Sender<std::result::Result<ResponseMessage, Error>>,
+}
 
 /// Abstraction over different writer types (stdin or TCP)
 enum DapWriter {
@@ -79,16 +88,19 @@ impl AsyncWrite for DapWriter {
 
 /// DAP client for communicating with a debug adapter
 pub struct DapClient {
-    /// Adapter subprocess
-    adapter: Child,
-    /// Buffered writer for adapter communication
-    writer: DapWriter,
+    /// Adapter subprocess, unless the client connected to an adapter that
+    /// was already running
+    adapter: Option<Child>,
+    /// Buffered writer for adapter communication, shared with cancel handles
+    writer: Arc<Mutex<DapWriter>>,
     /// Sequence number for requests
-    seq: AtomicI64,
+    seq: Arc<AtomicI64>,
     /// Adapter capabilities (populated after initialize)
     pub capabilities: Capabilities,
     /// Default timeout for DAP requests after initialization.
     request_timeout: Duration,
+    /// Timeout for `evaluate`, which may run arbitrary debuggee code
+    eval_timeout: Duration,
     /// Pending response waiters
     pending: PendingResponses,
     /// Channel for events (to session)
@@ -99,6 +111,9 @@ pub struct DapClient {
     reader_task: Option<tokio::task::JoinHandle<()>>,
     /// Channel to signal reader task to stop
     shutdown_tx: Option<mpsc::Sender<()>>,
+    /// Set by the reader task when the adapter's output ends or fails
+    /// before it reported the program exited or terminated
+    adapter_crashed: Arc<AtomicBool>,
 }
 
 impl DapClient {
@@ -131,38 +146,46 @@ impl DapClient {
         let pending: PendingResponses = Arc::new(Mutex::new(HashMap::new()));
 
         // Spawn background reader task
+        let adapter_crashed = Arc::new(AtomicBool::new(false));
         let reader_task = Self::spawn_stdio_reader_task(
             stdout,
             event_tx.clone(),
             pending.clone(),
             shutdown_rx,
+            adapter_crashed.clone(),
         );
 
         Ok(Self {
-            adapter,
-            writer: DapWriter::Stdio(BufWriter::new(stdin)),
-            seq: AtomicI64::new(1),
+            adapter: Some(adapter),
+            writer: Arc::new(Mutex::new(DapWriter::Stdio(BufWriter::new(stdin)))),
+            seq: Arc::new(AtomicI64::new(1)),
             capabilities: Capabilities::default(),
             request_timeout: Duration::from_secs(30),
+            eval_timeout: Duration::from_secs(120),
             pending,
             event_tx,
             event_rx: Some(event_rx),
             reader_task: Some(reader_task),
             shutdown_tx: Some(shutdown_tx),
+            adapter_crashed,
         })
     }
 
     /// Spawn a new DAP adapter that uses TCP for communication (e.g., Delve, js-debug)
+    ///
+    /// With [`SpawnStyle::ConnectExisting`] nothing is spawned and the client
+    /// connects to `address` instead.
     pub async fn spawn_tcp(
         adapter_path: &Path,
         args: &[String],
-        spawn_style: &crate::common::config::TcpSpawnStyle,
+        spawn_style: &SpawnStyle,
+        address: Option<&str>,
     ) -> Result<Self> {
         use crate::common::parse_listen_address;
         use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 
         let (mut adapter, addr) = match spawn_style {
-            crate::common::config::TcpSpawnStyle::TcpListen => {
+            SpawnStyle::ListenFlag => {
                 let mut cmd = Command::new(adapter_path);
                 cmd.args(args)
                     .arg("--listen=127.0.0.1:0")
@@ -222,9 +245,9 @@ impl DapClient {
                     }
                 };
 
-                (adapter, addr)
+                (Some(adapter), addr)
             }
-            crate::common::config::TcpSpawnStyle::TcpPortArg => {
+            SpawnStyle::PortArg => {
                 use std::net::TcpListener as StdTcpListener;
 
                 let listener = StdTcpListener::bind("127.0.0.1:0").map_err(|e| {
@@ -256,7 +279,16 @@ impl DapClient {
                     ))
                 })?;
 
-                (adapter, addr)
+                (Some(adapter), addr)
+            }
+            SpawnStyle::ConnectExisting => {
+                let addr = address.filter(|a| !a.is_empty()).ok_or_else(|| {
+                    Error::Config(
+                        "spawn_style \"connect-existing\" needs an address, e.g. address = \"127.0.0.1:4711\""
+                            .to_string(),
+                    )
+                })?;
+                (None, addr.to_string())
             }
         };
 
@@ -275,7 +307,9 @@ impl DapClient {
                     Ok(s) => break s,
                     Err(e) => {
                         if start.elapsed() >= timeout_duration {
-                            let _ = adapter.start_kill();
+                            if let Some(adapter) = adapter.as_mut() {
+                                let _ = adapter.start_kill();
+                            }
                             return Err(Error::AdapterStartFailed(format!(
                                 "Failed to connect to adapter at {} after {:?}: {}",
                                 addr, timeout_duration, e
@@ -295,24 +329,28 @@ impl DapClient {
         let pending: PendingResponses = Arc::new(Mutex::new(HashMap::new()));
 
         // Spawn background reader task for TCP
+        let adapter_crashed = Arc::new(AtomicBool::new(false));
         let reader_task = Self::spawn_tcp_reader_task(
             read_half,
             event_tx.clone(),
             pending.clone(),
             shutdown_rx,
+            adapter_crashed.clone(),
         );
 
         Ok(Self {
             adapter,
-            writer: DapWriter::Tcp(BufWriter::new(write_half)),
-            seq: AtomicI64::new(1),
+            writer: Arc::new(Mutex::new(DapWriter::Tcp(BufWriter::new(write_half)))),
+            seq: Arc::new(AtomicI64::new(1)),
             capabilities: Capabilities::default(),
             request_timeout: Duration::from_secs(30),
+            eval_timeout: Duration::from_secs(120),
             pending,
             event_tx,
             event_rx: Some(event_rx),
             reader_task: Some(reader_task),
             shutdown_tx: Some(shutdown_tx),
+            adapter_crashed,
         })
     }
 
@@ -322,9 +360,11 @@ impl DapClient {
         event_tx: mpsc::UnboundedSender<Event>,
         pending: PendingResponses,
         mut shutdown_rx: mpsc::Receiver<()>,
+        adapter_crashed: Arc<AtomicBool>,
     ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
             let mut reader = BufReader::new(stdout);
+            let mut program_ended = false;
 
             loop {
                 tokio::select! {
@@ -342,7 +382,14 @@ impl DapClient {
                             Ok(json) => {
                                 tracing::trace!("DAP <<< {}", json);
 
-                                if let Err(e) = Self::process_message(&json, &event_tx, &pending).await {
+                                let processed = Self::process_message(
+                                    &json,
+                                    &event_tx,
+                                    &pending,
+                                    &mut program_ended,
+                                )
+                                .await;
+                                if let Err(e) = processed {
                                     tracing::error!("Error processing DAP message: {}", e);
                                 }
                             }
@@ -362,10 +409,16 @@ impl DapClient {
 
                                 // Signal error to any pending requests
                                 let mut pending_guard = pending.lock().await;
-                                for (_, tx) in pending_guard.drain() {
-                                    let _ = tx.send(Err(Error::AdapterCrashed));
+                                for (_, request) in pending_guard.drain() {
+                                    let _ = request.tx.send(Err(Error::AdapterCrashed));
                                 }
 
+                                // Ending without an exit or terminated event
+                                // first means the adapter died under the program
+                                if !program_ended {
+                                    tracing::warn!("Debug adapter exited unexpectedly");
+                                    adapter_crashed.store(true, Ordering::SeqCst);
+                                }
                                 // Send terminated event to notify the session
                                 let _ = event_tx.send(Event::Terminated(None));
                                 break;
@@ -375,6 +428,7 @@ impl DapClient {
                 }
             }
 
+            progress::clear();
             tracing::debug!("Reader task exiting");
         })
     }
@@ -385,9 +439,11 @@ impl DapClient {
         event_tx: mpsc::UnboundedSender<Event>,
         pending: PendingResponses,
         mut shutdown_rx: mpsc::Receiver<()>,
+        adapter_crashed: Arc<AtomicBool>,
     ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
             let mut reader = BufReader::new(read_half);
+            let mut program_ended = false;
 
             loop {
                 tokio::select! {
@@ -405,7 +461,14 @@ impl DapClient {
                             Ok(json) => {
                                 tracing::trace!("DAP <<< {}", json);
 
-                                if let Err(e) = Self::process_message(&json, &event_tx, &pending).await {
+                                let processed = Self::process_message(
+                                    &json,
+                                    &event_tx,
+                                    &pending,
+                                    &mut program_ended,
+                                )
+                                .await;
+                                if let Err(e) = processed {
                                     tracing::error!("Error processing DAP message: {}", e);
                                 }
                             }
@@ -424,10 +487,16 @@ impl DapClient {
 
                                 // Signal error to any pending requests
                                 let mut pending_guard = pending.lock().await;
-                                for (_, tx) in pending_guard.drain() {
-                                    let _ = tx.send(Err(Error::AdapterCrashed));
+                                for (_, request) in pending_guard.drain() {
+                                    let _ = request.tx.send(Err(Error::AdapterCrashed));
                                 }
 
+                                // Ending without an exit or terminated event
+                                // first means the adapter died under the program
+                                if !program_ended {
+                                    tracing::warn!("Debug adapter exited unexpectedly");
+                                    adapter_crashed.store(true, Ordering::SeqCst);
+                                }
                                 // Send terminated event to notify the session
                                 let _ = event_tx.send(Event::Terminated(None));
                                 break;
@@ -437,15 +506,18 @@ impl DapClient {
                 }
             }
 
+            progress::clear();
             tracing::debug!("TCP reader task exiting");
         })
     }
 
-    /// Process a single message from the adapter
+    /// Process a single message from the adapter, noting in `program_ended`
+    /// when it reports the program exited or terminated
     async fn process_message(
         json: &str,
         event_tx: &mpsc::UnboundedSender<Event>,
         pending: &PendingResponses,
+        program_ended: &mut bool,
     ) -> Result<()> {
         let msg: Value = serde_json::from_str(json)
             .map_err(|e| Error::DapProtocol(format!("Invalid JSON: {}", e)))?;
@@ -461,8 +533,8 @@ impl DapClient {
                 let seq = response.request_seq;
 
                 let mut pending_guard = pending.lock().await;
-                if let Some(tx) = pending_guard.remove(&seq) {
-                    let _ = tx.send(Ok(response));
+                if let Some(request) = pending_guard.remove(&seq) {
+                    let _ = request.tx.send(Ok(response));
                 } else {
                     tracing::warn!("Received response for unknown request seq {}", seq);
                 }
@@ -470,6 +542,10 @@ impl DapClient {
             "event" => {
                 let event_msg: EventMessage = serde_json::from_value(msg)?;
                 let event = Event::from_message(&event_msg);
+                if matches!(event, Event::Exited(_) | Event::Terminated(_)) {
+                    *program_ended = true;
+                }
+                progress::record(&event);
                 let _ = event_tx.send(event);
             }
             _ => {
@@ -490,6 +566,11 @@ impl DapClient {
         self.request_timeout = timeout;
     }
 
+    /// Set the timeout used by `evaluate` requests.
+    pub fn set_eval_timeout(&mut self, timeout: Duration) {
+        self.eval_timeout = timeout;
+    }
+
     /// Get the next sequence number
     fn next_seq(&self) -> i64 {
         self.seq.fetch_add(1, Ordering::SeqCst)
@@ -526,10 +607,10 @@ impl DapClient {
         let (tx, _rx) = oneshot::channel();
         {
             let mut pending_guard = self.pending.lock().await;
-            pending_guard.insert(seq, tx);
+            pending_guard.insert(seq, PendingRequest { command: command.to_string(), tx });
         }
 
-        if let Err(error) = codec::write_message(&mut self.writer, &json).await {
+        if let Err(error) = codec::write_message(&mut *self.writer.lock().await, &json).await {
             let mut pending_guard = self.pending.lock().await;
             pending_guard.remove(&seq);
             return Err(error);
@@ -558,6 +639,18 @@ impl DapClient {
         command: &str,
         arguments: Option<Value>,
         timeout: Duration,
+    ) -> Result<T> {
+        self.request_inner(command, arguments, timeout, false).await
+    }
+
+    /// Send a request and wait for the response, optionally asking the
+    /// adapter to cancel it when the timeout expires
+    async fn request_inner<T: serde::de::DeserializeOwned>(
+        &mut self,
+        command: &str,
+        arguments: Option<Value>,
+        timeout: Duration,
+        cancel_on_timeout: bool,
     ) -> Result<T> {
         let seq = self.next_seq();
 
@@ -582,14 +675,14 @@ impl DapClient {
         let (tx, rx) = oneshot::channel();
         {
             let mut pending_guard = self.pending.lock().await;
-            pending_guard.insert(seq, tx);
+            pending_guard.insert(seq, PendingRequest { command: command.to_string(), tx });
         }
 
         // Now send the request
         let json = serde_json::to_string(&request)?;
         tracing::trace!("DAP >>> {}", json);
 
-        if let Err(e) = codec::write_message(&mut self.writer, &json).await {
+        if let Err(e) = codec::write_message(&mut *self.writer.lock().await, &json).await {
             // Remove the pending handler if send failed
             let mut pending_guard = self.pending.lock().await;
             pending_guard.remove(&seq);
@@ -597,18 +690,21 @@ impl DapClient {
         }
 
         // Wait for response with timeout
-        let response = tokio::time::timeout(timeout, rx)
-            .await
-            .map_err(|_| {
-                // Clean up pending handler on timeout
-                let pending = self.pending.clone();
-                tokio::spawn(async move {
-                    let mut pending_guard = pending.lock().await;
-                    pending_guard.remove(&seq);
-                });
-                Error::Timeout(timeout.as_secs())
-            })?
-            .map_err(|_| Error::AdapterCrashed)??;
+        let response = match tokio::time::timeout(timeout, rx).await {
+            Ok(response) => response.map_err(|_| Error::AdapterCrashed)??,
+            Err(_) => {
+                if cancel_on_timeout {
+                    // Cancelling also removes the pending handler
+                    if let Err(e) = self.cancel(seq).await {
+                        tracing::debug!("Failed to cancel timed out {}: {}", command, e);
+                    }
+                } else {
+                    // Clean up pending handler on timeout
+                    self.pending.lock().await.remove(&seq);
+                }
+                return Err(Error::Timeout(timeout.as_secs()));
+            }
+        };
 
         if response.success {
             let body = response.body.unwrap_or(Value::Null);
@@ -626,6 +722,22 @@ impl DapClient {
         }
     }
 
+    /// Handle for cancelling this client's requests while another task is
+    /// waiting on one of them
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            writer: self.writer.clone(),
+            seq: self.seq.clone(),
+            pending: self.pending.clone(),
+            supported: self.capabilities.supports_cancel_request,
+        }
+    }
+
+    /// Cancel a request in flight; see [`CancelHandle::cancel`]
+    pub async fn cancel(&self, request_seq: i64) -> Result<bool> {
+        self.cancel_handle().cancel(request_seq).await
+    }
+
     /// Poll for events - this is now non-blocking since events are already in the channel
     /// Note: This method is kept for API compatibility but is no longer necessary
     /// since the background reader task handles all event ingestion
@@ -638,18 +750,43 @@ impl DapClient {
 
     /// Initialize the debug adapter
     pub async fn initialize(&mut self, adapter_id: &str) -> Result<Capabilities> {
-        self.initialize_with_timeout(adapter_id, Duration::from_secs(10)).await
+        self.initialize_with_timeout(adapter_id, &DapConfig::default(), Duration::from_secs(10))
+            .await
     }
 
-    /// Initialize the debug adapter with configurable timeout
+    /// Initialize the debug adapter with configurable timeout, sending the
+    /// client arguments set in `dap` in place of the defaults
     pub async fn initialize_with_timeout(
         &mut self,
         adapter_id: &str,
+        dap: &DapConfig,
         timeout: Duration,
     ) -> Result<Capabilities> {
+        let defaults = InitializeArguments::default();
         let args = InitializeArguments {
             adapter_id: adapter_id.to_string(),
-            ..Default::default()
+            client_id: dap.client_id.clone().or(defaults.client_id),
+            client_name: dap.client_name.clone().or(defaults.client_name),
+            locale: dap.locale.clone().or(defaults.locale),
+            lines_start_at1: defaults.lines_start_at1,
+            columns_start_at1: defaults.columns_start_at1,
+            path_format: defaults.path_format,
+            supports_variable_type: dap
+                .supports_variable_type
+                .unwrap_or(defaults.supports_variable_type),
+            supports_variable_paging: dap
+                .supports_variable_paging
+                .unwrap_or(defaults.supports_variable_paging),
+            supports_run_in_terminal_request: defaults.supports_run_in_terminal_request,
+            supports_memory_references: dap
+                .supports_memory_references
+                .unwrap_or(defaults.supports_memory_references),
+            supports_progress_reporting: dap
+                .supports_progress_reporting
+                .unwrap_or(defaults.supports_progress_reporting),
+            supports_invalidated_event: dap
+                .supports_invalidated_event
+                .unwrap_or(defaults.supports_invalidated_event),
         };
 
         let caps: Capabilities = self
@@ -783,11 +920,32 @@ impl DapClient {
         Ok(response.breakpoints)
     }
 
+    /// Set instruction breakpoints
+    pub async fn set_instruction_breakpoints(
+        &mut self,
+        breakpoints: Vec<InstructionBreakpoint>,
+    ) -> Result<Vec<Breakpoint>> {
+        let args = SetInstructionBreakpointsArguments { breakpoints };
+
+        let response: SetBreakpointsResponseBody = self
+            .request(
+                "setInstructionBreakpoints",
+                Some(serde_json::to_value(&args)?),
+            )
+            .await?;
+
+        Ok(response.breakpoints)
+    }
+
     /// Continue execution
-    pub async fn continue_execution(&mut self, thread_id: i64) -> Result<bool> {
+    pub async fn continue_execution(
+        &mut self,
+        thread_id: i64,
+        single_thread: bool,
+    ) -> Result<bool> {
         let args = ContinueArguments {
             thread_id,
-            single_thread: false,
+            single_thread,
         };
 
         let response: ContinueResponseBody = self
@@ -798,9 +956,10 @@ impl DapClient {
     }
 
     /// Step over (next)
-    pub async fn next(&mut self, thread_id: i64) -> Result<()> {
+    pub async fn next(&mut self, thread_id: i64, single_thread: bool) -> Result<()> {
         let args = StepArguments {
             thread_id,
+            single_thread,
             granularity: Some("statement".to_string()),
         };
 
@@ -810,9 +969,10 @@ impl DapClient {
     }
 
     /// Step into
-    pub async fn step_in(&mut self, thread_id: i64) -> Result<()> {
+    pub async fn step_in(&mut self, thread_id: i64, single_thread: bool) -> Result<()> {
         let args = StepArguments {
             thread_id,
+            single_thread,
             granularity: Some("statement".to_string()),
         };
 
@@ -822,9 +982,10 @@ impl DapClient {
     }
 
     /// Step out
-    pub async fn step_out(&mut self, thread_id: i64) -> Result<()> {
+    pub async fn step_out(&mut self, thread_id: i64, single_thread: bool) -> Result<()> {
         let args = StepArguments {
             thread_id,
+            single_thread,
             granularity: Some("statement".to_string()),
         };
 
@@ -878,6 +1039,7 @@ impl DapClient {
     pub async fn variables(&mut self, variables_reference: i64) -> Result<Vec<Variable>> {
         let args = VariablesArguments {
             variables_reference,
+            filter: None,
             start: None,
             count: None,
         };
@@ -889,6 +1051,27 @@ impl DapClient {
         Ok(response.variables)
     }
 
+    /// Get `count` indexed children of a container, starting at `start`
+    pub async fn indexed_variables(
+        &mut self,
+        variables_reference: i64,
+        start: i64,
+        count: i64,
+    ) -> Result<Vec<Variable>> {
+        let args = VariablesArguments {
+            variables_reference,
+            filter: Some("indexed".to_string()),
+            start: Some(start),
+            count: Some(count),
+        };
+
+        let response: VariablesResponseBody = self
+            .request("variables", Some(serde_json::to_value(&args)?))
+            .await?;
+
+        Ok(response.variables)
+    }
+
     /// Evaluate an expression
     pub async fn evaluate(
         &mut self,
@@ -902,10 +1085,114 @@ impl DapClient {
             context: Some(context.to_string()),
         };
 
-        self.request("evaluate", Some(serde_json::to_value(&args)?))
+        // Evaluation can call into the debuggee and hang, so it gets its own
+        // timeout, after which the adapter is asked to abandon it
+        self.request_inner(
+            "evaluate",
+            Some(serde_json::to_value(&args)?),
+            self.eval_timeout,
+            true,
+        )
+        .await
+    }
+
+    /// Assign `value` to the variable `name` of a variables container
+    pub async fn set_variable(
+        &mut self,
+        variables_reference: i64,
+        name: &str,
+        value: &str,
+    ) -> Result<SetValueResponseBody> {
+        let args = SetVariableArguments {
+            variables_reference,
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+
+        self.request("setVariable", Some(serde_json::to_value(&args)?))
+            .await
+    }
+
+    /// Assign `value` to an assignable expression
+    pub async fn set_expression(
+        &mut self,
+        expression: &str,
+        value: &str,
+        frame_id: Option<i64>,
+    ) -> Result<SetValueResponseBody> {
+        let args = SetExpressionArguments {
+            expression: expression.to_string(),
+            value: value.to_string(),
+            frame_id,
+        };
+
+        // Like evaluate, assigning can run debuggee code (setters, operators)
+        self.request_inner(
+            "setExpression",
+            Some(serde_json::to_value(&args)?),
+            self.eval_timeout,
+            true,
+        )
+        .await
+    }
+
+    /// Read raw memory starting at a memory reference
+    pub async fn read_memory(
+        &mut self,
+        memory_reference: &str,
+        offset: Option<i64>,
+        count: u64,
+    ) -> Result<ReadMemoryResponseBody> {
+        let args = ReadMemoryArguments {
+            memory_reference: memory_reference.to_string(),
+            offset,
+            count,
+        };
+
+        self.request("readMemory", Some(serde_json::to_value(&args)?))
             .await
     }
 
+    /// Disassemble instructions starting at a memory reference
+    pub async fn disassemble(
+        &mut self,
+        memory_reference: &str,
+        instruction_count: i64,
+    ) -> Result<Vec<DisassembledInstruction>> {
+        let args = DisassembleArguments {
+            memory_reference: memory_reference.to_string(),
+            offset: None,
+            instruction_offset: None,
+            instruction_count,
+            resolve_symbols: Some(true),
+        };
+
+        let response: DisassembleResponseBody = self
+            .request("disassemble", Some(serde_json::to_value(&args)?))
+            .await?;
+
+        Ok(response.instructions)
+    }
+
+    /// Get details about the exception or signal that stopped a thread
+    pub async fn exception_info(&mut self, thread_id: i64) -> Result<ExceptionInfoResponseBody> {
+        let args = ExceptionInfoArguments { thread_id };
+
+        self.request("exceptionInfo", Some(serde_json::to_value(&args)?))
+            .await
+    }
+
+    /// Terminate the given threads, leaving the rest of the process running
+    pub async fn terminate_threads(&mut self, thread_ids: &[i64]) -> Result<()> {
+        let args = TerminateThreadsArguments {
+            thread_ids: thread_ids.to_vec(),
+        };
+
+        self.request::<Value>("terminateThreads", Some(serde_json::to_value(&args)?))
+            .await?;
+        Ok(())
+    }
+
     /// Disconnect from the debug adapter
     pub async fn disconnect(&mut self, terminate_debuggee: bool) -> Result<()> {
         let args = DisconnectArguments {
@@ -943,15 +1230,27 @@ impl DapClient {
             ).await;
         }
 
-        // Force kill if still running
-        let _ = self.adapter.kill().await;
+        // Force kill if still running; an adapter we didn't spawn is left alone
+        if let Some(adapter) = self.adapter.as_mut() {
+            let _ = adapter.kill().await;
+        }
 
         Ok(())
     }
 
     /// Check if the adapter is still running
     pub fn is_running(&mut self) -> bool {
-        self.adapter.try_wait().ok().flatten().is_none()
+        match self.adapter.as_mut() {
+            Some(adapter) => adapter.try_wait().ok().flatten().is_none(),
+            // Without a process, the connection is all there is to watch
+            None => self.reader_task.as_ref().is_some_and(|task| !task.is_finished()),
+        }
+    }
+
+    /// Whether the adapter's connection ended before it reported that the
+    /// program exited or terminated, i.e. the adapter died under the program
+    pub fn adapter_crashed(&self) -> bool {
+        self.adapter_crashed.load(Ordering::SeqCst)
     }
 
     /// Restart the debug session (for adapters that support it)
@@ -999,6 +1298,129 @@ impl Drop for DapClient {
 
         // Try to kill the adapter on drop
         // This is best-effort since we can't await in drop
-        let _ = self.adapter.start_kill();
+        if let Some(adapter) = self.adapter.as_mut() {
+            let _ = adapter.start_kill();
+        }
+    }
+}
+
+/// Cancels requests of a [`DapClient`] from outside the task that owns it
+#[derive(Clone)]
+pub struct CancelHandle {
+    writer: Arc<Mutex<DapWriter>>,
+    seq: Arc<AtomicI64>,
+    pending: PendingResponses,
+    /// Whether the adapter understands the DAP `cancel` request
+    supported: bool,
+}
+
+impl CancelHandle {
+    /// Cancel the request with sequence number `request_seq`. Its waiter
+    /// fails at once with [`Error::RequestCancelled`], and the adapter is
+    /// asked to abort the work when it supports `cancel`. Returns whether the
+    /// request was still in flight.
+    pub async fn cancel(&self, request_seq: i64) -> Result<bool> {
+        let Some(request) = self.pending.lock().await.remove(&request_seq) else {
+            return Ok(false);
+        };
+        let _ = request.tx.send(Err(Error::RequestCancelled(request.command)));
+
+        if self.supported {
+            let seq = self.seq.fetch_add(1, Ordering::SeqCst);
+            let message = serde_json::json!({
+                "seq": seq,
+                "type": "request",
+                "command": "cancel",
+                "arguments": { "requestId": request_seq }
+            });
+            let json = serde_json::to_string(&message)?;
+            tracing::trace!("DAP >>> {}", json);
+
+            // The response needs an entry, but nobody waits for it
+            let (tx, _rx) = oneshot::channel();
+            self.pending.lock().await.insert(
+                seq,
+                PendingRequest {
+                    command: "cancel".to_string(),
+                    tx,
+                },
+            );
+            codec::write_message(&mut *self.writer.lock().await, &json).await?;
+        }
+        Ok(true)
+    }
+
+    /// Cancel every request in flight for a DAP command, returning how many
+    /// there were
+    pub async fn cancel_command(&self, command: &str) -> Result<usize> {
+        let seqs: Vec<i64> = self
+            .pending
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, request)| request.command == command)
+            .map(|(seq, _)| *seq)
+            .collect();
+
+        let mut cancelled = 0;
+        for seq in seqs {
+            if self.cancel(seq).await? {
+                cancelled += 1;
+            }
+        }
+        Ok(cancelled)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancelling_fails_the_waiting_request() {
+        // `cat` echoes requests back, so no response ever arrives
+        let mut client = DapClient::spawn(Path::new("cat"), &[]).await.unwrap();
+        let handle = client.cancel_handle();
+
+        let evaluate = tokio::spawn(async move {
+            let result = client
+                .request_with_timeout::<Value>("evaluate", None, Duration::from_secs(30))
+                .await;
+            client.terminate().await.unwrap();
+            result
+        });
+
+        let mut cancelled = 0;
+        while cancelled == 0 {
+            tokio::task::yield_now().await;
+            cancelled = handle.cancel_command("evaluate").await.unwrap();
+        }
+
+        assert_eq!(cancelled, 1);
+        let result = evaluate.await.unwrap();
+        assert!(matches!(result, Err(Error::RequestCancelled(command)) if command == "evaluate"));
+    }
+
+    #[tokio::test]
+    async fn evaluate_uses_its_own_timeout_and_cancels() {
+        let mut client = DapClient::spawn(Path::new("cat"), &[]).await.unwrap();
+        client.set_eval_timeout(Duration::from_millis(50));
+
+        let result = client.evaluate("spin()", None, "repl").await;
+        let pending = client.pending.lock().await.len();
+        client.terminate().await.unwrap();
+
+        assert!(matches!(result, Err(Error::Timeout(0))));
+        assert_eq!(pending, 0);
+    }
+
+    #[tokio::test]
+    async fn adapter_exit_without_terminating_is_a_crash() {
+        let mut client = DapClient::spawn(Path::new("true"), &[]).await.unwrap();
+        let mut events = client.take_event_receiver().unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv()).await;
+        assert!(matches!(event, Ok(Some(Event::Terminated(None)))));
+        assert!(client.adapter_crashed());
     }
 }
@@ -0,0 +1,218 @@
+//! A scripted debug adapter for tests
+//!
+//! Listens on a loopback port, so sessions reach it through the
+//! `connect-existing` spawn style like any adapter that is already running.
+//! Every connection is a fresh adapter, numbered from 0, which lets a test
+//! script a crash on one connection and see the relaunch on the next.
+//!
+//! Requests are answered by the test's script, which returns the actions to
+//! take, or `None` for the default answer: success with a plausible body,
+//! plus the `initialized` event after `launch` and `attach`.
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::common::config::Config;
+
+use super::codec;
+
+/// Something the adapter does in answer to a request
+#[derive(Debug, Clone)]
+pub(crate) enum Action {
+    /// Respond successfully with this body
+    Respond(Value),
+    /// Respond with a failure and this message
+    Fail(&'static str),
+    /// Send an event with this name and body
+    Event(&'static str, Value),
+    /// Close the connection, as if the adapter had died
+    Hangup,
+}
+
+/// Decides the actions for a request: `(connection, command, arguments)`
+type Script = dyn Fn(usize, &str, &Value) -> Option<Vec<Action>> + Send + Sync;
+
+/// A request the adapter received
+#[derive(Debug, Clone)]
+pub(crate) struct Received {
+    pub connection: usize,
+    pub command: String,
+    pub arguments: Value,
+}
+
+pub(crate) struct MockAdapter {
+    address: String,
+    received: Arc<Mutex<Vec<Received>>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockAdapter {
+    /// Start an adapter that gives the default answer to everything
+    pub async fn start() -> Self {
+        Self::scripted(|_, _, _| None).await
+    }
+
+    /// Start an adapter that answers requests as `script` says
+    pub async fn scripted(
+        script: impl Fn(usize, &str, &Value) -> Option<Vec<Action>> + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let script: Arc<Script> = Arc::new(script);
+
+        let task = tokio::spawn({
+            let received = received.clone();
+            async move {
+                let mut connection = 0;
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(serve(stream, connection, script.clone(), received.clone()));
+                    connection += 1;
+                }
+            }
+        });
+
+        Self {
+            address,
+            received,
+            task,
+        }
+    }
+
+    /// A config whose adapter "mock" is this one
+    pub fn config(&self) -> Config {
+        toml::from_str(&format!(
+            r#"
+[adapters.mock]
+transport = "tcp"
+spawn_style = "connect-existing"
+address = "{}"
+"#,
+            self.address
+        ))
+        .unwrap()
+    }
+
+    /// Every request received so far, in order
+    pub fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockAdapter {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve(
+    stream: TcpStream,
+    connection: usize,
+    script: Arc<Script>,
+    received: Arc<Mutex<Vec<Received>>>,
+) {
+    let (read_half, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+    let mut seq = 1;
+    let mut next_breakpoint_id = 1;
+
+    while let Ok(json) = codec::read_message(&mut reader).await {
+        let request: Value = serde_json::from_str(&json).unwrap();
+        let command = request["command"].as_str().unwrap_or_default().to_string();
+        let arguments = request.get("arguments").cloned().unwrap_or(Value::Null);
+        received.lock().unwrap().push(Received {
+            connection,
+            command: command.clone(),
+            arguments: arguments.clone(),
+        });
+
+        let actions = script(connection, &command, &arguments)
+            .unwrap_or_else(|| default_actions(&command, &arguments, &mut next_breakpoint_id));
+        for action in actions {
+            let mut message = match action {
+                Action::Respond(body) => json!({
+                    "type": "response",
+                    "request_seq": request["seq"],
+                    "success": true,
+                    "command": command,
+                    "body": body,
+                }),
+                Action::Fail(message) => json!({
+                    "type": "response",
+                    "request_seq": request["seq"],
+                    "success": false,
+                    "command": command,
+                    "message": message,
+                }),
+                Action::Event(event, body) => json!({
+                    "type": "event",
+                    "event": event,
+                    "body": body,
+                }),
+                Action::Hangup => return,
+            };
+            message["seq"] = json!(seq);
+            seq += 1;
+            if codec::write_message(&mut writer, &message.to_string()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// What the adapter does for a request the script leaves alone
+fn default_actions(command: &str, arguments: &Value, next_breakpoint_id: &mut i64) -> Vec<Action> {
+    let mut verified = |line: Value| {
+        let id = *next_breakpoint_id;
+        *next_breakpoint_id += 1;
+        json!({ "id": id, "verified": true, "line": line })
+    };
+    let body = match command {
+        "initialize" => json!({
+            "supportsConfigurationDoneRequest": true,
+            "supportsFunctionBreakpoints": true,
+            "supportsSetVariable": true,
+            "supportsTerminateThreadsRequest": true,
+        }),
+        "launch" | "attach" => {
+            return vec![Action::Respond(json!({})), Action::Event("initialized", json!({}))]
+        }
+        "disconnect" => return vec![Action::Respond(json!({})), Action::Hangup],
+        "setBreakpoints" => {
+            let lines = arguments["breakpoints"].as_array().cloned().unwrap_or_default();
+            let breakpoints: Vec<Value> =
+                lines.into_iter().map(|bp| verified(bp["line"].clone())).collect();
+            json!({ "breakpoints": breakpoints })
+        }
+        "setFunctionBreakpoints" => {
+            let count = arguments["breakpoints"].as_array().map_or(0, Vec::len);
+            let breakpoints: Vec<Value> = (0..count).map(|_| verified(json!(1))).collect();
+            json!({ "breakpoints": breakpoints })
+        }
+        "continue" => json!({ "allThreadsContinued": true }),
+        "threads" => json!({ "threads": [{ "id": 1, "name": "main" }] }),
+        "stackTrace" => json!({
+            "stackFrames": [{
+                "id": 1000,
+                "name": "main",
+                "line": 3,
+                "column": 1,
+                "source": { "name": "main.c", "path": "/src/main.c" },
+            }],
+            "totalFrames": 1,
+        }),
+        "scopes" => json!({
+            "scopes": [{ "name": "Locals", "variablesReference": 1, "expensive": false }],
+        }),
+        "variables" => json!({
+            "variables": [{ "name": "x", "value": "1", "type": "int", "variablesReference": 0 }],
+        }),
+        "evaluate" => json!({ "result": "1", "variablesReference": 0 }),
+        "setVariable" => json!({ "value": arguments["value"], "variablesReference": 0 }),
+        _ => json!({}),
+    };
+    vec![Action::Respond(body)]
+}
@@ -5,7 +5,10 @@
 
 pub mod client;
 pub mod codec;
+#[cfg(test)]
+pub(crate) mod mock;
+pub mod progress;
 pub mod types;
 
-pub use client::DapClient;
+pub use client::{CancelHandle, DapClient};
 pub use types::*;
@@ -0,0 +1,104 @@
+//! Progress the adapter reports for long operations, like loading symbols
+//!
+//! Kept process-wide, like the session log: the operations that report the
+//! most progress (launch, attach) run before the session exists, while the
+//! daemon's session actor is busy waiting on them.
+
+use std::sync::Mutex;
+
+use super::types::Event;
+
+/// One operation the adapter reported as in progress
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub id: String,
+    pub title: String,
+    pub message: Option<String>,
+    pub percentage: Option<f64>,
+}
+
+/// Operations in progress, oldest first
+static ACTIVE: Mutex<Vec<Progress>> = Mutex::new(Vec::new());
+
+/// Track a progress event; other events are ignored
+pub(crate) fn record(event: &Event) {
+    apply(&mut ACTIVE.lock().unwrap(), event);
+}
+
+fn apply(active: &mut Vec<Progress>, event: &Event) {
+    match event {
+        Event::ProgressStart {
+            progress_id,
+            title,
+            message,
+            percentage,
+        } => {
+            active.retain(|progress| progress.id != *progress_id);
+            active.push(Progress {
+                id: progress_id.clone(),
+                title: title.clone(),
+                message: message.clone(),
+                percentage: *percentage,
+            });
+        }
+        Event::ProgressUpdate {
+            progress_id,
+            message,
+            percentage,
+        } => {
+            if let Some(progress) = active.iter_mut().find(|p| p.id == *progress_id) {
+                if message.is_some() {
+                    progress.message = message.clone();
+                }
+                if percentage.is_some() {
+                    progress.percentage = *percentage;
+                }
+            }
+        }
+        Event::ProgressEnd { progress_id } => {
+            active.retain(|progress| progress.id != *progress_id);
+        }
+        _ => {}
+    }
+}
+
+/// The most recently started operation still in progress
+pub fn latest() -> Option<Progress> {
+    ACTIVE.lock().unwrap().last().cloned()
+}
+
+/// Forget all progress, when the adapter that reported it is gone
+pub(crate) fn clear() {
+    ACTIVE.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_follows_start_update_and_end() {
+        let mut active = Vec::new();
+        apply(&mut active, &Event::ProgressStart {
+            progress_id: "symbols".to_string(),
+            title: "Loading symbols".to_string(),
+            message: None,
+            percentage: Some(0.0),
+        });
+        apply(&mut active, &Event::ProgressUpdate {
+            progress_id: "symbols".to_string(),
+            message: Some("libc.so.6".to_string()),
+            percentage: Some(40.0),
+        });
+
+        let progress = active.last().unwrap();
+        assert_eq!(progress.title, "Loading symbols");
+        assert_eq!(progress.message.as_deref(), Some("libc.so.6"));
+        assert_eq!(progress.percentage, Some(40.0));
+
+        apply(&mut active, &Event::ProgressEnd {
+            progress_id: "symbols".to_string(),
+        });
+        assert!(active.is_empty());
+    }
+}
@@ -84,6 +84,8 @@ pub struct InitializeArguments {
     pub supports_memory_references: bool,
     #[serde(default)]
     pub supports_progress_reporting: bool,
+    #[serde(default)]
+    pub supports_invalidated_event: bool,
 }
 
 fn default_true() -> bool {
@@ -104,7 +106,8 @@ impl Default for InitializeArguments {
             supports_variable_paging: true,
             supports_run_in_terminal_request: false,
             supports_memory_references: true,
-            supports_progress_reporting: false,
+            supports_progress_reporting: true,
+            supports_invalidated_event: true,
         }
     }
 }
@@ -188,6 +191,10 @@ pub struct AttachArguments {
     // lldb-dap specific
     #[serde(skip_serializing_if = "Option::is_none")]
     pub wait_for: Option<bool>,
+    /// Keep the process stopped after `configurationDone`; lldb-dap resumes
+    /// an attached process otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_on_entry: Option<bool>,
 }
 
 /// SetBreakpoints request arguments
@@ -206,6 +213,13 @@ pub struct SetFunctionBreakpointsArguments {
     pub breakpoints: Vec<FunctionBreakpoint>,
 }
 
+/// SetInstructionBreakpoints request arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetInstructionBreakpointsArguments {
+    pub breakpoints: Vec<InstructionBreakpoint>,
+}
+
 /// Continue request arguments
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -220,6 +234,8 @@ pub struct ContinueArguments {
 #[serde(rename_all = "camelCase")]
 pub struct StepArguments {
     pub thread_id: i64,
+    #[serde(default)]
+    pub single_thread: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub granularity: Option<String>,
 }
@@ -254,6 +270,9 @@ pub struct ScopesArguments {
 #[serde(rename_all = "camelCase")]
 pub struct VariablesArguments {
     pub variables_reference: i64,
+    /// "indexed" or "named", to fetch only that kind of child
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub start: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -271,6 +290,63 @@ pub struct EvaluateArguments {
     pub context: Option<String>,
 }
 
+/// SetVariable request arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetVariableArguments {
+    pub variables_reference: i64,
+    pub name: String,
+    pub value: String,
+}
+
+/// SetExpression request arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetExpressionArguments {
+    pub expression: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<i64>,
+}
+
+/// ReadMemory request arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMemoryArguments {
+    pub memory_reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    pub count: u64,
+}
+
+/// Disassemble request arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembleArguments {
+    pub memory_reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_offset: Option<i64>,
+    pub instruction_count: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_symbols: Option<bool>,
+}
+
+/// ExceptionInfo request arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionInfoArguments {
+    pub thread_id: i64,
+}
+
+/// TerminateThreads request arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminateThreadsArguments {
+    pub thread_ids: Vec<i64>,
+}
+
 /// Disconnect request arguments
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -302,6 +378,8 @@ pub struct Capabilities {
     #[serde(default)]
     pub supports_set_variable: bool,
     #[serde(default)]
+    pub supports_set_expression: bool,
+    #[serde(default)]
     pub supports_restart_frame: bool,
     #[serde(default)]
     pub supports_restart_request: bool,
@@ -321,6 +399,16 @@ pub struct Capabilities {
     pub supports_disassemble_request: bool,
     #[serde(default)]
     pub supports_terminate_request: bool,
+    #[serde(default)]
+    pub supports_terminate_threads_request: bool,
+    #[serde(default)]
+    pub supports_instruction_breakpoints: bool,
+    #[serde(default)]
+    pub supports_exception_info_request: bool,
+    #[serde(default)]
+    pub supports_cancel_request: bool,
+    #[serde(default)]
+    pub supports_single_thread_execution_requests: bool,
 }
 
 /// SetBreakpoints response body
@@ -365,6 +453,73 @@ pub struct EvaluateResponseBody {
     pub type_name: Option<String>,
     #[serde(default)]
     pub variables_reference: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<String>,
+}
+
+/// SetVariable and SetExpression response body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetValueResponseBody {
+    /// The new value, as the adapter renders it
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    #[serde(default)]
+    pub variables_reference: i64,
+}
+
+/// ReadMemory response body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMemoryResponseBody {
+    /// Address of the first byte read
+    pub address: String,
+    #[serde(default)]
+    pub unreadable_bytes: u64,
+    /// Base64-encoded bytes
+    #[serde(default)]
+    pub data: Option<String>,
+}
+
+/// ExceptionInfo response body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionInfoResponseBody {
+    /// Adapter-specific exception or signal identifier, e.g. "signal"
+    pub exception_id: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub break_mode: Option<String>,
+    #[serde(default)]
+    pub details: Option<ExceptionDetails>,
+}
+
+/// Detailed information about a thrown exception
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionDetails {
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub type_name: Option<String>,
+    #[serde(default)]
+    pub full_type_name: Option<String>,
+    #[serde(default)]
+    pub evaluate_name: Option<String>,
+    /// Stack trace at the time the exception was thrown, as text
+    #[serde(default)]
+    pub stack_trace: Option<String>,
+    #[serde(default)]
+    pub inner_exception: Vec<ExceptionDetails>,
+}
+
+/// Disassemble response body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisassembleResponseBody {
+    #[serde(default)]
+    pub instructions: Vec<DisassembledInstruction>,
 }
 
 /// Continue response body
@@ -387,6 +542,10 @@ pub struct Source {
     pub path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_reference: Option<i64>,
+    /// "normal", "emphasize" or "deemphasize"; deemphasized sources are
+    /// usually library code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
 }
 
 /// Breakpoint to set at a source location
@@ -415,6 +574,19 @@ pub struct FunctionBreakpoint {
     pub hit_condition: Option<String>,
 }
 
+/// Instruction breakpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionBreakpoint {
+    pub instruction_reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+}
+
 /// Breakpoint information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -430,6 +602,21 @@ pub struct Breakpoint {
     pub line: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub column: Option<u32>,
+    /// Why an unverified breakpoint isn't verified: "pending" when it may
+    /// still bind (e.g. once a library loads), "failed" when it won't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// A module (library, shared object, Python module...) of the debuggee
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Module {
+    /// A number or a string, depending on the adapter
+    pub id: Value,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
 }
 
 /// Stack frame
@@ -444,6 +631,33 @@ pub struct StackFrame {
     pub column: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub module_id: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_pointer_reference: Option<String>,
+    /// "normal", "label" (a separator such as an async boundary, not a real
+    /// frame) or "subtle" (library or runtime code)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
+}
+
+/// A single instruction from a disassemble response
+///
+/// Adapters may omit `location` when it is unchanged from the previous
+/// instruction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisassembledInstruction {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_bytes: Option<String>,
+    pub instruction: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
 }
 
 /// Thread
@@ -477,6 +691,30 @@ pub struct Variable {
     pub type_name: Option<String>,
     #[serde(default)]
     pub variables_reference: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<VariablePresentationHint>,
+    /// Number of indexed children, for arrays and other containers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<i64>,
+    /// Where the variable's value lives, for `readMemory`; sent by adapters
+    /// because we advertise `supportsMemoryReferences`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<String>,
+}
+
+/// How a client should present a variable
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablePresentationHint {
+    /// e.g. "property", "method", "data", "virtual"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// e.g. "static", "constant", "readOnly", "hasSideEffects"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    /// e.g. "public", "private", "internal"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
 }
 
 // === Event Bodies ===
@@ -544,6 +782,26 @@ pub enum Event {
     Thread(ThreadEventBody),
     Output(OutputEventBody),
     Breakpoint { reason: String, breakpoint: Breakpoint },
+    /// A module was loaded ("new"), changed or unloaded ("removed")
+    Module { reason: String, module: Module },
+    /// A source file became available ("new"), changed or went away ("removed")
+    LoadedSource { reason: String, source: Source },
+    /// A long operation (e.g. loading symbols) started
+    ProgressStart {
+        progress_id: String,
+        title: String,
+        message: Option<String>,
+        percentage: Option<f64>,
+    },
+    ProgressUpdate {
+        progress_id: String,
+        message: Option<String>,
+        percentage: Option<f64>,
+    },
+    ProgressEnd { progress_id: String },
+    /// Data the client may have cached is out of date. `areas` holds "all",
+    /// "stacks", "threads" or "variables"; empty means "all".
+    Invalidated { areas: Vec<String>, thread_id: Option<i64> },
     Unknown { event: String, body: Option<Value> },
 }
 
@@ -626,6 +884,68 @@ impl Event {
                     body: msg.body.clone(),
                 }
             }
+            "module" => {
+                if let Some(body) = &msg.body {
+                    let reason = body.get("reason").and_then(|v| v.as_str());
+                    let module = body.get("module").cloned().map(serde_json::from_value);
+                    if let (Some(reason), Some(Ok(module))) = (reason, module) {
+                        return Event::Module { reason: reason.to_string(), module };
+                    }
+                }
+                Event::Unknown {
+                    event: msg.event.clone(),
+                    body: msg.body.clone(),
+                }
+            }
+            "progressStart" | "progressUpdate" | "progressEnd" => {
+                let body = msg.body.as_ref();
+                let field = |key: &str| body.and_then(|b| b.get(key));
+                let text = |key: &str| field(key).and_then(|v| v.as_str()).map(String::from);
+                let Some(progress_id) = text("progressId") else {
+                    return Event::Unknown {
+                        event: msg.event.clone(),
+                        body: msg.body.clone(),
+                    };
+                };
+                let message = text("message");
+                let percentage = field("percentage").and_then(|v| v.as_f64());
+                match msg.event.as_str() {
+                    "progressStart" => Event::ProgressStart {
+                        progress_id,
+                        title: text("title").unwrap_or_default(),
+                        message,
+                        percentage,
+                    },
+                    "progressUpdate" => Event::ProgressUpdate {
+                        progress_id,
+                        message,
+                        percentage,
+                    },
+                    _ => Event::ProgressEnd { progress_id },
+                }
+            }
+            "invalidated" => {
+                let body = msg.body.as_ref();
+                let areas = body
+                    .and_then(|b| b.get("areas"))
+                    .and_then(|v| serde_json::from_value(v.clone()).ok())
+                    .unwrap_or_default();
+                let thread_id = body.and_then(|b| b.get("threadId")).and_then(|v| v.as_i64());
+                Event::Invalidated { areas, thread_id }
+            }
+            "loadedSource" => {
+                if let Some(body) = &msg.body {
+                    let reason = body.get("reason").and_then(|v| v.as_str());
+                    let source = body.get("source").cloned().map(serde_json::from_value);
+                    if let (Some(reason), Some(Ok(source))) = (reason, source) {
+                        return Event::LoadedSource { reason: reason.to_string(), source };
+                    }
+                }
+                Event::Unknown {
+                    event: msg.event.clone(),
+                    body: msg.body.clone(),
+                }
+            }
             _ => Event::Unknown {
                 event: msg.event.clone(),
                 body: msg.body.clone(),
@@ -633,3 +953,76 @@ impl Event {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(event: &str, body: Value) -> Event {
+        Event::from_message(&EventMessage {
+            seq: 1,
+            message_type: "event".to_string(),
+            event: event.to_string(),
+            body: Some(body),
+        })
+    }
+
+    #[test]
+    fn module_source_progress_and_invalidated_events_are_parsed() {
+        let module = event(
+            "module",
+            json!({ "reason": "new", "module": { "id": 3, "name": "libm.so.6" } }),
+        );
+        assert!(matches!(
+            module,
+            Event::Module { reason, module } if reason == "new" && module.id == json!(3)
+        ));
+
+        let source = event(
+            "loadedSource",
+            json!({ "reason": "changed", "source": { "path": "/app/main.py" } }),
+        );
+        assert!(matches!(
+            source,
+            Event::LoadedSource { reason, source }
+                if reason == "changed" && source.path.as_deref() == Some("/app/main.py")
+        ));
+
+        let invalidated = event("invalidated", json!({ "areas": ["variables"], "threadId": 4 }));
+        assert!(matches!(
+            invalidated,
+            Event::Invalidated { areas, thread_id: Some(4) } if areas == ["variables"]
+        ));
+
+        let progress = event(
+            "progressUpdate",
+            json!({ "progressId": "p1", "message": "libc.so.6", "percentage": 40 }),
+        );
+        assert!(matches!(
+            progress,
+            Event::ProgressUpdate { progress_id, percentage: Some(p), .. }
+                if progress_id == "p1" && p == 40.0
+        ));
+
+        // A module without a name is malformed
+        let malformed = event("module", json!({ "reason": "new", "module": { "id": 3 } }));
+        assert!(matches!(malformed, Event::Unknown { .. }));
+    }
+
+    #[test]
+    fn variables_carry_their_memory_reference() {
+        let pointer: Variable = serde_json::from_value(json!({
+            "name": "buf",
+            "value": "0x00005555555592a0",
+            "variablesReference": 7,
+            "memoryReference": "0x00005555555592a0",
+        }))
+        .unwrap();
+        assert_eq!(pointer.memory_reference.as_deref(), Some("0x00005555555592a0"));
+
+        let value: Variable =
+            serde_json::from_value(json!({ "name": "n", "value": "3" })).unwrap();
+        assert_eq!(value.memory_reference, None);
+    }
+}
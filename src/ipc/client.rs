@@ -1,5 +1,10 @@
 //! CLI-side IPC client for communicating with the daemon
 
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
 use tokio::io::{ReadHalf, WriteHalf};
 
 use crate::common::{Error, Result};
@@ -7,6 +12,101 @@ use crate::common::{Error, Result};
 use super::protocol::{Command, Request, Response};
 use super::transport::{self, Stream};
 
+/// File that every command sent by this process is appended to, if recording
+static RECORD_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Record every command sent to the daemon by this process to `path`,
+/// except the status and progress queries the CLI polls with.
+///
+/// Commands are appended as JSON lines so a recording can span several CLI
+/// invocations and be replayed with `debugger replay`.
+pub fn record_commands_to(path: PathBuf) {
+    let _ = RECORD_PATH.set(path);
+}
+
+/// Command history of the current project, if keeping one
+static HISTORY_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keep the commands sent by this process in the project history at `path`
+/// (see [`crate::common::paths::history_path`]).
+///
+/// Unlike a `--record` log, failing to write the history never fails the
+/// command. Polls are left out of both.
+pub fn record_history_to(path: PathBuf) {
+    let _ = HISTORY_PATH.set(path);
+}
+
+/// Whether to print the DAP traffic behind each command (`-vv`)
+static ECHO_DAP: AtomicBool = AtomicBool::new(false);
+
+/// Print to stderr the DAP requests and responses the daemon exchanges with
+/// the adapter while handling each command sent by this process, other than
+/// polls. The daemon sends them back with its response.
+pub fn echo_dap_traffic() {
+    ECHO_DAP.store(true, Ordering::Relaxed);
+}
+
+/// Whether `command` is a status or progress query, which the CLI sends on
+/// its own, e.g. every few hundred milliseconds while a launch loads symbols
+fn is_poll(command: &Command) -> bool {
+    matches!(command, Command::Status | Command::Progress)
+}
+
+/// Append one command to a recording file, unless it is a poll
+fn record_command(path: &Path, command: &Command) -> Result<()> {
+    if is_poll(command) {
+        return Ok(());
+    }
+    let mut line = serde_json::to_string(command)?;
+    line.push('\n');
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Append one command to the project history, creating its directory
+fn record_history(path: &Path, command: &Command) -> Result<()> {
+    if is_poll(command) {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    record_command(path, command)
+}
+
+/// The commands of the last session in a history: everything from the last
+/// `start` or `attach` on, or the whole history if it has neither
+pub fn last_session(mut commands: Vec<Command>) -> Vec<Command> {
+    let start = commands
+        .iter()
+        .rposition(|command| {
+            matches!(
+                command,
+                Command::Start { .. } | Command::Attach { .. } | Command::Reattach { .. }
+            )
+        })
+        .unwrap_or(0);
+    commands.split_off(start)
+}
+
+/// Read the commands recorded by [`record_commands_to`]
+pub fn read_recorded_commands(path: &Path) -> Result<Vec<Command>> {
+    let content = std::fs::read_to_string(path).map_err(|e| Error::FileRead {
+        path: path.display().to_string(),
+        error: e.to_string(),
+    })?;
+
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(Error::from))
+        .collect()
+}
+
 /// Client for communicating with the debugger daemon
 pub struct DaemonClient {
     reader: ReadHalf<Stream>,
@@ -38,10 +138,32 @@ impl DaemonClient {
 
     /// Send a command and wait for the response
     pub async fn send_command(&mut self, command: Command) -> Result<serde_json::Value> {
+        let trace_dap = ECHO_DAP.load(Ordering::Relaxed) && !is_poll(&command);
+        let response = self.exchange(command, trace_dap).await?;
+        for message in &response.dap_trace {
+            eprintln!("{}", message);
+        }
+        response.into_result()
+    }
+
+    async fn exchange(&mut self, command: Command, trace_dap: bool) -> Result<Response> {
         let id = self.next_id;
         self.next_id += 1;
 
-        let request = Request { id, command };
+        if let Some(path) = RECORD_PATH.get() {
+            record_command(path, &command)?;
+        }
+        if let Some(path) = HISTORY_PATH.get() {
+            if let Err(e) = record_history(path, &command) {
+                tracing::debug!("Failed to write history {}: {}", path.display(), e);
+            }
+        }
+
+        let request = Request {
+            id,
+            command,
+            trace_dap,
+        };
         let json = serde_json::to_vec(&request)?;
 
         transport::send_message(&mut self.writer, &json)
@@ -61,17 +183,7 @@ impl DaemonClient {
             )));
         }
 
-        if response.success {
-            Ok(response.result.unwrap_or(serde_json::json!({})))
-        } else {
-            let error = response
-                .error
-                .unwrap_or_else(|| crate::common::error::IpcError {
-                    code: "UNKNOWN".to_string(),
-                    message: "Unknown error".to_string(),
-                });
-            Err(error.into())
-        }
+        Ok(response)
     }
 
     /// Check if daemon is responding
@@ -83,3 +195,53 @@ impl DaemonClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_commands_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "debugger-cli-record-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        record_command(&path, &Command::Continue { this_thread: false }).unwrap();
+        record_command(&path, &Command::Progress).unwrap();
+        record_command(&path, &Command::Await { timeout_secs: 5 }).unwrap();
+        record_command(&path, &Command::Status).unwrap();
+
+        let commands = read_recorded_commands(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(commands.len(), 2);
+        assert!(matches!(commands[0], Command::Continue { .. }));
+        assert!(matches!(commands[1], Command::Await { timeout_secs: 5 }));
+    }
+
+    #[test]
+    fn history_replays_from_the_last_session() {
+        let commands = vec![
+            Command::Reattach {
+                pid: None,
+                keep_alive_minutes: None,
+            },
+            Command::Continue { this_thread: false },
+            Command::Stop,
+            Command::Attach {
+                pid: 7,
+                adapter: None,
+                keep_alive_minutes: None,
+            },
+            Command::Continue { this_thread: false },
+        ];
+        let session = last_session(commands);
+        assert_eq!(session.len(), 2);
+        assert!(matches!(session[0], Command::Attach { pid: 7, .. }));
+
+        let resumed = Command::Continue { this_thread: false };
+        assert_eq!(last_session(vec![resumed, Command::Stop]).len(), 2);
+    }
+}
@@ -3,6 +3,7 @@
 //! Defines the request/response format for CLI ↔ daemon communication.
 //! Uses a simple length-prefixed JSON protocol.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -15,6 +16,9 @@ pub struct Request {
     pub id: u64,
     /// The command to execute
     pub command: Command,
+    /// Send back the DAP messages exchanged while handling the command
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trace_dap: bool,
 }
 
 /// IPC response from daemon to CLI
@@ -30,6 +34,10 @@ pub struct Response {
     /// Error information on failure
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<IpcError>,
+    /// DAP messages exchanged while handling the command, when the request
+    /// asked for them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dap_trace: Vec<String>,
 }
 
 impl Response {
@@ -40,6 +48,7 @@ impl Response {
             success: true,
             result: Some(result),
             error: None,
+            dap_trace: Vec::new(),
         }
     }
 
@@ -50,6 +59,7 @@ impl Response {
             success: false,
             result: None,
             error: Some(error),
+            dap_trace: Vec::new(),
         }
     }
 
@@ -60,12 +70,26 @@ impl Response {
             success: true,
             result: Some(serde_json::json!({})),
             error: None,
+            dap_trace: Vec::new(),
+        }
+    }
+
+    /// The result value of a successful response, or its error
+    pub fn into_result(self) -> crate::common::Result<serde_json::Value> {
+        if self.success {
+            Ok(self.result.unwrap_or(serde_json::json!({})))
+        } else {
+            let error = self.error.unwrap_or_else(|| IpcError {
+                code: "UNKNOWN".to_string(),
+                message: "Unknown error".to_string(),
+            });
+            Err(error.into())
         }
     }
 }
 
 /// Commands that can be sent from CLI to daemon
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Command {
     // === Session Management ===
@@ -75,18 +99,34 @@ pub enum Command {
         args: Vec<String>,
         adapter: Option<String>,
         stop_on_entry: bool,
-        /// Initial breakpoints to set before program starts (file:line or function name)
+        /// Initial breakpoints to set before program starts (file:line, function name, or 0x address)
         #[serde(default)]
         initial_breakpoints: Vec<String>,
+        /// Minutes the session may sit idle before the daemon ends it,
+        /// overriding the idle timeout that otherwise only applies without one
+        #[serde(default)]
+        keep_alive_minutes: Option<u64>,
     },
 
     /// Attach to a running process
     Attach {
         pid: u32,
         adapter: Option<String>,
+        #[serde(default)]
+        keep_alive_minutes: Option<u64>,
+    },
+
+    /// Attach again to the process of the last session detached from,
+    /// restoring its breakpoints and watches. `pid`, if given, must be that
+    /// process.
+    Reattach {
+        pid: Option<u32>,
+        #[serde(default)]
+        keep_alive_minutes: Option<u64>,
     },
 
-    /// Detach from process (keeps it running)
+    /// Detach from process (keeps it running). An attached session's
+    /// breakpoints are kept for `Reattach`.
     Detach,
 
     /// Stop debugging (terminates debuggee)
@@ -95,6 +135,10 @@ pub enum Command {
     /// Restart program with same arguments
     Restart,
 
+    /// Stop any session, dropping its breakpoints and state, but keep the
+    /// daemon running
+    Reset,
+
     /// Get session status
     Status,
 
@@ -104,6 +148,9 @@ pub enum Command {
         location: BreakpointLocation,
         condition: Option<String>,
         hit_count: Option<u32>,
+        /// Commands run by the daemon each time this breakpoint is hit
+        #[serde(default)]
+        on_hit: Vec<String>,
     },
 
     /// Remove a breakpoint
@@ -115,24 +162,84 @@ pub enum Command {
     /// List all breakpoints
     BreakpointList,
 
+    /// Everything known about one breakpoint
+    BreakpointInfo { id: u32 },
+
     /// Enable a breakpoint
     BreakpointEnable { id: u32 },
 
     /// Disable a breakpoint
     BreakpointDisable { id: u32 },
 
+    // === Watches ===
+    /// Evaluate an expression at every stop, optionally only while
+    /// `function` is on the stack
+    WatchAdd {
+        expression: String,
+        function: Option<String>,
+    },
+
+    /// Remove a watch, or all of them when `id` is `None`
+    WatchRemove { id: Option<u32> },
+
+    /// List all watches
+    WatchList,
+
+    /// Start an adapter and initialize it without launching anything, to
+    /// report the capabilities it supports. Needs no session.
+    ProbeAdapter { adapter: String },
+
     // === Execution Control ===
+    // Execution commands take `this_thread` to resume only the current
+    // thread, keeping the others stopped (DAP `singleThread`), where the
+    // adapter supports it
     /// Continue execution
-    Continue,
+    Continue {
+        #[serde(default)]
+        this_thread: bool,
+    },
 
     /// Step over (next line, skip function calls)
-    Next,
+    Next {
+        #[serde(default)]
+        this_thread: bool,
+    },
 
     /// Step into (next line, enter function calls)
-    StepIn,
+    StepIn {
+        #[serde(default)]
+        this_thread: bool,
+    },
 
     /// Step out (run until function returns)
-    StepOut,
+    StepOut {
+        #[serde(default)]
+        this_thread: bool,
+    },
+
+    /// Step `count` times, waiting up to `timeout_secs` for each step.
+    /// Returns early with the stop that interrupted the steps (a breakpoint,
+    /// an exception, the program exiting).
+    StepCount {
+        kind: StepKind,
+        count: u32,
+        timeout_secs: u64,
+        #[serde(default)]
+        this_thread: bool,
+    },
+
+    /// Step until `expression`'s value in the top frame differs from its
+    /// value before the first step (it changes, or a condition becomes
+    /// true), for at most `max_steps` steps of up to `timeout_secs` each.
+    /// Like `StepCount`, other stops end it early.
+    StepUntil {
+        expression: String,
+        kind: StepKind,
+        max_steps: u32,
+        timeout_secs: u64,
+        #[serde(default)]
+        this_thread: bool,
+    },
 
     /// Pause execution
     Pause,
@@ -142,31 +249,116 @@ pub enum Command {
     StackTrace {
         thread_id: Option<i64>,
         limit: usize,
+        /// Leave out frames without source or from outside the program's
+        /// directory (libc, runtime internals)
+        #[serde(default)]
+        user_only: bool,
     },
 
     /// Get local variables
-    Locals { frame_id: Option<i64> },
+    Locals {
+        frame_id: Option<i64>,
+        /// Frame by index (0 = top of stack), for this command only; the
+        /// selected frame is unchanged
+        #[serde(default)]
+        frame_index: Option<usize>,
+        /// Characters kept of each string value, overriding
+        /// `output.max_string_length`
+        #[serde(default)]
+        max_string_length: Option<usize>,
+        /// Show values in full, ignoring the configured limits up to a
+        /// fixed ceiling
+        #[serde(default)]
+        no_truncate: bool,
+        /// Drop the variables cached for this stop and fetch them again
+        #[serde(default)]
+        no_cache: bool,
+    },
 
     /// Evaluate expression
     Evaluate {
         expression: String,
         frame_id: Option<i64>,
+        /// Frame by index (0 = top of stack), for this command only
+        #[serde(default)]
+        frame_index: Option<usize>,
         context: EvaluateContext,
+        /// Characters kept of a string result, overriding
+        /// `output.max_string_length`
+        #[serde(default)]
+        max_string_length: Option<usize>,
+        /// Show the result in full, ignoring the configured limits up to a
+        /// fixed ceiling
+        #[serde(default)]
+        no_truncate: bool,
+    },
+
+    /// Assign `value` to `target` with the adapter's setExpression or
+    /// setVariable, rather than as a side effect of evaluating
+    Assign {
+        target: String,
+        value: String,
+        /// Frame by index (0 = top of stack), for this command only
+        #[serde(default)]
+        frame_index: Option<usize>,
     },
 
+    /// Evaluate an expression in each of the first `limit` frames of the
+    /// current stack
+    EvaluateAll { expression: String, limit: usize },
+
     /// Get scopes for a frame
     Scopes { frame_id: i64 },
 
     /// Get variables in a scope
     Variables { reference: i64 },
 
+    /// Read raw memory
+    ReadMemory { address: String, count: u32 },
+
+    /// Read raw memory at the memory reference of a variable in the selected
+    /// frame
+    ReadVariableMemory { name: String, count: u32 },
+
+    /// Disassemble instructions (at the current frame when no address is given)
+    Disassemble {
+        address: Option<String>,
+        count: u32,
+        /// Attach the source line text for each instruction
+        #[serde(default)]
+        source: bool,
+    },
+
+    /// Instruction, stack and frame pointers of the selected frame
+    Registers,
+
+    /// Modules (libraries, shared objects...) the adapter reported loading
+    Modules,
+
     // === Thread/Frame Management ===
-    /// List all threads
-    Threads,
+    /// List threads whose name contains `filter` (ignoring case), at most
+    /// `limit` of them. The list is cached between calls unless `refresh`.
+    Threads {
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        filter: Option<String>,
+        #[serde(default)]
+        refresh: bool,
+    },
+
+    /// Look through the top `frames` frames of every thread for threads
+    /// blocked on locks or waits, and for cycles of threads that may be
+    /// waiting on each other
+    AnalyzeDeadlock { frames: usize },
 
     /// Switch to thread
     ThreadSelect { id: i64 },
 
+    /// Terminate threads with the adapter's terminateThreads, leaving the
+    /// rest of the process running
+    TerminateThreads { ids: Vec<i64> },
+
     /// Select stack frame
     FrameSelect { number: usize },
 
@@ -180,10 +372,31 @@ pub enum Command {
     /// Get current position with source context
     Context { lines: usize },
 
+    /// One report on the current stop, for a crash: the stop with its
+    /// signal or exception, the stopped thread's top `limit` frames, and the
+    /// selected frame's source (`lines` either side) and locals
+    Triage { limit: usize, lines: usize },
+
     // === Async ===
     /// Wait for next stop event
     Await { timeout_secs: u64 },
 
+    /// Wait for the next stop in the background, writing the stop result to
+    /// `path` as JSON rather than replying with it
+    AwaitNotify { timeout_secs: u64, path: PathBuf },
+
+    /// Make every await in progress, blocking or background, return early
+    /// with reason "cancelled"
+    CancelAwait,
+
+    /// Abort the expression evaluations in flight, asking the adapter to
+    /// cancel them when it supports DAP `cancel`
+    CancelEval,
+
+    /// The adapter's latest progress report (e.g. loading symbols), answered
+    /// even while a launch or attach is still running
+    Progress,
+
     // === Output ===
     /// Get buffered output
     GetOutput {
@@ -191,42 +404,117 @@ pub enum Command {
         clear: bool,
     },
 
+    /// Output the session received since this connection's last
+    /// `FollowOutput`, copied as it arrives rather than read from the
+    /// bounded buffer, so none is lost to eviction. Waits up to `wait_ms`
+    /// for some. Only served over a daemon connection, which holds the
+    /// follower's place from its first `FollowOutput`.
+    FollowOutput {
+        #[serde(default)]
+        wait_ms: u64,
+    },
+
+    /// Session events since this connection's last `FollowEvents`: stops,
+    /// resumes, output, thread starts and exits, and the end of the program.
+    /// Waits up to `wait_ms` for some. Only served over a daemon connection,
+    /// which holds the subscriber's place from its first `FollowEvents`.
+    FollowEvents {
+        #[serde(default)]
+        wait_ms: u64,
+        /// Kinds of event to receive (see `SessionEvent::KINDS`), all if
+        /// empty. Fixed by the connection's first `FollowEvents`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        only: Vec<String>,
+    },
+
+    /// Change the live session's output buffer limits, overriding the
+    /// `[output]` config; limits not given are kept. Lowering a limit drops
+    /// the oldest buffered output.
+    SetOutputLimits {
+        #[serde(default)]
+        max_bytes_mb: Option<usize>,
+        #[serde(default)]
+        max_events: Option<usize>,
+    },
+
     // === Shutdown ===
     /// Shutdown the daemon
     Shutdown,
 }
 
 /// Breakpoint location specification
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum BreakpointLocation {
     /// File and line number
     Line { file: PathBuf, line: u32 },
     /// Function name
     Function { name: String },
+    /// Instruction address
+    Address { addr: u64 },
+    /// Lines after (or before) the line the program is stopped at, in the
+    /// given file or the current one
+    RelativeLine { file: Option<PathBuf>, offset: i32 },
+    /// Lines into a function, counted from where a breakpoint on the
+    /// function itself would stop
+    FunctionOffset { name: String, offset: u32 },
 }
 
 impl BreakpointLocation {
-    /// Parse a location string like "file.rs:42" or "main"
+    /// Parse a location string like "file.rs:42", "main" or "0x401000",
+    /// or a relative one like "file.rs:+3", "-2" or "main+4".
+    ///
+    /// A single ':' after something path-like (not a `::` scope, a drive
+    /// letter's or one inside parentheses) makes it a file and line, so a
+    /// bad line after one is an error rather than a function name that
+    /// would never match.
     pub fn parse(s: &str) -> Result<Self, crate::common::Error> {
-        // Handle file:line format, careful with Windows paths like "C:\path\file.rs:10"
-        // Strategy: find the last ':' that's followed by digits only
-        if let Some(colon_idx) = s.rfind(':') {
-            let (file_part, line_part) = s.split_at(colon_idx);
-            let line_str = &line_part[1..]; // Skip the ':'
-
-            // Only treat as file:line if the part after ':' is a valid line number
-            if !line_str.is_empty() && line_str.chars().all(|c| c.is_ascii_digit()) {
-                let line: u32 = line_str.parse().map_err(|_| {
-                    crate::common::Error::InvalidLocation(format!(
-                        "invalid line number: {}",
-                        line_str
-                    ))
-                })?;
-                return Ok(Self::Line {
-                    file: PathBuf::from(file_part),
-                    line,
-                });
+        let invalid = |problem: String| crate::common::Error::InvalidLocation(problem);
+
+        if let Some(offset) = parse_signed_offset(s) {
+            return Ok(Self::RelativeLine { file: None, offset: offset? });
+        }
+
+        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            let addr = u64::from_str_radix(hex, 16)
+                .map_err(|_| invalid(format!("invalid address '{}'", s)))?;
+            return Ok(Self::Address { addr });
+        }
+
+        if let Some(colon_idx) = file_line_colon(s) {
+            let (file_part, line_str) = (&s[..colon_idx], &s[colon_idx + 1..]);
+
+            if let Some(offset) = parse_signed_offset(line_str) {
+                let file = (!file_part.is_empty()).then(|| PathBuf::from(file_part));
+                return Ok(Self::RelativeLine { file, offset: offset? });
+            }
+            if file_part.is_empty() {
+                return Err(invalid(format!("missing file name before the line in '{}'", s)));
+            }
+            if line_str.is_empty() {
+                return Err(invalid(format!("missing line number after '{}'", s)));
+            }
+            if !is_line_number(line_str) {
+                return Err(invalid(format!(
+                    "invalid line number '{}' in '{}'",
+                    line_str, s
+                )));
+            }
+            let line = line_str.parse().map_err(|_| {
+                invalid(format!("line number '{}' is too large in '{}'", line_str, s))
+            })?;
+            return Ok(Self::Line {
+                file: PathBuf::from(file_part),
+                line,
+            });
+        }
+
+        if let Some((name, offset)) = s.rsplit_once('+') {
+            if !name.is_empty() && is_line_number(offset) {
+                let offset = offset
+                    .parse()
+                    .map_err(|_| invalid(format!("offset '{}' is too large in '{}'", offset, s)))?;
+                return Ok(Self::FunctionOffset { name: name.to_string(), offset });
             }
         }
 
@@ -242,12 +530,69 @@ impl std::fmt::Display for BreakpointLocation {
         match self {
             Self::Line { file, line } => write!(f, "{}:{}", file.display(), line),
             Self::Function { name } => write!(f, "{}", name),
+            Self::Address { addr } => write!(f, "0x{:x}", addr),
+            Self::RelativeLine { file: Some(file), offset } => {
+                write!(f, "{}:{:+}", file.display(), offset)
+            }
+            Self::RelativeLine { file: None, offset } => write!(f, "{:+}", offset),
+            Self::FunctionOffset { name, offset } => write!(f, "{}+{}", name, offset),
         }
     }
 }
 
+/// Index of the ':' separating a file from its line: the last one that is
+/// neither half of a `::` scope, a drive letter's (`C:\src`) nor inside
+/// parentheses (Swift's `greet(name:)`), and only when what comes before
+/// looks like a path or what comes after like a line. Objective-C selectors
+/// like `-[Foo bar:]` and `initWithFrame:style:` have none.
+fn file_line_colon(s: &str) -> Option<usize> {
+    if s.contains('[') {
+        return None;
+    }
+    let bytes = s.as_bytes();
+    let is_drive = |i: usize| {
+        i == 1 && bytes[0].is_ascii_alphabetic() && matches!(bytes.get(2), Some(b'\\' | b'/'))
+    };
+    let mut depth = 0usize;
+    let mut colon = None;
+    for (i, &byte) in bytes.iter().enumerate() {
+        match byte {
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            b':' if depth == 0
+                && bytes.get(i + 1) != Some(&b':')
+                && (i == 0 || bytes[i - 1] != b':')
+                && !is_drive(i) =>
+            {
+                colon = Some(i)
+            }
+            _ => {}
+        }
+    }
+
+    let colon = colon?;
+    let (file, line) = (&s[..colon], &s[colon + 1..]);
+    let path_like = file.contains(['/', '\\', '.']);
+    (path_like || is_line_number(line) || parse_signed_offset(line).is_some()).then_some(colon)
+}
+
+fn is_line_number(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Parse an explicitly signed line offset like "+3" or "-2"
+fn parse_signed_offset(s: &str) -> Option<Result<i32, crate::common::Error>> {
+    let digits = s.strip_prefix('+').or_else(|| s.strip_prefix('-'))?;
+    if !is_line_number(digits) {
+        return None;
+    }
+    Some(s.parse().map_err(|_| {
+        crate::common::Error::InvalidLocation(format!("line offset '{}' is too large", s))
+    }))
+}
+
 /// Context for expression evaluation
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum EvaluateContext {
     /// Watch expression (read-only evaluation)
@@ -259,10 +604,34 @@ pub enum EvaluateContext {
     Hover,
 }
 
+/// Which step command `StepCount` repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StepKind {
+    /// `next`
+    Over,
+    /// `step`
+    In,
+    /// `finish`
+    Out,
+}
+
+impl StepKind {
+    /// The single-step command, resuming only the current thread if
+    /// `this_thread` is set
+    pub fn command(self, this_thread: bool) -> Command {
+        match self {
+            Self::Over => Command::Next { this_thread },
+            Self::In => Command::StepIn { this_thread },
+            Self::Out => Command::StepOut { this_thread },
+        }
+    }
+}
+
 // === Result types for responses ===
 
 /// Status response
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct StatusResult {
     pub daemon_running: bool,
     pub session_active: bool,
@@ -277,49 +646,210 @@ pub struct StatusResult {
 }
 
 /// Breakpoint information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BreakpointInfo {
     pub id: u32,
     pub verified: bool,
     pub source: Option<String>,
     pub line: Option<u32>,
+    /// The adapter's message about the breakpoint
     pub message: Option<String>,
+    /// Why an unverified breakpoint isn't bound yet, from the adapter's
+    /// reason for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
     pub enabled: bool,
     pub condition: Option<String>,
     pub hit_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_hit: Vec<String>,
+    /// Why the condition failed a trial evaluation at the current stop, when
+    /// the breakpoint was added while stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// A breakpoint with the details `breakpoint list` leaves out
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BreakpointDetails {
+    #[serde(flatten)]
+    pub info: BreakpointInfo,
+    /// The location as it was requested, before the adapter moved it
+    pub location: String,
+    /// How many times the program has stopped at this breakpoint
+    pub hits: u32,
+    /// The adapter's ID for the breakpoint, once it has been sent
+    pub adapter_id: Option<u32>,
 }
 
 /// Stack frame information
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct StackFrameInfo {
     pub id: i64,
+    /// Position on the stack (0 = top), which differs from the position in
+    /// a filtered list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    /// "label" for a separator that isn't a real frame (e.g. an async
+    /// boundary), "subtle" for library or runtime code, as the adapter
+    /// suggests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
     pub name: String,
     pub source: Option<String>,
     pub line: Option<u32>,
     pub column: Option<u32>,
 }
 
+/// Result of `analyze deadlock`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DeadlockReport {
+    /// Threads whose stacks were examined
+    pub threads: usize,
+    /// Threads with a known lock or wait function on their stack
+    pub blocked: Vec<BlockedThread>,
+    /// Blocked threads that may be waiting on each other, by thread ID. Each
+    /// thread waits in a function the next one's stack has already passed
+    /// through, so the next thread may hold what it waits for.
+    pub cycles: Vec<Vec<i64>>,
+    /// Threads whose stack the adapter did not return
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<i64>,
+}
+
+/// A thread that looks blocked, from `analyze deadlock`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BlockedThread {
+    pub id: i64,
+    pub name: String,
+    /// The outermost frame in a lock or wait function, e.g.
+    /// `pthread_mutex_lock`
+    pub wait_function: String,
+    /// The frame that called it: where the program is waiting
+    pub waiting_in: Option<StackFrameInfo>,
+}
+
 /// Thread information
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ThreadInfo {
     pub id: i64,
     pub name: String,
     pub state: Option<String>,
 }
 
+/// Limits of the session's output buffer, and how much of them is used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OutputLimits {
+    pub max_events: usize,
+    pub max_bytes: usize,
+    /// Output events buffered now
+    pub events: usize,
+    /// Bytes of output buffered now
+    pub bytes: usize,
+}
+
+/// An operation the adapter reported as in progress
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProgressInfo {
+    pub title: String,
+    pub message: Option<String>,
+    pub percentage: Option<f64>,
+}
+
+impl std::fmt::Display for ProgressInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        match self.percentage {
+            Some(percentage) => write!(f, " ({:.0}%)", percentage),
+            None => write!(f, "..."),
+        }
+    }
+}
+
+/// A module the debuggee loaded
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ModuleInfo {
+    /// The adapter's ID for the module, a number or a string
+    pub id: String,
+    pub name: String,
+    pub path: Option<String>,
+}
+
 /// Variable information
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct VariableInfo {
     pub name: String,
     pub value: String,
     pub type_name: Option<String>,
     pub variables_reference: i64,
+    /// The adapter's presentation hint, when it sent one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<PresentationHint>,
+    /// First and last elements of a large container, e.g.
+    /// `[0]=1, [1]=2, ... (9996 more), [9998]=7, [9999]=8`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elements: Option<String>,
+    /// Adapter memory reference for the variable's value, readable with
+    /// `memory`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<String>,
+}
+
+impl VariableInfo {
+    /// Short notes on why a value may not be what it seems, e.g. that it was
+    /// optimized out or can't be assigned
+    pub fn annotations(&self) -> Vec<&'static str> {
+        let mut notes = Vec::new();
+        let value = self.value.to_ascii_lowercase();
+        if value.contains("optimized out") || value.contains("optimised out") {
+            notes.push("optimized out");
+        } else if value.starts_with('<')
+            && (value.contains("unavailable") || value.contains("not available"))
+        {
+            notes.push("unavailable");
+        }
+
+        if let Some(hint) = &self.presentation_hint {
+            if hint.kind.as_deref() == Some("virtual") {
+                notes.push("virtual");
+            }
+            if hint
+                .attributes
+                .iter()
+                .any(|a| a == "readOnly" || a == "constant")
+            {
+                notes.push("read-only");
+            }
+            if hint.attributes.iter().any(|a| a == "hasSideEffects") {
+                notes.push("side effects");
+            }
+        }
+        notes
+    }
+}
+
+/// How the adapter suggests a variable be presented (DAP
+/// `VariablePresentationHint`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PresentationHint {
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    pub visibility: Option<String>,
 }
 
 /// Stop event result
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct StopResult {
+    /// Canonical stop reason, the same for every adapter: "breakpoint",
+    /// "data-breakpoint", "step", "exception", "pause", "entry" or "goto"
     pub reason: String,
+    /// The adapter's own reason, when it differs from `reason`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_reason: Option<String>,
     pub description: Option<String>,
     #[serde(default)]
     pub thread_id: Option<i64>,
@@ -331,18 +861,204 @@ pub struct StopResult {
     pub source: Option<String>,
     pub line: Option<u32>,
     pub column: Option<u32>,
+    /// Function of the top stack frame
+    #[serde(default)]
+    pub function: Option<String>,
+    /// Signal that stopped the program, e.g. "SIGSEGV"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal_number: Option<i32>,
+    /// Adapter's full text for an exception or signal stop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exception_text: Option<String>,
+    /// Exception details from the adapter's `exceptionInfo` response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exception: Option<ExceptionInfo>,
+    /// Output of the hit breakpoints' on-hit commands, in execution order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_hit: Vec<OnHitOutput>,
+    /// Values of the watches in scope at this stop
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watches: Vec<WatchValue>,
+}
+
+/// A session event, as streamed by `debugger events`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// The program stopped; `reason` is normalized as in `StopResult`
+    Stopped {
+        reason: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        raw_reason: Option<String>,
+        #[serde(default)]
+        description: Option<String>,
+        #[serde(default)]
+        thread_id: Option<i64>,
+        #[serde(default)]
+        all_threads_stopped: bool,
+        #[serde(default)]
+        hit_breakpoint_ids: Vec<u32>,
+    },
+    Continued {
+        thread_id: i64,
+        all_threads_continued: bool,
+    },
+    Output {
+        category: String,
+        output: String,
+    },
+    /// A thread started ("started") or ended ("exited")
+    Thread {
+        reason: String,
+        thread_id: i64,
+    },
+    Exited {
+        exit_code: i32,
+    },
+    /// The adapter ended the debug session
+    Terminated,
+    /// The subscriber fell behind and missed `dropped` events; sent
+    /// whichever kinds it asked for
+    Lagged {
+        dropped: usize,
+    },
+}
+
+impl SessionEvent {
+    /// Every kind of event the session sends, as named in the `event` field
+    pub const KINDS: [&'static str; 6] =
+        ["stopped", "continued", "output", "thread", "exited", "terminated"];
+
+    /// Kind of this event, as named in the `event` field
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SessionEvent::Stopped { .. } => "stopped",
+            SessionEvent::Continued { .. } => "continued",
+            SessionEvent::Output { .. } => "output",
+            SessionEvent::Thread { .. } => "thread",
+            SessionEvent::Exited { .. } => "exited",
+            SessionEvent::Terminated => "terminated",
+            SessionEvent::Lagged { .. } => "lagged",
+        }
+    }
+}
+
+/// A thrown exception, as reported by the adapter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExceptionInfo {
+    /// Adapter-specific exception identifier
+    pub id: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// When the adapter breaks on this exception: "always", "unhandled", ...
+    #[serde(default)]
+    pub break_mode: Option<String>,
+    #[serde(default)]
+    pub type_name: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Stack trace at the time the exception was thrown, as text
+    #[serde(default)]
+    pub stack_trace: Option<String>,
+}
+
+/// Result of one on-hit command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OnHitOutput {
+    pub command: String,
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A watch expression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct WatchInfo {
+    pub id: u32,
+    pub expression: String,
+    /// Function that must be on the stack for the watch to be evaluated
+    pub function: Option<String>,
+}
+
+/// A watch evaluated at a stop, in the innermost frame of its function when
+/// it has one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct WatchValue {
+    pub id: u32,
+    pub expression: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
 /// Evaluate result
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct EvaluateResult {
     pub result: String,
     pub type_name: Option<String>,
     pub variables_reference: i64,
 }
 
+/// An expression evaluated in one stack frame, by `EvaluateAll`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FrameEvaluation {
+    /// Frame index (0 = top of stack)
+    pub index: usize,
+    pub function: String,
+    pub source: Option<String>,
+    pub line: Option<u32>,
+    #[serde(default)]
+    pub result: Option<String>,
+    #[serde(default)]
+    pub type_name: Option<String>,
+    /// Why the expression could not be evaluated in this frame
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Raw memory read result
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MemoryResult {
+    /// Address of the first byte, as reported by the adapter
+    pub address: String,
+    pub data: Vec<u8>,
+    /// Bytes after `data` that could not be read
+    #[serde(default)]
+    pub unreadable_bytes: u64,
+}
+
+/// A disassembled instruction
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct InstructionInfo {
+    pub address: String,
+    pub instruction: String,
+    pub bytes: Option<String>,
+    pub symbol: Option<String>,
+    pub source: Option<String>,
+    pub line: Option<u32>,
+    /// Text of `source:line`, present when requested and readable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_text: Option<String>,
+}
+
+/// Instruction, stack and frame pointers of a frame. A pointer the adapter
+/// could not evaluate under any of its usual names is `None`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RegistersResult {
+    pub pc: Option<String>,
+    pub sp: Option<String>,
+    pub fp: Option<String>,
+    /// Adapter memory reference for the instruction pointer, accepted as an
+    /// address by `disassemble` and `memory`
+    pub instruction_reference: Option<String>,
+}
+
 /// Context result with source code
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ContextResult {
     pub thread_id: i64,
     pub source: Option<String>,
@@ -355,8 +1071,22 @@ pub struct ContextResult {
     pub locals: Vec<VariableInfo>,
 }
 
+/// Result of `triage`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TriageResult {
+    /// Why and where the program stopped, with signal or exception details
+    pub stop: StopResult,
+    /// Stack of the stopped thread
+    pub frames: Vec<StackFrameInfo>,
+    /// Source around the top frame of the stopped thread, and its locals
+    pub context: Option<ContextResult>,
+    /// Parts of the report that couldn't be gathered, and why
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
 /// A source line with its number
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SourceLine {
     pub number: u32,
     pub content: String,
@@ -401,16 +1131,108 @@ mod tests {
         }
     }
 
-    #[cfg(windows)]
     #[test]
-    fn test_parse_windows_path() {
-        let loc = BreakpointLocation::parse(r"C:\Users\test\src\main.rs:42").unwrap();
+    fn test_parse_address() {
+        let loc = BreakpointLocation::parse("0x401a2f").unwrap();
         match loc {
-            BreakpointLocation::Line { file, line } => {
-                assert_eq!(file, PathBuf::from(r"C:\Users\test\src\main.rs"));
-                assert_eq!(line, 42);
+            BreakpointLocation::Address { addr } => assert_eq!(addr, 0x401a2f),
+            _ => panic!("Expected Address variant"),
+        }
+        assert_eq!(loc.to_string(), "0x401a2f");
+        assert!(BreakpointLocation::parse("0xnothex").is_err());
+    }
+
+    #[test]
+    fn test_parse_relative_line() {
+        let loc = BreakpointLocation::parse("src/main.rs:+3").unwrap();
+        match &loc {
+            BreakpointLocation::RelativeLine { file, offset } => {
+                assert_eq!(file.as_deref(), Some(std::path::Path::new("src/main.rs")));
+                assert_eq!(*offset, 3);
             }
-            _ => panic!("Expected Line variant"),
+            _ => panic!("Expected RelativeLine variant"),
+        }
+        assert_eq!(loc.to_string(), "src/main.rs:+3");
+
+        let loc = BreakpointLocation::parse("-2").unwrap();
+        assert!(matches!(loc, BreakpointLocation::RelativeLine { file: None, offset: -2 }));
+        assert_eq!(loc.to_string(), "-2");
+        assert!(BreakpointLocation::parse("+99999999999").is_err());
+    }
+
+    #[test]
+    fn test_parse_function_offset() {
+        let loc = BreakpointLocation::parse("mymod::run+4").unwrap();
+        match &loc {
+            BreakpointLocation::FunctionOffset { name, offset } => {
+                assert_eq!(name, "mymod::run");
+                assert_eq!(*offset, 4);
+            }
+            _ => panic!("Expected FunctionOffset variant"),
+        }
+        assert_eq!(loc.to_string(), "mymod::run+4");
+
+        // Not an offset: C++ operators keep their trailing '+'
+        let loc = BreakpointLocation::parse("Vec::operator+").unwrap();
+        assert!(matches!(loc, BreakpointLocation::Function { .. }));
+    }
+
+    #[test]
+    fn test_parse_malformed_file_line() {
+        let error = |s: &str| BreakpointLocation::parse(s).unwrap_err().to_string();
+
+        assert!(error("file.c:notanumber")
+            .contains("invalid line number 'notanumber' in 'file.c:notanumber'"));
+        assert!(error("file.c:42:abc").contains("invalid line number 'abc' in 'file.c:42:abc'"));
+        assert!(error(":42").contains("missing file name"));
+        assert!(error("file.c:").contains("missing line number after 'file.c:'"));
+        assert!(error("file.c:99999999999").contains("too large"));
+
+        // Scopes, selectors and trailing operators are still function names
+        let names = [
+            "ns::Type::method",
+            "-[NSView setFrame:]",
+            "std::vector<int>::push_back",
+            "greet(name:)",
+            "Greeter.greet(name:times:)",
+            "initWithFrame:style:",
+        ];
+        for name in names {
+            let loc = BreakpointLocation::parse(name).unwrap();
+            assert!(matches!(loc, BreakpointLocation::Function { .. }), "{}", name);
+        }
+
+        // A file without an extension still takes a line
+        let loc = BreakpointLocation::parse("Makefile:3").unwrap();
+        assert!(matches!(loc, BreakpointLocation::Line { line: 3, .. }));
+    }
+
+    #[test]
+    fn test_parse_drive_letter_paths() {
+        // Parsing is textual, so Windows paths parse the same on every platform
+        let cases = [
+            (r"C:\a\b.c:10", r"C:\a\b.c", 10),
+            (r"C:\Users\test\src\main.rs:42", r"C:\Users\test\src\main.rs", 42),
+            ("C:/src/main.c:42", "C:/src/main.c", 42),
+            ("/usr/x.c:5", "/usr/x.c", 5),
+        ];
+        for (text, expected_file, expected_line) in cases {
+            match BreakpointLocation::parse(text).unwrap() {
+                BreakpointLocation::Line { file, line } => {
+                    assert_eq!(file, PathBuf::from(expected_file), "{}", text);
+                    assert_eq!(line, expected_line, "{}", text);
+                }
+                other => panic!("Expected Line variant for {}, got {:?}", text, other),
+            }
+        }
+
+        let loc = BreakpointLocation::parse(r"C:\a\b.c:+2").unwrap();
+        assert!(matches!(loc, BreakpointLocation::RelativeLine { file: Some(_), offset: 2 }));
+        assert!(BreakpointLocation::parse(r"C:\a\b.c:main").is_err());
+
+        for name in ["main", "process_data", "mymod::run"] {
+            let loc = BreakpointLocation::parse(name).unwrap();
+            assert!(matches!(loc, BreakpointLocation::Function { .. }), "{}", name);
         }
     }
 }
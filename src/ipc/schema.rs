@@ -0,0 +1,464 @@
+//! JSON Schema definitions for IPC commands and their results
+//!
+//! Input schemas are derived from the serde types of each `Command` variant.
+//! Output schemas describe the JSON value the daemon returns for the command;
+//! typed results reuse their derived schemas, and the handful of ad-hoc
+//! `json!` results are described here by hand. The daemon's tests check the
+//! values its handlers actually return against these, so the two can't drift.
+
+use schemars::{JsonSchema, SchemaGenerator};
+use serde_json::{json, Map, Value};
+
+use super::protocol::{
+    BreakpointDetails, BreakpointInfo, Command, ContextResult, DeadlockReport, EvaluateResult,
+    FrameEvaluation, InstructionInfo, MemoryResult, ModuleInfo, OutputLimits, ProgressInfo,
+    RegistersResult, SessionEvent, StackFrameInfo, StatusResult, StopResult, ThreadInfo,
+    TriageResult, VariableInfo, WatchInfo,
+};
+
+/// Schemas for every command, keyed by command name, with shared definitions
+/// under `$defs`
+pub fn command_schemas() -> Value {
+    let mut generator = SchemaGenerator::default();
+    let mut commands = Map::new();
+
+    for (name, input) in command_inputs(&mut generator) {
+        let output =
+            output_schema(&name, &mut generator).unwrap_or_else(|| json!({ "type": "object" }));
+        let description = input.get("description").cloned().unwrap_or(Value::Null);
+        commands.insert(
+            name,
+            json!({
+                "description": description,
+                "input": input,
+                "output": output,
+            }),
+        );
+    }
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "commands": commands,
+        "$defs": generator.take_definitions(true),
+    })
+}
+
+/// Schema for a single command, or `None` if no command has that name
+pub fn command_schema(name: &str) -> Option<Value> {
+    let mut all = command_schemas();
+    let command = all["commands"].get_mut(name)?.take();
+
+    let mut schema = json!({
+        "$schema": all["$schema"].take(),
+        "name": name,
+    });
+    if let (Value::Object(schema), Value::Object(command)) = (&mut schema, command) {
+        schema.extend(command);
+    }
+    let definitions = all["$defs"].take();
+    schema["$defs"] = referenced_definitions(&schema, &definitions);
+    Some(schema)
+}
+
+/// The subset of `definitions` reachable through `$ref`s from `schema`
+fn referenced_definitions(schema: &Value, definitions: &Value) -> Value {
+    fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(name) = map
+                    .get("$ref")
+                    .and_then(Value::as_str)
+                    .and_then(|r| r.strip_prefix("#/$defs/"))
+                {
+                    refs.push(name);
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    let mut pending = Vec::new();
+    collect_refs(schema, &mut pending);
+
+    let mut used = Map::new();
+    while let Some(name) = pending.pop() {
+        if used.contains_key(name) {
+            continue;
+        }
+        if let Some(definition) = definitions.get(name) {
+            collect_refs(definition, &mut pending);
+            used.insert(name.to_string(), definition.clone());
+        }
+    }
+
+    Value::Object(used)
+}
+
+/// Names of all commands, in declaration order
+pub fn command_names() -> Vec<String> {
+    command_inputs(&mut SchemaGenerator::default())
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Split the derived `Command` schema into one schema per variant, named by
+/// the variant's `type` tag.
+fn command_inputs(generator: &mut SchemaGenerator) -> Vec<(String, Value)> {
+    generator.subschema_for::<Command>();
+    let command = generator
+        .definitions()
+        .get("Command")
+        .cloned()
+        .unwrap_or(Value::Null);
+    generator.definitions_mut().remove("Command");
+
+    command["oneOf"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|variant| {
+            let name = variant["properties"]["type"]["const"].as_str()?;
+            Some((name.to_string(), variant.clone()))
+        })
+        .collect()
+}
+
+fn schema_of<T: JsonSchema>(generator: &mut SchemaGenerator) -> Value {
+    generator.subschema_for::<T>().to_value()
+}
+
+fn object(properties: Value) -> Value {
+    let required: Vec<&String> = properties
+        .as_object()
+        .map(|p| p.keys().collect())
+        .unwrap_or_default();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn array_of<T: JsonSchema>(generator: &mut SchemaGenerator) -> Value {
+    json!({ "type": "array", "items": schema_of::<T>(generator) })
+}
+
+/// A stop, or how the wait for one ended
+fn await_result(generator: &mut SchemaGenerator) -> Value {
+    let integer = || json!({ "type": "integer" });
+    json!({
+        "oneOf": [
+            schema_of::<StopResult>(generator),
+            object(json!({ "reason": { "const": "exited" }, "exit_code": integer() })),
+            object(json!({ "reason": { "const": "terminated" } })),
+            object(json!({ "reason": { "const": "cancelled" } })),
+        ],
+    })
+}
+
+/// Output schema for a command, or `None` if the command is unknown
+fn output_schema(name: &str, generator: &mut SchemaGenerator) -> Option<Value> {
+    let status = || object(json!({ "status": { "type": "string" } }));
+    let integer = || json!({ "type": "integer" });
+
+    let schema = match name {
+        "start" => object(json!({
+            "status": { "const": "started" },
+            "program": { "type": "string" },
+            "adapter": { "type": "string" },
+            // Per-session daemon log, when the daemon writes one
+            "log": { "type": ["string", "null"] },
+        })),
+        "attach" => object(json!({
+            "status": { "const": "attached" },
+            "pid": integer(),
+            "log": { "type": ["string", "null"] },
+        })),
+        "reattach" => object(json!({
+            "status": { "const": "attached" },
+            "pid": integer(),
+            "log": { "type": ["string", "null"] },
+            // Breakpoints restored from the detached session
+            "breakpoints": integer(),
+        })),
+        "detach" => object(json!({
+            "status": { "const": "detached" },
+            // Whether `attach --resume` can restore the session
+            "resumable": { "type": "boolean" },
+        })),
+        "stop" | "restart" | "next" | "step_in" | "step_out" | "pause" => status(),
+        "continue" => object(json!({
+            "status": { "const": "running" },
+            // False when only the current thread was resumed
+            "all_threads_continued": { "type": "boolean" },
+        })),
+        "reset" => object(json!({
+            "status": { "const": "reset" },
+            "stopped_session": { "type": "boolean" },
+        })),
+        "status" => schema_of::<StatusResult>(generator),
+        "breakpoint_add" => schema_of::<BreakpointInfo>(generator),
+        "breakpoint_remove" => object(json!({
+            "removed": { "oneOf": [integer(), { "const": "all" }] },
+        })),
+        "breakpoint_list" => object(json!({
+            "breakpoints": array_of::<BreakpointInfo>(generator),
+        })),
+        "breakpoint_info" => schema_of::<BreakpointDetails>(generator),
+        "breakpoint_enable" => object(json!({ "enabled": integer() })),
+        "breakpoint_disable" => object(json!({ "disabled": integer() })),
+        "watch_add" => schema_of::<WatchInfo>(generator),
+        "watch_remove" => object(json!({
+            "removed": { "oneOf": [integer(), { "const": "all" }] },
+        })),
+        "watch_list" => object(json!({ "watches": array_of::<WatchInfo>(generator) })),
+        "stack_trace" => object(json!({
+            "frames": array_of::<StackFrameInfo>(generator),
+            "hidden": integer(),
+        })),
+        "locals" => object(json!({
+            "variables": array_of::<VariableInfo>(generator),
+            // Why `variables` is empty, when it is
+            "note": { "type": ["string", "null"] },
+        })),
+        "variables" => object(json!({
+            "variables": array_of::<VariableInfo>(generator),
+        })),
+        "evaluate" | "assign" => schema_of::<EvaluateResult>(generator),
+        "evaluate_all" => object(json!({
+            "expression": { "type": "string" },
+            "frames": array_of::<FrameEvaluation>(generator),
+        })),
+        // Raw DAP scopes, passed through unchanged
+        "scopes" => object(json!({
+            "scopes": { "type": "array", "items": { "type": "object" } },
+        })),
+        "read_memory" | "read_variable_memory" => schema_of::<MemoryResult>(generator),
+        "registers" => schema_of::<RegistersResult>(generator),
+        "disassemble" => object(json!({
+            "instructions": array_of::<InstructionInfo>(generator),
+        })),
+        "threads" => object(json!({
+            "threads": array_of::<ThreadInfo>(generator),
+            // Threads matching the filter, of which at most `limit` are listed
+            "total": integer(),
+            "truncated": { "type": "boolean" },
+        })),
+        "analyze_deadlock" => schema_of::<DeadlockReport>(generator),
+        "modules" => object(json!({ "modules": array_of::<ModuleInfo>(generator) })),
+        // Raw DAP capabilities, passed through unchanged
+        "probe_adapter" => object(json!({
+            "adapter": { "type": "string" },
+            "capabilities": { "type": "object" },
+        })),
+        "thread_select" => object(json!({ "selected": integer() })),
+        "terminate_threads" => object(json!({
+            "terminated": { "type": "array", "items": integer() },
+        })),
+        "frame_select" | "frame_up" | "frame_down" => object(json!({
+            "selected": integer(),
+            "frame": schema_of::<StackFrameInfo>(generator),
+        })),
+        "context" => schema_of::<ContextResult>(generator),
+        "triage" => schema_of::<TriageResult>(generator),
+        "await" => await_result(generator),
+        "step_until" => object(json!({
+            "expression": { "type": "string" },
+            "steps": integer(),
+            "max_steps": integer(),
+            "changed": { "type": "boolean" },
+            // Values are shown as `<error: ...>` where evaluation failed
+            "initial": { "type": "string" },
+            "value": { "type": "string" },
+            "stop": await_result(generator),
+        })),
+        "step_count" => object(json!({
+            "steps": integer(),
+            "count": integer(),
+            "stop": await_result(generator),
+        })),
+        "cancel_await" | "cancel_eval" => object(json!({ "cancelled": integer() })),
+        "progress" => object(json!({
+            "progress": { "oneOf": [{ "type": "null" }, schema_of::<ProgressInfo>(generator)] },
+        })),
+        "await_notify" => object(json!({
+            "status": { "const": "waiting" },
+            "notify": { "type": "string" },
+        })),
+        "set_output_limits" => schema_of::<OutputLimits>(generator),
+        "follow_output" => object(json!({
+            "output": { "type": "string" },
+            "events": {
+                "type": "array",
+                "items": object(json!({
+                    "category": { "type": "string" },
+                    "output": { "type": "string" },
+                })),
+            },
+            "session_ended": {
+                "oneOf": [
+                    { "type": "null" },
+                    object(json!({ "exit_code": { "type": ["integer", "null"] } })),
+                ],
+            },
+        })),
+        "follow_events" => object(json!({
+            "events": array_of::<SessionEvent>(generator),
+            "session_ended": {
+                "oneOf": [
+                    { "type": "null" },
+                    object(json!({ "exit_code": { "type": ["integer", "null"] } })),
+                ],
+            },
+        })),
+        "get_output" => object(json!({
+            "output": { "type": "string" },
+            "count": integer(),
+            "events": {
+                "type": "array",
+                "items": object(json!({
+                    "category": { "type": "string" },
+                    "output": { "type": "string" },
+                })),
+            },
+            "session_ended": {
+                "oneOf": [
+                    { "type": "null" },
+                    object(json!({ "exit_code": { "type": ["integer", "null"] } })),
+                ],
+            },
+        })),
+        "shutdown" => json!({ "type": "object" }),
+        _ => return None,
+    };
+
+    Some(schema)
+}
+
+/// Check `value` against the output schema of the command `name`, giving
+/// the first place they disagree. Only the keywords the schemas here use are
+/// understood.
+#[cfg(test)]
+pub(crate) fn check_output(name: &str, value: &Value) -> std::result::Result<(), String> {
+    fn check(value: &Value, schema: &Value, defs: &Value, path: &str) -> Result<(), String> {
+        let fail = |what: String| Err(format!("{}: {}", path, what));
+        if let Some(name) = schema["$ref"].as_str() {
+            let name = name.trim_start_matches("#/$defs/");
+            return check(value, &defs[name], defs, path);
+        }
+        if let Some(expected) = schema.get("const") {
+            if value != expected {
+                return fail(format!("{} is not {}", value, expected));
+            }
+        }
+        if let Some(options) = schema["enum"].as_array() {
+            if !options.contains(value) {
+                return fail(format!("{} is not one of {:?}", value, options));
+            }
+        }
+        if let Some(types) = schema.get("type") {
+            let kind = match value {
+                Value::Null => "null",
+                Value::Bool(_) => "boolean",
+                Value::Number(n) if n.is_f64() => "number",
+                Value::Number(_) => "integer",
+                Value::String(_) => "string",
+                Value::Array(_) => "array",
+                Value::Object(_) => "object",
+            };
+            let allowed = |t: &Value| t == kind || (t == "number" && kind == "integer");
+            let ok = match types {
+                Value::Array(types) => types.iter().any(allowed),
+                single => allowed(single),
+            };
+            if !ok {
+                return fail(format!("{} is not of type {}", value, types));
+            }
+        }
+        if let Some(all) = schema["allOf"].as_array() {
+            for sub in all {
+                check(value, sub, defs, path)?;
+            }
+        }
+        if let Some(any) = schema["anyOf"].as_array() {
+            if !any.iter().any(|sub| check(value, sub, defs, path).is_ok()) {
+                return fail(format!("{} matches none of anyOf", value));
+            }
+        }
+        if let Some(one) = schema["oneOf"].as_array() {
+            let results: Vec<_> = one.iter().map(|sub| check(value, sub, defs, path)).collect();
+            match results.iter().filter(|r| r.is_ok()).count() {
+                1 => {}
+                0 => {
+                    let reasons: Vec<String> =
+                        results.into_iter().filter_map(|r| r.err()).collect();
+                    return fail(format!("matches none of oneOf: {}", reasons.join("; ")));
+                }
+                n => return fail(format!("{} matches {} of oneOf", value, n)),
+            }
+        }
+        if let Value::Object(map) = value {
+            for key in schema["required"].as_array().into_iter().flatten() {
+                let key = key.as_str().unwrap_or_default();
+                if !map.contains_key(key) {
+                    return fail(format!("missing '{}'", key));
+                }
+            }
+            let properties = schema["properties"].as_object();
+            for (key, field) in map {
+                let path = format!("{}.{}", path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(sub) => check(field, sub, defs, &path)?,
+                    None if schema["additionalProperties"] == false => {
+                        return Err(format!("{}: not in the schema", path))
+                    }
+                    None => {}
+                }
+            }
+        }
+        if let (Value::Array(items), Some(sub)) = (value, schema.get("items")) {
+            for (index, item) in items.iter().enumerate() {
+                check(item, sub, defs, &format!("{}[{}]", path, index))?;
+            }
+        }
+        Ok(())
+    }
+
+    let schema = command_schema(name).ok_or_else(|| format!("no command '{}'", name))?;
+    check(value, &schema["output"], &schema["$defs"], name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_command_has_input_and_output_schemas() {
+        let names = command_names();
+        assert!(names.contains(&"breakpoint_add".to_string()));
+
+        let mut generator = SchemaGenerator::default();
+        for name in &names {
+            assert!(
+                output_schema(name, &mut generator).is_some(),
+                "command {} has no output schema",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn single_command_schema_carries_its_definitions() {
+        let schema = command_schema("breakpoint_add").unwrap();
+        assert_eq!(schema["name"], "breakpoint_add");
+        assert!(schema["input"]["properties"]["location"].is_object());
+        assert!(schema["$defs"]["BreakpointLocation"].is_object());
+        assert!(schema["$defs"]["BreakpointInfo"].is_object());
+        assert!(schema["$defs"].get("StopResult").is_none());
+        assert!(command_schema("no_such_command").is_none());
+    }
+}
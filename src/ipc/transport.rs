@@ -45,23 +45,7 @@ pub async fn create_listener() -> io::Result<Listener> {
     paths::ensure_socket_dir()?;
     paths::remove_socket()?;
 
-    let name = paths::socket_name();
-
-    #[cfg(unix)]
-    let listener = {
-        let name = name.to_fs_name::<GenericFilePath>()?;
-        ListenerOptions::new()
-            .name(name)
-            .create_tokio()?
-    };
-
-    #[cfg(windows)]
-    let listener = {
-        let name = name.to_ns_name::<GenericNamespaced>()?;
-        ListenerOptions::new()
-            .name(name)
-            .create_tokio()?
-    };
+    let listener = listen(&paths::socket_name())?;
 
     // Set socket permissions on Unix
     #[cfg(unix)]
@@ -76,21 +60,27 @@ pub async fn create_listener() -> io::Result<Listener> {
 
 /// Connect to the daemon's IPC socket
 pub async fn connect() -> io::Result<Stream> {
-    let name = paths::socket_name();
+    connect_to(&paths::socket_name()).await
+}
 
+/// Listen on `name`: a socket file path on Unix, a pipe name on Windows
+fn listen(name: &str) -> io::Result<Listener> {
     #[cfg(unix)]
-    let stream = {
-        let name = name.to_fs_name::<GenericFilePath>()?;
-        Stream::connect(name).await?
-    };
+    let name = name.to_fs_name::<GenericFilePath>()?;
+    #[cfg(windows)]
+    let name = name.to_ns_name::<GenericNamespaced>()?;
 
+    ListenerOptions::new().name(name).create_tokio()
+}
+
+/// Connect to `name`, as given to [`listen`]
+async fn connect_to(name: &str) -> io::Result<Stream> {
+    #[cfg(unix)]
+    let name = name.to_fs_name::<GenericFilePath>()?;
     #[cfg(windows)]
-    let stream = {
-        let name = name.to_ns_name::<GenericNamespaced>()?;
-        Stream::connect(name).await?
-    };
+    let name = name.to_ns_name::<GenericNamespaced>()?;
 
-    Ok(stream)
+    Stream::connect(name).await
 }
 
 /// Send a length-prefixed message
@@ -144,3 +134,41 @@ pub fn socket_exists() -> bool {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A name of its own, so the test never meets a running daemon
+    fn test_name() -> String {
+        let name = format!("debugger-cli-test-{}", std::process::id());
+        #[cfg(unix)]
+        let name = std::env::temp_dir()
+            .join(format!("{}.sock", name))
+            .to_string_lossy()
+            .into_owned();
+        name
+    }
+
+    #[tokio::test]
+    async fn messages_round_trip_over_the_local_socket() {
+        let name = test_name();
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&name);
+
+        let listener = listen(&name).unwrap();
+        let server = tokio::spawn(async move {
+            let mut stream = listener.accept().await.unwrap();
+            let message = recv_message(&mut stream).await.unwrap();
+            send_message(&mut stream, &message).await.unwrap();
+        });
+
+        let mut client = connect_to(&name).await.unwrap();
+        send_message(&mut client, b"status").await.unwrap();
+        assert_eq!(recv_message(&mut client).await.unwrap(), b"status");
+        server.await.unwrap();
+
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&name);
+    }
+}
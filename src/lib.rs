@@ -9,6 +9,7 @@ pub mod common;
 pub mod daemon;
 pub mod dap;
 pub mod ipc;
+pub mod mcp;
 pub mod setup;
 pub mod testing;
 
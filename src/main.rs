@@ -3,17 +3,41 @@
 //! This CLI tool uses the Debug Adapter Protocol (DAP) to provide debugging
 //! capabilities through a simple command-line interface optimized for LLM agents.
 
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{CommandFactory, Parser};
 use debugger::commands::Commands;
-use debugger::common::logging;
-use debugger::{cli, daemon};
+use debugger::common::{logging, paths};
+use debugger::{cli, daemon, ipc};
 
 #[derive(Parser)]
 #[command(name = "debugger", about = "LLM-friendly debugger CLI")]
-#[command(version, long_about = None)]
+#[command(version, long_about = None, disable_help_subcommand = true)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Append every command sent to the daemon to this file (see 'replay')
+    #[arg(long, global = true, value_name = "PATH")]
+    record: Option<PathBuf>,
+
+    /// Terse, grep-friendly output for LLM agents (e.g. `STOP breakpoint main.c:12 fn=main`)
+    #[arg(long, global = true)]
+    agent: bool,
+
+    /// Include variable types in --agent output
+    #[arg(long, global = true, requires = "agent")]
+    types: bool,
+
+    /// More output: -v logs debug messages to stderr, -vv also echoes the
+    /// DAP requests and responses behind each command
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Run the session inside this process instead of the background daemon
+    /// (only for self-contained commands such as 'run')
+    #[arg(long, global = true)]
+    no_daemon: bool,
 }
 
 #[tokio::main]
@@ -21,18 +45,62 @@ async fn main() {
     let cli = Cli::parse();
 
     // Initialize logging differently for daemon vs CLI mode
-    let is_daemon = matches!(cli.command, Commands::Daemon);
+    let is_daemon = matches!(cli.command, Commands::Daemon { .. });
     if is_daemon {
         if let Some(log_path) = logging::init_daemon() {
             eprintln!("Daemon logging to: {}", log_path.display());
         }
     } else {
-        logging::init_cli();
+        logging::init_cli(cli.verbose);
+    }
+    if cli.verbose >= 2 {
+        ipc::client::echo_dap_traffic();
+    }
+
+    if let Some(path) = cli.record {
+        ipc::client::record_commands_to(path);
     }
 
-    let result = match cli.command {
-        Commands::Daemon => daemon::run().await,
-        command => cli::dispatch(command).await,
+    // Keep the project history of what a person types; agents, the MCP server
+    // and test scenarios drive the daemon on their own
+    let typed = !matches!(
+        cli.command,
+        Commands::Daemon { .. }
+            | Commands::Mcp
+            | Commands::Test { .. }
+            | Commands::Replay { .. }
+            | Commands::History { .. }
+    );
+    if typed && !cli.agent {
+        let history = std::env::current_dir()
+            .ok()
+            .and_then(|dir| paths::history_path(&dir));
+        if let Some(path) = history {
+            ipc::client::record_history_to(path);
+        }
+    }
+
+    let mut command = cli.command;
+    if let Commands::Test { verbose, .. } = &mut command {
+        *verbose = cli.verbose > 0;
+    }
+
+    let result = match command {
+        Commands::Daemon { http } => daemon::run(http).await,
+        Commands::Completions { shell } => cli::completions::print(shell, Cli::command()),
+        Commands::Help { command, format } => cli::help::print(Cli::command(), &command, format),
+        command => {
+            let mode = if cli.agent {
+                cli::OutputMode::Agent { types: cli.types }
+            } else {
+                cli::OutputMode::Human
+            };
+            if cli.no_daemon {
+                cli::dispatch_local(command, mode).await
+            } else {
+                cli::dispatch(command, mode).await
+            }
+        }
     };
 
     if let Err(e) = result {
@@ -40,3 +108,30 @@ async fn main() {
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_parses_with_no_daemon_anywhere_on_the_line() {
+        for line in [
+            "debugger --no-daemon run --break main.c:10 --print x ./prog -- a b",
+            "debugger run --break main.c:10 --print x ./prog --no-daemon -- a b",
+        ] {
+            let cli = Cli::try_parse_from(line.split_whitespace()).unwrap();
+            assert!(cli.no_daemon, "{}", line);
+            let Commands::Run { options } = cli.command else {
+                panic!("{} did not parse as run", line);
+            };
+            assert_eq!(options.program, PathBuf::from("./prog"));
+            assert_eq!(options.args, vec!["a", "b"]);
+            assert_eq!(options.breakpoints, vec!["main.c:10"]);
+            assert_eq!(options.print, vec!["x"]);
+            assert_eq!(options.timeout, 60);
+        }
+
+        let cli = Cli::try_parse_from(["debugger", "run", "./prog"]).unwrap();
+        assert!(!cli.no_daemon);
+    }
+}
@@ -0,0 +1,752 @@
+//! MCP (Model Context Protocol) server mode
+//!
+//! `debugger mcp` speaks JSON-RPC 2.0 over stdio, one message per line, and
+//! exposes debugging operations as MCP tools. Every tool is a thin wrapper
+//! around an IPC `Command`, sent to the daemon exactly like the CLI does, so
+//! this module adds no debugging logic of its own.
+
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::cli::spawn;
+use crate::common::Result;
+use crate::ipc::protocol::{BreakpointLocation, Command, EvaluateContext, StepKind};
+use crate::ipc::DaemonClient;
+
+/// MCP protocol revision implemented by this server
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Run the MCP server until stdin is closed
+pub async fn run() -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Some(reply) = handle_message(&line).await else {
+            continue;
+        };
+
+        let mut bytes = serde_json::to_vec(&reply)?;
+        bytes.push(b'\n');
+        stdout.write_all(&bytes).await?;
+        stdout.flush().await?;
+    }
+
+    Ok(())
+}
+
+/// Handle one JSON-RPC message; notifications produce no reply
+async fn handle_message(line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => return Some(error_reply(Value::Null, PARSE_ERROR, &e.to_string())),
+    };
+
+    let id = message.get("id").cloned()?;
+    let method = message["method"].as_str().unwrap_or_default();
+    let params = &message["params"];
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": {
+                "name": "debugger-cli",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tool_definitions() }),
+        "tools/call" => {
+            let Some(name) = params["name"].as_str() else {
+                return Some(error_reply(id, INVALID_PARAMS, "missing tool name"));
+            };
+            let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+            match tool_command(name, &arguments) {
+                Ok(command) => call_tool(command).await,
+                Err(message) => return Some(error_reply(id, INVALID_PARAMS, &message)),
+            }
+        }
+        _ => {
+            return Some(error_reply(
+                id,
+                METHOD_NOT_FOUND,
+                &format!("method not found: {}", method),
+            ))
+        }
+    };
+
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn error_reply(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Send a tool's command to the daemon and wrap the outcome as a tool result.
+///
+/// Debugger errors are tool results with `isError` set, not JSON-RPC errors,
+/// so the agent sees them and can react.
+async fn call_tool(command: Command) -> Value {
+    let outcome = async {
+        spawn::ensure_daemon_running().await?;
+        let mut client = DaemonClient::connect().await?;
+        client.send_command(command).await
+    }
+    .await;
+
+    let (text, is_error) = match outcome {
+        Ok(result) => (
+            serde_json::to_string_pretty(&result).unwrap_or_else(|_| result.to_string()),
+            false,
+        ),
+        Err(e) => (e.to_string(), true),
+    };
+
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+/// Descriptions and input schemas of every tool
+fn tool_definitions() -> Vec<Value> {
+    let tool = |name: &str, description: &str, properties: Value, required: &[&str]| {
+        json!({
+            "name": name,
+            "description": description,
+            "inputSchema": {
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            },
+        })
+    };
+    let none = || json!({});
+    let this_thread = || {
+        json!({
+            "type": "boolean",
+            "default": false,
+            "description": "Resume only the current thread, keeping the others stopped",
+        })
+    };
+    let resume = || json!({ "this_thread": this_thread() });
+
+    vec![
+        tool(
+            "start",
+            "Start debugging a program",
+            json!({
+                "program": { "type": "string", "description": "Path to the executable" },
+                "args": { "type": "array", "items": { "type": "string" } },
+                "adapter": { "type": "string", "description": "Debug adapter name" },
+                "stop_on_entry": { "type": "boolean" },
+                "breakpoints": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Initial breakpoints (file:line, function name, or 0x address)",
+                },
+                "keep_alive_minutes": {
+                    "type": "integer",
+                    "description": "End the session after this many idle minutes",
+                },
+            }),
+            &["program"],
+        ),
+        tool(
+            "attach",
+            "Attach to a running process",
+            json!({
+                "pid": { "type": "integer" },
+                "adapter": { "type": "string" },
+                "keep_alive_minutes": {
+                    "type": "integer",
+                    "description": "End the session after this many idle minutes",
+                },
+            }),
+            &["pid"],
+        ),
+        tool("stop", "Stop debugging and terminate the debuggee", none(), &[]),
+        tool("detach", "Detach from the debuggee and leave it running", none(), &[]),
+        tool(
+            "reset",
+            "Stop any session and forget its breakpoints, keeping the daemon running",
+            none(),
+            &[],
+        ),
+        tool("status", "Get daemon and session status", none(), &[]),
+        tool(
+            "probe_adapter",
+            "Start a debug adapter and report the DAP capabilities it supports, \
+                without launching a program",
+            json!({
+                "adapter": { "type": "string", "description": "Adapter name from config.toml" },
+            }),
+            &["adapter"],
+        ),
+        tool(
+            "set_breakpoint",
+            "Add a breakpoint",
+            json!({
+                "location": {
+                    "type": "string",
+                    "description": "file:line, function name, or 0x address; \
+                        file:+N, -N or function+N relative to the current stop",
+                },
+                "condition": { "type": "string" },
+                "hit_count": { "type": "integer" },
+                "on_hit": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Commands to run each time the breakpoint is hit; only ones \
+                        that inspect the stop, such as print, locals or backtrace",
+                },
+            }),
+            &["location"],
+        ),
+        tool(
+            "remove_breakpoint",
+            "Remove the breakpoint with this id, or every breakpoint with all: true",
+            json!({
+                "id": { "type": "integer" },
+                "all": { "type": "boolean" },
+            }),
+            &[],
+        ),
+        tool("list_breakpoints", "List all breakpoints", none(), &[]),
+        tool(
+            "breakpoint_info",
+            "Show one breakpoint in detail: requested location, hits, on-hit commands and \
+             the adapter's verification message",
+            json!({ "id": { "type": "integer" } }),
+            &["id"],
+        ),
+        tool("continue", "Continue execution", resume(), &[]),
+        tool("step_over", "Step over the current line", resume(), &[]),
+        tool("step_into", "Step into the current line", resume(), &[]),
+        tool("step_out", "Run until the current function returns", resume(), &[]),
+        tool(
+            "step_count",
+            "Step several times and wait for the result, stopping early at a breakpoint, \
+             exception or exit",
+            json!({
+                "kind": { "enum": ["over", "in", "out"], "default": "over" },
+                "count": { "type": "integer", "minimum": 1 },
+                "timeout_secs": { "type": "integer", "default": 300 },
+                "this_thread": this_thread(),
+            }),
+            &["count"],
+        ),
+        tool(
+            "step_until",
+            "Single-step until an expression's value in the top frame changes, stopping early \
+             at a breakpoint, exception or exit",
+            json!({
+                "expression": { "type": "string" },
+                "into": { "type": "boolean", "default": false },
+                "max_steps": { "type": "integer", "minimum": 1, "default": 100 },
+                "timeout_secs": { "type": "integer", "default": 300 },
+                "this_thread": this_thread(),
+            }),
+            &["expression"],
+        ),
+        tool("pause", "Pause execution", none(), &[]),
+        tool(
+            "await_stop",
+            "Wait for the program to stop or exit",
+            json!({ "timeout_secs": { "type": "integer", "default": 300 } }),
+            &[],
+        ),
+        tool(
+            "stack_trace",
+            "Get the stack trace of the stopped thread",
+            json!({
+                "limit": { "type": "integer", "default": 20 },
+                "user_only": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Hide frames without source or outside the program's directory",
+                },
+            }),
+            &[],
+        ),
+        tool(
+            "get_locals",
+            "Get local variables of the current frame",
+            json!({
+                "frame": {
+                    "type": "integer",
+                    "description": "Stack frame index (0 = top) to use instead of the selected frame",
+                },
+                "no_truncate": {
+                    "type": "boolean",
+                    "description": "Show values in full instead of truncating long ones",
+                },
+            }),
+            &[],
+        ),
+        tool(
+            "evaluate",
+            "Evaluate an expression in the current frame",
+            json!({
+                "expression": { "type": "string" },
+                "frame": {
+                    "type": "integer",
+                    "description": "Stack frame index (0 = top) to use instead of the selected frame",
+                },
+                "allow_side_effects": {
+                    "type": "boolean",
+                    "description": "Evaluate in REPL context, which may modify program state",
+                },
+                "no_truncate": {
+                    "type": "boolean",
+                    "description": "Show the value in full instead of truncating it",
+                },
+            }),
+            &["expression"],
+        ),
+        tool(
+            "assign",
+            "Assign a value to a variable or expression, returning its new value",
+            json!({
+                "target": { "type": "string", "description": "Variable or assignable expression" },
+                "value": { "type": "string" },
+                "frame": {
+                    "type": "integer",
+                    "description": "Stack frame index (0 = top) to use instead of the selected frame",
+                },
+            }),
+            &["target", "value"],
+        ),
+        tool(
+            "evaluate_all_frames",
+            "Evaluate an expression in each frame of the current stack, to find which holds a value",
+            json!({
+                "expression": { "type": "string" },
+                "limit": { "type": "integer", "default": 20 },
+            }),
+            &["expression"],
+        ),
+        tool(
+            "triage",
+            "Everything to look at first after a crash, in one report: the stop with its signal \
+             or exception, the stopped thread's backtrace, and the source and locals where it \
+             stopped",
+            json!({
+                "limit": { "type": "integer", "default": 20 },
+                "lines": { "type": "integer", "default": 5 },
+            }),
+            &[],
+        ),
+        tool(
+            "context",
+            "Get the current position with source context and locals",
+            json!({ "lines": { "type": "integer", "default": 5 } }),
+            &[],
+        ),
+        tool(
+            "threads",
+            "List threads, optionally only those whose name contains `filter`, at most `limit`",
+            json!({
+                "limit": { "type": "integer" },
+                "filter": { "type": "string" },
+                "refresh": {
+                    "type": "boolean",
+                    "description": "Enumerate the threads again instead of reusing the cached list",
+                },
+            }),
+            &[],
+        ),
+        tool(
+            "analyze_deadlock",
+            "Find threads blocked on locks or waits, and cycles of threads that may be waiting \
+             on each other",
+            json!({ "frames": { "type": "integer", "default": 32 } }),
+            &[],
+        ),
+        tool("modules", "List the modules (libraries) the program has loaded", none(), &[]),
+        tool(
+            "select_thread",
+            "Switch the thread used for inspection",
+            json!({ "id": { "type": "integer" } }),
+            &["id"],
+        ),
+        tool(
+            "terminate_threads",
+            "Terminate threads, leaving the rest of the process running",
+            json!({ "ids": { "type": "array", "items": { "type": "integer" } } }),
+            &["ids"],
+        ),
+        tool(
+            "select_frame",
+            "Select a stack frame (0 = innermost)",
+            json!({ "number": { "type": "integer" } }),
+            &["number"],
+        ),
+        tool(
+            "get_output",
+            "Get buffered debuggee output",
+            json!({
+                "tail": { "type": "integer", "description": "Only the last N lines" },
+                "clear": { "type": "boolean" },
+            }),
+            &[],
+        ),
+        tool(
+            "set_output_limits",
+            "Change how much debuggee output the session buffers; lowering a limit drops the \
+             oldest output",
+            json!({
+                "max_bytes_mb": { "type": "integer" },
+                "max_events": { "type": "integer" },
+            }),
+            &[],
+        ),
+        tool(
+            "read_memory",
+            "Read raw memory from the debuggee",
+            json!({
+                "address": { "type": "string" },
+                "count": { "type": "integer", "default": 64 },
+            }),
+            &["address"],
+        ),
+        tool(
+            "read_variable_memory",
+            "Read raw memory at a variable's memory reference, e.g. the bytes behind a pointer",
+            json!({
+                "name": { "type": "string" },
+                "count": { "type": "integer", "default": 64 },
+            }),
+            &["name"],
+        ),
+        tool(
+            "registers",
+            "Get the current frame's instruction, stack and frame pointers",
+            none(),
+            &[],
+        ),
+        tool(
+            "disassemble",
+            "Disassemble instructions at an address or the current frame",
+            json!({
+                "address": { "type": "string" },
+                "count": { "type": "integer", "default": 20 },
+                "source": { "type": "boolean" },
+            }),
+            &[],
+        ),
+    ]
+}
+
+/// Map a tool call to the IPC command it wraps
+fn tool_command(name: &str, args: &Value) -> std::result::Result<Command, String> {
+    let command = match name {
+        "start" => {
+            let program = PathBuf::from(required_str(args, "program")?);
+            Command::Start {
+                program: program.canonicalize().unwrap_or(program),
+                args: string_list(args, "args")?,
+                adapter: optional_str(args, "adapter"),
+                stop_on_entry: args["stop_on_entry"].as_bool().unwrap_or(false),
+                initial_breakpoints: string_list(args, "breakpoints")?,
+                keep_alive_minutes: args["keep_alive_minutes"].as_u64(),
+            }
+        }
+        "attach" => Command::Attach {
+            pid: required_u32(args, "pid")?,
+            adapter: optional_str(args, "adapter"),
+            keep_alive_minutes: args["keep_alive_minutes"].as_u64(),
+        },
+        "stop" => Command::Stop,
+        "detach" => Command::Detach,
+        "reset" => Command::Reset,
+        "status" => Command::Status,
+        "probe_adapter" => Command::ProbeAdapter {
+            adapter: required_str(args, "adapter")?.to_string(),
+        },
+        "set_breakpoint" => Command::BreakpointAdd {
+            location: BreakpointLocation::parse(required_str(args, "location")?)
+                .map_err(|e| e.to_string())?,
+            condition: optional_str(args, "condition"),
+            hit_count: optional_u32(args, "hit_count")?,
+            on_hit: string_list(args, "on_hit")?,
+        },
+        "remove_breakpoint" => {
+            let id = optional_u32(args, "id")?;
+            let all = args["all"].as_bool().unwrap_or(false);
+            match (id, all) {
+                (Some(_), true) => return Err("Give either 'id' or 'all', not both".to_string()),
+                (None, false) => {
+                    return Err("'id' is required; pass all: true to remove every breakpoint"
+                        .to_string())
+                }
+                _ => Command::BreakpointRemove { id, all },
+            }
+        }
+        "list_breakpoints" => Command::BreakpointList,
+        "breakpoint_info" => Command::BreakpointInfo {
+            id: required_u32(args, "id")?,
+        },
+        "continue" => Command::Continue {
+            this_thread: args["this_thread"].as_bool().unwrap_or(false),
+        },
+        "step_over" => Command::Next {
+            this_thread: args["this_thread"].as_bool().unwrap_or(false),
+        },
+        "step_into" => Command::StepIn {
+            this_thread: args["this_thread"].as_bool().unwrap_or(false),
+        },
+        "step_out" => Command::StepOut {
+            this_thread: args["this_thread"].as_bool().unwrap_or(false),
+        },
+        "step_count" => Command::StepCount {
+            kind: match optional_str(args, "kind").as_deref() {
+                None | Some("over") => StepKind::Over,
+                Some("in") => StepKind::In,
+                Some("out") => StepKind::Out,
+                Some(other) => return Err(format!("Unknown step kind '{}'", other)),
+            },
+            count: match required_u64(args, "count")? {
+                0 => return Err("'count' must be at least 1".to_string()),
+                count => u32::try_from(count).map_err(|_| "'count' is too large".to_string())?,
+            },
+            timeout_secs: optional_u64(args, "timeout_secs")?.unwrap_or(300),
+            this_thread: args["this_thread"].as_bool().unwrap_or(false),
+        },
+        "pause" => Command::Pause,
+        "await_stop" => Command::Await {
+            timeout_secs: optional_u64(args, "timeout_secs")?.unwrap_or(300),
+        },
+        "step_until" => Command::StepUntil {
+            expression: required_str(args, "expression")?.to_string(),
+            kind: if args["into"].as_bool().unwrap_or(false) {
+                StepKind::In
+            } else {
+                StepKind::Over
+            },
+            max_steps: match optional_u64(args, "max_steps")?.unwrap_or(100) {
+                0 => return Err("'max_steps' must be at least 1".to_string()),
+                steps => u32::try_from(steps).map_err(|_| "'max_steps' is too large".to_string())?,
+            },
+            timeout_secs: optional_u64(args, "timeout_secs")?.unwrap_or(300),
+            this_thread: args["this_thread"].as_bool().unwrap_or(false),
+        },
+        "stack_trace" => Command::StackTrace {
+            thread_id: None,
+            limit: optional_u64(args, "limit")?.unwrap_or(20) as usize,
+            user_only: args["user_only"].as_bool().unwrap_or(false),
+        },
+        "get_locals" => Command::Locals {
+            frame_id: None,
+            frame_index: optional_u64(args, "frame")?.map(|n| n as usize),
+            max_string_length: None,
+            no_truncate: args["no_truncate"].as_bool().unwrap_or(false),
+            no_cache: false,
+        },
+        "evaluate" => Command::Evaluate {
+            expression: required_str(args, "expression")?.to_string(),
+            frame_id: None,
+            frame_index: optional_u64(args, "frame")?.map(|n| n as usize),
+            context: if args["allow_side_effects"].as_bool().unwrap_or(false) {
+                EvaluateContext::Repl
+            } else {
+                EvaluateContext::Watch
+            },
+            max_string_length: None,
+            no_truncate: args["no_truncate"].as_bool().unwrap_or(false),
+        },
+        "assign" => Command::Assign {
+            target: required_str(args, "target")?.to_string(),
+            value: required_str(args, "value")?.to_string(),
+            frame_index: optional_u64(args, "frame")?.map(|n| n as usize),
+        },
+        "evaluate_all_frames" => Command::EvaluateAll {
+            expression: required_str(args, "expression")?.to_string(),
+            limit: optional_u64(args, "limit")?.unwrap_or(20) as usize,
+        },
+        "triage" => Command::Triage {
+            limit: optional_u64(args, "limit")?.unwrap_or(20) as usize,
+            lines: optional_u64(args, "lines")?.unwrap_or(5) as usize,
+        },
+        "context" => Command::Context {
+            lines: optional_u64(args, "lines")?.unwrap_or(5) as usize,
+        },
+        "threads" => Command::Threads {
+            limit: optional_u64(args, "limit")?.map(|n| n as usize),
+            filter: optional_str(args, "filter"),
+            refresh: args["refresh"].as_bool().unwrap_or(false),
+        },
+        "analyze_deadlock" => Command::AnalyzeDeadlock {
+            frames: optional_u64(args, "frames")?.unwrap_or(32) as usize,
+        },
+        "modules" => Command::Modules,
+        "select_thread" => Command::ThreadSelect {
+            id: args["id"]
+                .as_i64()
+                .ok_or_else(|| "'id' must be an integer".to_string())?,
+        },
+        "terminate_threads" => Command::TerminateThreads {
+            ids: args["ids"]
+                .as_array()
+                .and_then(|ids| ids.iter().map(|id| id.as_i64()).collect())
+                .ok_or_else(|| "'ids' must be an array of integers".to_string())?,
+        },
+        "select_frame" => Command::FrameSelect {
+            number: required_u64(args, "number")? as usize,
+        },
+        "get_output" => Command::GetOutput {
+            tail: optional_u64(args, "tail")?.map(|n| n as usize),
+            clear: args["clear"].as_bool().unwrap_or(false),
+        },
+        "set_output_limits" => Command::SetOutputLimits {
+            max_bytes_mb: optional_u64(args, "max_bytes_mb")?.map(|n| n as usize),
+            max_events: optional_u64(args, "max_events")?.map(|n| n as usize),
+        },
+        "read_memory" => Command::ReadMemory {
+            address: required_str(args, "address")?.to_string(),
+            count: optional_u32(args, "count")?.unwrap_or(64),
+        },
+        "read_variable_memory" => Command::ReadVariableMemory {
+            name: required_str(args, "name")?.to_string(),
+            count: optional_u32(args, "count")?.unwrap_or(64),
+        },
+        "registers" => Command::Registers,
+        "disassemble" => Command::Disassemble {
+            address: optional_str(args, "address"),
+            count: optional_u32(args, "count")?.unwrap_or(20),
+            source: args["source"].as_bool().unwrap_or(false),
+        },
+        _ => return Err(format!("unknown tool: {}", name)),
+    };
+
+    Ok(command)
+}
+
+fn required_str<'a>(args: &'a Value, key: &str) -> std::result::Result<&'a str, String> {
+    args[key]
+        .as_str()
+        .ok_or_else(|| format!("'{}' must be a string", key))
+}
+
+fn optional_str(args: &Value, key: &str) -> Option<String> {
+    args[key].as_str().map(String::from)
+}
+
+fn required_u64(args: &Value, key: &str) -> std::result::Result<u64, String> {
+    optional_u64(args, key)?.ok_or_else(|| format!("'{}' is required", key))
+}
+
+fn optional_u64(args: &Value, key: &str) -> std::result::Result<Option<u64>, String> {
+    match &args[key] {
+        Value::Null => Ok(None),
+        value => value
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| format!("'{}' must be a non-negative integer", key)),
+    }
+}
+
+fn required_u32(args: &Value, key: &str) -> std::result::Result<u32, String> {
+    optional_u32(args, key)?.ok_or_else(|| format!("'{}' is required", key))
+}
+
+fn optional_u32(args: &Value, key: &str) -> std::result::Result<Option<u32>, String> {
+    optional_u64(args, key)?
+        .map(|n| u32::try_from(n).map_err(|_| format!("'{}' is too large", key)))
+        .transpose()
+}
+
+fn string_list(args: &Value, key: &str) -> std::result::Result<Vec<String>, String> {
+    match &args[key] {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map(String::from)
+                    .ok_or_else(|| format!("'{}' must be a list of strings", key))
+            })
+            .collect(),
+        _ => Err(format!("'{}' must be a list of strings", key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_listed_tool_maps_to_a_command() {
+        let sample_args = json!({
+            "program": "./a.out",
+            "pid": 42,
+            "adapter": "lldb-dap",
+            "location": "main.c:10",
+            "expression": "x",
+            "id": 1,
+            "number": 0,
+            "address": "0x1000",
+            "name": "buf",
+            "ids": [1],
+            "target": "x",
+            "value": "1",
+            "count": 3,
+        });
+
+        for tool in tool_definitions() {
+            let name = tool["name"].as_str().unwrap();
+            assert!(
+                tool_command(name, &sample_args).is_ok(),
+                "tool {} did not map to a command",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn tool_arguments_are_validated() {
+        assert!(tool_command("evaluate", &json!({})).is_err());
+        assert!(tool_command("stack_trace", &json!({ "limit": "ten" })).is_err());
+        assert!(tool_command("remove_breakpoint", &json!({})).is_err());
+        assert!(tool_command("remove_breakpoint", &json!({ "id": 1, "all": true })).is_err());
+        assert!(tool_command("remove_breakpoint", &json!({ "id": 1u64 << 32 })).is_err());
+        assert!(matches!(
+            tool_command("remove_breakpoint", &json!({ "all": true })).unwrap(),
+            Command::BreakpointRemove { id: None, all: true }
+        ));
+        assert!(matches!(
+            tool_command("remove_breakpoint", &json!({ "id": 2 })).unwrap(),
+            Command::BreakpointRemove { id: Some(2), all: false }
+        ));
+        assert!(tool_command("no_such_tool", &json!({})).is_err());
+    }
+
+    #[tokio::test]
+    async fn notifications_get_no_reply() {
+        let reply =
+            handle_message(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).await;
+        assert!(reply.is_none());
+
+        let reply = handle_message(r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#)
+            .await
+            .unwrap();
+        assert_eq!(reply["id"], 1);
+        assert!(reply["result"]["tools"].as_array().unwrap().len() > 10);
+    }
+}
@@ -1,16 +1,20 @@
 //! CodeLLDB installer
 //!
-//! Installs the CodeLLDB debug adapter from GitHub releases.
+//! Installs the CodeLLDB debug adapter from the platform-specific `.vsix` on
+//! its GitHub releases. The adapter binary lives at `extension/adapter/codelldb`
+//! inside the extracted vsix and speaks DAP over stdio (1.11.0 and later), so
+//! it needs no extra arguments.
 
 use crate::common::{Error, Result};
 use crate::setup::installer::{
     adapters_dir, arch_str, download_file, ensure_adapters_dir, extract_zip,
-    get_github_release, make_executable, platform_str, read_version_file,
+    get_github_release, latest_github_version, make_executable, platform_str, read_version_file,
     write_version_file, InstallMethod, InstallOptions, InstallResult, InstallStatus, Installer,
 };
 use crate::setup::registry::{DebuggerInfo, Platform};
 use crate::setup::verifier::{verify_dap_adapter, VerifyResult};
 use async_trait::async_trait;
+use std::path::Path;
 
 static INFO: DebuggerInfo = DebuggerInfo {
     id: "codelldb",
@@ -23,6 +27,10 @@ static INFO: DebuggerInfo = DebuggerInfo {
 
 const GITHUB_REPO: &str = "vadimcn/codelldb";
 
+/// First release whose adapter can be driven over stdio; earlier ones only
+/// listen on a TCP port
+const MIN_STDIO_VERSION: semver::Version = semver::Version::new(1, 11, 0);
+
 pub struct CodeLldbInstaller;
 
 #[async_trait]
@@ -77,12 +85,22 @@ impl Installer for CodeLldbInstaller {
         Ok(())
     }
 
+    async fn latest_version(&self) -> Result<Option<String>> {
+        latest_github_version(GITHUB_REPO).await.map(Some)
+    }
+
     async fn verify(&self) -> Result<VerifyResult> {
         let status = self.status().await?;
 
         match status {
             InstallStatus::Installed { path, .. } => {
-                // CodeLLDB uses different arguments
+                if let Err(e) = get_version(&path).await {
+                    return Ok(VerifyResult {
+                        success: false,
+                        capabilities: None,
+                        error: Some(e),
+                    });
+                }
                 verify_dap_adapter(&path, &[]).await
             }
             InstallStatus::Broken { reason, .. } => Ok(VerifyResult {
@@ -107,24 +125,81 @@ fn binary_name() -> &'static str {
     }
 }
 
+/// Run `codelldb --version`, returning its output or why it failed
+async fn get_version(path: &Path) -> std::result::Result<String, String> {
+    let output = tokio::process::Command::new(path)
+        .arg("--version")
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {} --version: {}", path.display(), e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "{} --version failed ({}): {}",
+            path.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
 fn get_asset_pattern() -> Vec<String> {
     let platform = platform_str();
     let arch = arch_str();
 
-    vec![
-        format!("codelldb-{}-{}.vsix", arch, platform),
-        // Alternative naming patterns
-        format!("codelldb-{}-{}-*.vsix", arch, platform),
-    ]
+    let mut patterns = Vec::new();
+    // Current releases use VS Code target names (codelldb-linux-x64.vsix)
+    if let Some(target) = vscode_target(platform, arch) {
+        patterns.push(format!("codelldb-{}.vsix", target));
+    }
+    // Older releases use the Rust-style naming (codelldb-x86_64-linux.vsix)
+    patterns.push(format!("codelldb-{}-{}.vsix", arch, platform));
+    patterns.push(format!("codelldb-{}-{}-*.vsix", arch, platform));
+    patterns
+}
+
+/// VS Code's name for a platform and architecture, as used in vsix names
+fn vscode_target(platform: &str, arch: &str) -> Option<String> {
+    let platform = match platform {
+        "windows" => "win32",
+        other => other,
+    };
+    let arch = match arch {
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        _ => return None,
+    };
+    Some(format!("{}-{}", platform, arch))
+}
+
+/// Release tag for a requested version; CodeLLDB tags are `v`-prefixed
+fn release_tag(version: &str) -> String {
+    format!("v{}", version.trim_start_matches('v'))
+}
+
+/// Reject releases older than [`MIN_STDIO_VERSION`]. Unparseable versions
+/// are let through.
+fn check_supported_version(version: &str) -> Result<()> {
+    match semver::Version::parse(version) {
+        Ok(parsed) if parsed < MIN_STDIO_VERSION => Err(Error::Internal(format!(
+            "CodeLLDB {} only speaks DAP over a TCP port; install {} or later",
+            version, MIN_STDIO_VERSION
+        ))),
+        _ => Ok(()),
+    }
 }
 
 async fn install_from_github(opts: &InstallOptions) -> Result<InstallResult> {
     println!("Checking for existing installation... not found");
     println!("Finding latest CodeLLDB release...");
 
-    let release = get_github_release(GITHUB_REPO, opts.version.as_deref()).await?;
+    let tag = opts.version.as_deref().map(release_tag);
+    let release = get_github_release(GITHUB_REPO, tag.as_deref()).await?;
     let version = release.tag_name.trim_start_matches('v').to_string();
     println!("Found version: {}", version);
+    check_supported_version(&version)?;
 
     // Find appropriate asset
     let patterns = get_asset_pattern();
@@ -221,3 +296,26 @@ async fn install_from_github(opts: &InstallOptions) -> Result<InstallResult> {
         args: Vec::new(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vsix_names_follow_vscode_targets() {
+        assert_eq!(vscode_target("linux", "x86_64").as_deref(), Some("linux-x64"));
+        assert_eq!(vscode_target("darwin", "aarch64").as_deref(), Some("darwin-arm64"));
+        assert_eq!(vscode_target("windows", "x86_64").as_deref(), Some("win32-x64"));
+        assert_eq!(vscode_target("linux", "i686"), None);
+    }
+
+    #[test]
+    fn only_stdio_capable_releases_are_installed() {
+        assert_eq!(release_tag("1.11.0"), "v1.11.0");
+        assert_eq!(release_tag("v1.11.0"), "v1.11.0");
+        assert!(check_supported_version("1.11.0").is_ok());
+        assert!(check_supported_version("1.12.1").is_ok());
+        assert!(check_supported_version("1.10.0").is_err());
+        assert!(check_supported_version("nightly").is_ok());
+    }
+}
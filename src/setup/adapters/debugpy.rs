@@ -4,7 +4,7 @@
 
 use crate::common::{Error, Result};
 use crate::setup::installer::{
-    adapters_dir, ensure_adapters_dir, run_command_args, write_version_file,
+    adapters_dir, ensure_adapters_dir, latest_pypi_version, run_command_args, write_version_file,
     InstallMethod, InstallOptions, InstallResult, InstallStatus, Installer,
 };
 use crate::setup::registry::{DebuggerInfo, Platform};
@@ -102,6 +102,10 @@ impl Installer for DebugpyInstaller {
         Ok(())
     }
 
+    async fn latest_version(&self) -> Result<Option<String>> {
+        latest_pypi_version("debugpy").await.map(Some)
+    }
+
     async fn verify(&self) -> Result<VerifyResult> {
         let status = self.status().await?;
 
@@ -2,10 +2,11 @@
 //!
 //! Installs the Go debugger with DAP support.
 
+use crate::common::config::SpawnStyle;
 use crate::common::{Error, Result};
 use crate::setup::installer::{
     adapters_dir, arch_str, download_file, ensure_adapters_dir, extract_tar_gz,
-    get_github_release, make_executable, platform_str, read_version_file, run_command_args,
+    get_github_release, latest_github_version, make_executable, platform_str, read_version_file, run_command_args,
     write_version_file, InstallMethod, InstallOptions, InstallResult, InstallStatus, Installer,
     PackageManager,
 };
@@ -113,13 +114,21 @@ impl Installer for DelveInstaller {
         Ok(())
     }
 
+    async fn latest_version(&self) -> Result<Option<String>> {
+        latest_github_version(GITHUB_REPO).await.map(Some)
+    }
+
+    fn spawn_style(&self) -> Option<SpawnStyle> {
+        Some(SpawnStyle::ListenFlag)
+    }
+
     async fn verify(&self) -> Result<VerifyResult> {
         let status = self.status().await?;
 
         match status {
             InstallStatus::Installed { path, .. } => {
                 // Delve uses TCP-based DAP mode with 'dap' subcommand
-                verify_dap_adapter_tcp(&path, &["dap".to_string()], crate::common::config::TcpSpawnStyle::TcpListen).await
+                verify_dap_adapter_tcp(&path, &["dap".to_string()], SpawnStyle::ListenFlag).await
             }
             InstallStatus::Broken { reason, .. } => Ok(VerifyResult {
                 success: false,
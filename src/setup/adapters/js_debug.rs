@@ -2,9 +2,10 @@
 //!
 //! Installs Microsoft's JavaScript/TypeScript debugger via npm.
 
+use crate::common::config::SpawnStyle;
 use crate::common::{Error, Result};
 use crate::setup::installer::{
-    adapters_dir, ensure_adapters_dir, run_command_args, write_version_file,
+    adapters_dir, ensure_adapters_dir, latest_npm_version, run_command_args, write_version_file,
     InstallMethod, InstallOptions, InstallResult, InstallStatus, Installer,
 };
 use crate::setup::registry::{DebuggerInfo, Platform};
@@ -72,6 +73,15 @@ impl Installer for JsDebugInstaller {
         Ok(())
     }
 
+    async fn latest_version(&self) -> Result<Option<String>> {
+        latest_npm_version("@vscode/js-debug").await.map(Some)
+    }
+
+    fn spawn_style(&self) -> Option<SpawnStyle> {
+        // dapDebugServer.js takes the port to listen on as its argument
+        Some(SpawnStyle::PortArg)
+    }
+
     async fn verify(&self) -> Result<VerifyResult> {
         let status = self.status().await?;
 
@@ -81,8 +91,8 @@ impl Installer for JsDebugInstaller {
                 let node_path = which::which("node").map_err(|_| {
                     Error::Internal("node not found in PATH".to_string())
                 })?;
-                // TcpPortArg appends port as positional argument, no extra args needed
-                verify_dap_adapter_tcp(&node_path, &[path.to_string_lossy().to_string()], crate::common::config::TcpSpawnStyle::TcpPortArg).await
+                // PortArg appends port as positional argument, no extra args needed
+                verify_dap_adapter_tcp(&node_path, &[path.to_string_lossy().to_string()], SpawnStyle::PortArg).await
             }
             InstallStatus::Broken { reason, .. } => Ok(VerifyResult {
                 success: false,
@@ -167,7 +177,7 @@ async fn install_js_debug(opts: &InstallOptions) -> Result<InstallResult> {
     println!("js-debug installation completed.");
 
     // Return node as the executable with the JS file as an argument
-    // TcpPortArg will append the port as a positional argument
+    // PortArg will append the port as a positional argument
     Ok(InstallResult {
         path: node_path,
         version,
@@ -1,9 +1,17 @@
 //! Project type detection and debugger recommendations
 //!
-//! Detects project types from the current directory and recommends appropriate debuggers.
+//! Detects project types from the current directory, and projects nested
+//! inside it (as in a monorepo), and recommends appropriate debuggers.
 
 use std::path::Path;
 
+/// How many directory levels below the starting one are searched by default
+pub const DEFAULT_DETECT_DEPTH: usize = 3;
+
+/// Dependency and build output directories, which hold other people's
+/// projects rather than the user's
+const IGNORED_DIRS: &[&str] = &["node_modules", "target", "vendor"];
+
 /// Detected project type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ProjectType {
@@ -19,8 +27,50 @@ pub enum ProjectType {
     Java,
 }
 
-/// Detect project types in a directory
-pub fn detect_project_types(dir: &Path) -> Vec<ProjectType> {
+/// Detect project types in a directory and its subdirectories, up to
+/// `max_depth` levels down. Each type is listed once, in the order found.
+pub fn detect_project_types(dir: &Path, max_depth: usize) -> Vec<ProjectType> {
+    let mut types = Vec::new();
+    collect_project_types(dir, max_depth, &mut types);
+    types
+}
+
+fn collect_project_types(dir: &Path, depth_left: usize, types: &mut Vec<ProjectType>) {
+    for project_type in detect_in_dir(dir) {
+        if !types.contains(&project_type) {
+            types.push(project_type);
+        }
+    }
+
+    if depth_left == 0 {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut subdirs: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.path())
+        .filter(|path| !is_ignored_dir(path))
+        .collect();
+    subdirs.sort();
+
+    for subdir in subdirs {
+        collect_project_types(&subdir, depth_left - 1, types);
+    }
+}
+
+/// Vendor/build directories and hidden ones (`.git`, `.venv`) are skipped
+fn is_ignored_dir(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.') || IGNORED_DIRS.contains(&name))
+}
+
+/// Detect project types from the files directly in a directory
+fn detect_in_dir(dir: &Path) -> Vec<ProjectType> {
     let mut types = Vec::new();
 
     // Rust
@@ -47,14 +97,12 @@ pub fn detect_project_types(dir: &Path) -> Vec<ProjectType> {
         types.push(ProjectType::Python);
     }
 
-    // JavaScript / TypeScript
-    if dir.join("package.json").exists() {
-        // Check for TypeScript
-        if dir.join("tsconfig.json").exists() {
-            types.push(ProjectType::TypeScript);
-        } else {
-            types.push(ProjectType::JavaScript);
-        }
+    // JavaScript / TypeScript; a tsconfig.json marks TypeScript even when
+    // the package.json lives further up
+    if dir.join("tsconfig.json").exists() {
+        types.push(ProjectType::TypeScript);
+    } else if dir.join("package.json").exists() {
+        types.push(ProjectType::JavaScript);
     }
 
     // C / C++
@@ -104,6 +152,40 @@ pub fn debuggers_for_project(project: &ProjectType) -> Vec<&'static str> {
     }
 }
 
+/// Detect the language of a program to debug from the file itself: a script
+/// by its extension, or a Go binary by the build ID the Go linker embeds.
+/// Other binaries give `None`, as any native debugger fits them.
+pub fn detect_program_type(program: &Path) -> Option<ProjectType> {
+    let ext = program.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match ext {
+        "py" => return Some(ProjectType::Python),
+        "js" | "mjs" | "cjs" => return Some(ProjectType::JavaScript),
+        "ts" | "mts" | "cts" => return Some(ProjectType::TypeScript),
+        _ => {}
+    }
+
+    if is_go_binary(program) {
+        return Some(ProjectType::Go);
+    }
+    None
+}
+
+/// Whether a binary was built by Go, which writes `Go build ID: "..."` at
+/// the start of its text segment
+fn is_go_binary(program: &Path) -> bool {
+    use std::io::Read;
+
+    const MARKER: &[u8] = b"Go build ID: \"";
+    let Ok(file) = std::fs::File::open(program) else {
+        return false;
+    };
+    let mut head = Vec::new();
+    if file.take(64 * 1024).read_to_end(&mut head).is_err() {
+        return false;
+    }
+    head.windows(MARKER.len()).any(|window| window == MARKER)
+}
+
 /// Check if directory contains C++ files
 fn has_cpp_files(dir: &Path) -> bool {
     has_extension_in_dir(dir, "cpp")
@@ -140,7 +222,7 @@ mod tests {
     fn test_detect_rust_project() {
         let dir = tempdir().unwrap();
         std::fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
-        let types = detect_project_types(dir.path());
+        let types = detect_project_types(dir.path(), 0);
         assert!(types.contains(&ProjectType::Rust));
     }
 
@@ -148,10 +230,59 @@ mod tests {
     fn test_detect_python_project() {
         let dir = tempdir().unwrap();
         std::fs::write(dir.path().join("requirements.txt"), "requests").unwrap();
-        let types = detect_project_types(dir.path());
+        let types = detect_project_types(dir.path(), 0);
         assert!(types.contains(&ProjectType::Python));
     }
 
+    #[test]
+    fn test_detect_typescript_project() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("tsconfig.json"), "{}").unwrap();
+        let types = detect_project_types(dir.path(), 0);
+        assert_eq!(types, vec![ProjectType::TypeScript]);
+        assert_eq!(debuggers_for_project(&types[0]), vec!["js-debug"]);
+    }
+
+    #[test]
+    fn test_detect_nested_projects() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for sub in ["services/api", "web", "node_modules/dep", "target/pkg"] {
+            std::fs::create_dir_all(root.join(sub)).unwrap();
+        }
+        std::fs::write(root.join("Cargo.toml"), "[workspace]").unwrap();
+        std::fs::write(root.join("services/api/go.mod"), "module api").unwrap();
+        std::fs::write(root.join("web/package.json"), "{}").unwrap();
+        std::fs::write(root.join("node_modules/dep/setup.py"), "").unwrap();
+        std::fs::write(root.join("target/pkg/go.mod"), "module pkg").unwrap();
+
+        assert_eq!(
+            detect_project_types(root, DEFAULT_DETECT_DEPTH),
+            vec![ProjectType::Rust, ProjectType::Go, ProjectType::JavaScript]
+        );
+        // Too shallow to reach services/api
+        assert_eq!(
+            detect_project_types(root, 1),
+            vec![ProjectType::Rust, ProjectType::JavaScript]
+        );
+    }
+
+    #[test]
+    fn test_detect_program_type() {
+        let dir = tempdir().unwrap();
+        let go = dir.path().join("server");
+        let mut binary = b"\x7fELF\x02\x01\x01".to_vec();
+        binary.extend_from_slice(b"\xff Go build ID: \"abc/def\"\n \xff");
+        std::fs::write(&go, binary).unwrap();
+        let native = dir.path().join("tool");
+        std::fs::write(&native, b"\x7fELF\x02\x01\x01").unwrap();
+
+        assert_eq!(detect_program_type(Path::new("app.py")), Some(ProjectType::Python));
+        assert_eq!(detect_program_type(Path::new("dist/app.js")), Some(ProjectType::JavaScript));
+        assert_eq!(detect_program_type(&go), Some(ProjectType::Go));
+        assert_eq!(detect_program_type(&native), None);
+    }
+
     #[test]
     fn test_debuggers_for_rust() {
         let debuggers = debuggers_for_project(&ProjectType::Rust);
@@ -0,0 +1,163 @@
+//! First-run configuration
+//!
+//! When no config file exists yet, the daemon probes for installed adapters
+//! with the installers' own detection and writes a `config.toml` naming them,
+//! so the first `debugger start` works instead of failing with "adapter not
+//! found".
+
+use std::path::PathBuf;
+
+use crate::common::paths::{config_path, ensure_config_dir};
+use crate::common::Result;
+
+use super::installer::InstallStatus;
+use super::registry;
+
+/// An adapter probed on first run
+struct KnownAdapter {
+    /// Name of the `[adapters]` entry, as passed to `--adapter`
+    name: &'static str,
+    /// Registry id of the installer that detects it
+    installer: &'static str,
+    args: &'static [&'static str],
+    /// DAP transport, when it isn't stdio
+    transport: Option<&'static str>,
+}
+
+/// Probed adapters, most preferred default first. js-debug and cuda-gdb need
+/// more than a detected path, so they are left to `debugger setup`.
+const KNOWN_ADAPTERS: &[KnownAdapter] = &[
+    KnownAdapter {
+        name: "lldb-dap",
+        installer: "lldb",
+        args: &[],
+        transport: None,
+    },
+    KnownAdapter {
+        name: "gdb",
+        installer: "gdb",
+        args: &["-i=dap"],
+        transport: None,
+    },
+    KnownAdapter {
+        name: "codelldb",
+        installer: "codelldb",
+        args: &[],
+        transport: None,
+    },
+    KnownAdapter {
+        name: "debugpy",
+        installer: "python",
+        args: &["-m", "debugpy.adapter"],
+        transport: None,
+    },
+    KnownAdapter {
+        name: "go",
+        installer: "go",
+        args: &["dap"],
+        transport: Some("tcp"),
+    },
+];
+
+/// Write a default config file if there is none, returning its path when one
+/// was written. Nothing is written when no adapter is found, so a later run
+/// can pick up adapters installed in the meantime.
+pub async fn write_default_config_if_missing() -> Result<Option<PathBuf>> {
+    let Some(path) = config_path() else {
+        return Ok(None);
+    };
+    if path.exists() {
+        return Ok(None);
+    }
+
+    let mut found = Vec::new();
+    for adapter in KNOWN_ADAPTERS {
+        let Some(installer) = registry::get_installer(adapter.installer) else {
+            continue;
+        };
+        // Broken covers versions too old to speak DAP (e.g. GDB before 14.1)
+        if let Ok(InstallStatus::Installed { path, .. }) = installer.status().await {
+            tracing::info!(adapter = adapter.name, path = %path.display(), "Found adapter");
+            found.push((adapter, path));
+        }
+    }
+    if found.is_empty() {
+        return Ok(None);
+    }
+
+    ensure_config_dir()?;
+    let content = toml::to_string_pretty(&default_config(&found))
+        .map_err(|e| crate::common::Error::Internal(format!("Failed to write config: {}", e)))?;
+    std::fs::write(&path, content)?;
+    Ok(Some(path))
+}
+
+/// The config for the found adapters, with the first as the default
+fn default_config(found: &[(&KnownAdapter, PathBuf)]) -> toml::Table {
+    let mut adapters = toml::Table::new();
+    for (adapter, path) in found {
+        let mut entry = toml::Table::new();
+        entry.insert(
+            "path".to_string(),
+            toml::Value::String(path.display().to_string()),
+        );
+        if !adapter.args.is_empty() {
+            entry.insert(
+                "args".to_string(),
+                toml::Value::Array(
+                    adapter
+                        .args
+                        .iter()
+                        .map(|arg| toml::Value::String(arg.to_string()))
+                        .collect(),
+                ),
+            );
+        }
+        if let Some(transport) = adapter.transport {
+            entry.insert(
+                "transport".to_string(),
+                toml::Value::String(transport.to_string()),
+            );
+        }
+        adapters.insert(adapter.name.to_string(), toml::Value::Table(entry));
+    }
+
+    let mut defaults = toml::Table::new();
+    if let Some((adapter, _)) = found.first() {
+        defaults.insert(
+            "adapter".to_string(),
+            toml::Value::String(adapter.name.to_string()),
+        );
+    }
+
+    let mut config = toml::Table::new();
+    config.insert("defaults".to_string(), toml::Value::Table(defaults));
+    config.insert("adapters".to_string(), toml::Value::Table(adapters));
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::config::{Config, TransportMode};
+
+    #[test]
+    fn default_config_loads_with_the_found_adapters() {
+        let gdb = &KNOWN_ADAPTERS[1];
+        let delve = &KNOWN_ADAPTERS[4];
+        let found = vec![
+            (gdb, PathBuf::from("/usr/bin/gdb")),
+            (delve, PathBuf::from("/home/me/go/bin/dlv")),
+        ];
+
+        let content = toml::to_string_pretty(&default_config(&found)).unwrap();
+        let config: Config = toml::from_str(&content).unwrap();
+
+        assert_eq!(config.defaults.adapter, "gdb");
+        let gdb = &config.adapters["gdb"];
+        assert_eq!(gdb.path, PathBuf::from("/usr/bin/gdb"));
+        assert_eq!(gdb.args, vec!["-i=dap"]);
+        assert_eq!(gdb.transport, TransportMode::Stdio);
+        assert_eq!(config.adapters["go"].transport, TransportMode::Tcp);
+    }
+}
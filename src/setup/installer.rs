@@ -4,6 +4,7 @@
 
 use super::registry::{DebuggerInfo, Platform};
 use super::verifier::VerifyResult;
+use crate::common::config::SpawnStyle;
 use crate::common::{Error, Result};
 use async_trait::async_trait;
 use futures_util::StreamExt;
@@ -159,6 +160,19 @@ pub trait Installer: Send + Sync {
 
     /// Verify the installation works
     async fn verify(&self) -> Result<VerifyResult>;
+
+    /// Newest version available from the install source, or `None` when the
+    /// debugger comes from a system package manager or toolkit this tool
+    /// doesn't track
+    async fn latest_version(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// How the installed adapter is started over TCP, or `None` for adapters
+    /// that speak DAP over stdio
+    fn spawn_style(&self) -> Option<SpawnStyle> {
+        None
+    }
 }
 
 /// Get the adapters installation directory
@@ -391,7 +405,6 @@ pub async fn run_command_args<S: AsRef<std::ffi::OsStr>>(
 
 /// Query GitHub API for latest release with retry logic
 pub async fn get_github_release(repo: &str, version: Option<&str>) -> Result<GitHubRelease> {
-    let client = reqwest::Client::new();
     let url = if let Some(v) = version {
         format!(
             "https://api.github.com/repos/{}/releases/tags/{}",
@@ -401,6 +414,61 @@ pub async fn get_github_release(repo: &str, version: Option<&str>) -> Result<Git
         format!("https://api.github.com/repos/{}/releases/latest", repo)
     };
 
+    fetch_json(&url, JsonApi::GitHub).await
+}
+
+/// A service whose JSON API the installers query for releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonApi {
+    GitHub,
+    PyPi,
+    Npm,
+}
+
+impl JsonApi {
+    /// Name of the service in error messages
+    fn name(self) -> &'static str {
+        match self {
+            Self::GitHub => "GitHub",
+            Self::PyPi => "PyPI",
+            Self::Npm => "npm",
+        }
+    }
+
+    /// Headers the service wants beyond the user agent: GitHub's versioned
+    /// media type, and its token when `GITHUB_TOKEN` is set
+    fn headers(self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::GitHub => {
+                let mut headers =
+                    vec![("Accept", "application/vnd.github.v3+json".to_string())];
+                if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+                    headers.push(("Authorization", format!("Bearer {}", token)));
+                }
+                headers
+            }
+            Self::PyPi | Self::Npm => vec![("Accept", "application/json".to_string())],
+        }
+    }
+
+    /// Error for a rate-limited request, with how to avoid it if there's a way
+    fn rate_limited(self) -> String {
+        match self {
+            Self::GitHub => {
+                "GitHub API rate limit exceeded. Set GITHUB_TOKEN env var to increase limit."
+                    .to_string()
+            }
+            api => format!("{} API rate limit exceeded", api.name()),
+        }
+    }
+}
+
+/// GET a JSON document from `api`, retrying transient failures with
+/// exponential backoff (1s, 2s, 4s)
+pub async fn fetch_json<T: serde::de::DeserializeOwned>(url: &str, api: JsonApi) -> Result<T> {
+    let client = reqwest::Client::new();
+    let source = api.name();
+
     // Retry with exponential backoff (1s, 2s, 4s)
     let delays = [1, 2, 4];
     let mut last_error = None;
@@ -410,16 +478,14 @@ pub async fn get_github_release(repo: &str, version: Option<&str>) -> Result<Git
             tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
         }
 
-        let response = match client
-            .get(&url)
-            .header("User-Agent", "debugger-cli")
-            .header("Accept", "application/vnd.github.v3+json")
-            .send()
-            .await
-        {
+        let mut request = client.get(url).header("User-Agent", "debugger-cli");
+        for (name, value) in api.headers() {
+            request = request.header(name, value);
+        }
+        let response = match request.send().await {
             Ok(r) => r,
             Err(e) => {
-                last_error = Some(format!("GitHub API error: {}", e));
+                last_error = Some(format!("{} API error: {}", source, e));
                 continue;
             }
         };
@@ -428,15 +494,12 @@ pub async fn get_github_release(repo: &str, version: Option<&str>) -> Result<Git
         if response.status() == reqwest::StatusCode::FORBIDDEN
             || response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
         {
-            last_error = Some(
-                "GitHub API rate limit exceeded. Set GITHUB_TOKEN env var to increase limit."
-                    .to_string(),
-            );
+            last_error = Some(api.rate_limited());
             continue;
         }
 
         if !response.status().is_success() {
-            last_error = Some(format!("GitHub API returned status {}", response.status()));
+            last_error = Some(format!("{} API returned status {}", source, response.status()));
             // Don't retry on 404 or other client errors
             if response.status().is_client_error() {
                 break;
@@ -444,19 +507,63 @@ pub async fn get_github_release(repo: &str, version: Option<&str>) -> Result<Git
             continue;
         }
 
-        let release: GitHubRelease = response
-            .json()
-            .await
-            .map_err(|e| Error::Internal(format!("Failed to parse GitHub response: {}", e)))?;
-
-        return Ok(release);
+        return response.json().await.map_err(|e| {
+            Error::Internal(format!("Failed to parse {} response: {}", source, e))
+        });
     }
 
     Err(Error::Internal(
-        last_error.unwrap_or_else(|| "GitHub API request failed".to_string()),
+        last_error.unwrap_or_else(|| format!("{} API request failed", source)),
     ))
 }
 
+/// Latest release version of a GitHub repository, without a leading `v`
+pub async fn latest_github_version(repo: &str) -> Result<String> {
+    let release = get_github_release(repo, None).await?;
+    Ok(release.tag_name.trim_start_matches('v').to_string())
+}
+
+/// Latest version of a package on PyPI
+pub async fn latest_pypi_version(package: &str) -> Result<String> {
+    #[derive(serde::Deserialize)]
+    struct Project {
+        info: ProjectInfo,
+    }
+    #[derive(serde::Deserialize)]
+    struct ProjectInfo {
+        version: String,
+    }
+
+    let url = format!("https://pypi.org/pypi/{}/json", package);
+    let project: Project = fetch_json(&url, JsonApi::PyPi).await?;
+    Ok(project.info.version)
+}
+
+/// Version behind the `latest` tag of an npm package
+pub async fn latest_npm_version(package: &str) -> Result<String> {
+    let url = format!("https://registry.npmjs.org/-/package/{}/dist-tags", package);
+    let tags: std::collections::HashMap<String, String> = fetch_json(&url, JsonApi::Npm).await?;
+    tags.get("latest")
+        .cloned()
+        .ok_or_else(|| Error::Internal(format!("npm has no latest version of {}", package)))
+}
+
+/// Whether `latest` is a newer version than `installed`. Versions are
+/// compared as semver where possible (`v1.2` reads as 1.2.0), and otherwise
+/// any difference counts as newer.
+pub fn is_newer_version(latest: &str, installed: &str) -> bool {
+    fn parse(version: &str) -> Option<semver::Version> {
+        let version = version.trim().trim_start_matches('v');
+        let padding = 2usize.saturating_sub(version.matches('.').count());
+        semver::Version::parse(&format!("{}{}", version, ".0".repeat(padding))).ok()
+    }
+
+    match (parse(latest), parse(installed)) {
+        (Some(latest), Some(installed)) => latest > installed,
+        _ => latest.trim().trim_start_matches('v') != installed.trim().trim_start_matches('v'),
+    }
+}
+
 /// GitHub release information
 #[derive(Debug, serde::Deserialize)]
 pub struct GitHubRelease {
@@ -529,3 +636,21 @@ pub fn read_version_file(dir: &Path) -> Option<String> {
         .ok()
         .map(|s| s.trim().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_versions_compare_as_semver() {
+        assert!(is_newer_version("1.11.1", "1.11.0"));
+        assert!(is_newer_version("v1.23.0", "1.22.1"));
+        assert!(is_newer_version("1.10.0", "1.9"));
+        assert!(!is_newer_version("1.8.0", "1.8.0"));
+        assert!(!is_newer_version("v1.8.0", "1.8"));
+        assert!(!is_newer_version("1.7.0", "1.8.0"));
+        // Unparseable versions only compare for equality
+        assert!(is_newer_version("nightly-2", "nightly-1"));
+        assert!(!is_newer_version("nightly-1", "nightly-1"));
+    }
+}
@@ -5,10 +5,12 @@
 
 pub mod adapters;
 pub mod detector;
+pub mod first_run;
 pub mod installer;
 pub mod registry;
 pub mod verifier;
 
+use crate::common::config::SpawnStyle;
 use crate::common::Result;
 use std::path::PathBuf;
 
@@ -23,8 +25,15 @@ pub struct SetupOptions {
     pub list: bool,
     /// Check installed debuggers
     pub check: bool,
+    /// Compare installed debuggers with their latest releases
+    pub check_updates: bool,
+    /// Reinstall debuggers that are broken or fail verification
+    pub repair: bool,
     /// Auto-detect project types and install appropriate debuggers
     pub auto_detect: bool,
+    /// How many directory levels below the current one auto-detection
+    /// searches for projects
+    pub depth: usize,
     /// Uninstall instead of install
     pub uninstall: bool,
     /// Show installation path
@@ -74,6 +83,14 @@ pub async fn run(opts: SetupOptions) -> Result<()> {
         return check_debuggers(opts.json).await;
     }
 
+    if opts.check_updates {
+        return check_updates(opts.json).await;
+    }
+
+    if opts.repair {
+        return repair_debuggers(opts).await;
+    }
+
     if opts.auto_detect {
         return auto_setup(opts).await;
     }
@@ -246,9 +263,161 @@ async fn check_debuggers(json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Report installed debuggers that have a newer release available
+async fn check_updates(json: bool) -> Result<()> {
+    let mut results = Vec::new();
+    let mut upgradable = Vec::new();
+    let mut found_any = false;
+
+    if !json {
+        println!("Checking for updates...\n");
+    }
+
+    for info in registry::all_debuggers() {
+        let installer = match registry::get_installer(info.id) {
+            Some(i) => i,
+            None => continue,
+        };
+
+        let installed = match installer.status().await {
+            Ok(installer::InstallStatus::Installed { version, .. }) => version,
+            _ => continue,
+        };
+        found_any = true;
+
+        let latest = installer.latest_version().await;
+        let update_available = match (&latest, &installed) {
+            (Ok(Some(latest)), Some(installed)) => installer::is_newer_version(latest, installed),
+            _ => false,
+        };
+        if update_available {
+            upgradable.push(info.id);
+        }
+
+        if json {
+            results.push(serde_json::json!({
+                "id": info.id,
+                "installed": installed,
+                "latest": latest.as_ref().ok().cloned().flatten(),
+                "update_available": update_available,
+                "error": latest.as_ref().err().map(|e| e.to_string()),
+            }));
+            continue;
+        }
+
+        let installed_str = installed.as_deref().unwrap_or("unknown version");
+        match &latest {
+            Ok(Some(latest)) if update_available => {
+                println!("↑ {:12} {} → {}", info.id, installed_str, latest)
+            }
+            Ok(Some(_)) if installed.is_none() => {
+                println!("? {:12} installed version unknown", info.id)
+            }
+            Ok(Some(_)) => println!("✓ {:12} {} (up to date)", info.id, installed_str),
+            Ok(None) => println!(
+                "  {:12} {} (managed outside debugger-cli)",
+                info.id, installed_str
+            ),
+            Err(e) => println!("✗ {:12} {} (update check failed: {})", info.id, installed_str, e),
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else if !found_any {
+        println!("No debuggers installed.");
+        println!("Use 'debugger setup --list' to see available debuggers.");
+    } else if upgradable.is_empty() {
+        println!("\nAll checked debuggers are up to date.");
+    } else {
+        println!();
+        for id in upgradable {
+            println!("Upgrade with: debugger setup {} --force", id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reinstall every debugger that is broken or installed but failing
+/// verification, e.g. after an interrupted download
+async fn repair_debuggers(opts: SetupOptions) -> Result<()> {
+    let mut results = Vec::new();
+
+    if !opts.json {
+        println!("Checking installed debuggers...\n");
+    }
+
+    for info in registry::all_debuggers() {
+        let installer = match registry::get_installer(info.id) {
+            Some(i) => i,
+            None => continue,
+        };
+
+        let problem = match installer.status().await {
+            Ok(installer::InstallStatus::Broken { reason, .. }) => reason,
+            Ok(installer::InstallStatus::Installed { .. }) => match installer.verify().await {
+                Ok(verify) if verify.success => continue,
+                Ok(verify) => verify
+                    .error
+                    .unwrap_or_else(|| "verification failed".to_string()),
+                Err(e) => e.to_string(),
+            },
+            _ => continue,
+        };
+
+        if !opts.json {
+            println!("✗ {} is broken: {}", info.id, problem);
+            println!("Repairing {}...", info.id);
+        }
+
+        let result = install_debugger_inner(
+            info.id,
+            &SetupOptions {
+                debugger: Some(info.id.to_string()),
+                force: true,
+                ..opts.clone()
+            },
+        )
+        .await;
+        results.push(result);
+    }
+
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else if results.is_empty() {
+        println!("No broken debuggers found.");
+    } else {
+        let with_status = |wanted: fn(SetupStatus) -> bool| -> Vec<&str> {
+            results
+                .iter()
+                .filter(|r| wanted(r.status))
+                .map(|r| r.debugger.as_str())
+                .collect()
+        };
+
+        println!();
+        for (label, ids) in [
+            ("Repaired", with_status(|s| s == SetupStatus::Success)),
+            ("Would repair", with_status(|s| s == SetupStatus::DryRun)),
+            (
+                "Could not repair",
+                with_status(|s| !matches!(s, SetupStatus::Success | SetupStatus::DryRun)),
+            ),
+        ] {
+            if !ids.is_empty() {
+                println!("{}: {}", label, ids.join(", "));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Auto-detect project types and install appropriate debuggers
 async fn auto_setup(opts: SetupOptions) -> Result<()> {
-    let project_types = detector::detect_project_types(std::env::current_dir()?.as_path());
+    let project_types =
+        detector::detect_project_types(std::env::current_dir()?.as_path(), opts.depth);
 
     if project_types.is_empty() {
         if opts.json {
@@ -265,12 +434,13 @@ async fn auto_setup(opts: SetupOptions) -> Result<()> {
         return Ok(());
     }
 
-    let debuggers: Vec<&str> = project_types
-        .iter()
-        .flat_map(|pt| detector::debuggers_for_project(pt))
-        .collect::<std::collections::HashSet<_>>()
-        .into_iter()
-        .collect();
+    // Every project's debuggers, each once, in the order projects were found
+    let mut debuggers: Vec<&str> = Vec::new();
+    for debugger in project_types.iter().flat_map(detector::debuggers_for_project) {
+        if !debuggers.contains(&debugger) {
+            debuggers.push(debugger);
+        }
+    }
 
     if !opts.json {
         println!(
@@ -552,7 +722,10 @@ async fn install_debugger_inner(debugger: &str, opts: &SetupOptions) -> SetupRes
     match installer.install(install_opts).await {
         Ok(result) => {
             // Update configuration
-            if let Err(e) = update_config(debugger, &result.path, &result.args).await {
+            let spawn_style = installer.spawn_style();
+            if let Err(e) =
+                update_config(debugger, &result.path, &result.args, spawn_style.as_ref()).await
+            {
                 if !opts.json {
                     println!("Warning: Failed to update configuration: {}", e);
                 }
@@ -606,7 +779,12 @@ async fn install_debugger_inner(debugger: &str, opts: &SetupOptions) -> SetupRes
 }
 
 /// Update the configuration file with the installed adapter
-async fn update_config(debugger: &str, path: &std::path::Path, args: &[String]) -> Result<()> {
+async fn update_config(
+    debugger: &str,
+    path: &std::path::Path,
+    args: &[String],
+    spawn_style: Option<&SpawnStyle>,
+) -> Result<()> {
     use crate::common::paths::{config_path, ensure_config_dir};
     use std::io::Write;
 
@@ -660,6 +838,14 @@ async fn update_config(debugger: &str, path: &std::path::Path, args: &[String])
         );
     }
 
+    if let Some(style) = spawn_style {
+        adapter_table.insert("transport".to_string(), toml::Value::String("tcp".to_string()));
+        adapter_table.insert(
+            "spawn_style".to_string(),
+            toml::Value::String(style.as_str().to_string()),
+        );
+    }
+
     adapters.insert(debugger.to_string(), toml::Value::Table(adapter_table));
 
     // Write back
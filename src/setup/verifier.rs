@@ -2,7 +2,7 @@
 //!
 //! Verifies that installed debuggers work correctly by sending DAP messages.
 
-use crate::common::config::TcpSpawnStyle;
+use crate::common::config::SpawnStyle;
 use crate::common::{parse_listen_address, Error, Result};
 use std::path::Path;
 use std::process::Stdio;
@@ -68,10 +68,10 @@ pub async fn verify_dap_adapter(
 pub async fn verify_dap_adapter_tcp(
     path: &Path,
     args: &[String],
-    spawn_style: TcpSpawnStyle,
+    spawn_style: SpawnStyle,
 ) -> Result<VerifyResult> {
     let (mut child, addr) = match spawn_style {
-        TcpSpawnStyle::TcpListen => {
+        SpawnStyle::ListenFlag => {
             let mut cmd = Command::new(path);
             cmd.args(args)
                 .arg("--listen=127.0.0.1:0")
@@ -132,7 +132,7 @@ pub async fn verify_dap_adapter_tcp(
 
             (child, addr)
         }
-        TcpSpawnStyle::TcpPortArg => {
+        SpawnStyle::PortArg => {
             use std::net::TcpListener as StdTcpListener;
 
             let listener = StdTcpListener::bind("127.0.0.1:0").map_err(|e| {
@@ -160,6 +160,13 @@ pub async fn verify_dap_adapter_tcp(
 
             (child, addr)
         }
+        SpawnStyle::ConnectExisting => {
+            return Ok(VerifyResult {
+                success: false,
+                capabilities: None,
+                error: Some("An adapter that is already running can't be verified by spawning it".to_string()),
+            });
+        }
     };
 
     // Retry TCP connection with exponential backoff
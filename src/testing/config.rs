@@ -2,9 +2,13 @@
 //!
 //! Defines the data structures for deserializing YAML test scenarios.
 
-use serde::Deserialize;
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
 use std::path::PathBuf;
 
+use crate::common::{Error, Result};
+
 /// A complete test scenario loaded from a YAML file
 #[derive(Deserialize, Debug)]
 pub struct TestScenario {
@@ -14,13 +18,18 @@ pub struct TestScenario {
     pub description: Option<String>,
     /// Optional setup steps to run before the test (e.g., compilation)
     pub setup: Option<Vec<SetupStep>>,
+    /// Optional shell steps run after the test, whether it passed or failed
+    /// (e.g., killing an attach target)
+    pub teardown: Option<Vec<SetupStep>>,
     /// Configuration for the debug target
     pub target: TargetConfig,
     /// The sequence of test steps to execute
     pub steps: Vec<TestStep>,
+    /// Limit in seconds for running all steps
+    pub timeout: Option<u64>,
 }
 
-/// A setup step that runs before the test
+/// A shell step that runs before (setup) or after (teardown) the test
 #[derive(Deserialize, Debug)]
 pub struct SetupStep {
     /// Shell command to execute
@@ -48,6 +57,114 @@ pub struct TargetConfig {
     pub stop_on_entry: bool,
 }
 
+impl TestScenario {
+    /// Parse a scenario from YAML or JSON text. `${VAR}` and
+    /// `${VAR:-default}` in string values are resolved against the
+    /// environment; `$${` stands for a literal `${`.
+    pub fn parse(content: &str, json: bool) -> Result<Self> {
+        let parse_error = |e: String| Error::Config(format!("Failed to parse test scenario: {}", e));
+
+        let mut value: Value = if json {
+            serde_json::from_str(content).map_err(|e| parse_error(e.to_string()))?
+        } else {
+            serde_yaml::from_str(content).map_err(|e| parse_error(e.to_string()))?
+        };
+        interpolate_strings(&mut value, &|name| std::env::var(name).ok())?;
+        let scenario: Self =
+            serde_json::from_value(value).map_err(|e| parse_error(e.to_string()))?;
+        scenario.validate().map_err(parse_error)?;
+        Ok(scenario)
+    }
+
+    /// Reject step settings that can't be acted on, before anything runs
+    fn validate(&self) -> std::result::Result<(), String> {
+        for (index, step) in self.steps.iter().enumerate() {
+            if let TestStep::Await {
+                expect,
+                retries: Some(retries),
+                ..
+            } = step
+            {
+                if *retries > MAX_AWAIT_RETRIES {
+                    return Err(format!(
+                        "await step {} asks for {} retries; at most {} are allowed",
+                        index + 1,
+                        retries,
+                        MAX_AWAIT_RETRIES
+                    ));
+                }
+                if *retries > 0 && expect.as_ref().and_then(|e| e.reason.as_ref()).is_none() {
+                    return Err(format!(
+                        "await step {} sets retries without expect.reason to retry on",
+                        index + 1
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resolve `${VAR}` references in every string inside `value`
+fn interpolate_strings(value: &mut Value, lookup: &dyn Fn(&str) -> Option<String>) -> Result<()> {
+    match value {
+        Value::String(text) => *text = interpolate(text, lookup)?,
+        Value::Array(items) => {
+            for item in items {
+                interpolate_strings(item, lookup)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                interpolate_strings(item, lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replace `${VAR}` and `${VAR:-default}` in `text`, and `$${` with a
+/// literal `${`; other `$`s are kept
+fn interpolate(text: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        if let Some(before) = rest[..start].strip_suffix('$') {
+            result.push_str(before);
+            result.push_str("${");
+            rest = after;
+            continue;
+        }
+        result.push_str(&rest[..start]);
+        let end = after.find('}').ok_or_else(|| {
+            Error::Config(format!("Unterminated '${{' in scenario value: {}", text))
+        })?;
+        let reference = &after[..end];
+
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        match (lookup(name), default) {
+            (Some(value), _) => result.push_str(&value),
+            (None, Some(default)) => result.push_str(default),
+            (None, None) => {
+                return Err(Error::Config(format!(
+                    "Environment variable '{}' is not set (use ${{{}:-default}} to give it a default)",
+                    name, name
+                )))
+            }
+        }
+        rest = &after[end + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
 fn default_mode() -> String {
     "launch".to_string()
 }
@@ -62,6 +179,8 @@ pub enum TestStep {
         command: String,
         /// Optional expectations for the command result
         expect: Option<CommandExpectation>,
+        /// Limit in seconds for this step
+        timeout: Option<u64>,
     },
     /// Wait for a stop event (breakpoint, step completion, etc.)
     Await {
@@ -69,16 +188,24 @@ pub enum TestStep {
         timeout: Option<u64>,
         /// Expected stop event properties
         expect: Option<StopExpectation>,
+        /// Continue and wait again up to this many times (at most
+        /// `MAX_AWAIT_RETRIES`, checked on load) when the stop reason doesn't
+        /// match `expect.reason`, for adapters that race an extra stop in first
+        retries: Option<u32>,
     },
     /// Inspect local variables and make assertions
     InspectLocals {
         /// Variable assertions to check
         asserts: Vec<VariableAssertion>,
+        /// Limit in seconds for this step
+        timeout: Option<u64>,
     },
     /// Inspect the call stack
     InspectStack {
         /// Frame assertions to check
         asserts: Vec<FrameAssertion>,
+        /// Limit in seconds for this step
+        timeout: Option<u64>,
     },
     /// Check program output
     CheckOutput {
@@ -86,6 +213,10 @@ pub enum TestStep {
         contains: Option<String>,
         /// Expected exact output
         equals: Option<String>,
+        /// Regex the output must match somewhere
+        matches: Option<Pattern>,
+        /// Limit in seconds for this step
+        timeout: Option<u64>,
     },
     /// Evaluate an expression
     Evaluate {
@@ -93,9 +224,42 @@ pub enum TestStep {
         expression: String,
         /// Expected result
         expect: Option<EvaluateExpectation>,
+        /// Limit in seconds for this step
+        timeout: Option<u64>,
     },
 }
 
+/// Stop timeout of an `await` step that doesn't set one
+pub const DEFAULT_AWAIT_TIMEOUT_SECS: u64 = 30;
+
+/// Extra time an `await` step gets beyond its own stop timeout, for the
+/// daemon to report that timeout
+const AWAIT_GRACE_SECS: u64 = 5;
+
+/// Upper bound for `retries` on an `await` step, so a retry can't hide a
+/// real failure for long
+pub const MAX_AWAIT_RETRIES: u32 = 3;
+
+impl TestStep {
+    /// Limit in seconds for the whole step, if any. `await` steps are always
+    /// bounded by their stop timeout, once per attempt.
+    pub fn timeout(&self) -> Option<u64> {
+        match self {
+            Self::Await {
+                timeout, retries, ..
+            } => {
+                let attempts = u64::from(retries.unwrap_or(0)) + 1;
+                Some((timeout.unwrap_or(DEFAULT_AWAIT_TIMEOUT_SECS) + AWAIT_GRACE_SECS) * attempts)
+            }
+            Self::Command { timeout, .. }
+            | Self::InspectLocals { timeout, .. }
+            | Self::InspectStack { timeout, .. }
+            | Self::CheckOutput { timeout, .. }
+            | Self::Evaluate { timeout, .. } => *timeout,
+        }
+    }
+}
+
 /// Expectations for a command result
 #[derive(Deserialize, Debug)]
 pub struct CommandExpectation {
@@ -133,11 +297,44 @@ pub struct VariableAssertion {
     pub value: Option<String>,
     /// Expected value substring (partial match)
     pub value_contains: Option<String>,
+    /// Regex the value must match somewhere
+    pub matches: Option<Pattern>,
+    /// The value, read as a number, must be greater than this
+    pub value_gt: Option<f64>,
+    /// The value, read as a number, must be less than this
+    pub value_lt: Option<f64>,
+    /// The value, read as a number, must equal this (so `5`, `5.0` and
+    /// `0x5` all match 5)
+    pub value_eq: Option<f64>,
     /// Expected type name
     #[serde(rename = "type")]
     pub type_name: Option<String>,
 }
 
+/// A regex from a `matches` field, compiled once when the scenario loads so
+/// a bad pattern is reported before anything runs
+#[derive(Debug)]
+pub struct Pattern(pub Regex);
+
+impl Pattern {
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern).map(Pattern).map_err(|e| {
+            serde::de::Error::custom(format!("invalid regex in 'matches': {}", e))
+        })
+    }
+}
+
 /// Assertion for a stack frame
 #[derive(Deserialize, Debug)]
 pub struct FrameAssertion {
@@ -165,3 +362,118 @@ pub struct EvaluateExpectation {
     #[serde(rename = "type")]
     pub type_name: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "WORKSPACE" => Some("/work".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn env_references_are_interpolated() {
+        assert_eq!(
+            interpolate("${WORKSPACE}/bin/app", &lookup).unwrap(),
+            "/work/bin/app"
+        );
+        assert_eq!(
+            interpolate("${ADAPTER_PATH:-lldb-dap} $5 $x", &lookup).unwrap(),
+            "lldb-dap $5 $x"
+        );
+        assert_eq!(interpolate("[${EMPTY:-unused}]", &lookup).unwrap(), "[]");
+        assert_eq!(
+            interpolate("echo $${HOME} ${WORKSPACE}", &lookup).unwrap(),
+            "echo ${HOME} /work"
+        );
+        assert_eq!(interpolate("$${MISSING", &lookup).unwrap(), "${MISSING");
+
+        let error = interpolate("${MISSING}", &lookup).unwrap_err().to_string();
+        assert!(error.contains("'MISSING' is not set"), "{}", error);
+        assert!(interpolate("${WORKSPACE", &lookup).is_err());
+    }
+
+    #[test]
+    fn await_retries_extend_the_step_timeout() {
+        let step = |retries| TestStep::Await {
+            timeout: Some(10),
+            expect: None,
+            retries,
+        };
+        assert_eq!(step(None).timeout(), Some(15));
+        assert_eq!(step(Some(2)).timeout(), Some(45));
+    }
+
+    #[test]
+    fn await_retries_are_checked_on_load() {
+        let scenario = |step: serde_json::Value| {
+            let json = serde_json::json!({
+                "name": "Retries",
+                "target": { "program": "./app" },
+                "steps": [step],
+            });
+            TestScenario::parse(&json.to_string(), true)
+        };
+
+        let retried = serde_json::json!({
+            "action": "await", "retries": 2, "expect": { "reason": "breakpoint" },
+        });
+        assert!(scenario(retried).is_ok());
+
+        let too_many = serde_json::json!({
+            "action": "await", "retries": 50, "expect": { "reason": "breakpoint" },
+        });
+        let error = scenario(too_many).unwrap_err().to_string();
+        assert!(error.contains("at most 3"), "{}", error);
+
+        let no_reason = serde_json::json!({ "action": "await", "retries": 1 });
+        let error = scenario(no_reason).unwrap_err().to_string();
+        assert!(error.contains("without expect.reason"), "{}", error);
+    }
+
+    #[test]
+    fn match_patterns_are_compiled_on_load() {
+        let scenario = |pattern: &str| {
+            let json = serde_json::json!({
+                "name": "Regex",
+                "target": { "program": "./app" },
+                "steps": [{ "action": "check_output", "matches": pattern }],
+            });
+            TestScenario::parse(&json.to_string(), true)
+        };
+
+        let loaded = scenario(r"at 0x[0-9a-f]+").unwrap();
+        match &loaded.steps[0] {
+            TestStep::CheckOutput {
+                matches: Some(pattern),
+                ..
+            } => assert!(pattern.is_match("fault at 0x7ffe12")),
+            step => panic!("unexpected step {:?}", step),
+        }
+
+        let error = scenario("(unclosed").unwrap_err().to_string();
+        assert!(error.contains("invalid regex in 'matches'"), "{}", error);
+    }
+
+    #[test]
+    fn only_string_values_are_interpolated() {
+        let mut value = serde_json::json!({
+            "program": "${WORKSPACE}/app",
+            "args": ["--root", "${WORKSPACE}"],
+            "pid": 42,
+        });
+        interpolate_strings(&mut value, &lookup).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "program": "/work/app",
+                "args": ["--root", "/work"],
+                "pid": 42,
+            })
+        );
+    }
+}
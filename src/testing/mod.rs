@@ -1,12 +1,15 @@
 //! E2E Test Runner
 //!
-//! Provides a robust test executor that reads YAML test scenarios
+//! Provides a robust test executor that reads YAML (or JSON) test scenarios
 //! and uses the DaemonClient to communicate with the debug daemon.
 //! This ensures assertions are made against structured data rather
 //! than fragile string matching.
 
 mod config;
 mod runner;
+mod suite;
 
 pub use config::*;
-pub use runner::run_scenario;
+pub use runner::{load_scenario, run_scenario};
+pub(crate) use runner::shell;
+pub use suite::{run_suite, SuiteSummary};
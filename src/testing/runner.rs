@@ -3,23 +3,26 @@
 //! Executes test scenarios by communicating directly with the daemon
 //! using structured data rather than parsing CLI output.
 
+use std::cmp::Ordering;
 use std::path::Path;
 use std::process::Stdio;
+use std::time::Duration;
 
 use colored::Colorize;
 use tokio::process::Command as TokioCommand;
 
 use crate::cli::spawn::ensure_daemon_running;
-use crate::common::{Error, Result};
+use crate::commands::parse::parse_command;
+use crate::common::{truncate_utf8_to_bytes, Error, Result};
 use crate::ipc::protocol::{
-    BreakpointLocation, Command, EvaluateContext, EvaluateResult, StackFrameInfo,
+    Command, EvaluateContext, EvaluateResult, StackFrameInfo,
     StopResult, VariableInfo,
 };
 use crate::ipc::DaemonClient;
 
 use super::config::{
-    CommandExpectation, EvaluateExpectation, FrameAssertion, StopExpectation, TestScenario,
-    TestStep, VariableAssertion,
+    CommandExpectation, EvaluateExpectation, FrameAssertion, Pattern, StopExpectation,
+    TestScenario, TestStep, VariableAssertion, DEFAULT_AWAIT_TIMEOUT_SECS,
 };
 
 /// Result of a test run
@@ -32,9 +35,18 @@ pub struct TestResult {
     pub error: Option<String>,
 }
 
-/// Run a test scenario from a YAML file
-pub async fn run_scenario(path: &Path, verbose: bool) -> Result<TestResult> {
-    // Load and parse the YAML scenario
+/// Parse a scenario file, as JSON when it has a `.json` extension and as
+/// YAML otherwise.
+fn parse_scenario(path: &Path, content: &str) -> Result<TestScenario> {
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    TestScenario::parse(content, is_json)
+}
+
+/// Read and parse a scenario file
+pub fn load_scenario(path: &Path) -> Result<TestScenario> {
     let content = std::fs::read_to_string(path).map_err(|e| {
         Error::Config(format!(
             "Failed to read test scenario '{}': {}",
@@ -43,9 +55,26 @@ pub async fn run_scenario(path: &Path, verbose: bool) -> Result<TestResult> {
         ))
     })?;
 
-    let scenario: TestScenario = serde_yaml::from_str(&content)
-        .map_err(|e| Error::Config(format!("Failed to parse test scenario: {}", e)))?;
+    parse_scenario(path, &content)
+}
+
+/// Run a test scenario from a YAML or JSON file
+pub async fn run_scenario(path: &Path, verbose: bool) -> Result<TestResult> {
+    let scenario = load_scenario(path)?;
 
+    // Teardown runs however the scenario ends, so resources made by setup
+    // (servers, attach targets, files) are not leaked on failure
+    let result = run_scenario_steps(path, &scenario, verbose).await;
+    run_teardown(&scenario, verbose).await;
+    result
+}
+
+/// Run the setup steps, then the debug session and its test steps
+async fn run_scenario_steps(
+    path: &Path,
+    scenario: &TestScenario,
+    verbose: bool,
+) -> Result<TestResult> {
     let steps_total = scenario.steps.len();
 
     println!(
@@ -66,21 +95,7 @@ pub async fn run_scenario(path: &Path, verbose: bool) -> Result<TestResult> {
                 println!("  $ {}", step.shell.dimmed());
             }
 
-            let status = TokioCommand::new("sh")
-                .arg("-c")
-                .arg(&step.shell)
-                .stdin(Stdio::null())
-                .stdout(if verbose {
-                    Stdio::inherit()
-                } else {
-                    Stdio::null()
-                })
-                .stderr(if verbose {
-                    Stdio::inherit()
-                } else {
-                    Stdio::null()
-                })
-                .status()
+            let status = run_shell(&step.shell, verbose)
                 .await
                 .map_err(|e| Error::Config(format!("Setup command failed to execute: {}", e)))?;
 
@@ -164,6 +179,7 @@ pub async fn run_scenario(path: &Path, verbose: bool) -> Result<TestResult> {
             .send_command(Command::Attach {
                 pid,
                 adapter: scenario.target.adapter.clone(),
+                keep_alive_minutes: None,
             })
             .await?;
 
@@ -199,6 +215,7 @@ pub async fn run_scenario(path: &Path, verbose: bool) -> Result<TestResult> {
                 adapter: scenario.target.adapter.clone(),
                 stop_on_entry: scenario.target.stop_on_entry,
                 initial_breakpoints: Vec::new(),
+                keep_alive_minutes: None,
             })
             .await?;
 
@@ -218,28 +235,47 @@ pub async fn run_scenario(path: &Path, verbose: bool) -> Result<TestResult> {
     // Execute test steps
     println!("\n{}", "Steps:".cyan());
 
-    for (i, step) in scenario.steps.iter().enumerate() {
-        let step_num = i + 1;
-
-        match execute_step(&mut client, step, step_num, verbose).await {
-            Ok(()) => {
-                // Step passed
-            }
-            Err(e) => {
-                println!("  {} Step {}: {}", "✗".red(), step_num, e);
+    let mut current_step = 0;
+    let steps = run_steps(&mut client, &scenario.steps, &mut current_step, verbose);
+    let outcome = match scenario.timeout {
+        Some(secs) => tokio::time::timeout(Duration::from_secs(secs), steps)
+            .await
+            .unwrap_or_else(|_| {
+                Err(StepFailure {
+                    error: format!(
+                        "scenario timed out after {}s during step {}",
+                        secs, current_step
+                    ),
+                    timed_out: true,
+                })
+            }),
+        None => steps.await,
+    };
 
-                // Cleanup: stop the debug session
-                let _ = client.send_command(Command::Stop).await;
+    if let Err(failure) = outcome {
+        if failure.timed_out {
+            println!("  {} {}", "✗".red(), failure.error);
+        } else {
+            println!("  {} Step {}: {}", "✗".red(), current_step, failure.error);
+        }
 
-                return Ok(TestResult {
-                    name: scenario.name.clone(),
-                    passed: false,
-                    steps_run: step_num,
-                    steps_total,
-                    error: Some(e.to_string()),
-                });
+        // Cleanup: stop the debug session. A timed-out step may have left a
+        // response unread on this connection, so use a fresh one.
+        if failure.timed_out {
+            if let Ok(mut fresh) = DaemonClient::connect().await {
+                let _ = fresh.send_command(Command::Stop).await;
             }
+        } else {
+            let _ = client.send_command(Command::Stop).await;
         }
+
+        return Ok(TestResult {
+            name: scenario.name.clone(),
+            passed: false,
+            steps_run: current_step,
+            steps_total,
+            error: Some(failure.error),
+        });
     }
 
     // Cleanup: stop the debug session
@@ -252,7 +288,7 @@ pub async fn run_scenario(path: &Path, verbose: bool) -> Result<TestResult> {
     );
 
     Ok(TestResult {
-        name: scenario.name,
+        name: scenario.name.clone(),
         passed: true,
         steps_run: steps_total,
         steps_total,
@@ -260,6 +296,109 @@ pub async fn run_scenario(path: &Path, verbose: bool) -> Result<TestResult> {
     })
 }
 
+/// Run the teardown commands. Every command runs even if an earlier one
+/// fails; failures are reported but don't change the test result.
+async fn run_teardown(scenario: &TestScenario, verbose: bool) {
+    let Some(teardown_steps) = &scenario.teardown else {
+        return;
+    };
+
+    println!("\n{}", "Teardown:".cyan());
+    for step in teardown_steps {
+        if verbose {
+            println!("  $ {}", step.shell.dimmed());
+        }
+
+        match run_shell(&step.shell, verbose).await {
+            Ok(status) if status.success() => {
+                println!("  {} {}", "✓".green(), step.shell.dimmed());
+            }
+            Ok(status) => println!(
+                "  {} {} (exit code {:?})",
+                "✗".yellow(),
+                step.shell.dimmed(),
+                status.code()
+            ),
+            Err(e) => println!("  {} {} ({})", "✗".yellow(), step.shell.dimmed(), e),
+        }
+    }
+}
+
+/// Run a setup or teardown shell command, showing its output when verbose
+async fn run_shell(command: &str, verbose: bool) -> std::io::Result<std::process::ExitStatus> {
+    let output = || {
+        if verbose {
+            Stdio::inherit()
+        } else {
+            Stdio::null()
+        }
+    };
+    shell(command)
+        .stdin(Stdio::null())
+        .stdout(output())
+        .stderr(output())
+        .status()
+        .await
+}
+
+/// `command` run by the platform's shell: `sh -c`, or `cmd /C` on Windows
+pub(crate) fn shell(command: &str) -> TokioCommand {
+    #[cfg(windows)]
+    {
+        let mut process = TokioCommand::new("cmd");
+        // cmd does its own parsing, so the command is passed as written
+        process.arg("/C").raw_arg(command);
+        process
+    }
+    #[cfg(not(windows))]
+    {
+        let mut process = TokioCommand::new("sh");
+        process.arg("-c").arg(command);
+        process
+    }
+}
+
+/// Why a scenario's steps stopped early
+struct StepFailure {
+    error: String,
+    /// The step was abandoned mid-request by a timeout
+    timed_out: bool,
+}
+
+/// Run the steps in order, stopping at the first failure. `current_step`
+/// tracks the 1-based step in progress so it is known even if the whole run
+/// is cancelled by the scenario timeout.
+async fn run_steps(
+    client: &mut DaemonClient,
+    steps: &[TestStep],
+    current_step: &mut usize,
+    verbose: bool,
+) -> std::result::Result<(), StepFailure> {
+    for (i, step) in steps.iter().enumerate() {
+        let step_num = i + 1;
+        *current_step = step_num;
+
+        let run = execute_step(client, step, step_num, verbose);
+        let result = match step.timeout() {
+            Some(secs) => match tokio::time::timeout(Duration::from_secs(secs), run).await {
+                Ok(result) => result,
+                Err(_) => {
+                    return Err(StepFailure {
+                        error: format!("step {} timed out after {}s", step_num, secs),
+                        timed_out: true,
+                    })
+                }
+            },
+            None => run.await,
+        };
+        result.map_err(|e| StepFailure {
+            error: e.to_string(),
+            timed_out: false,
+        })?;
+    }
+    Ok(())
+}
+
 /// Execute a single test step
 async fn execute_step(
     client: &mut DaemonClient,
@@ -268,23 +407,47 @@ async fn execute_step(
     verbose: bool,
 ) -> Result<()> {
     match step {
-        TestStep::Command { command, expect } => {
+        TestStep::Command { command, expect, .. } => {
             execute_command_step(client, command, expect.as_ref(), step_num, verbose).await
         }
-        TestStep::Await { timeout, expect } => {
-            execute_await_step(client, *timeout, expect.as_ref(), step_num, verbose).await
+        TestStep::Await {
+            timeout,
+            expect,
+            retries,
+        } => {
+            execute_await_step(
+                client,
+                *timeout,
+                expect.as_ref(),
+                retries.unwrap_or(0),
+                step_num,
+                verbose,
+            )
+            .await
         }
-        TestStep::InspectLocals { asserts } => {
+        TestStep::InspectLocals { asserts, .. } => {
             execute_inspect_locals_step(client, asserts, step_num, verbose).await
         }
-        TestStep::InspectStack { asserts } => {
+        TestStep::InspectStack { asserts, .. } => {
             execute_inspect_stack_step(client, asserts, step_num, verbose).await
         }
-        TestStep::CheckOutput { contains, equals } => {
-            execute_check_output_step(client, contains.as_ref(), equals.as_ref(), step_num, verbose)
-                .await
+        TestStep::CheckOutput {
+            contains,
+            equals,
+            matches,
+            ..
+        } => {
+            execute_check_output_step(
+                client,
+                contains.as_ref(),
+                equals.as_ref(),
+                matches.as_ref(),
+                step_num,
+                verbose,
+            )
+            .await
         }
-        TestStep::Evaluate { expression, expect } => {
+        TestStep::Evaluate { expression, expect, .. } => {
             execute_evaluate_step(client, expression, expect.as_ref(), step_num, verbose).await
         }
     }
@@ -378,17 +541,43 @@ async fn execute_await_step(
     client: &mut DaemonClient,
     timeout: Option<u64>,
     expect: Option<&StopExpectation>,
+    retries: u32,
     step_num: usize,
     _verbose: bool,
 ) -> Result<()> {
-    let timeout_secs = timeout.unwrap_or(30);
+    let timeout_secs = timeout.unwrap_or(DEFAULT_AWAIT_TIMEOUT_SECS);
+    let expected_reason = expect.and_then(|exp| exp.reason.as_deref());
 
-    let result = client
-        .send_command(Command::Await { timeout_secs })
-        .await?;
+    let mut attempt = 0;
+    let stop_result = loop {
+        let result = client
+            .send_command(Command::Await { timeout_secs })
+            .await?;
 
-    let stop_result: StopResult = serde_json::from_value(result)
-        .map_err(|e| Error::TestAssertion(format!("Failed to parse stop result: {}", e)))?;
+        let stop_result: StopResult = serde_json::from_value(result)
+            .map_err(|e| Error::TestAssertion(format!("Failed to parse stop result: {}", e)))?;
+
+        // Only an unexpected reason is retried, and never once the program
+        // is gone; a wrong file or line is a real failure
+        let retry = attempt < retries
+            && expected_reason.is_some_and(|reason| !stop_result.reason.contains(reason))
+            && !matches!(stop_result.reason.as_str(), "exited" | "terminated");
+        if !retry {
+            break stop_result;
+        }
+
+        attempt += 1;
+        println!(
+            "  {} Step {}: got '{}' stop, expected '{}'; continuing (retry {}/{})",
+            "↻".yellow(),
+            step_num,
+            stop_result.reason,
+            expected_reason.unwrap_or_default(),
+            attempt,
+            retries
+        );
+        client.send_command(Command::Continue { this_thread: false }).await?;
+    };
 
     // Check expectations
     if let Some(exp) = expect {
@@ -451,7 +640,14 @@ async fn execute_inspect_locals_step(
     _verbose: bool,
 ) -> Result<()> {
     let result = client
-        .send_command(Command::Locals { frame_id: None })
+        .send_command(Command::Locals {
+            frame_id: None,
+            frame_index: None,
+            // Assertions compare whole values
+            max_string_length: Some(0),
+            no_truncate: false,
+            no_cache: false,
+        })
         .await?;
 
     let vars: Vec<VariableInfo> = serde_json::from_value(result["variables"].clone())
@@ -482,6 +678,20 @@ async fn execute_inspect_locals_step(
                     }
                 }
 
+                // Check value (regex)
+                if let Some(pattern) = &assertion.matches {
+                    if !pattern.is_match(&v.value) {
+                        return Err(Error::TestAssertion(format!(
+                            "Variable '{}': expected value matching /{}/, got '{}'",
+                            assertion.name,
+                            pattern.as_str(),
+                            v.value
+                        )));
+                    }
+                }
+
+                check_numeric_value(assertion, &v.value)?;
+
                 // Check type
                 if let Some(expected_type) = &assertion.type_name {
                     let actual_type = v.type_name.as_deref().unwrap_or("");
@@ -514,6 +724,57 @@ async fn execute_inspect_locals_step(
     Ok(())
 }
 
+/// Check the `value_gt`/`value_lt`/`value_eq` comparisons of an assertion
+fn check_numeric_value(assertion: &VariableAssertion, value: &str) -> Result<()> {
+    let comparisons = [
+        (assertion.value_gt, "greater than", Ordering::Greater),
+        (assertion.value_lt, "less than", Ordering::Less),
+        (assertion.value_eq, "equal to", Ordering::Equal),
+    ];
+    if comparisons.iter().all(|(bound, _, _)| bound.is_none()) {
+        return Ok(());
+    }
+
+    let number = parse_number(value).ok_or_else(|| {
+        Error::TestAssertion(format!(
+            "Variable '{}': expected a numeric value, got '{}'",
+            assertion.name, value
+        ))
+    })?;
+
+    for (bound, relation, expected) in comparisons {
+        if let Some(bound) = bound {
+            if number.partial_cmp(&bound) != Some(expected) {
+                return Err(Error::TestAssertion(format!(
+                    "Variable '{}': expected a value {} {}, got '{}'",
+                    assertion.name, relation, bound, value
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read a debugger-formatted value as a number. Adapters may follow the
+/// number with more detail (lldb shows chars as `97 'a'`), so only the first
+/// word counts; hex (`0x1f`) is accepted too.
+fn parse_number(value: &str) -> Option<f64> {
+    let word = value.split_whitespace().next()?;
+    let (negative, digits) = match word.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, word),
+    };
+
+    let magnitude = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()? as f64,
+        None => digits.parse::<f64>().ok()?,
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
 /// Execute an inspect stack step
 async fn execute_inspect_stack_step(
     client: &mut DaemonClient,
@@ -525,6 +786,7 @@ async fn execute_inspect_stack_step(
         .send_command(Command::StackTrace {
             thread_id: None,
             limit: 50,
+            user_only: false,
         })
         .await?;
 
@@ -582,11 +844,22 @@ async fn execute_inspect_stack_step(
     Ok(())
 }
 
+/// The start of `output` for an assertion failure, cut on a character
+/// boundary
+fn output_excerpt(output: &str) -> String {
+    if output.len() > 200 {
+        format!("{}...", truncate_utf8_to_bytes(output, 200))
+    } else {
+        output.to_string()
+    }
+}
+
 /// Execute a check output step
 async fn execute_check_output_step(
     client: &mut DaemonClient,
     contains: Option<&String>,
     equals: Option<&String>,
+    matches: Option<&Pattern>,
     step_num: usize,
     _verbose: bool,
 ) -> Result<()> {
@@ -604,11 +877,17 @@ async fn execute_check_output_step(
             return Err(Error::TestAssertion(format!(
                 "Output does not contain '{}'. Got: '{}'",
                 expected_substr,
-                if output.len() > 200 {
-                    format!("{}...", &output[..200])
-                } else {
-                    output.to_string()
-                }
+                output_excerpt(output)
+            )));
+        }
+    }
+
+    if let Some(pattern) = matches {
+        if !pattern.is_match(output) {
+            return Err(Error::TestAssertion(format!(
+                "Output does not match /{}/. Got: '{}'",
+                pattern.as_str(),
+                output_excerpt(output)
             )));
         }
     }
@@ -643,7 +922,10 @@ async fn execute_evaluate_step(
         .send_command(Command::Evaluate {
             expression: expression.to_string(),
             frame_id: None,
+            frame_index: None,
             context: EvaluateContext::Watch,
+            max_string_length: Some(0),
+            no_truncate: false,
         })
         .await;
 
@@ -737,407 +1019,98 @@ async fn execute_evaluate_step(
     Ok(())
 }
 
-/// Parse a command string into a Command enum
-fn parse_command(s: &str) -> Result<Command> {
-    let parts: Vec<&str> = s.split_whitespace().collect();
-    if parts.is_empty() {
-        return Err(Error::Config("Empty command".to_string()));
-    }
-
-    let cmd = parts[0].to_lowercase();
-    let args = &parts[1..];
-
-    match cmd.as_str() {
-        "continue" | "c" => Ok(Command::Continue),
-        "next" | "n" => Ok(Command::Next),
-        "step" | "s" => Ok(Command::StepIn),
-        "finish" | "out" => Ok(Command::StepOut),
-        "pause" => Ok(Command::Pause),
-
-        "break" | "b" => {
-            if args.is_empty() {
-                return Err(Error::Config(
-                    "break command requires a location".to_string(),
-                ));
-            }
-            // Skip "add" subcommand if present AND there are more args
-            // (otherwise "add" is the function name to break on)
-            let breakpoint_args = if args.first() == Some(&"add") && args.len() > 1 {
-                &args[1..]
-            } else {
-                args
-            };
-            parse_breakpoint_add(breakpoint_args, "break")
-        }
-
-        "breakpoint" => {
-            if args.is_empty() {
-                return Err(Error::Config(
-                    "breakpoint command requires a subcommand".to_string(),
-                ));
-            }
-
-            match args[0] {
-                "add" => {
-                    if args.len() < 2 {
-                        return Err(Error::Config(
-                            "breakpoint add requires a location".to_string(),
-                        ));
-                    }
-                    parse_breakpoint_add(&args[1..], "breakpoint add")
-                }
-                "remove" => {
-                    if args.len() < 2 {
-                        return Ok(Command::BreakpointRemove { id: None, all: true });
-                    }
-                    if args[1] == "all" || args[1] == "--all" {
-                        return Ok(Command::BreakpointRemove { id: None, all: true });
-                    }
-                    let id: u32 = args[1].parse().map_err(|_| {
-                        Error::Config(format!("Invalid breakpoint ID: {}", args[1]))
-                    })?;
-                    Ok(Command::BreakpointRemove {
-                        id: Some(id),
-                        all: false,
-                    })
-                }
-                "list" => Ok(Command::BreakpointList),
-                "enable" => {
-                    if args.len() < 2 {
-                        return Err(Error::Config(
-                            "breakpoint enable requires an ID".to_string(),
-                        ));
-                    }
-                    let id: u32 = args[1].parse().map_err(|_| {
-                        Error::Config(format!("Invalid breakpoint ID: {}", args[1]))
-                    })?;
-                    Ok(Command::BreakpointEnable { id })
-                }
-                "disable" => {
-                    if args.len() < 2 {
-                        return Err(Error::Config(
-                            "breakpoint disable requires an ID".to_string(),
-                        ));
-                    }
-                    let id: u32 = args[1].parse().map_err(|_| {
-                        Error::Config(format!("Invalid breakpoint ID: {}", args[1]))
-                    })?;
-                    Ok(Command::BreakpointDisable { id })
-                }
-                _ => Err(Error::Config(format!(
-                    "Unknown breakpoint subcommand: {}",
-                    args[0]
-                ))),
-            }
-        }
-
-        "context" | "where" => {
-            let lines = match args {
-                [] => 5,
-                [value] => value.parse().map_err(|_| {
-                    Error::Config(format!("{} requires a numeric line count", cmd))
-                })?,
-                [flag, value] if *flag == "--lines" => value.parse().map_err(|_| {
-                    Error::Config(format!("{} --lines requires a number", cmd))
-                })?,
-                _ => {
-                    return Err(Error::Config(format!(
-                        "{} accepts either <lines> or --lines <lines>",
-                        cmd
-                    )))
-                }
-            };
-            Ok(Command::Context { lines })
-        }
-
-        "locals" => Ok(Command::Locals { frame_id: None }),
-
-        "backtrace" | "bt" => Ok(Command::StackTrace {
-            thread_id: None,
-            limit: 20,
-        }),
-
-        "threads" => Ok(Command::Threads),
-
-        "thread" => {
-            if args.is_empty() {
-                return Err(Error::Config("thread command requires an ID".to_string()));
-            }
-            let id: i64 = args[0]
-                .parse()
-                .map_err(|_| Error::Config(format!("Invalid thread ID: {}", args[0])))?;
-            Ok(Command::ThreadSelect { id })
-        }
-
-        "frame" => {
-            if args.is_empty() {
-                return Err(Error::Config(
-                    "frame command requires a number".to_string(),
-                ));
-            }
-            let number: usize = args[0]
-                .parse()
-                .map_err(|_| Error::Config(format!("Invalid frame number: {}", args[0])))?;
-            Ok(Command::FrameSelect { number })
-        }
-
-        "up" => Ok(Command::FrameUp),
-        "down" => Ok(Command::FrameDown),
-
-        "print" | "p" | "eval" => {
-            if args.is_empty() {
-                return Err(Error::Config(
-                    "print/eval command requires an expression".to_string(),
-                ));
-            }
-            Ok(Command::Evaluate {
-                expression: args.join(" "),
-                frame_id: None,
-                context: if cmd == "eval" {
-                    EvaluateContext::Repl
-                } else {
-                    EvaluateContext::Watch
-                },
-            })
-        }
-
-        "stop" => Ok(Command::Stop),
-        "detach" => Ok(Command::Detach),
-        "restart" => Ok(Command::Restart),
-
-        "output" => {
-            // Parse the same options accepted by the user-facing CLI.
-            let mut tail: Option<usize> = None;
-            let mut clear = false;
-            let mut i = 0;
-            while i < args.len() {
-                match args[i] {
-                    "--tail" | "-t" => {
-                        let value = args.get(i + 1).ok_or_else(|| {
-                            Error::Config("output --tail requires a number".to_string())
-                        })?;
-                        tail = Some(value.parse().map_err(|_| {
-                            Error::Config(format!("Invalid output tail value: {}", value))
-                        })?);
-                        i += 2;
-                    }
-                    "--clear" => {
-                        clear = true;
-                        i += 1;
-                    }
-                    option => {
-                        return Err(Error::Config(format!(
-                            "Unknown output option: {}",
-                            option
-                        )));
-                    }
-                }
-            }
-            Ok(Command::GetOutput { tail, clear })
-        }
-
-        _ => Err(Error::Config(format!("Unknown command: {}", cmd))),
-    }
-}
-
-/// Parse a breakpoint location and the shared breakpoint options used by the
-/// CLI shorthand and the `breakpoint add` subcommand.
-fn parse_breakpoint_add(args: &[&str], command: &str) -> Result<Command> {
-    let mut location_parts = Vec::new();
-    let mut condition = None;
-    let mut hit_count = None;
-    let mut index = 0;
-
-    while index < args.len() {
-        match args[index] {
-            "--condition" | "-c" => {
-                index += 1;
-                let mut condition_parts = Vec::new();
-                while index < args.len() && !args[index].starts_with("--") {
-                    condition_parts.push(args[index]);
-                    index += 1;
-                }
-                if condition_parts.is_empty() {
-                    return Err(Error::Config(format!(
-                        "{} --condition requires an expression",
-                        command
-                    )));
-                }
-                condition = Some(condition_parts.join(" ").trim_matches('"').to_string());
-            }
-            "--hit-count" => {
-                let value = args.get(index + 1).ok_or_else(|| {
-                    Error::Config(format!("{} --hit-count requires a number", command))
-                })?;
-                hit_count = Some(value.parse().map_err(|_| {
-                    Error::Config(format!("Invalid hit count: {}", value))
-                })?);
-                index += 2;
-            }
-            option if option.starts_with('-') => {
-                return Err(Error::Config(format!(
-                    "Unknown {} option: {}",
-                    command, option
-                )));
-            }
-            location => {
-                location_parts.push(location);
-                index += 1;
-            }
-        }
-    }
-
-    if location_parts.is_empty() {
-        return Err(Error::Config(format!("{} requires a location", command)));
-    }
-
-    Ok(Command::BreakpointAdd {
-        location: BreakpointLocation::parse(&location_parts.join(" "))?,
-        condition,
-        hit_count,
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_parse_simple_commands() {
-        assert!(matches!(parse_command("continue").unwrap(), Command::Continue));
-        assert!(matches!(parse_command("c").unwrap(), Command::Continue));
-        assert!(matches!(parse_command("next").unwrap(), Command::Next));
-        assert!(matches!(parse_command("step").unwrap(), Command::StepIn));
-        assert!(matches!(parse_command("finish").unwrap(), Command::StepOut));
-        assert!(matches!(parse_command("pause").unwrap(), Command::Pause));
+    fn json_and_yaml_scenarios_parse_to_the_same_steps() {
+        let yaml = r#"
+name: "Smoke"
+target:
+  program: "./app"
+steps:
+  - action: command
+    command: "break main"
+  - action: await
+    timeout: 10
+    expect:
+      reason: "breakpoint"
+"#;
+        let json = r#"{
+  "name": "Smoke",
+  "target": { "program": "./app" },
+  "steps": [
+    { "action": "command", "command": "break main" },
+    { "action": "await", "timeout": 10, "expect": { "reason": "breakpoint" } }
+  ]
+}"#;
+
+        let from_yaml = parse_scenario(Path::new("smoke.yml"), yaml).unwrap();
+        let from_json = parse_scenario(Path::new("smoke.JSON"), json).unwrap();
+        assert_eq!(format!("{:?}", from_yaml), format!("{:?}", from_json));
+        assert_eq!(from_json.steps.len(), 2);
+
+        // JSON is only used for .json files
+        assert!(parse_scenario(Path::new("smoke.json"), yaml).is_err());
     }
 
     #[test]
-    fn test_parse_break_commands() {
-        let cmd = parse_command("break main").unwrap();
-        assert!(matches!(cmd, Command::BreakpointAdd { .. }));
-
-        let cmd = parse_command("break add main.rs:42").unwrap();
-        assert!(matches!(cmd, Command::BreakpointAdd { .. }));
-
-        let cmd = parse_command("b foo.c:10").unwrap();
-        assert!(matches!(cmd, Command::BreakpointAdd { .. }));
+    fn output_excerpts_cut_on_character_boundaries() {
+        assert_eq!(output_excerpt("short"), "short");
+        // 199 ASCII bytes, then a character spanning bytes 199..202
+        let output = format!("{}€ and more", "a".repeat(199));
+        assert_eq!(output_excerpt(&output), format!("{}...", "a".repeat(199)));
     }
 
     #[test]
-    fn test_parse_breakpoint_subcommands() {
-        assert!(matches!(
-            parse_command("breakpoint add main").unwrap(),
-            Command::BreakpointAdd { .. }
-        ));
-        assert!(matches!(
-            parse_command("breakpoint list").unwrap(),
-            Command::BreakpointList
-        ));
-        assert!(matches!(
-            parse_command("breakpoint remove 1").unwrap(),
-            Command::BreakpointRemove { .. }
-        ));
+    fn numeric_values_are_read_from_debugger_output() {
+        assert_eq!(parse_number("42"), Some(42.0));
+        assert_eq!(parse_number("-3.5"), Some(-3.5));
+        assert_eq!(parse_number("0x1f"), Some(31.0));
+        assert_eq!(parse_number("97 'a'"), Some(97.0));
+        assert_eq!(parse_number("\"hello\""), None);
+        assert_eq!(parse_number(""), None);
     }
 
     #[test]
-    fn test_parse_print_commands() {
-        let cmd = parse_command("print x + y").unwrap();
-        match cmd {
-            Command::Evaluate {
-                expression, context, ..
-            } => {
-                assert_eq!(expression, "x + y");
-                assert!(matches!(context, EvaluateContext::Watch));
-            }
-            _ => panic!("Expected Evaluate command"),
-        }
-
-        let cmd = parse_command("eval counter = counter + 1").unwrap();
-        assert!(matches!(
-            cmd,
-            Command::Evaluate {
-                context: EvaluateContext::Repl,
-                ..
-            }
-        ));
-    }
-
-    #[test]
-    fn test_parse_break_with_hit_count() {
-        let cmd = parse_command("break factorial --hit-count 3").unwrap();
-        match cmd {
-            Command::BreakpointAdd { hit_count, .. } => {
-                assert_eq!(hit_count, Some(3));
-            }
-            _ => panic!("Expected BreakpointAdd command"),
-        }
-
-        let cmd = parse_command("break main.c:10 --hit-count 5").unwrap();
-        match cmd {
-            Command::BreakpointAdd { hit_count, .. } => {
-                assert_eq!(hit_count, Some(5));
-            }
-            _ => panic!("Expected BreakpointAdd command"),
-        }
-    }
-
-    #[test]
-    fn test_parse_break_with_condition_and_hit_count() {
-        let cmd = parse_command("break foo --condition \"x > 5\" --hit-count 2").unwrap();
-        match cmd {
-            Command::BreakpointAdd { condition, hit_count, .. } => {
-                assert_eq!(condition, Some("x > 5".to_string()));
-                assert_eq!(hit_count, Some(2));
-            }
-            _ => panic!("Expected BreakpointAdd command"),
-        }
-    }
-
-    #[test]
-    fn test_parse_breakpoint_subcommand_options() {
-        let cmd = parse_command("breakpoint add foo.c:10 --condition x > 5 --hit-count 2")
-            .unwrap();
-        match cmd {
-            Command::BreakpointAdd {
-                condition,
-                hit_count,
-                ..
-            } => {
-                assert_eq!(condition, Some("x > 5".to_string()));
-                assert_eq!(hit_count, Some(2));
-            }
-            _ => panic!("Expected BreakpointAdd command"),
-        }
-    }
+    fn numeric_assertions_compare_bounds() {
+        let assertion = VariableAssertion {
+            name: "counter".to_string(),
+            value: None,
+            value_contains: None,
+            matches: None,
+            value_gt: Some(5.0),
+            value_lt: Some(10.0),
+            value_eq: None,
+            type_name: None,
+        };
+        assert!(check_numeric_value(&assertion, "7").is_ok());
+        assert!(check_numeric_value(&assertion, "5").is_err());
+        assert!(check_numeric_value(&assertion, "0xff").is_err());
 
-    #[test]
-    fn test_parse_context_commands() {
-        assert!(matches!(
-            parse_command("context").unwrap(),
-            Command::Context { lines: 5 }
-        ));
-        assert!(matches!(
-            parse_command("where 3").unwrap(),
-            Command::Context { lines: 3 }
-        ));
-        assert!(matches!(
-            parse_command("context --lines 7").unwrap(),
-            Command::Context { lines: 7 }
-        ));
-        assert!(parse_command("context --lines nope").is_err());
+        let error = check_numeric_value(&assertion, "<unavailable>").unwrap_err();
+        assert!(error.to_string().contains("expected a numeric value"), "{}", error);
     }
 
     #[test]
-    fn test_parse_output_commands() {
-        assert!(matches!(
-            parse_command("output -t 4 --clear").unwrap(),
-            Command::GetOutput {
-                tail: Some(4),
-                clear: true
-            }
-        ));
-        assert!(parse_command("output --tail invalid").is_err());
-        assert!(parse_command("output --follow").is_err());
+    fn step_timeouts_default_to_unbounded_except_await() {
+        let yaml = r#"
+name: "Timeouts"
+timeout: 120
+target:
+  program: "./app"
+steps:
+  - action: evaluate
+    expression: "x"
+    timeout: 5
+  - action: command
+    command: "continue"
+  - action: await
+"#;
+        let scenario = parse_scenario(Path::new("timeouts.yml"), yaml).unwrap();
+        assert_eq!(scenario.timeout, Some(120));
+        let timeouts: Vec<_> = scenario.steps.iter().map(TestStep::timeout).collect();
+        assert_eq!(timeouts, vec![Some(5), None, Some(35)]);
     }
 }
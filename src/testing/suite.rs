@@ -0,0 +1,223 @@
+//! Running a directory of test scenarios
+//!
+//! Scenarios run one after another against the shared daemon, or with
+//! `--parallel N` up to N at a time, each in a child `debugger test` process
+//! bound to its own daemon instance so sessions never collide.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+
+use colored::Colorize;
+use tokio::process::Command as TokioCommand;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::common::{paths, Error, Result};
+
+use super::runner::{load_scenario, run_scenario};
+
+/// Extensions recognised as scenario files
+const SCENARIO_EXTENSIONS: &[&str] = &["yml", "yaml", "json"];
+
+/// Outcome counts for a directory of scenarios
+#[derive(Debug, Default)]
+pub struct SuiteSummary {
+    pub passed: usize,
+    pub failed: usize,
+    /// Scenarios that could not be loaded, and so never ran
+    pub skipped: usize,
+    /// Failed or skipped scenario files, with the reason when known
+    pub problems: Vec<(PathBuf, String)>,
+}
+
+impl SuiteSummary {
+    /// Whether every scenario ran and passed
+    pub fn success(&self) -> bool {
+        self.failed == 0 && self.skipped == 0
+    }
+
+    fn fail(&mut self, path: &Path, reason: String) {
+        self.failed += 1;
+        self.problems.push((path.to_path_buf(), reason));
+    }
+
+    fn skip(&mut self, path: &Path, reason: String) {
+        self.skipped += 1;
+        self.problems.push((path.to_path_buf(), reason));
+    }
+
+    fn print(&self) {
+        println!("\n{}", "Summary:".blue().bold());
+        for (path, reason) in &self.problems {
+            println!("  {} {}: {}", "✗".red(), path.display(), reason);
+        }
+        println!(
+            "  {} passed, {} failed, {} skipped",
+            self.passed.to_string().green(),
+            self.failed.to_string().red(),
+            self.skipped.to_string().yellow()
+        );
+    }
+}
+
+/// Run every scenario in `dir`, at most `parallel` at a time, and print a
+/// summary at the end
+pub async fn run_suite(dir: &Path, parallel: usize, verbose: bool) -> Result<SuiteSummary> {
+    let files = scenario_files(dir)?;
+    if files.is_empty() {
+        return Err(Error::Config(format!(
+            "No test scenarios (.yml, .yaml, .json) found in '{}'",
+            dir.display()
+        )));
+    }
+
+    // Load everything up front so broken files are reported as skipped
+    // rather than as test failures
+    let mut summary = SuiteSummary::default();
+    let mut runnable = Vec::new();
+    for path in files {
+        match load_scenario(&path) {
+            Ok(_) => runnable.push(path),
+            Err(e) => {
+                println!("{} {}: {}", "Skipped".yellow(), path.display(), e);
+                summary.skip(&path, e.to_string());
+            }
+        }
+    }
+
+    if parallel <= 1 {
+        for path in runnable {
+            match run_scenario(&path, verbose).await {
+                Ok(result) if result.passed => summary.passed += 1,
+                Ok(result) => summary.fail(
+                    &path,
+                    result.error.unwrap_or_else(|| "failed".to_string()),
+                ),
+                Err(e) => {
+                    println!("  {} {}", "✗".red(), e);
+                    summary.fail(&path, e.to_string());
+                }
+            }
+        }
+    } else {
+        run_in_children(runnable, parallel, verbose, &mut summary).await?;
+    }
+
+    summary.print();
+    Ok(summary)
+}
+
+/// Scenario files directly inside `dir`, in name order
+fn scenario_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        Error::Config(format!(
+            "Failed to read test directory '{}': {}",
+            dir.display(),
+            e
+        ))
+    })?;
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && is_scenario_file(path))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn is_scenario_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        SCENARIO_EXTENSIONS
+            .iter()
+            .any(|known| ext.eq_ignore_ascii_case(known))
+    })
+}
+
+/// Run each scenario in a child `debugger test` process with its own daemon
+/// instance. A child's output is printed in one piece once it finishes, so
+/// concurrent scenarios don't interleave.
+async fn run_in_children(
+    files: Vec<PathBuf>,
+    parallel: usize,
+    verbose: bool,
+    summary: &mut SuiteSummary,
+) -> Result<()> {
+    let exe = std::env::current_exe().map_err(|e| {
+        Error::Internal(format!("Failed to get current executable path: {}", e))
+    })?;
+
+    println!(
+        "Running {} scenarios, up to {} at a time",
+        files.len(),
+        parallel
+    );
+
+    let slots = Arc::new(Semaphore::new(parallel));
+    let mut tasks = JoinSet::new();
+    for (index, path) in files.into_iter().enumerate() {
+        let slots = slots.clone();
+        let exe = exe.clone();
+        let instance = format!("test-{}-{}", std::process::id(), index);
+
+        tasks.spawn(async move {
+            let _slot = slots.acquire_owned().await;
+
+            let mut command = TokioCommand::new(&exe);
+            command
+                .arg("test")
+                .arg(&path)
+                .arg("--shutdown-daemon")
+                .env(paths::INSTANCE_ENV, &instance)
+                .stdin(Stdio::null());
+            if verbose {
+                command.arg("--verbose");
+            }
+            let output = command.output().await;
+            (path, output)
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        let (path, output) =
+            joined.map_err(|e| Error::Internal(format!("Scenario task failed: {}", e)))?;
+
+        match output {
+            Ok(output) => {
+                print!("{}", String::from_utf8_lossy(&output.stdout));
+                eprint!("{}", String::from_utf8_lossy(&output.stderr));
+                if output.status.success() {
+                    summary.passed += 1;
+                } else {
+                    summary.fail(&path, output.status.to_string());
+                }
+            }
+            Err(e) => summary.fail(&path, format!("failed to run: {}", e)),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_scenario_files_are_collected_in_order() {
+        let dir = std::env::temp_dir().join(format!("debugger-suite-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested.yml")).unwrap();
+        for name in ["b.yaml", "a.yml", "c.JSON", "notes.md"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        let files = scenario_files(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<_> = files
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["a.yml", "b.yaml", "c.JSON"]);
+    }
+}
@@ -626,6 +626,42 @@ fn test_basic_debugging_workflow_c_gdb() {
     let _ = ctx.run_debugger(&["stop"]);
 }
 
+#[test]
+fn test_stop_on_entry_c_gdb() {
+    let gdb_path = match gdb_available() {
+        Some(path) => path,
+        None => {
+            eprintln!("Skipping test: GDB ≥14.1 not available");
+            return;
+        }
+    };
+
+    let mut ctx = TestContext::new("stop_on_entry_c_gdb");
+    ctx.create_config_with_args("gdb", gdb_path.to_str().unwrap(), &["-i=dap"]);
+    let binary = ctx.build_c_fixture("simple").clone();
+    ctx.cleanup_daemon();
+
+    // Stop-on-entry is emulated with a breakpoint on main for GDB
+    ctx.run_debugger_ok(&["start", binary.to_str().unwrap(), "--stop-on-entry"]);
+    let output = ctx.run_debugger_ok(&["await", "--timeout", "30"]);
+    assert!(output.contains("entry"), "Expected an entry stop: {}", output);
+    let output = ctx.run_debugger_ok(&["backtrace"]);
+    assert!(output.contains("main"), "Expected to stop in main: {}", output);
+
+    // The emulated breakpoint is gone, so nothing but the exit follows
+    ctx.run_debugger_ok(&["continue"]);
+    let output = ctx.run_debugger_ok(&["await", "--timeout", "30"]);
+    assert!(
+        output.contains("exited") || output.contains("terminated"),
+        "Expected the program to run to completion: {}",
+        output
+    );
+    let output = ctx.run_debugger_ok(&["breakpoint", "list"]);
+    assert!(!output.contains("main"), "Entry breakpoint should not be listed: {}", output);
+
+    let _ = ctx.run_debugger(&["stop"]);
+}
+
 #[test]
 fn test_cuda_gdb_adapter_available() {
     let cuda_gdb_path = match cuda_gdb_available() {
@@ -691,6 +727,63 @@ fn test_stepping_c() {
     ctx.run_debugger(&["stop"]);
 }
 
+#[test]
+#[ignore = "requires lldb-dap"]
+fn test_attach_then_breakpoint_c() {
+    let lldb_path = match lldb_dap_available() {
+        Some(path) => path,
+        None => {
+            eprintln!("Skipping test: lldb-dap not available");
+            return;
+        }
+    };
+
+    let mut ctx = TestContext::new("attach_breakpoint_c");
+    ctx.create_config("lldb-dap", lldb_path.to_str().unwrap());
+
+    let binary = ctx.build_c_fixture("attach_target").clone();
+    let markers = ctx.find_breakpoint_markers(&ctx.fixtures_dir.join("attach_target.c"));
+    let loop_line = markers.get("loop_body").expect("Missing loop_body marker");
+
+    ctx.cleanup_daemon();
+
+    let mut target = Command::new(&binary)
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("Failed to start attach target");
+    std::thread::sleep(Duration::from_millis(500));
+
+    // Attaching leaves the process stopped, so the breakpoint is in place
+    // before it runs again
+    let output = ctx.run_debugger_ok(&["attach", &target.id().to_string()]);
+    assert!(output.contains("Attached"), "Unexpected attach output: {}", output);
+    let output = ctx.run_debugger_ok(&["status"]);
+    assert!(output.contains("State: stopped"), "Expected a stopped process: {}", output);
+
+    let bp_location = format!("attach_target.c:{}", loop_line);
+    ctx.run_debugger_ok(&["break", &bp_location]);
+    ctx.run_debugger_ok(&["continue"]);
+
+    // The loop sleeps a second per iteration
+    let output = ctx.run_debugger_ok(&["await", "--timeout", "10"]);
+    assert!(
+        output.contains("breakpoint"),
+        "Expected to stop at the breakpoint: {}",
+        output
+    );
+    let output = ctx.run_debugger_ok(&["backtrace"]);
+    assert!(
+        output.contains(&format!("attach_target.c:{}", loop_line)),
+        "Expected to stop on line {}: {}",
+        loop_line,
+        output
+    );
+
+    ctx.run_debugger(&["detach"]);
+    let _ = target.kill();
+    let _ = target.wait();
+}
+
 #[test]
 #[ignore = "requires lldb-dap"]
 fn test_expression_evaluation_c() {